@@ -0,0 +1,179 @@
+//! 下载器基准测试：`cargo bench --bench downloader`
+//!
+//! - LowCost 写盘吞吐：从本地模拟直播流读取并经写盘线程落盘
+//! - 事件管道延迟：下载线程推送事件到事件处理任务取出的往返耗时
+//! - 100 个房间一轮状态更新的耗时
+
+use std::{
+    io::{Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use blive::{
+    core::{
+        danmaku::{DanmakuFilter, DanmakuMessage},
+        downloader::{
+            context::{DownloaderEvent, EventChannel},
+            stats::{BufferStats, DownloadStats, PROGRESS_REPORT_INTERVAL, ProgressAggregator},
+            writer::FileWriter,
+        },
+    },
+    settings::DEFAULT_READ_BUFFER_KB,
+    state::RoomCardState,
+};
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use parking_lot::Mutex;
+
+/// 每次迭代推送的直播流大小
+const STREAM_SIZE: usize = 64 * 1024 * 1024;
+
+/// 模拟服务端每次写出的块大小
+const STREAM_CHUNK: usize = 16 * 1024;
+
+const ROOM_COUNT: u64 = 100;
+
+/// 启动本地模拟直播流，每个连接推送 [`STREAM_SIZE`] 字节后关闭
+fn spawn_mock_stream() -> u16 {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+
+            thread::spawn(move || {
+                let chunk = vec![0x42; STREAM_CHUNK];
+                for _ in 0..STREAM_SIZE / STREAM_CHUNK {
+                    if stream.write_all(&chunk).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    port
+}
+
+/// 与 LowCost 下载循环相同：读入缓冲区、统计进度，再交给写盘线程
+fn record_stream(port: u16, path: &std::path::Path) -> u64 {
+    let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+    let writer = FileWriter::spawn(std::fs::File::create(path).unwrap()).unwrap();
+
+    let mut buffer = vec![0; DEFAULT_READ_BUFFER_KB as usize * 1024];
+    let mut buffer_stats = BufferStats::new(buffer.len());
+    let mut progress = ProgressAggregator::new(PROGRESS_REPORT_INTERVAL, Instant::now());
+
+    futures::executor::block_on(async {
+        loop {
+            let bytes_read = stream.read(&mut buffer).unwrap();
+            if bytes_read == 0 {
+                break;
+            }
+
+            buffer_stats.record(bytes_read);
+            assert!(writer.write(buffer[..bytes_read].to_vec()).await);
+            progress.record(bytes_read as u64, Instant::now());
+        }
+
+        writer.finish().await.unwrap()
+    })
+}
+
+fn bench_low_cost_write(c: &mut Criterion) {
+    let port = spawn_mock_stream();
+    let path = std::env::temp_dir().join(format!("blive-bench-{}.flv", std::process::id()));
+
+    let mut group = c.benchmark_group("low_cost");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(STREAM_SIZE as u64));
+    group.bench_function("write_throughput", |b| {
+        b.iter(|| assert_eq!(record_stream(port, &path), STREAM_SIZE as u64));
+    });
+    group.finish();
+
+    let _ = std::fs::remove_file(path);
+}
+
+fn bench_event_pipeline(c: &mut Criterion) {
+    let channel = EventChannel::default();
+    let (ack_tx, ack_rx) = flume::bounded::<()>(1);
+
+    // 模拟事件处理任务：逐个取出事件后应答
+    let consumer = channel.clone();
+    thread::spawn(move || {
+        futures::executor::block_on(async {
+            while consumer.recv().await.is_some() {
+                if ack_tx.send(()).is_err() {
+                    break;
+                }
+            }
+        })
+    });
+
+    c.bench_function("event_pipeline_latency", |b| {
+        b.iter(|| {
+            channel.send(DownloaderEvent::Progress {
+                bytes_downloaded: 1024,
+                download_speed_kbps: 1024.0,
+                duration_ms: 1000,
+            });
+            ack_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        });
+    });
+}
+
+fn bench_room_updates(c: &mut Criterion) {
+    let setup = || {
+        (1..=ROOM_COUNT)
+            .map(|room_id| {
+                (
+                    RoomCardState::new(room_id),
+                    Mutex::new(DownloadStats::default()),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let filter = DanmakuFilter::default();
+
+    // 每个房间收到一次进度和一条弹幕：按房间号查找状态、更新统计、重置轮询
+    c.bench_function("room_state_update_100", |b| {
+        b.iter_batched_ref(
+            setup,
+            |rooms| {
+                for room_id in 1..=ROOM_COUNT {
+                    let (state, stats) = rooms
+                        .iter_mut()
+                        .find(|(state, _)| state.room_id == room_id)
+                        .unwrap();
+
+                    stats.lock().download_speed_kbps = room_id as f32;
+                    state.reconnect_manager.record_stable(None);
+                    state.record_danmaku(
+                        DanmakuMessage {
+                            timestamp: room_id as i64,
+                            uid: room_id,
+                            uname: "bench".to_string(),
+                            content: "弹幕".to_string(),
+                        },
+                        &filter,
+                    );
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_low_cost_write,
+    bench_event_pipeline,
+    bench_room_updates
+);
+criterion_main!(benches);
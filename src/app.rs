@@ -1,22 +1,72 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use gpui::{
     App, AppContext, Axis, Entity, EventEmitter, Subscription, Window, div, prelude::*, px,
 };
 use gpui_component::{
-    ActiveTheme as _, ContextModal, Root, StyledExt, h_flex, notification::Notification,
-    text::Text, v_flex,
+    ActiveTheme as _, ContextModal, Root, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{InputEvent, InputState, TextInput},
+    notification::Notification,
+    text::Text,
+    v_flex,
 };
 
 use crate::{
-    components::{RoomCard, RoomCardEvent, RoomCardStatus, RoomInput, RoomInputEvent},
-    core::{downloader::BLiveDownloader, http_client::room::LiveStatus},
+    cli::{self, CliCommand},
+    clipboard::parse_live_room_id,
+    components::{
+        GlobalBanner, RoomCard, RoomCardEvent, RoomCardStatus, RoomInput, RoomInputEvent,
+    },
+    core::{
+        auth::{self, LOGIN_CHECK_INTERVAL},
+        cover,
+        disk::{LOW_SPACE_THRESHOLD, disk_space},
+        downloader::{BLiveDownloader, ROTATION_CHECK_INTERVAL, utils::pretty_bytes},
+        ffmpeg::{FfmpegStatus, ffmpeg_status},
+        history::{self, HistoryEvent, HistoryRecord},
+        http_client::room::LiveStatus,
+        metered::is_metered,
+        monitor::{
+            MEMORY_SAMPLE_INTERVAL, MemorySample, PROCESS_SAMPLE_INTERVAL, ProcessSampler,
+            process_memory,
+        },
+        mqtt,
+        netwatch::{NETWORK_CHECK_INTERVAL, NetworkWatcher, interface_snapshot},
+        record_lock::LOCK_HEARTBEAT_INTERVAL,
+        relay,
+        repair::repair_unfinished,
+        scheduler::{TaskPriority, scheduler},
+        storage::{self, StorageUsage},
+        webhook::{self, WebhookPayload},
+    },
+    crash::take_unseen_crash_report,
+    heartbeat::{HEARTBEAT_INTERVAL, serve_healthz, write_heartbeat_file},
+    hotkey::StopAllHotkey,
     logger::log_user_action,
-    settings::RoomSettings,
-    state::AppState,
+    notification::push_system_notification,
+    profile_scope,
+    settings::{MeteredAction, RoomQuota, RoomSettings, StorageLimitAction, parse_tags},
+    state::{AppState, GlobalIssue, OfflineDebounce, POLL_INTERVAL},
+    status::AppStatus,
     title_bar::AppTitleBar,
 };
 
+/// 全局问题检查间隔
+const GLOBAL_ISSUE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 全局热键事件的检查间隔
+const HOTKEY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// D-Bus 状态信号的合并间隔
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+const STATUS_DEBOUNCE: Duration = Duration::from_secs(1);
+
 enum BLiveAppEvent {
     InitRoom(RoomSettings),
 }
@@ -26,6 +76,12 @@ pub struct BLiveApp {
     room_input: Entity<RoomInput>,
     title_bar: Entity<AppTitleBar>,
     room_cards: Vec<Entity<RoomCard>>,
+    /// 房间列表的搜索框
+    search_input: Entity<InputState>,
+    /// 选中的标签，为 `None` 时显示全部房间
+    tag_filter: Option<String>,
+    /// 批量管理标签的输入框
+    tag_batch_input: Entity<InputState>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -41,10 +97,19 @@ impl BLiveApp {
         let title_bar = cx.new(|cx| AppTitleBar::new(title, window, cx));
         let room_id = 1804892069;
         let room_input = RoomInput::view(room_id, window, cx);
+        let search_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("搜索房间号、主播、标题或备注"));
+        let tag_batch_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("标签，多个用逗号分隔"));
 
         let _subscriptions = vec![
             cx.subscribe_in(&room_input, window, Self::on_room_input_change),
             cx.subscribe_in(&cx.entity(), window, Self::on_app_event),
+            cx.subscribe_in(&search_input, window, |_, _, event: &InputEvent, _, cx| {
+                if let InputEvent::Change(_) = event {
+                    cx.notify();
+                }
+            }),
         ];
 
         for room in rooms {
@@ -53,11 +118,31 @@ impl BLiveApp {
             cx.emit(BLiveAppEvent::InitRoom(room));
         }
 
+        Self::watch_clipboard(window, cx);
+        Self::notify_crash_report(window, cx);
+        Self::repair_unfinished_recordings(window, cx);
+        Self::watch_memory(window, cx);
+        Self::watch_processes(window, cx);
+        Self::start_heartbeat(window, cx);
+        Self::start_relay(cx);
+        Self::watch_global_issues(window, cx);
+        Self::watch_cli_commands(window, cx);
+        Self::watch_stop_all_hotkey(window, cx);
+        Self::watch_network_changes(window, cx);
+        Self::watch_stream_expiry(window, cx);
+        Self::watch_record_locks(window, cx);
+        Self::watch_login(window, cx);
+        #[cfg(all(target_os = "linux", feature = "dbus"))]
+        Self::watch_status_changes(cx);
+
         Self {
             room_id,
             room_input,
             title_bar,
             room_cards: vec![],
+            search_input,
+            tag_filter: None,
+            tag_batch_input,
             _subscriptions,
         }
     }
@@ -79,12 +164,82 @@ impl BLiveApp {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let RoomInputEvent::RoomInputSubmit(room_id) = event;
-        self.room_id = *room_id;
+        match event {
+            RoomInputEvent::RoomInputSubmit(room_id) => {
+                log_user_action("点击添加录制按钮", Some(&format!("房间号: {room_id}")));
+
+                self.add_room(*room_id, window, cx);
+            }
+            RoomInputEvent::ImportRooms(rooms) => self.import_rooms(rooms, window, cx),
+        }
+    }
+
+    /// 批量导入房间，已存在的房间保持原有设置
+    fn import_rooms(
+        &mut self,
+        rooms: &[RoomSettings],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (imported, skipped) = cx.update_global(|state: &mut AppState, cx| {
+            let mut imported = 0;
+            let mut skipped = 0;
+            for settings in rooms {
+                if state.has_room(settings.room_id) {
+                    skipped += 1;
+                    continue;
+                }
 
-        let room_id = self.room_id;
+                state.add_room(settings.clone());
+                cx.emit(BLiveAppEvent::InitRoom(settings.clone()));
+                imported += 1;
+            }
+            (imported, skipped)
+        });
+
+        log_user_action(
+            "从录播姬导入房间",
+            Some(&format!("导入: {imported}, 跳过: {skipped}")),
+        );
 
-        log_user_action("点击添加录制按钮", Some(&format!("房间号: {room_id}")));
+        if imported > 0 {
+            AppState::global(cx).save_settings();
+        }
+
+        let message = if skipped > 0 {
+            format!("已导入 {imported} 个房间，跳过 {skipped} 个已存在的房间")
+        } else {
+            format!("已导入 {imported} 个房间")
+        };
+        window.push_notification(Notification::success(message), cx);
+    }
+
+    /// 添加新的录制房间，短号会先解析为真实房间号再去重
+    fn add_room(&mut self, room_id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        let client = AppState::global(cx).client.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let real_room_id = match client.get_live_room_info(room_id).await {
+                Ok(room_info) if room_info.room_id > 0 => room_info.room_id,
+                _ => room_id,
+            };
+
+            if real_room_id != room_id {
+                log_user_action(
+                    "房间短号解析完成",
+                    Some(&format!("短号: {room_id}, 房间号: {real_room_id}")),
+                );
+            }
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                this.insert_room(real_room_id, window, cx);
+            });
+        })
+        .detach();
+    }
+
+    fn insert_room(&mut self, room_id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        self.room_id = room_id;
 
         cx.update_global(|state: &mut AppState, cx| {
             // 检查是否已经存在
@@ -102,6 +257,714 @@ impl BLiveApp {
             }
         });
     }
+
+    /// 将以短号保存的房间合并到真实房间号
+    fn merge_room_alias(&mut self, short_id: u64, room_id: u64, cx: &mut Context<Self>) {
+        log_user_action(
+            "合并短号房间",
+            Some(&format!("短号: {short_id}, 房间号: {room_id}")),
+        );
+
+        let (entity, settings, downloader) = cx.update_global(|state: &mut AppState, _| {
+            let entity = state
+                .get_room_state(short_id)
+                .and_then(|room_state| room_state.entity.clone());
+            let (settings, downloader) = state.merge_room_alias(short_id, room_id);
+            (entity, settings, downloader)
+        });
+
+        // 短号卡片随即移除，不停止的话录制会在后台一直运行
+        if let Some(downloader) = downloader {
+            tracing::info!(
+                "停止短号房间的录制，由真实房间号接着录制 - 短号: {}, 房间号: {}",
+                short_id,
+                room_id
+            );
+            cx.foreground_executor()
+                .spawn(async move {
+                    downloader.stop().await;
+                })
+                .detach();
+        }
+
+        if let Some(entity) = entity {
+            self.room_cards
+                .retain(|card| card.entity_id() != entity.entity_id());
+        }
+
+        if let Some(settings) = settings {
+            cx.emit(BLiveAppEvent::InitRoom(settings));
+        }
+
+        AppState::global(cx).save_settings();
+        cx.notify();
+    }
+
+    /// 上次运行异常退出时提示用户查看崩溃报告
+    fn notify_crash_report(window: &mut Window, cx: &mut Context<Self>) {
+        let Some(report) = take_unseen_crash_report() else {
+            return;
+        };
+
+        cx.spawn_in(window, async move |_, cx| {
+            let _ = cx.update(|window, cx| {
+                window.push_notification(
+                    Notification::warning(
+                        "点击查看崩溃报告，可在设置中导出诊断信息并附到 issue 反馈",
+                    )
+                    .title("检测到上次异常退出")
+                    .on_click(move |_, _, cx| {
+                        cx.reveal_path(&report);
+                    }),
+                    cx,
+                );
+            });
+        })
+        .detach();
+    }
+
+    /// 修复上次异常中断时未正常收尾的录制文件
+    fn repair_unfinished_recordings(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |_, cx| {
+            let repaired = cx
+                .background_executor()
+                .spawn(async { repair_unfinished() })
+                .await;
+
+            let Some(first) = repaired.first().cloned() else {
+                return;
+            };
+
+            let _ = cx.update(|window, cx| {
+                window.push_notification(
+                    Notification::info(format!(
+                        "已自动修复 {} 个异常中断的录制文件，点击查看",
+                        repaired.len()
+                    ))
+                    .title("录制文件已修复")
+                    .on_click(move |_, _, cx| {
+                        cx.reveal_path(&first);
+                    }),
+                    cx,
+                );
+            });
+        })
+        .detach();
+    }
+
+    /// 定期检查磁盘空间与 ffmpeg 等全局问题，更新顶部横幅
+    fn watch_global_issues(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            loop {
+                let Ok((record_dir, record_dirs, limit, action, protected, quotas, metered_action)) =
+                    cx.update(|_, cx| {
+                        let state = AppState::global(cx);
+                        let settings = &state.settings;
+                        let quotas = settings
+                            .rooms
+                            .iter()
+                            .filter_map(|room| {
+                                let quota = room.quota.clone()?;
+                                (!quota.is_empty()).then_some((room.room_id, quota))
+                            })
+                            .collect::<Vec<_>>();
+                        (
+                            settings.record_dir.clone(),
+                            settings.record_dirs(),
+                            settings.storage_limit_gb as u64 * 1024 * 1024 * 1024,
+                            settings.storage_limit_action,
+                            state.recording_files(),
+                            quotas,
+                            settings.metered_action,
+                        )
+                    })
+                else {
+                    break;
+                };
+
+                let (disk_full, ffmpeg_missing, storage_usage, over_quota, metered) = cx
+                    .background_executor()
+                    .spawn(async move {
+                        // 先按房间配额清理，再统计总占用
+                        let over_quota = enforce_room_quotas(&quotas);
+
+                        let disk_full = disk_space(Path::new(&record_dir))
+                            .filter(|space| space.available < LOW_SPACE_THRESHOLD)
+                            .map(|space| GlobalIssue::DiskFull {
+                                record_dir,
+                                available: space.available,
+                            });
+
+                        let storage_usage =
+                            enforce_storage_limit(&record_dirs, limit, action, &protected);
+
+                        // 只反映按需检测的结果，不在这里启动 ffmpeg
+                        (
+                            disk_full,
+                            ffmpeg_status() == FfmpegStatus::Missing,
+                            storage_usage,
+                            over_quota,
+                            is_metered() == Some(true),
+                        )
+                    })
+                    .await;
+
+                let result = this.update(cx, |_, cx| {
+                    let state = AppState::global_mut(cx);
+                    let before = state.issues.clone();
+
+                    state.storage_usage = storage_usage;
+                    if storage_usage.exceeded() {
+                        if !before
+                            .iter()
+                            .any(|issue| matches!(issue, GlobalIssue::StorageLimit { .. }))
+                        {
+                            tracing::warn!(
+                                "录像总占用已达上限，暂停新录制 - 占用: {}, 上限: {}",
+                                pretty_bytes(storage_usage.used),
+                                pretty_bytes(storage_usage.limit)
+                            );
+                        }
+                        state.set_issue(GlobalIssue::StorageLimit {
+                            used: storage_usage.used,
+                            limit: storage_usage.limit,
+                        });
+                    } else {
+                        state
+                            .clear_issue(|issue| matches!(issue, GlobalIssue::StorageLimit { .. }));
+                    }
+
+                    match disk_full {
+                        Some(issue) => state.set_issue(issue),
+                        None => {
+                            state.clear_issue(|issue| matches!(issue, GlobalIssue::DiskFull { .. }))
+                        }
+                    }
+                    if ffmpeg_missing {
+                        state.set_issue(GlobalIssue::FfmpegMissing);
+                    } else {
+                        state.clear_issue(|issue| matches!(issue, GlobalIssue::FfmpegMissing));
+                    }
+
+                    if metered {
+                        if state.metered_action().is_none() {
+                            tracing::info!("当前处于计费网络 - 处理方式: {}", metered_action);
+                        }
+                        state.set_issue(GlobalIssue::MeteredNetwork {
+                            action: metered_action,
+                        });
+                    } else {
+                        state.clear_issue(|issue| {
+                            matches!(issue, GlobalIssue::MeteredNetwork { .. })
+                        });
+                    }
+
+                    if state.issues != before {
+                        cx.notify();
+                    }
+                });
+
+                // 超出配额且设置为暂停的房间交给卡片关闭自动录制
+                if !over_quota.is_empty() {
+                    let _ = this.update(cx, |this, cx| {
+                        for card in &this.room_cards {
+                            if over_quota.contains(&card.read(cx).room_id()) {
+                                card.update(cx, |_, cx| cx.emit(RoomCardEvent::QuotaExceeded));
+                            }
+                        }
+                    });
+                }
+
+                if result.is_err() {
+                    break;
+                }
+
+                cx.background_executor().timer(GLOBAL_ISSUE_INTERVAL).await;
+            }
+        })
+        .detach();
+    }
+
+    /// 启动本地转发服务，供播放器边录边看
+    fn start_relay(cx: &mut Context<Self>) {
+        let port = AppState::global(cx).settings.relay_port;
+        if port > 0
+            && let Err(e) = relay::serve(port)
+        {
+            tracing::error!("本地转发启动失败 - 端口: {}, 错误: {}", port, e);
+        }
+    }
+
+    /// 处理启动参数、后续启动的实例转发来的命令与状态查询
+    fn watch_cli_commands(window: &mut Window, cx: &mut Context<Self>) {
+        let commands = cli::command_receiver();
+
+        cx.spawn_in(window, async move |this, cx| {
+            while let Ok(command) = commands.recv_async().await {
+                let handled = this.update_in(cx, |this, window, cx| {
+                    this.on_cli_command(command, window, cx);
+                });
+
+                if handled.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        let status_requests = cli::status_request_receiver();
+        cx.spawn_in(window, async move |_, cx| {
+            while let Ok(reply) = status_requests.recv_async().await {
+                let Ok(status) = cx.update(|_, cx| AppStatus::collect(AppState::global(cx))) else {
+                    break;
+                };
+
+                let _ = reply.send(serde_json::to_string(&status).unwrap_or_default());
+            }
+        })
+        .detach();
+    }
+
+    /// 全局状态更新后合并一段时间再重新生成状态，忽略速度等持续变化的字段，有变化时通过 D-Bus 发出信号
+    #[cfg(all(target_os = "linux", feature = "dbus"))]
+    fn watch_status_changes(cx: &mut Context<Self>) {
+        use std::{
+            cell::{Cell, RefCell},
+            rc::Rc,
+        };
+
+        let last: Rc<RefCell<Option<AppStatus>>> = Rc::default();
+        let pending = Rc::new(Cell::new(false));
+
+        cx.observe_global::<AppState>(move |_, cx| {
+            // 下载进度每秒都会更新全局状态，合并期间的更新只生成一次状态
+            if pending.replace(true) {
+                return;
+            }
+
+            let last = last.clone();
+            let pending = pending.clone();
+            cx.spawn(async move |_, cx| {
+                cx.background_executor().timer(STATUS_DEBOUNCE).await;
+                pending.set(false);
+
+                let _ = cx.update(|cx| {
+                    let status = AppStatus::collect(AppState::global(cx));
+                    let mut last = last.borrow_mut();
+                    if last.as_ref().is_some_and(|last| last.same_state(&status)) {
+                        return;
+                    }
+
+                    crate::dbus::publish_status(&status);
+                    *last = Some(status);
+                });
+            })
+            .detach();
+        })
+        .detach();
+    }
+
+    fn on_cli_command(&mut self, command: CliCommand, window: &mut Window, cx: &mut Context<Self>) {
+        match command {
+            CliCommand::Activate => show_window(window),
+            CliCommand::AddRoom(room_id) => {
+                log_user_action("命令行添加房间", Some(&format!("房间号: {room_id}")));
+                self.add_room(room_id, window, cx);
+            }
+            CliCommand::StopAll => {
+                log_user_action("命令行停止所有录制", None);
+                self.stop_all_recordings(cx);
+            }
+            CliCommand::ShowRoom(room_id) => {
+                show_window(window);
+                self.show_room(room_id, window, cx);
+            }
+        }
+    }
+
+    /// 房间卡片被搜索或标签过滤隐藏时清除过滤，置顶的房间随即出现在列表最前
+    fn show_room(&mut self, room_id: u64, window: &mut Window, cx: &mut Context<Self>) {
+        let visible = self
+            .visible_room_cards(cx)
+            .iter()
+            .any(|card| card.read(cx).room_id() == room_id);
+        if visible {
+            return;
+        }
+
+        self.tag_filter = None;
+        self.search_input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        cx.notify();
+    }
+
+    /// 停止全部录制，返回停止的房间数
+    fn stop_all_recordings(&mut self, cx: &mut Context<Self>) -> usize {
+        let mut stopped = 0;
+
+        // 与手动停止一致，关闭自动录制，避免下一次轮询又开始录制
+        for card in self.room_cards.clone() {
+            if card.read(cx).downloader.is_some() {
+                card.update(cx, |_, cx| cx.emit(RoomCardEvent::StopRecording(true)));
+                stopped += 1;
+            }
+        }
+
+        AppState::global(cx).save_settings();
+
+        stopped
+    }
+
+    /// 注册停止全部录制的全局热键，设置变化后重新注册
+    fn watch_stop_all_hotkey(window: &mut Window, cx: &mut Context<Self>) {
+        let mut hotkey = match StopAllHotkey::new() {
+            Ok(hotkey) => hotkey,
+            Err(e) => {
+                tracing::warn!("全局热键不可用: {}", e);
+                return;
+            }
+        };
+
+        cx.spawn_in(window, async move |this, cx| {
+            loop {
+                let Ok(shortcut) =
+                    cx.update(|_, cx| AppState::global(cx).settings.stop_all_hotkey.clone())
+                else {
+                    break;
+                };
+
+                if let Err(e) = hotkey.update(&shortcut) {
+                    tracing::warn!("全局热键注册失败 - 热键: {}, 错误: {:#}", shortcut, e);
+                }
+
+                if hotkey.pressed() {
+                    log_user_action("全局热键停止所有录制", Some(&format!("热键: {shortcut}")));
+                    let Ok(stopped) = this.update(cx, |this, cx| this.stop_all_recordings(cx))
+                    else {
+                        break;
+                    };
+
+                    let body = if stopped > 0 {
+                        format!("已停止 {stopped} 个房间的录制，自动录制已关闭")
+                    } else {
+                        "当前没有正在进行的录制".to_string()
+                    };
+                    cx.background_executor()
+                        .spawn(async move { push_system_notification("已停止全部录制", &body) })
+                        .detach();
+                }
+
+                cx.background_executor().timer(HOTKEY_POLL_INTERVAL).await;
+            }
+        })
+        .detach();
+    }
+
+    /// 网络切换后立即重连断流的房间，不等待退避计时
+    fn watch_network_changes(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |_, cx| {
+            let mut watcher = NetworkWatcher::default();
+
+            loop {
+                let snapshot = cx
+                    .background_executor()
+                    .spawn(async { interface_snapshot() })
+                    .await;
+
+                if watcher.observe(snapshot) {
+                    let Ok(reconnected) = cx.update(|_, cx| reconnect_interrupted_rooms(cx)) else {
+                        break;
+                    };
+                    tracing::info!("检测到网络切换，立即重连 {} 个断流房间", reconnected);
+                }
+
+                cx.background_executor().timer(NETWORK_CHECK_INTERVAL).await;
+            }
+        })
+        .detach();
+    }
+
+    /// 定期检查录制中的流地址，即将过期时提前轮换到新地址
+    fn watch_stream_expiry(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |_, cx| {
+            loop {
+                if cx.update(|_, cx| rotate_expiring_streams(cx)).is_err() {
+                    break;
+                }
+
+                cx.background_executor()
+                    .timer(ROTATION_CHECK_INTERVAL)
+                    .await;
+            }
+        })
+        .detach();
+    }
+
+    /// 录制中定期刷新录制锁的心跳，其他实例据此判断房间是否有人在录
+    fn watch_record_locks(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |_, cx| {
+            loop {
+                let refreshed = cx.update(|_, cx| {
+                    for downloader in AppState::global(cx)
+                        .room_states
+                        .iter()
+                        .filter_map(|room| room.downloader.as_ref())
+                    {
+                        downloader.refresh_lock();
+                    }
+                });
+                if refreshed.is_err() {
+                    break;
+                }
+
+                cx.background_executor()
+                    .timer(LOCK_HEARTBEAT_INTERVAL)
+                    .await;
+            }
+        })
+        .detach();
+    }
+
+    /// 定期确认登录 Cookie 仍然有效，失效时在顶部提示重新登录
+    fn watch_login(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            loop {
+                let Ok(client) = cx.update(|_, cx| AppState::global(cx).client.clone()) else {
+                    break;
+                };
+
+                if auth::current().is_some() {
+                    match client.get_nav().await {
+                        Ok(nav) => {
+                            let _ = this.update(cx, |_, cx| {
+                                let state = AppState::global_mut(cx);
+                                if nav.is_login {
+                                    state.clear_issue(|issue| {
+                                        matches!(issue, GlobalIssue::CookieExpired)
+                                    });
+                                } else {
+                                    tracing::warn!("登录 Cookie 已失效，需要重新登录");
+                                    state.set_issue(GlobalIssue::CookieExpired);
+                                }
+                                cx.notify();
+                            });
+                        }
+                        Err(e) => tracing::warn!("检查登录状态失败: {}", e),
+                    }
+                }
+
+                cx.background_executor().timer(LOGIN_CHECK_INTERVAL).await;
+            }
+        })
+        .detach();
+    }
+
+    /// 启动对外心跳：健康检查端点与心跳文件
+    fn start_heartbeat(window: &mut Window, cx: &mut Context<Self>) {
+        let port = AppState::global(cx).settings.healthz_port;
+        if port > 0
+            && let Err(e) = serve_healthz(port)
+        {
+            tracing::error!("健康检查端点启动失败 - 端口: {}, 错误: {}", port, e);
+        }
+
+        cx.spawn_in(window, async move |_, cx| {
+            loop {
+                let Ok(enabled) = cx.update(|_, cx| AppState::global(cx).settings.heartbeat_file)
+                else {
+                    break;
+                };
+
+                if enabled {
+                    cx.background_executor()
+                        .spawn(async { write_heartbeat_file() })
+                        .await;
+                }
+
+                cx.background_executor().timer(HEARTBEAT_INTERVAL).await;
+            }
+        })
+        .detach();
+    }
+
+    /// 定期采样内存占用，超过阈值时提示并输出各子系统对象计数
+    fn watch_memory(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            loop {
+                cx.background_executor().timer(MEMORY_SAMPLE_INTERVAL).await;
+
+                let Some(rss) = cx
+                    .background_executor()
+                    .spawn(async { process_memory() })
+                    .await
+                else {
+                    continue;
+                };
+
+                let result = this.update_in(cx, |_, window, cx| {
+                    let state = AppState::global_mut(cx);
+                    let threshold = (state.settings.memory_warn_mb > 0)
+                        .then(|| state.settings.memory_warn_mb as u64 * 1024 * 1024);
+                    let sample = MemorySample {
+                        timestamp: chrono::Local::now().timestamp_millis(),
+                        rss,
+                    };
+
+                    if !state.monitor.record(sample, threshold) {
+                        return;
+                    }
+
+                    let counts = state
+                        .object_counts()
+                        .into_iter()
+                        .map(|(name, count)| format!("{name}: {count}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    tracing::warn!(
+                        "内存占用过高 - 当前: {}, 对象计数: {}",
+                        pretty_bytes(rss),
+                        counts
+                    );
+
+                    window.push_notification(
+                        Notification::warning(format!(
+                            "当前内存占用 {}，已超过告警阈值，详情已写入日志",
+                            pretty_bytes(rss)
+                        ))
+                        .title("内存占用过高"),
+                        cx,
+                    );
+                });
+
+                if result.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// 定期统计各路录制 ffmpeg 子进程的 CPU 与内存占用
+    fn watch_processes(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let mut sampler = ProcessSampler::default();
+
+            loop {
+                cx.background_executor()
+                    .timer(PROCESS_SAMPLE_INTERVAL)
+                    .await;
+
+                let Ok(processes) = this.update(cx, |_, cx| {
+                    AppState::global(cx)
+                        .room_states
+                        .iter()
+                        .filter_map(|state| {
+                            let pid = state.downloader.as_ref()?.context.ffmpeg_pid()?;
+                            Some((state.room_id, pid))
+                        })
+                        .collect::<Vec<_>>()
+                }) else {
+                    break;
+                };
+
+                let pids = processes.iter().map(|(_, pid)| *pid).collect::<Vec<_>>();
+                let (returned, usages) = cx
+                    .background_executor()
+                    .spawn(async move {
+                        let usages = sampler.sample(&pids);
+                        (sampler, usages)
+                    })
+                    .await;
+                sampler = returned;
+
+                let result = this.update(cx, |_, cx| {
+                    let state = AppState::global_mut(cx);
+                    let mut changed = vec![];
+
+                    for room_state in state.room_states.iter_mut() {
+                        let usage = processes
+                            .iter()
+                            .position(|(room_id, _)| *room_id == room_state.room_id)
+                            .and_then(|index| usages[index]);
+
+                        if room_state.process_usage != usage {
+                            room_state.process_usage = usage;
+                            changed.extend(room_state.entity.clone());
+                        }
+                    }
+
+                    for entity in changed {
+                        cx.notify(entity.entity_id());
+                    }
+                });
+
+                if result.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// 监听剪贴板，出现未添加的直播间链接时提示用户添加
+    fn watch_clipboard(window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let mut last_text = None;
+
+            loop {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+
+                let Ok(text) = cx.update(|_, cx| {
+                    if !AppState::global(cx).settings.clipboard_watch {
+                        return None;
+                    }
+
+                    cx.read_from_clipboard().and_then(|item| item.text())
+                }) else {
+                    break;
+                };
+
+                let Some(text) = text else {
+                    continue;
+                };
+
+                if last_text.as_ref() == Some(&text) {
+                    continue;
+                }
+
+                let room_id = parse_live_room_id(&text);
+                last_text = Some(text);
+
+                let Some(room_id) = room_id else {
+                    continue;
+                };
+
+                let _ = this.update_in(cx, |_, window, cx| {
+                    if AppState::global(cx).has_room(room_id) {
+                        return;
+                    }
+
+                    log_user_action(
+                        "检测到剪贴板直播间链接",
+                        Some(&format!("房间号: {room_id}")),
+                    );
+
+                    let this = cx.entity().downgrade();
+                    window.push_notification(
+                        Notification::info(format!("是否添加房间 {room_id}？点击即可添加"))
+                            .title("检测到直播间链接")
+                            .on_click(move |_, window, cx| {
+                                let _ = this.update(cx, |this, cx| {
+                                    this.add_room(room_id, window, cx);
+                                });
+                            }),
+                        cx,
+                    );
+                });
+            }
+        })
+        .detach();
+    }
 }
 
 impl BLiveApp {
@@ -121,32 +984,122 @@ impl BLiveApp {
                         state.add_room_state(room_id);
 
                         let client = state.client.clone();
-                        cx.spawn(async move |_, cx| {
+                        cx.spawn(async move |this, cx| {
                             loop {
+                                // 轮询统一由调度器限制并发，大量房间启动时不会同时发起请求
+                                let permit = scheduler().acquire(TaskPriority::Polling).await;
                                 let (room_data, user_data) = futures::join!(
                                     client.get_live_room_info(room_id),
                                     client.get_live_room_user_info(room_id)
                                 );
+                                drop(permit);
+
+                                // 封面 URL 变化时才重新下载，离线时使用缓存
+                                if let Ok(room_info) = &room_data
+                                    && !room_info.user_cover.is_empty()
+                                    && let Err(e) = cover::ensure_cover(&client, room_id, &room_info.user_cover).await
+                                {
+                                    tracing::warn!("下载封面失败 - 房间: {}, 错误: {}", room_id, e);
+                                }
+
+                                // 直播中卡片显示关键帧截图，每分钟最多刷新一次
+                                if let Ok(room_info) = &room_data {
+                                    if room_info.live_status == LiveStatus::Live && !room_info.keyframe.is_empty() {
+                                        if let Err(e) = cover::refresh_keyframe(cx.background_executor(), &client, room_id, &room_info.keyframe).await {
+                                            tracing::debug!("下载关键帧截图失败 - 房间: {}, 错误: {}", room_id, e);
+                                        }
+                                    } else {
+                                        cover::clear_keyframe(room_id);
+                                    }
+                                }
+
+                                // 以短号添加的房间，合并到真实房间号后结束当前轮询
+                                if let Ok(room_info) = &room_data
+                                    && room_info.room_id > 0
+                                    && room_info.room_id != room_id
+                                {
+                                    let real_room_id = room_info.room_id;
+                                    let _ = this.update(cx, |this, cx| {
+                                        this.merge_room_alias(room_id, real_room_id, cx);
+                                    });
+                                    break;
+                                }
 
                                 match (room_data, user_data) {
                                     (Ok(room_info), Ok(user_info)) => {
                                         let _ = cx.update_global(|state: &mut AppState, cx| {
                                             let global_settings = state.settings.clone();
                                             let room_settings = state.get_room_settings(room_id).cloned();
+                                            let storage_full = state.storage_usage.exceeded();
+                                            let metered_paused = state.metered_action() == Some(MeteredAction::PauseAutoRecord);
+
+                                            let previous_status = state
+                                                .get_room_state(room_id)
+                                                .and_then(|room_state| room_state.room_info.as_ref())
+                                                .map(|room_info| room_info.live_status);
+                                            if let Some(event) = webhook::live_event(previous_status, room_info.live_status) {
+                                                let payload = WebhookPayload::new(event, room_id, &user_info.info.uname, &room_info.title);
+                                                mqtt::dispatch(state, &payload);
+                                                webhook::dispatch(state, cx, payload);
+                                            }
 
                                             if let (Some(room_state), Some(mut room_settings)) = (state.get_room_state_mut(room_id), room_settings)
                                             {
                                                 let room_settings = room_settings.merge_global(&global_settings);
                                                 let live_status = room_info.live_status;
-                                                room_state.room_info = Some(room_info);
+                                                let title_changed = room_state.update_room_info(room_info);
                                                 room_state.user_info = Some(user_info.info);
 
                                                 match live_status {
                                                     LiveStatus::Live => {
+                                                        room_state.offline_debounce.observe_live();
+                                                        room_state.poll.reset();
+
+                                                        // 换环节就换文件：标题变化时结束当前分段，按新标题重新开始
+                                                        if title_changed
+                                                            && room_settings.split_on_title_change
+                                                            && let Some(downloader) = room_state.downloader.clone()
+                                                            && downloader.is_running()
+                                                        {
+                                                            tracing::info!("直播标题变化，开始新分段 - 房间: {}", room_id);
+                                                            let record_dir = room_settings.record_dir.clone().unwrap_or_default();
+                                                            cx.spawn(async move |cx| {
+                                                                if let Err(e) = downloader.restart(cx, &record_dir).await {
+                                                                    tracing::error!("按标题分段失败 - 房间: {}, 错误: {}", room_id, e);
+                                                                }
+                                                            })
+                                                            .detach();
+                                                            return;
+                                                        }
+
                                                         if !room_settings.auto_record {
                                                             return;
                                                         }
 
+                                                        // 静默时段内只记录开播，时段结束后的轮询会照常开始录制
+                                                        if global_settings.quiet_hours.is_active()
+                                                            && !room_state.downloader.as_ref().is_some_and(|downloader| downloader.is_running())
+                                                        {
+                                                            if !room_state.quiet_skipped {
+                                                                room_state.quiet_skipped = true;
+                                                                let room_info = room_state.room_info.clone().unwrap_or_default();
+                                                                let up_name = room_state.user_info.as_ref().map(|user_info| user_info.uname.clone()).unwrap_or_default();
+                                                                tracing::info!("静默时段内开播，暂不录制 - 房间: {}", room_id);
+                                                                history::append_record(&HistoryRecord::now(
+                                                                    room_id,
+                                                                    HistoryEvent::QuietSkipped { title: room_info.title, up_name },
+                                                                ));
+                                                            }
+                                                            return;
+                                                        }
+
+                                                        // 录像总占用已达上限或计费网络下暂停，等恢复后再开始
+                                                        if (storage_full || metered_paused)
+                                                            && !room_state.downloader.as_ref().is_some_and(|downloader| downloader.is_running())
+                                                        {
+                                                            return;
+                                                        }
+
                                                         if room_state.downloader.is_some()
                                                             && room_state
                                                                 .downloader
@@ -215,7 +1168,20 @@ impl BLiveApp {
                                                         room_state.reconnecting = false;
                                                     }
                                                     LiveStatus::Offline | LiveStatus::Carousel => {
-                                                        if room_state.downloader.is_some() {
+                                                        room_state.quiet_skipped = false;
+
+                                                        // 宽限期内仍在录制时保持高频轮询
+                                                        if room_state.downloader.is_none() {
+                                                            room_state.poll.observe_offline();
+                                                        }
+
+                                                        // 短暂掉线在宽限期内恢复时继续录制，避免产生碎片
+                                                        let grace = Duration::from_secs(global_settings.offline_grace_seconds as u64);
+                                                        if room_state.downloader.is_some()
+                                                            && room_state.offline_debounce.observe_offline(grace)
+                                                        {
+                                                            room_state.offline_debounce = OfflineDebounce::default();
+
                                                             if let Some(downloader) =
                                                                 room_state.downloader.take()
                                                             {
@@ -228,6 +1194,18 @@ impl BLiveApp {
                                                                 room_state.downloader = None;
                                                             }
                                                         }
+
+                                                        // 本场录制已结束，交给卡片关闭自动录制并提示
+                                                        if room_state.stop_after_live
+                                                            && room_state.downloader.is_none()
+                                                        {
+                                                            room_state.stop_after_live = false;
+                                                            if let Some(entity) = room_state.entity.as_ref().and_then(|entity| entity.upgrade()) {
+                                                                entity.update(cx, |_, cx| {
+                                                                    cx.emit(RoomCardEvent::StoppedAfterLive);
+                                                                });
+                                                            }
+                                                        }
                                                     }
                                                 }
 
@@ -235,16 +1213,26 @@ impl BLiveApp {
                                                     if room_state.reconnect_manager.should_reconnect() {
                                                         let delay = room_state.reconnect_manager.calculate_delay();
                                                         let record_dir = room_settings.record_dir.clone().unwrap_or_default();
+                                                        let generation = room_state.reconnect_manager.schedule();
 
                                                         if let Some(downloader) = room_state.downloader.clone() {
                                                             cx.spawn(async move |cx| {
                                                                 cx.background_executor().timer(delay).await;
-                                                                let _ = downloader.restart(cx, &record_dir).await;
+                                                                // 等待期间网络恢复时已提前重连
+                                                                let scheduled = cx
+                                                                    .update_global(|state: &mut AppState, _| {
+                                                                        state
+                                                                            .get_room_state_mut(room_id)
+                                                                            .is_some_and(|room| room.reconnect_manager.take_scheduled(generation))
+                                                                    })
+                                                                    .unwrap_or(false);
+                                                                if scheduled {
+                                                                    let _ = downloader.restart(cx, &record_dir).await;
+                                                                }
                                                             })
                                                             .detach();
                                                         }
 
-                                                        room_state.reconnect_manager.increment_attempt();
                                                         room_state.reconnecting = false;
                                                     }
                                                 }
@@ -260,7 +1248,7 @@ impl BLiveApp {
                                                 if let Some(room_state) =
                                                     state.get_room_state_mut(room_id)
                                                 {
-                                                    room_state.room_info = Some(room_info);
+                                                    room_state.update_room_info(room_info);
 
                                                     if let Some(entity) = room_state.entity.clone() {
                                                         cx.notify(entity.entity_id());
@@ -286,9 +1274,14 @@ impl BLiveApp {
                                         }
                                 }
 
-                                cx.background_executor()
-                                    .timer(Duration::from_secs(10))
-                                    .await;
+                                // 长期未开播的房间逐步拉长轮询间隔
+                                let interval = cx
+                                    .try_read_global(|state: &AppState, _| {
+                                        state.get_room_state(room_id).map(|room_state| room_state.poll.interval())
+                                    })
+                                    .flatten()
+                                    .unwrap_or(POLL_INTERVAL);
+                                cx.background_executor().timer(interval).await;
 
                                 // 检查房间是否移除
                                 if let Some(removed) = cx.try_read_global(|state: &AppState, _| !state.has_room(room_id)) {
@@ -324,24 +1317,387 @@ impl BLiveApp {
         }
     }
 
+    /// 按搜索框与标签过滤后的房间卡片，置顶的房间排在最前
+    fn visible_room_cards(&self, cx: &App) -> Vec<Entity<RoomCard>> {
+        let query = self.search_input.read(cx).value();
+        let state = AppState::global(cx);
+
+        let mut cards = self
+            .room_cards
+            .iter()
+            .filter(|card| {
+                let room_id = card.read(cx).room_id();
+                let Some(settings) = state.get_room_settings(room_id) else {
+                    return true;
+                };
+
+                let room_state = state.get_room_state(room_id);
+                let up_name = room_state
+                    .and_then(|room| room.user_info.as_ref())
+                    .map(|user_info| user_info.uname.as_str())
+                    .unwrap_or_default();
+                let title = room_state
+                    .and_then(|room| room.room_info.as_ref())
+                    .map(|room_info| room_info.title.as_str())
+                    .unwrap_or_default();
+
+                self.tag_filter
+                    .as_ref()
+                    .is_none_or(|tag| settings.has_tag(tag))
+                    && settings.matches_search(&query, up_name, title)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        cards.sort_by_key(|card| {
+            !state
+                .get_room_settings(card.read(cx).room_id())
+                .is_some_and(|settings| settings.pinned)
+        });
+        cards
+    }
+
+    /// 为当前筛选出的房间批量添加或移除标签
+    fn batch_update_tags(&mut self, add: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let tags = parse_tags(&self.tag_batch_input.read(cx).value());
+        if tags.is_empty() {
+            window.push_notification(Notification::warning("请先输入标签"), cx);
+            return;
+        }
+
+        let cards = self.visible_room_cards(cx);
+        for card in &cards {
+            let room_id = card.read(cx).room_id();
+            let updated = AppState::global_mut(cx)
+                .get_room_settings_mut(room_id)
+                .map(|room| {
+                    if add {
+                        for tag in &tags {
+                            if !room.has_tag(tag) {
+                                room.tags.push(tag.clone());
+                            }
+                        }
+                    } else {
+                        room.tags.retain(|tag| !tags.contains(tag));
+                    }
+
+                    room.tags.clone()
+                });
+
+            if let Some(updated) = updated {
+                card.update(cx, |card, cx| card.set_tags(updated, window, cx));
+            }
+        }
+
+        let state = AppState::global(cx);
+        // 选中的标签已从所有房间移除时回到全部
+        if let Some(tag) = &self.tag_filter
+            && !state.settings.all_tags().contains(tag)
+        {
+            self.tag_filter = None;
+        }
+        state.save_settings();
+
+        log_user_action(
+            if add {
+                "批量添加标签"
+            } else {
+                "批量移除标签"
+            },
+            Some(&format!(
+                "标签: {}, 房间数: {}",
+                tags.join(","),
+                cards.len()
+            )),
+        );
+        window.push_notification(
+            Notification::success(format!("已更新 {} 个房间的标签", cards.len())),
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// 标签筛选与批量管理
+    fn render_tag_bar(&self, cx: &Context<Self>) -> impl IntoElement {
+        let tags = AppState::global(cx).settings.all_tags();
+        let chip = |id: String, label: String, tag: Option<String>, selected: bool| {
+            Button::new(id)
+                .label(label)
+                .map(|button| {
+                    if selected {
+                        button.primary()
+                    } else {
+                        button.ghost()
+                    }
+                })
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    this.tag_filter = tag.clone();
+                    cx.notify();
+                }))
+        };
+
+        let chips = std::iter::once(chip(
+            "tag-all".to_string(),
+            "全部".to_string(),
+            None,
+            self.tag_filter.is_none(),
+        ))
+        .chain(tags.into_iter().map(|tag| {
+            let selected = self.tag_filter.as_ref() == Some(&tag);
+            chip(format!("tag-{tag}"), tag.clone(), Some(tag), selected)
+        }))
+        .collect::<Vec<_>>();
+
+        h_flex()
+            .flex_wrap()
+            .gap_2()
+            .items_center()
+            .children(chips)
+            .child(div().flex_1())
+            .child(div().w_48().child(TextInput::new(&self.tag_batch_input)))
+            .child(
+                Button::new("tag-batch-add")
+                    .label("添加标签")
+                    .tooltip("为当前筛选出的房间添加标签")
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.batch_update_tags(true, window, cx);
+                    })),
+            )
+            .child(
+                Button::new("tag-batch-remove")
+                    .label("移除标签")
+                    .tooltip("从当前筛选出的房间移除标签")
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.batch_update_tags(false, window, cx);
+                    })),
+            )
+    }
+
     fn on_room_card_event(
         &mut self,
         _: Entity<RoomCard>,
         event: &RoomCardEvent,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
-        if let RoomCardEvent::Deleted(entity_id) = event {
-            self.room_cards
-                .retain(|card| card.entity_id() != *entity_id);
+        match event {
+            RoomCardEvent::Deleted(entity_id) => {
+                self.room_cards
+                    .retain(|card| card.entity_id() != *entity_id);
+            }
+            RoomCardEvent::PinnedChanged => cx.notify(),
+            _ => {}
         }
     }
 }
 
+/// 统计 BLive 录制的录像占用，超过上限且设置为清理时从最旧的录像开始删除，返回处理后的占用
+fn enforce_storage_limit(
+    record_dirs: &[PathBuf],
+    limit: u64,
+    action: StorageLimitAction,
+    protected: &[PathBuf],
+) -> StorageUsage {
+    // 只处理历史记录中录制完成的文件，录制目录下用户自己的文件不计入也不删除
+    let recorded = storage::recorded_set(history::completed_files(&history::load_records()));
+    let files = storage::scan_recordings(record_dirs, &recorded);
+    let mut usage = StorageUsage {
+        used: files.iter().map(|file| file.size).sum(),
+        limit,
+    };
+
+    if !usage.exceeded() || action != StorageLimitAction::DeleteOldest {
+        return usage;
+    }
+
+    for file in storage::select_for_cleanup(&files, limit, protected) {
+        match storage::remove_recording(&file.path) {
+            Ok(size) => {
+                usage.used = usage.used.saturating_sub(size);
+                tracing::info!(
+                    "录像总占用超过上限，已删除最旧录像: {} ({})",
+                    file.path.display(),
+                    pretty_bytes(size)
+                );
+            }
+            Err(e) => tracing::warn!("删除录像失败: {} - {}", file.path.display(), e),
+        }
+    }
+
+    usage
+}
+
+/// 重连断流后尚未重连或仍在退避等待的房间，清零重连计数，返回重连的房间数
+fn reconnect_interrupted_rooms(cx: &mut App) -> usize {
+    let restarts = cx.update_global(|state: &mut AppState, _| {
+        let global_settings = state.settings.clone();
+        let mut restarts = vec![];
+
+        for room_id in state
+            .room_states
+            .iter()
+            .map(|room| room.room_id)
+            .collect::<Vec<_>>()
+        {
+            let record_dir = state
+                .get_room_settings(room_id)
+                .cloned()
+                .map(|mut settings| settings.merge_global(&global_settings))
+                .and_then(|settings| settings.record_dir)
+                .unwrap_or_default();
+            let Some(room) = state.get_room_state_mut(room_id) else {
+                continue;
+            };
+            let Some(downloader) = room.downloader.clone() else {
+                continue;
+            };
+
+            if room.reconnecting {
+                room.reconnecting = false;
+                room.reconnect_manager.reset_attempts();
+            } else if !room.reconnect_manager.skip_backoff() {
+                continue;
+            }
+
+            restarts.push((room_id, downloader, record_dir));
+        }
+
+        restarts
+    });
+
+    let count = restarts.len();
+    for (room_id, downloader, record_dir) in restarts {
+        cx.spawn(async move |cx| {
+            if let Err(e) = downloader.restart(cx, &record_dir).await {
+                tracing::warn!("网络切换后重连失败 - 房间: {}, 错误: {}", room_id, e);
+            }
+        })
+        .detach();
+    }
+
+    count
+}
+
+/// 流地址即将过期的房间执行计划内轮换，返回开始轮换的房间数
+fn rotate_expiring_streams(cx: &mut App) -> usize {
+    let now = chrono::Local::now().timestamp();
+    let rotations = cx.update_global(|state: &mut AppState, _| {
+        let global_settings = state.settings.clone();
+
+        state
+            .room_states
+            .iter()
+            .filter_map(|room| {
+                let downloader = room.downloader.clone()?;
+                let expires_at = downloader.take_rotation_due(now)?;
+                let record_dir = state
+                    .get_room_settings(room.room_id)
+                    .cloned()
+                    .map(|mut settings| settings.merge_global(&global_settings))
+                    .and_then(|settings| settings.record_dir)
+                    .unwrap_or_default();
+
+                Some((room.room_id, downloader, record_dir, expires_at))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let count = rotations.len();
+    for (room_id, downloader, record_dir, expires_at) in rotations {
+        cx.spawn(async move |cx| {
+            if let Err(e) = downloader.rotate(cx, &record_dir, expires_at).await {
+                tracing::warn!("计划内轮换失败 - 房间: {}, 错误: {}", room_id, e);
+            }
+        })
+        .detach();
+    }
+
+    count
+}
+
+/// 按房间配额处理录像，设置为清理时删除最旧的场次，返回超出配额且设置为暂停的房间
+fn enforce_room_quotas(quotas: &[(u64, RoomQuota)]) -> Vec<u64> {
+    if quotas.is_empty() {
+        return vec![];
+    }
+
+    let records = history::load_records();
+    let mut over_quota = vec![];
+
+    for (room_id, quota) in quotas {
+        // 只统计仍存在的录像，已被手动删除的场次不计入；出现第二个分 P 后首个分 P 已被移动
+        let sessions = history::session_files(&records, *room_id)
+            .into_iter()
+            .map(|files| {
+                files
+                    .into_iter()
+                    .filter_map(|path| storage::locate_recording(&path))
+                    .filter_map(|path| Some((std::fs::metadata(&path).ok()?.len(), path)))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|files| !files.is_empty())
+            .collect::<Vec<_>>();
+        let sizes = sessions
+            .iter()
+            .map(|files| files.iter().map(|(size, _)| size).sum())
+            .collect::<Vec<u64>>();
+
+        let count = storage::sessions_over_quota(
+            &sizes,
+            quota.max_gb as u64 * 1024 * 1024 * 1024,
+            quota.max_sessions as usize,
+        );
+        if count == 0 {
+            continue;
+        }
+
+        match quota.action {
+            StorageLimitAction::Pause => over_quota.push(*room_id),
+            StorageLimitAction::DeleteOldest => {
+                for (_, path) in sessions.iter().take(count).flatten() {
+                    match storage::remove_recording(path) {
+                        Ok(size) => tracing::info!(
+                            "房间录像超出配额，已删除最旧录像 - 房间: {}, 文件: {} ({})",
+                            room_id,
+                            path.display(),
+                            pretty_bytes(size)
+                        ),
+                        Err(e) => tracing::warn!("删除录像失败: {} - {}", path.display(), e),
+                    }
+                }
+            }
+        }
+    }
+
+    over_quota
+}
+
+/// 显示并激活窗口，Windows 上已最小化的窗口先还原
+pub fn show_window(window: &mut Window) {
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use raw_window_handle::HasWindowHandle;
+        use windows::Win32::Foundation::*;
+        use windows::Win32::UI::WindowsAndMessaging::{SW_RESTORE, ShowWindow};
+
+        if let Ok(handle) = window.window_handle()
+            && let raw_window_handle::RawWindowHandle::Win32(handle) = handle.as_raw()
+        {
+            let _ = ShowWindow(HWND(handle.hwnd.get() as *mut std::ffi::c_void), SW_RESTORE);
+        }
+    }
+
+    window.activate_window();
+}
+
 impl Render for BLiveApp {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        profile_scope!("render.app");
+
         let modal_layer = Root::render_modal_layer(window, cx);
         let notification_layer = Root::render_notification_layer(window, cx);
+        let room_cards = self.visible_room_cards(cx);
         let state = AppState::global(cx);
+        let issues = state.issues.clone();
         let recording_count = state
             .room_states
             .iter()
@@ -356,6 +1712,9 @@ impl Render for BLiveApp {
             .min_w_full()
             .min_h_full()
             .child(self.title_bar.clone())
+            .when(!issues.is_empty(), |this| {
+                this.child(div().px_8().pt_4().child(GlobalBanner::new(issues)))
+            })
             .child(
                 v_flex()
                 .flex_1()
@@ -420,6 +1779,12 @@ impl Render for BLiveApp {
                                                                     .text_lg()
                                                                     .child(Text::String("录制房间列表".into())),
                                                             )
+                                                            .child(
+                                                                div()
+                                                                    .flex_1()
+                                                                    .max_w_64()
+                                                                    .child(TextInput::new(&self.search_input)),
+                                                            )
                                                             .child(
                                                                 div()
                                                                     .px_3()
@@ -434,6 +1799,7 @@ impl Render for BLiveApp {
                                     )),
                                                             ),
                                                     )
+                                                    .child(self.render_tag_bar(cx))
                                                     .child(
                                                         // 统计信息
                                                         div()
@@ -507,7 +1873,7 @@ impl Render for BLiveApp {
                                                                         .size_full()
                                                                         .gap_4()
                                                                         .scrollable(Axis::Vertical)
-                                                                        .children(self.room_cards.to_vec()),
+                                                                        .children(room_cards),
                                                                 )
                                                         } else {
                                                             div()
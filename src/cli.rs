@@ -0,0 +1,407 @@
+//! 命令行参数与单实例转发
+//!
+//! 已有实例运行时，新进程把命令转发给它后直接退出，便于脚本与快捷方式集成。
+//! 端口只监听本机，每次连接的第一行须是实例启动时写入令牌文件的令牌，其他用户的进程读不到令牌
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use crate::{
+    clipboard::parse_live_room_id,
+    service::ServiceAction,
+    settings::{config_dir, write_private},
+};
+
+pub const USAGE: &str = "用法: blive [选项]
+      blive status [--json]
+      blive service <install|uninstall>
+
+命令:
+    status                           查询运行中实例的各房间状态，--json 输出 JSON
+                                     返回码: 0 正常，1 有房间出错或存在全局问题，3 没有运行中的实例
+    service install                  登录系统后自动最小化启动并开始录制，可配合 --config
+    service uninstall                取消自动启动
+
+选项:
+    --add-room <房间号或直播间链接>  添加录制房间，可重复指定
+    --minimized                      启动时最小化窗口
+    --config <路径>                  使用指定的设置文件
+    --stop-all                       停止所有正在进行的录制
+    -h, --help                       显示帮助";
+
+/// 与已运行实例通信的超时时间
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 查询状态的请求行，不属于交给主窗口的命令
+const STATUS_REQUEST: &str = "status";
+
+/// 携带令牌的请求行前缀
+const TOKEN_PREFIX: &str = "token ";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CliArgs {
+    pub add_rooms: Vec<u64>,
+    pub minimized: bool,
+    pub config: Option<PathBuf>,
+    pub stop_all: bool,
+    /// `status` 子命令
+    pub status: bool,
+    pub json: bool,
+    /// `service` 子命令
+    pub service: Option<ServiceAction>,
+    pub help: bool,
+}
+
+/// 交给主窗口处理的命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    /// 显示并激活主窗口
+    Activate,
+    AddRoom(u64),
+    StopAll,
+    /// 显示主窗口并定位到房间卡片
+    ShowRoom(u64),
+}
+
+impl CliCommand {
+    fn to_line(&self) -> String {
+        match self {
+            CliCommand::Activate => "activate".to_string(),
+            CliCommand::AddRoom(room_id) => format!("add-room {room_id}"),
+            CliCommand::StopAll => "stop-all".to_string(),
+            CliCommand::ShowRoom(room_id) => format!("show-room {room_id}"),
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        let command = match (parts.next()?, parts.next()) {
+            ("activate", None) => CliCommand::Activate,
+            ("add-room", Some(room_id)) => CliCommand::AddRoom(room_id.parse().ok()?),
+            ("stop-all", None) => CliCommand::StopAll,
+            ("show-room", Some(room_id)) => CliCommand::ShowRoom(room_id.parse().ok()?),
+            _ => return None,
+        };
+
+        parts.next().is_none().then_some(command)
+    }
+}
+
+impl CliArgs {
+    /// 解析命令行参数，不含程序名
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = |name: &str| {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} 缺少参数值"))
+            };
+
+            match name {
+                "--add-room" => {
+                    let room = value(name)?;
+                    let room_id = room
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|room_id| *room_id > 0)
+                        .or_else(|| parse_live_room_id(&room))
+                        .ok_or_else(|| format!("无效的房间号: {room}"))?;
+                    if !parsed.add_rooms.contains(&room_id) {
+                        parsed.add_rooms.push(room_id);
+                    }
+                }
+                "--config" => parsed.config = Some(PathBuf::from(value(name)?)),
+                "--minimized" => parsed.minimized = true,
+                "--stop-all" => parsed.stop_all = true,
+                "status" => parsed.status = true,
+                "--json" => parsed.json = true,
+                "service" => parsed.service = Some(value(name)?.parse()?),
+                "-h" | "--help" => parsed.help = true,
+                // macOS 从 Finder 启动时会附带进程序列号
+                name if name.starts_with("-psn_") => {}
+                _ => return Err(format!("未知参数: {arg}")),
+            }
+        }
+
+        if parsed.json && !parsed.status {
+            return Err("--json 只能与 status 一起使用".to_string());
+        }
+
+        Ok(parsed)
+    }
+
+    /// 需要主窗口处理的命令
+    ///
+    /// 没有其他命令时激活窗口，重复启动等同于打开主窗口
+    pub fn commands(&self) -> Vec<CliCommand> {
+        let mut commands = self
+            .add_rooms
+            .iter()
+            .map(|room_id| CliCommand::AddRoom(*room_id))
+            .collect::<Vec<_>>();
+
+        if self.stop_all {
+            commands.push(CliCommand::StopAll);
+        }
+
+        if commands.is_empty() && !self.minimized {
+            commands.push(CliCommand::Activate);
+        }
+
+        commands
+    }
+}
+
+static COMMANDS: LazyLock<(flume::Sender<CliCommand>, flume::Receiver<CliCommand>)> =
+    LazyLock::new(flume::unbounded);
+
+type StatusReply = flume::Sender<String>;
+
+static STATUS_REQUESTS: LazyLock<(flume::Sender<StatusReply>, flume::Receiver<StatusReply>)> =
+    LazyLock::new(flume::unbounded);
+
+/// 把命令交给主窗口，窗口尚未创建时会在创建后处理
+pub fn dispatch(command: CliCommand) {
+    let _ = COMMANDS.0.send(command);
+}
+
+/// 主窗口接收命令的通道
+pub fn command_receiver() -> flume::Receiver<CliCommand> {
+    COMMANDS.1.clone()
+}
+
+/// 主窗口接收状态查询的通道，收到后把状态 JSON 发回
+pub fn status_request_receiver() -> flume::Receiver<StatusReply> {
+    STATUS_REQUESTS.1.clone()
+}
+
+/// 由主窗口生成状态 JSON，主窗口未响应时返回 `None`
+pub(crate) fn collect_status() -> Option<String> {
+    let (reply, response) = flume::bounded(1);
+    STATUS_REQUESTS.0.send(reply).ok()?;
+    response.recv_timeout(FORWARD_TIMEOUT).ok()
+}
+
+/// 记录当前实例监听端口的文件，不同设置文件的实例互不影响
+fn instance_file() -> PathBuf {
+    config_dir().join("instance.port")
+}
+
+/// 转发命令须携带的令牌，只有当前用户可读
+fn token_file() -> PathBuf {
+    config_dir().join("instance.token")
+}
+
+/// 尝试把命令转发给已运行的实例，成功时返回 true
+pub fn forward_to_running(commands: &[CliCommand]) -> bool {
+    let lines = commands.iter().map(CliCommand::to_line).collect::<Vec<_>>();
+
+    request_running(&lines).is_some()
+}
+
+/// 向已运行的实例查询状态 JSON，没有运行中的实例时返回 `None`
+pub fn query_status() -> Option<String> {
+    request_running(&[STATUS_REQUEST.to_string()])?
+        .into_iter()
+        .next()
+}
+
+/// 发送请求行并返回应答，没有运行中的实例时返回 `None`
+fn request_running(lines: &[String]) -> Option<Vec<String>> {
+    let port = std::fs::read_to_string(instance_file())
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok())?;
+    let token = std::fs::read_to_string(token_file()).ok()?;
+
+    match request(port, token.trim(), lines) {
+        Ok(reply) => reply,
+        Err(e) => {
+            tracing::debug!("未连接到已运行的实例: {}", e);
+            None
+        }
+    }
+}
+
+fn request(port: u16, token: &str, lines: &[String]) -> io::Result<Option<Vec<String>>> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, FORWARD_TIMEOUT)?;
+    // 查询状态时对方还要等待主窗口响应
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT * 2))?;
+
+    writeln!(stream, "{TOKEN_PREFIX}{token}")?;
+    for line in lines {
+        writeln!(stream, "{line}")?;
+    }
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    // 应答以 ok 结尾；端口文件可能已过期且端口被其他程序占用，只认本程序的应答
+    let mut reply = BufReader::new(&stream)
+        .lines()
+        .collect::<io::Result<Vec<_>>>()?;
+    if reply.pop().as_deref() != Some("ok") {
+        return Ok(None);
+    }
+
+    Ok(Some(reply))
+}
+
+/// 监听后续启动的实例转发来的命令
+pub fn serve_instance() -> io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let port = listener.local_addr()?.port();
+    let token = format!("{:032x}", rand::random::<u128>());
+    // 先写令牌再写端口，读到新端口的进程一定能读到对应的令牌
+    write_private(&token_file(), token.as_bytes())?;
+    std::fs::write(instance_file(), port.to_string())?;
+    tracing::info!("单实例转发已启动 - 端口: {}", port);
+
+    std::thread::Builder::new()
+        .name("instance".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = handle_connection(stream, &token) {
+                    tracing::debug!("处理转发命令失败: {}", e);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, token: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+
+    let lines = BufReader::new(&stream)
+        .lines()
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // 令牌不符时不执行任何命令，也不应答
+    let mut lines = lines.into_iter();
+    let authorized = lines
+        .next()
+        .as_deref()
+        .and_then(|line| line.strip_prefix(TOKEN_PREFIX))
+        == Some(token);
+    if !authorized {
+        tracing::warn!("拒绝未携带有效令牌的转发连接");
+        return Ok(());
+    }
+
+    for line in lines {
+        if line == STATUS_REQUEST {
+            // JSON 中不含换行，按行应答
+            let status = collect_status().unwrap_or_else(|| "null".to_string());
+            writeln!(stream, "{status}")?;
+            continue;
+        }
+
+        match CliCommand::from_line(&line) {
+            Some(command) => {
+                tracing::info!("收到转发的命令: {}", line);
+                dispatch(command);
+            }
+            None => tracing::warn!("忽略无法识别的转发命令: {}", line),
+        }
+    }
+
+    stream.write_all(b"ok\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&[
+            "--add-room",
+            "732",
+            "--add-room=https://live.bilibili.com/21452505",
+            "--add-room",
+            "732",
+            "--minimized",
+            "--config",
+            "/tmp/blive/settings.json",
+        ])
+        .unwrap();
+
+        assert_eq!(args.add_rooms, [732, 21452505]);
+        assert!(args.minimized);
+        assert!(!args.stop_all);
+        assert_eq!(args.config, Some(PathBuf::from("/tmp/blive/settings.json")));
+        assert_eq!(
+            args.commands(),
+            [CliCommand::AddRoom(732), CliCommand::AddRoom(21452505)]
+        );
+
+        assert_eq!(parse(&[]).unwrap().commands(), [CliCommand::Activate]);
+        assert_eq!(
+            parse(&["--stop-all"]).unwrap().commands(),
+            [CliCommand::StopAll]
+        );
+        assert!(parse(&["--minimized"]).unwrap().commands().is_empty());
+
+        assert!(parse(&["--add-room"]).is_err());
+        assert!(parse(&["--add-room", "abc"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+
+        let args = parse(&["status", "--json"]).unwrap();
+        assert!(args.status && args.json);
+        assert!(parse(&["--json"]).is_err());
+
+        let args = parse(&["service", "install", "--config", "settings.json"]).unwrap();
+        assert_eq!(args.service, Some(ServiceAction::Install));
+        assert!(parse(&["service"]).is_err());
+        assert!(parse(&["service", "start"]).is_err());
+    }
+
+    #[test]
+    fn test_command_line() {
+        for command in [
+            CliCommand::Activate,
+            CliCommand::AddRoom(732),
+            CliCommand::StopAll,
+            CliCommand::ShowRoom(732),
+        ] {
+            assert_eq!(CliCommand::from_line(&command.to_line()), Some(command));
+        }
+
+        assert_eq!(CliCommand::from_line("add-room"), None);
+        assert_eq!(CliCommand::from_line("stop-all now"), None);
+        assert_eq!(CliCommand::from_line("GET / HTTP/1.1"), None);
+    }
+
+    #[test]
+    fn test_forward_requires_token() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = handle_connection(stream, "secret");
+            }
+        });
+
+        let lines = ["show-room 732".to_string()];
+        assert_eq!(request(port, "guess", &lines).unwrap(), None);
+        assert_eq!(request(port, "secret", &lines).unwrap(), Some(vec![]));
+        assert_eq!(command_receiver().try_recv(), Ok(CliCommand::ShowRoom(732)));
+        assert!(command_receiver().is_empty());
+    }
+}
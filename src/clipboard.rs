@@ -0,0 +1,42 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static LIVE_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"live\.bilibili\.com/(?:h5/|blanc/)?(\d+)").expect("无效的直播间链接正则")
+});
+
+/// 从文本中解析 B 站直播间链接对应的房间号
+pub fn parse_live_room_id(text: &str) -> Option<u64> {
+    LIVE_URL_REGEX
+        .captures(text)
+        .and_then(|captures| captures.get(1))
+        .and_then(|room_id| room_id.as_str().parse().ok())
+        .filter(|room_id| *room_id > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_live_room_id() {
+        assert_eq!(
+            parse_live_room_id("https://live.bilibili.com/1804892069"),
+            Some(1804892069)
+        );
+        assert_eq!(
+            parse_live_room_id("https://live.bilibili.com/732?spm_id_from=333.1007"),
+            Some(732)
+        );
+        assert_eq!(
+            parse_live_room_id("快来看 https://live.bilibili.com/h5/21452505 直播"),
+            Some(21452505)
+        );
+        assert_eq!(
+            parse_live_room_id("https://www.bilibili.com/video/BV1xx"),
+            None
+        );
+        assert_eq!(parse_live_room_id("1804892069"), None);
+    }
+}
@@ -2,6 +2,11 @@ use std::sync::{Arc, atomic};
 
 use crate::{
     components::{SettingsModal, SettingsModalEvent},
+    core::{
+        dns,
+        scheduler::{TaskPriority, scheduler},
+    },
+    logger::{apply_log_levels, cleanup_logs},
     state::AppState,
 };
 use gpui::{App, Entity, FocusHandle, Focusable, Subscription, Window, div, prelude::*};
@@ -45,8 +50,19 @@ impl AppSettings {
     ) {
         match event {
             SettingsModalEvent::SaveSettings(settings) => {
-                AppState::global_mut(cx).settings = settings.clone();
-                settings.save();
+                let state = AppState::global_mut(cx);
+                state.replace_settings(settings.clone());
+                state.save_settings();
+                apply_log_levels(&settings.log_levels);
+                dns::resolver().configure(&settings.network);
+
+                let retention_days = settings.log_retention_days;
+                cx.background_executor()
+                    .spawn(async move {
+                        let _permit = scheduler().acquire(TaskPriority::Cleanup).await;
+                        cleanup_logs(retention_days);
+                    })
+                    .detach();
             }
             SettingsModalEvent::QuitSettings => {
                 self.show.store(false, atomic::Ordering::Relaxed);
@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+
+use gpui::{App, ClickEvent, Entity, Window, prelude::*};
+use gpui_component::{
+    ContextModal, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{InputState, TextInput},
+    notification::Notification,
+    text::Text,
+    v_flex,
+};
+
+use crate::{
+    core::clip::{clip_path, export_clip, parse_timestamp},
+    logger::log_user_action,
+};
+
+/// 选择起止时间，从完成的录像中导出切片
+pub struct ClipModal {
+    file_path: PathBuf,
+    start_input: Entity<InputState>,
+    end_input: Entity<InputState>,
+}
+
+impl ClipModal {
+    fn new(file_path: PathBuf, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let start_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("00:00:00")
+                .default_value("00:00:00")
+        });
+        let end_input = cx.new(|cx| InputState::new(window, cx).placeholder("00:05:00"));
+
+        Self {
+            file_path,
+            start_input,
+            end_input,
+        }
+    }
+
+    pub fn view(file_path: PathBuf, window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(file_path, window, cx))
+    }
+
+    fn export(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let start = parse_timestamp(&self.start_input.read(cx).value());
+        let end = parse_timestamp(&self.end_input.read(cx).value());
+
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) if end > start => (start, end),
+            (Some(_), Some(_)) => {
+                window.push_notification(Notification::warning("结束时间必须晚于开始时间"), cx);
+                return;
+            }
+            _ => {
+                window
+                    .push_notification(Notification::warning("请输入 时:分:秒 格式的起止时间"), cx);
+                return;
+            }
+        };
+
+        let input = self.file_path.clone();
+        let output = clip_path(&input, start, end);
+        log_user_action(
+            "导出切片",
+            Some(&format!("文件: {}, 区间: {start}-{end}", input.display())),
+        );
+        window.close_modal(cx);
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let output = output.clone();
+                    async move { export_clip(&input, start, end, &output) }
+                })
+                .await;
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(()) => window.push_notification(
+                    Notification::success(format!("切片已保存到 {}", output.display())),
+                    cx,
+                ),
+                Err(e) => {
+                    tracing::error!("导出切片失败: {}", e);
+                    window.push_notification(Notification::error(format!("导出切片失败: {e}")), cx)
+                }
+            });
+        })
+        .detach();
+    }
+}
+
+impl Render for ClipModal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_4()
+            .child(Text::String(
+                self.file_path.to_string_lossy().to_string().into(),
+            ))
+            .child(
+                h_flex()
+                    .gap_4()
+                    .child(
+                        v_flex()
+                            .font_bold()
+                            .gap_2()
+                            .child(Text::String("开始时间".into()))
+                            .child(TextInput::new(&self.start_input).max_w_32()),
+                    )
+                    .child(
+                        v_flex()
+                            .font_bold()
+                            .gap_2()
+                            .child(Text::String("结束时间".into()))
+                            .child(TextInput::new(&self.end_input).max_w_32()),
+                    ),
+            )
+            .child(
+                h_flex().justify_end().child(
+                    Button::new("export-clip")
+                        .primary()
+                        .label("导出切片")
+                        .on_click(cx.listener(Self::export)),
+                ),
+            )
+    }
+}
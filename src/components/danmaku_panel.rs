@@ -0,0 +1,86 @@
+use chrono::{DateTime, Local};
+use gpui::{App, Axis, IntoElement, RenderOnce, Window, div, prelude::*, px};
+use gpui_component::{ActiveTheme as _, StyledExt, h_flex, text::Text, v_flex};
+
+use crate::core::danmaku::{DanmakuBuffer, DanmakuFilter};
+use crate::profile_scope;
+
+/// 房间卡片展开后的弹幕滚动面板，最新的弹幕显示在最上方
+#[derive(IntoElement)]
+pub struct DanmakuPanel {
+    buffer: DanmakuBuffer,
+    filter: DanmakuFilter,
+}
+
+impl DanmakuPanel {
+    pub fn new(buffer: DanmakuBuffer, filter: DanmakuFilter) -> Self {
+        Self { buffer, filter }
+    }
+}
+
+impl RenderOnce for DanmakuPanel {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        profile_scope!("render.danmaku_panel");
+
+        let messages = self
+            .buffer
+            .iter()
+            .rev()
+            .filter(|message| !self.filter.is_blocked(message))
+            .map(|message| {
+                let time = DateTime::from_timestamp_millis(message.timestamp)
+                    .map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                let highlighted = self.filter.is_highlighted(message);
+
+                h_flex()
+                    .gap_2()
+                    .text_sm()
+                    .child(
+                        div()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String(time.into())),
+                    )
+                    .child(
+                        div()
+                            .font_semibold()
+                            .text_color(cx.theme().primary)
+                            .child(Text::String(format!("{}:", message.uname).into())),
+                    )
+                    .child(
+                        div()
+                            .when(highlighted, |div| {
+                                div.font_bold().text_color(cx.theme().warning)
+                            })
+                            .child(Text::String(message.content.clone().into())),
+                    )
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .rounded_lg()
+            .p_3()
+            .h(px(240.))
+            .border(px(1.0))
+            .border_color(cx.theme().border)
+            .bg(cx.theme().background)
+            .map(|this| {
+                if messages.is_empty() {
+                    this.justify_center().items_center().child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String("暂无弹幕".into())),
+                    )
+                } else {
+                    this.child(
+                        v_flex()
+                            .size_full()
+                            .gap_1()
+                            .scrollable(Axis::Vertical)
+                            .children(messages),
+                    )
+                }
+            })
+    }
+}
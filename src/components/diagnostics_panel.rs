@@ -0,0 +1,349 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use gpui::{App, ClickEvent, Entity, Window, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme as _, ColorName, Disableable, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    tag::Tag,
+    text::Text,
+    v_flex,
+};
+
+use crate::{
+    core::{
+        downloader::{error::ReconnectReason, utils::pretty_bytes},
+        history::{load_records, reconnect_reason_counts},
+        scheduler::scheduler,
+    },
+    diagnostics::health::{
+        HealthCheck, HealthStatus, check_cookie, check_disk_space, check_ffmpeg, check_hwaccel,
+        check_network, check_record_dir,
+    },
+    logger::log_user_action,
+    state::AppState,
+};
+
+/// 诊断页面，一键检测运行环境
+pub struct DiagnosticsPanel {
+    checks: Vec<HealthCheck>,
+    reconnect_counts: BTreeMap<ReconnectReason, u32>,
+    running: bool,
+}
+
+impl DiagnosticsPanel {
+    pub fn view(_window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|_| Self {
+            checks: vec![],
+            reconnect_counts: BTreeMap::new(),
+            running: false,
+        })
+    }
+
+    fn run_checks(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        if self.running {
+            return;
+        }
+
+        log_user_action("运行诊断检查", None);
+
+        self.running = true;
+        self.checks.clear();
+        cx.notify();
+
+        let state = AppState::global(cx);
+        let client = state.client.clone();
+        let record_dir = PathBuf::from(&state.settings.record_dir);
+
+        cx.spawn(async move |this, cx| {
+            let mut checks = vec![check_network(&client).await];
+
+            let (local_checks, reconnect_counts) = cx
+                .background_executor()
+                .spawn(async move {
+                    let checks = vec![
+                        check_ffmpeg(),
+                        check_hwaccel(),
+                        check_record_dir(&record_dir),
+                        check_disk_space(&record_dir),
+                    ];
+                    let reconnect_counts = reconnect_reason_counts(&load_records(), None);
+
+                    (checks, reconnect_counts)
+                })
+                .await;
+            checks.extend(local_checks);
+            checks.push(check_cookie(&client).await);
+
+            let _ = this.update(cx, |this, cx| {
+                this.checks = checks;
+                this.reconnect_counts = reconnect_counts;
+                this.running = false;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+}
+
+/// 内存曲线的柱高
+const MEMORY_CHART_HEIGHT: f32 = 48.0;
+
+impl DiagnosticsPanel {
+    fn render_memory(&self, cx: &App) -> impl IntoElement {
+        let monitor = &AppState::global(cx).monitor;
+        let peak = monitor.peak().unwrap_or_default();
+
+        v_flex()
+            .gap_y_2()
+            .child(div().font_bold().child(Text::String("内存占用".into())))
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(Text::String(
+                        match monitor.latest() {
+                            Some(latest) => format!(
+                                "当前: {}，峰值: {}",
+                                pretty_bytes(latest.rss),
+                                pretty_bytes(peak)
+                            ),
+                            None => "暂无采样数据".to_string(),
+                        }
+                        .into(),
+                    )),
+            )
+            .when(peak > 0, |this| {
+                this.child(
+                    h_flex()
+                        .h(px(MEMORY_CHART_HEIGHT))
+                        .items_end()
+                        .gap_px()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .children(monitor.samples().map(|sample| {
+                            let height = sample.rss as f32 / peak as f32 * MEMORY_CHART_HEIGHT;
+
+                            div()
+                                .flex_1()
+                                .max_w_1()
+                                .h(px(height.max(1.0)))
+                                .bg(cx.theme().primary)
+                        })),
+                )
+            })
+    }
+}
+
+impl DiagnosticsPanel {
+    /// 录像总占用与上限的比例，每次全局检查时更新
+    fn render_storage(&self, cx: &App) -> impl IntoElement {
+        let usage = AppState::global(cx).storage_usage;
+
+        v_flex()
+            .gap_y_2()
+            .child(div().font_bold().child(Text::String("录像占用".into())))
+            .child(
+                h_flex()
+                    .gap_x_3()
+                    .when_some(usage.ratio(), |this, ratio| {
+                        let color = if ratio >= 1.0 {
+                            ColorName::Red
+                        } else if ratio >= 0.9 {
+                            ColorName::Yellow
+                        } else {
+                            ColorName::Green
+                        };
+                        this.child(Tag::color(color).child(format!("{:.0}%", ratio * 100.0)))
+                    })
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String(
+                                match usage.limit {
+                                    0 => format!("{}，未设置上限", pretty_bytes(usage.used)),
+                                    limit => format!(
+                                        "{} / {}",
+                                        pretty_bytes(usage.used),
+                                        pretty_bytes(limit)
+                                    ),
+                                }
+                                .into(),
+                            )),
+                    ),
+            )
+    }
+
+    fn render_tasks(&self, cx: &App) -> impl IntoElement {
+        let snapshot = scheduler().snapshot();
+
+        v_flex()
+            .gap_y_2()
+            .child(div().font_bold().child(Text::String(
+                format!("任务队列（共享并发 {}）", snapshot.pool_size).into(),
+            )))
+            .children(snapshot.tasks.into_iter().map(|(priority, stats)| {
+                h_flex()
+                    .gap_x_3()
+                    .child(
+                        div()
+                            .min_w_24()
+                            .child(Text::String(priority.to_string().into())),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String(
+                                format!(
+                                    "运行 {}，等待 {}，已完成 {}",
+                                    stats.running, stats.waiting, stats.completed
+                                )
+                                .into(),
+                            )),
+                    )
+            }))
+    }
+}
+
+impl DiagnosticsPanel {
+    /// 各录制流的读缓冲使用情况，仅 LowCost 策略直接读取直播流
+    fn render_buffers(&self, cx: &App) -> impl IntoElement {
+        let buffers = AppState::global(cx)
+            .room_states
+            .iter()
+            .filter_map(|state| {
+                let stats = state.downloader.as_ref()?.get_download_stats()?;
+                (stats.buffer.size > 0).then_some((state.room_id, stats.buffer))
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_y_2()
+            .child(div().font_bold().child(Text::String("读缓冲".into())))
+            .when(buffers.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(Text::String("暂无直接读取的录制流".into())),
+                )
+            })
+            .children(buffers.into_iter().map(|(room_id, buffer)| {
+                let ratio = buffer.full_ratio();
+                // 大部分读取都能读满时说明缓冲区偏小
+                let color = if ratio >= 0.8 {
+                    ColorName::Yellow
+                } else {
+                    ColorName::Green
+                };
+
+                h_flex()
+                    .gap_x_3()
+                    .child(Tag::color(color).child(format!("读满 {:.0}%", ratio * 100.0)))
+                    .child(
+                        div()
+                            .min_w_24()
+                            .child(Text::String(format!("房间 {room_id}").into())),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String(
+                                format!(
+                                    "缓冲区 {}，读取 {} 次，平均每次 {}",
+                                    pretty_bytes(buffer.size as u64),
+                                    buffer.reads,
+                                    pretty_bytes(buffer.average_read())
+                                )
+                                .into(),
+                            )),
+                    )
+            }))
+    }
+}
+
+impl Render for DiagnosticsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .gap_y_4()
+            .child(
+                v_flex()
+                    .gap_y_3()
+                    .when(self.checks.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(Text::String(
+                                    if self.running {
+                                        "正在检测..."
+                                    } else {
+                                        "点击下方按钮开始检测"
+                                    }
+                                    .into(),
+                                )),
+                        )
+                    })
+                    .children(self.checks.iter().map(|check| {
+                        let (color, label) = match check.status {
+                            HealthStatus::Ok => (ColorName::Green, "正常"),
+                            HealthStatus::Warning => (ColorName::Yellow, "警告"),
+                            HealthStatus::Error => (ColorName::Red, "异常"),
+                            HealthStatus::Skipped => (ColorName::Gray, "跳过"),
+                        };
+
+                        h_flex()
+                            .gap_x_3()
+                            .child(Tag::color(color).child(label))
+                            .child(
+                                div()
+                                    .font_bold()
+                                    .min_w_24()
+                                    .child(Text::String(check.name.into())),
+                            )
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(Text::String(check.detail.clone().into())),
+                            )
+                    })),
+            )
+            .child(self.render_memory(cx))
+            .child(self.render_storage(cx))
+            .child(self.render_tasks(cx))
+            .child(self.render_buffers(cx))
+            .when(!self.reconnect_counts.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .gap_y_2()
+                        .child(
+                            div()
+                                .font_bold()
+                                .child(Text::String("历史重连原因统计".into())),
+                        )
+                        .child(h_flex().gap_2().flex_wrap().children(
+                            self.reconnect_counts.iter().map(|(reason, count)| {
+                                Tag::secondary().child(format!("{reason}: {count} 次"))
+                            }),
+                        )),
+                )
+            })
+            .child(
+                h_flex().justify_end().child(
+                    Button::new("run_diagnostics")
+                        .label(if self.running {
+                            "检测中"
+                        } else {
+                            "运行检测"
+                        })
+                        .primary()
+                        .disabled(self.running)
+                        .on_click(cx.listener(Self::run_checks)),
+                ),
+            )
+    }
+}
@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use gpui::{App, IntoElement, RenderOnce, Window, div, prelude::*};
+use gpui_component::{
+    ActiveTheme as _, Icon, IconName, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    text::Text,
+    v_flex,
+};
+
+use crate::{
+    components::LoginPanel,
+    core::downloader::utils::pretty_bytes,
+    settings::{METERED_QUALITY, MeteredAction},
+    state::{AppState, GlobalIssue},
+};
+
+const FFMPEG_DOWNLOAD_URL: &str = "https://ffmpeg.org/download.html";
+
+/// 主界面顶部的全局问题横幅，附带修复入口
+#[derive(IntoElement)]
+pub struct GlobalBanner {
+    issues: Vec<GlobalIssue>,
+}
+
+impl GlobalBanner {
+    pub fn new(issues: Vec<GlobalIssue>) -> Self {
+        Self { issues }
+    }
+}
+
+impl RenderOnce for GlobalBanner {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        v_flex()
+            .gap_2()
+            .children(self.issues.into_iter().enumerate().map(|(index, issue)| {
+                let (message, action) = match &issue {
+                    GlobalIssue::CookieExpired => (
+                        "登录 Cookie 已失效，部分画质无法录制".to_string(),
+                        Some("去登录"),
+                    ),
+                    GlobalIssue::DiskFull {
+                        record_dir,
+                        available,
+                    } => (
+                        format!(
+                            "录制目录所在磁盘仅剩 {}，录制可能失败: {record_dir}",
+                            pretty_bytes(*available)
+                        ),
+                        Some("清理磁盘"),
+                    ),
+                    GlobalIssue::FfmpegMissing => (
+                        "未找到可用的 ffmpeg，转码录制与产物校验不可用".to_string(),
+                        Some("安装 ffmpeg"),
+                    ),
+                    GlobalIssue::StorageLimit { used, limit } => (
+                        format!(
+                            "录像总占用 {} 已达上限 {}，新开播的房间暂不录制",
+                            pretty_bytes(*used),
+                            pretty_bytes(*limit)
+                        ),
+                        Some("打开录制目录"),
+                    ),
+                    GlobalIssue::MeteredNetwork { action } => (
+                        match action {
+                            MeteredAction::Ignore => "当前处于计费网络，录制会消耗流量".to_string(),
+                            MeteredAction::PauseAutoRecord => {
+                                "当前处于计费网络，已暂停自动录制".to_string()
+                            }
+                            MeteredAction::LowQuality => {
+                                format!("当前处于计费网络，新开始的录制画质不高于{METERED_QUALITY}")
+                            }
+                        },
+                        None,
+                    ),
+                };
+                // 计费网络只是提示，不需要处理
+                let color = if matches!(issue, GlobalIssue::MeteredNetwork { .. }) {
+                    cx.theme().warning
+                } else {
+                    cx.theme().red
+                };
+
+                h_flex()
+                    .gap_3()
+                    .px_4()
+                    .py_2()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(color)
+                    .child(Icon::new(IconName::TriangleAlert).text_color(color))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .font_bold()
+                            .child(Text::String(message.into())),
+                    )
+                    .when_some(action, |this, action| {
+                        this.child(
+                            Button::new(("global_issue", index))
+                                .label(action)
+                                .small()
+                                .danger()
+                                .on_click(move |_, window, cx| match &issue {
+                                    GlobalIssue::CookieExpired => LoginPanel::open(window, cx),
+                                    GlobalIssue::DiskFull { record_dir, .. } => {
+                                        cx.reveal_path(&PathBuf::from(record_dir))
+                                    }
+                                    GlobalIssue::FfmpegMissing => cx.open_url(FFMPEG_DOWNLOAD_URL),
+                                    GlobalIssue::StorageLimit { .. } => cx.reveal_path(
+                                        &PathBuf::from(&AppState::global(cx).settings.record_dir),
+                                    ),
+                                    GlobalIssue::MeteredNetwork { .. } => {}
+                                }),
+                        )
+                    })
+            }))
+    }
+}
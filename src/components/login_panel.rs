@@ -0,0 +1,351 @@
+use std::time::Duration;
+
+use gpui::{App, ClickEvent, Entity, Task, Window, div, prelude::*, px};
+use gpui_component::{
+    ActiveTheme as _, ContextModal, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{InputState, TextInput},
+    notification::Notification,
+    text::Text,
+    v_flex,
+};
+use qrcode::QrCode;
+
+use crate::{
+    core::{
+        auth::{self, Credentials},
+        http_client::passport::{
+            LoginQrCodePoll, NavData, QRCODE_CONFIRMED, QRCODE_EXPIRED, QRCODE_SCANNED,
+            QRCODE_WAITING,
+        },
+    },
+    logger::log_user_action,
+    state::{AppState, GlobalIssue},
+};
+
+/// 扫码登录的轮询间隔
+const QRCODE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 二维码每个模块的边长
+const QRCODE_MODULE_SIZE: f32 = 5.0;
+
+/// 二维码的模块矩阵，`true` 为深色
+struct QrMatrix {
+    width: usize,
+    modules: Vec<bool>,
+}
+
+impl QrMatrix {
+    fn encode(data: &str) -> Option<Self> {
+        let code = QrCode::new(data.as_bytes()).ok()?;
+
+        Some(Self {
+            width: code.width(),
+            modules: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == qrcode::Color::Dark)
+                .collect(),
+        })
+    }
+}
+
+/// 账号登录，粘贴 Cookie 或使用哔哩哔哩 App 扫码
+pub struct LoginPanel {
+    cookie_input: Entity<InputState>,
+    account: Option<NavData>,
+    qrcode: Option<QrMatrix>,
+    hint: Option<String>,
+    _qrcode_task: Option<Task<()>>,
+}
+
+impl LoginPanel {
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let cookie_input = cx
+            .new(|cx| InputState::new(window, cx).placeholder("粘贴浏览器中的 Cookie 或 SESSDATA"));
+
+        let mut this = Self {
+            cookie_input,
+            account: None,
+            qrcode: None,
+            hint: None,
+            _qrcode_task: None,
+        };
+        this.refresh_account(false, cx);
+
+        this
+    }
+
+    pub fn view(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| Self::new(window, cx))
+    }
+
+    /// 打开账号弹窗
+    pub fn open(window: &mut Window, cx: &mut App) {
+        let panel = Self::view(window, cx);
+
+        window.open_modal(cx, move |modal, _, _| {
+            modal
+                .rounded_lg()
+                .title(div().font_bold().text_2xl().child("账号"))
+                .child(panel.clone())
+        });
+    }
+
+    /// 查询当前 Cookie 对应的账号，`logout_if_invalid` 时无效的 Cookie 不予保留
+    fn refresh_account(&mut self, logout_if_invalid: bool, cx: &mut Context<Self>) {
+        if auth::current().is_none() {
+            self.account = None;
+            cx.notify();
+            return;
+        }
+
+        let client = AppState::global(cx).client.clone();
+        cx.spawn(async move |this, cx| {
+            let result = client.get_nav().await;
+
+            let _ = this.update(cx, |this, cx| {
+                match result {
+                    Ok(nav) if nav.is_login => {
+                        this.hint = None;
+                        this.account = Some(nav);
+                        AppState::global_mut(cx)
+                            .clear_issue(|issue| matches!(issue, GlobalIssue::CookieExpired));
+                    }
+                    Ok(_) if logout_if_invalid => {
+                        auth::logout();
+                        this.account = None;
+                        this.hint = Some("Cookie 无效或已过期，请重新获取".to_string());
+                    }
+                    Ok(_) => {
+                        this.account = None;
+                        this.hint = Some("登录已失效，请重新登录".to_string());
+                        AppState::global_mut(cx).set_issue(GlobalIssue::CookieExpired);
+                    }
+                    Err(e) => this.hint = Some(format!("无法获取账号信息: {e}")),
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn login(&mut self, credentials: Credentials, window: &mut Window, cx: &mut Context<Self>) {
+        log_user_action("登录账号", Some(&format!("uid: {}", credentials.uid)));
+
+        if let Err(e) = auth::login(credentials) {
+            tracing::error!("保存登录信息失败: {}", e);
+            window.push_notification(Notification::error(format!("保存登录信息失败: {e}")), cx);
+            return;
+        }
+
+        self.refresh_account(true, cx);
+    }
+
+    fn save_cookie(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let value = self.cookie_input.read(cx).value();
+        let Some(credentials) = Credentials::parse_cookie(&value) else {
+            window.push_notification(
+                Notification::warning("未找到 SESSDATA，请粘贴完整的 Cookie"),
+                cx,
+            );
+            return;
+        };
+
+        self.cookie_input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        self.login(credentials, window, cx);
+    }
+
+    fn logout(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
+        log_user_action("退出登录", None);
+
+        auth::logout();
+        self.account = None;
+        self.hint = None;
+        AppState::global_mut(cx).clear_issue(|issue| matches!(issue, GlobalIssue::CookieExpired));
+        cx.notify();
+    }
+
+    fn start_qrcode_login(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        log_user_action("扫码登录", None);
+
+        let client = AppState::global(cx).client.clone();
+        self.qrcode = None;
+        self.hint = Some("正在获取二维码...".to_string());
+        cx.notify();
+
+        // 重新获取时替换旧任务，旧二维码的轮询随之取消
+        self._qrcode_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let qrcode = match client.generate_login_qrcode().await {
+                Ok(qrcode) => qrcode,
+                Err(e) => {
+                    let _ = this.update(cx, |this, cx| {
+                        this.hint = Some(format!("获取二维码失败: {e}"));
+                        cx.notify();
+                    });
+                    return;
+                }
+            };
+
+            let updated = this.update(cx, |this, cx| {
+                this.qrcode = QrMatrix::encode(&qrcode.url);
+                this.hint = Some("请使用哔哩哔哩 App 扫码".to_string());
+                cx.notify();
+            });
+            if updated.is_err() {
+                return;
+            }
+
+            loop {
+                cx.background_executor().timer(QRCODE_POLL_INTERVAL).await;
+
+                let poll = client.poll_login_qrcode(&qrcode.qrcode_key).await;
+                let finished =
+                    this.update_in(cx, |this, window, cx| this.on_qrcode_poll(poll, window, cx));
+                if !matches!(finished, Ok(false)) {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// 处理扫码状态，返回是否结束轮询
+    fn on_qrcode_poll(
+        &mut self,
+        poll: anyhow::Result<LoginQrCodePoll>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let poll = match poll {
+            Ok(poll) => poll,
+            Err(e) => {
+                // 偶发的网络错误不影响二维码，下次继续查询
+                tracing::warn!("查询扫码状态失败: {}", e);
+                return false;
+            }
+        };
+
+        let finished = match poll.code {
+            QRCODE_WAITING => false,
+            QRCODE_SCANNED => {
+                self.hint = Some("已扫码，请在手机上确认登录".to_string());
+                false
+            }
+            QRCODE_EXPIRED => {
+                self.qrcode = None;
+                self.hint = Some("二维码已失效，请重新获取".to_string());
+                true
+            }
+            QRCODE_CONFIRMED => {
+                self.qrcode = None;
+                match Credentials::from_login_url(&poll.url) {
+                    Some(credentials) => self.login(credentials, window, cx),
+                    None => {
+                        self.hint =
+                            Some("登录成功但未能读取 Cookie，请改用粘贴 Cookie 登录".to_string())
+                    }
+                }
+                true
+            }
+            _ => {
+                self.qrcode = None;
+                self.hint = Some(format!("扫码登录失败: {}", poll.message));
+                true
+            }
+        };
+        cx.notify();
+
+        finished
+    }
+
+    fn render_qrcode(&self, qrcode: &QrMatrix) -> impl IntoElement {
+        // 白底与留白保证深色主题下也能识别
+        v_flex()
+            .p_4()
+            .bg(gpui::white())
+            .children(qrcode.modules.chunks(qrcode.width).map(|row| {
+                h_flex().children(row.iter().map(|dark| {
+                    div()
+                        .size(px(QRCODE_MODULE_SIZE))
+                        .when(*dark, |this| this.bg(gpui::black()))
+                }))
+            }))
+    }
+}
+
+impl Render for LoginPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = match &self.account {
+            Some(account) => format!(
+                "已登录 {}{}（uid {}）",
+                account.uname,
+                if account.is_vip() { "，大会员" } else { "" },
+                account.mid
+            ),
+            None if auth::current().is_some() => "已保存登录信息".to_string(),
+            None => "未登录，4K、杜比等需要登录的画质无法录制".to_string(),
+        };
+
+        v_flex()
+            .gap_4()
+            .child(
+                h_flex()
+                    .gap_4()
+                    .justify_between()
+                    .child(div().font_bold().child(Text::String(status.into())))
+                    .when(auth::current().is_some(), |this| {
+                        this.child(
+                            Button::new("logout")
+                                .label("退出登录")
+                                .small()
+                                .danger()
+                                .on_click(cx.listener(Self::logout)),
+                        )
+                    }),
+            )
+            .child(
+                v_flex()
+                    .font_bold()
+                    .gap_2()
+                    .child(Text::String("粘贴 Cookie".into()))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(TextInput::new(&self.cookie_input))
+                            .child(
+                                Button::new("save_cookie")
+                                    .primary()
+                                    .label("保存")
+                                    .on_click(cx.listener(Self::save_cookie)),
+                            ),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .gap_2()
+                    .items_start()
+                    .child(
+                        Button::new("qrcode_login")
+                            .label(if self.qrcode.is_some() {
+                                "刷新二维码"
+                            } else {
+                                "扫码登录"
+                            })
+                            .on_click(cx.listener(Self::start_qrcode_login)),
+                    )
+                    .when_some(self.qrcode.as_ref(), |this, qrcode| {
+                        this.child(self.render_qrcode(qrcode))
+                    }),
+            )
+            .when_some(self.hint.clone(), |this, hint| {
+                this.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(Text::String(hint.into())),
+                )
+            })
+    }
+}
@@ -1,13 +1,25 @@
 mod app_settings;
+mod clip_modal;
+mod danmaku_panel;
+mod diagnostics_panel;
+mod global_banner;
+mod login_panel;
 mod room_card;
 mod room_input;
 mod room_settings_modal;
 mod settings_modal;
+mod timeline_panel;
 
 pub use app_settings::AppSettings;
+pub use clip_modal::ClipModal;
+pub use danmaku_panel::DanmakuPanel;
+pub use diagnostics_panel::DiagnosticsPanel;
+pub use global_banner::GlobalBanner;
+pub use login_panel::LoginPanel;
 pub use room_card::*;
 pub use room_input::RoomInput;
 pub use room_input::RoomInputEvent;
 pub use room_settings_modal::*;
 pub use settings_modal::SettingsModal;
 pub use settings_modal::SettingsModalEvent;
+pub use timeline_panel::TimelinePanel;
@@ -1,20 +1,33 @@
 use crate::{
-    components::room_settings_modal::{RoomSettingsModal, RoomSettingsModalEvent},
+    components::{
+        danmaku_panel::DanmakuPanel,
+        room_settings_modal::{RoomSettingsModal, RoomSettingsModalEvent},
+        timeline_panel::TimelinePanel,
+    },
     core::{
+        cover,
         downloader::{
             BLiveDownloader,
             context::DownloaderEvent,
+            metadata::RecordingMetadata,
+            template::split_subdirectory,
+            timeline::live_start_millis,
             utils::{pretty_bytes, pretty_duration},
         },
-        http_client::room::LiveStatus,
+        http_client::{playback::Replay, room::LiveStatus},
+        playback::{download_replay, replay_filename},
+        player::open_in_player,
+        relay,
     },
     logger::log_user_action,
-    settings::RoomSettings,
+    notification::{NotificationKind, push_room_notification},
+    profile_scope,
+    settings::{GlobalSettings, RoomSettings},
     state::{AppState, RoomCardState},
 };
 use gpui::{
-    App, ClickEvent, Entity, EntityId, EventEmitter, ObjectFit, SharedString, Subscription, Window,
-    div, img, prelude::*, px,
+    App, ClickEvent, ClipboardItem, Entity, EntityId, EventEmitter, ObjectFit, SharedString,
+    Subscription, Window, div, img, prelude::*, px,
 };
 use gpui_component::{
     ActiveTheme as _, ColorName, ContextModal, Disableable, Icon, IconName, StyledExt,
@@ -26,7 +39,14 @@ use gpui_component::{
     v_flex,
 };
 use rand::seq::IndexedRandom;
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// 进度类更新触发重绘的最小间隔
+const PROGRESS_NOTIFY_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Clone, Debug)]
 pub enum RoomCardEvent {
@@ -34,6 +54,12 @@ pub enum RoomCardEvent {
     StopRecording(bool),
     WillDeleted(u64),
     Deleted(EntityId),
+    /// 置顶状态变化，房间列表需要重新排序
+    PinnedChanged,
+    /// 设置了本场结束后停止，直播已结束
+    StoppedAfterLive,
+    /// 录像超出房间配额，停止自动录制
+    QuotaExceeded,
 }
 
 #[derive(Clone, Default, PartialEq, Debug)]
@@ -56,6 +82,11 @@ pub enum DownloaderStatus {
     Error {
         cause: String,
     },
+    /// 录制完成但产物校验未通过
+    Corrupted {
+        file_path: String,
+        reason: String,
+    },
 }
 
 pub struct RoomCard {
@@ -63,6 +94,10 @@ pub struct RoomCard {
     pub settings_modal: Entity<RoomSettingsModal>,
     pub downloader_speed: Option<f32>,
     pub downloader: Option<Arc<BLiveDownloader>>,
+    /// 上次因进度更新而重绘的时间
+    last_progress_notify: Option<Instant>,
+    show_danmaku: bool,
+    show_timeline: bool,
     area_tag_color: ColorName,
     live_time_tag_color: ColorName,
     live_attention_tag_color: ColorName,
@@ -92,6 +127,9 @@ impl RoomCard {
             settings_modal,
             downloader_speed: None,
             downloader,
+            last_progress_notify: None,
+            show_danmaku: false,
+            show_timeline: false,
             area_tag_color: *area_tag_color,
             live_time_tag_color: *live_time_tag_color,
             live_attention_tag_color: *live_attention_tag_color,
@@ -117,49 +155,32 @@ impl RoomCard {
                         card.settings = settings.clone();
 
                         cx.update_global(|state: &mut AppState, _| {
-                            let global_settings = state.settings.clone();
-
-                            // 更新房间设置
-                            for room in state.settings.rooms.iter_mut() {
-                                if room.room_id == settings.room_id {
-                                    if settings.codec.unwrap_or(global_settings.codec)
-                                        == global_settings.codec
-                                    {
-                                        room.codec = None;
-                                    } else {
-                                        room.codec =
-                                            Some(settings.codec.unwrap_or(global_settings.codec));
-                                    }
-
-                                    if settings.format.unwrap_or(global_settings.format)
-                                        == global_settings.format
-                                    {
-                                        room.format = None;
-                                    } else {
-                                        room.format =
-                                            Some(settings.format.unwrap_or(global_settings.format));
-                                    }
-
-                                    if settings.quality.unwrap_or(global_settings.quality)
-                                        == global_settings.quality
-                                    {
-                                        room.quality = None;
-                                    } else {
-                                        room.quality = Some(
-                                            settings.quality.unwrap_or(global_settings.quality),
-                                        );
-                                    }
-
-                                    if settings.strategy.unwrap_or(global_settings.strategy)
-                                        == global_settings.strategy
-                                    {
-                                        room.strategy = None;
-                                    } else {
-                                        room.strategy = Some(
-                                            settings.strategy.unwrap_or(global_settings.strategy),
-                                        );
-                                    }
-                                }
+                            // 只取出需要比较的默认值，避免持有设置导致写时复制
+                            let GlobalSettings {
+                                codec,
+                                format,
+                                quality,
+                                strategy,
+                                ..
+                            } = *state.settings;
+
+                            // 更新房间设置，与全局默认值相同时不单独保存
+                            if let Some(room) = state.get_room_settings_mut(settings.room_id) {
+                                room.alias = settings.alias.clone();
+                                room.note = settings.note.clone();
+                                room.tags = settings.tags.clone();
+                                room.record_name = settings.record_name.clone();
+                                room.split_on_title_change = settings.split_on_title_change;
+                                room.record_danmaku = settings.record_danmaku;
+                                room.audio_only = settings.audio_only;
+                                room.audio_format = settings.audio_format;
+                                room.quota = settings.quota.clone();
+                                room.webhook = settings.webhook.clone();
+                                room.codec = settings.codec.filter(|value| *value != codec);
+                                room.format = settings.format.filter(|value| *value != format);
+                                room.quality = settings.quality.filter(|value| *value != quality);
+                                room.strategy =
+                                    settings.strategy.filter(|value| *value != strategy);
                             }
                         });
 
@@ -177,15 +198,270 @@ impl RoomCard {
         Self::new(settings, settings_modal, subscription, downloader)
     }
 
+    /// 卡片对应的房间号
+    pub fn room_id(&self) -> u64 {
+        self.settings.room_id
+    }
+
+    /// 批量修改标签后同步卡片与设置弹窗
+    pub fn set_tags(&mut self, tags: Vec<String>, window: &mut Window, cx: &mut Context<Self>) {
+        self.settings_modal.update(cx, |modal, cx| {
+            modal.set_tags(tags.clone(), window, cx);
+        });
+        self.settings.tags = tags;
+        cx.notify();
+    }
+
     // 从全局状态获取房间状态
     fn get_room_state(&self, cx: &App) -> Option<RoomCardState> {
         AppState::global(cx)
             .get_room_state(self.settings.room_id)
             .cloned()
     }
+
+    /// 通知等处展示的房间名称，优先使用别名，其次主播名，都没有时为房间号
+    fn display_name(&self, cx: &App) -> String {
+        let up_name = AppState::global(cx)
+            .get_room_state(self.settings.room_id)
+            .and_then(|state| state.user_info.as_ref())
+            .map(|user_info| user_info.uname.clone())
+            .unwrap_or_else(|| self.settings.room_id.to_string());
+
+        self.settings.display_name(&up_name).to_string()
+    }
 }
 
 impl RoomCard {
+    /// 用外部播放器打开：直播中打开直播流，否则打开最近一次录制的文件
+    fn on_open_player(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(state) = self.get_room_state(cx) else {
+            return;
+        };
+        let command = AppState::global(cx).settings.player_command.clone();
+        let is_live = state
+            .room_info
+            .as_ref()
+            .is_some_and(|room_info| room_info.live_status == LiveStatus::Live);
+
+        if is_live && let Some(downloader) = state.downloader.clone() {
+            cx.spawn_in(window, async move |this, cx| {
+                let result = downloader
+                    .play_url()
+                    .await
+                    .and_then(|url| open_in_player(&command, &url).map_err(Into::into));
+
+                if let Err(e) = result {
+                    let _ = this.update_in(cx, |_, window, cx| {
+                        window.push_notification(
+                            Notification::error(format!("打开播放器失败: {e}")),
+                            cx,
+                        )
+                    });
+                }
+            })
+            .detach();
+            return;
+        }
+
+        let Some(
+            DownloaderStatus::Completed { file_path, .. }
+            | DownloaderStatus::Corrupted { file_path, .. },
+        ) = state.downloader_status
+        else {
+            window.push_notification(Notification::warning("暂无可播放的直播流或录制文件"), cx);
+            return;
+        };
+
+        if let Err(e) = open_in_player(&command, &file_path) {
+            window.push_notification(Notification::error(format!("打开播放器失败: {e}")), cx);
+        }
+    }
+
+    /// 查询房间已生成的官方回放，用于补录漏掉的场次
+    fn on_find_replays(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(uid) = self
+            .get_room_state(cx)
+            .and_then(|state| state.room_info)
+            .map(|room_info| room_info.uid)
+            .filter(|uid| *uid > 0)
+        else {
+            window.push_notification(Notification::warning("房间信息尚未加载，请稍后再试"), cx);
+            return;
+        };
+
+        let room_id = self.settings.room_id;
+        log_user_action("查询直播回放", Some(&format!("房间号: {room_id}")));
+
+        let client = AppState::global(cx).client.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = client.get_replay_list(uid).await;
+
+            let _ = this.update_in(cx, |this, window, cx| match result {
+                Ok(replays) => {
+                    let replays = replays
+                        .into_iter()
+                        .filter(Replay::is_ready)
+                        .collect::<Vec<_>>();
+
+                    if replays.is_empty() {
+                        window.push_notification(
+                            Notification::warning("该房间没有可下载的直播回放"),
+                            cx,
+                        );
+                    } else {
+                        this.open_replays(replays, window, cx);
+                    }
+                }
+                Err(e) => window
+                    .push_notification(Notification::error(format!("获取直播回放失败: {e}")), cx),
+            });
+        })
+        .detach();
+    }
+
+    fn open_replays(&mut self, replays: Vec<Replay>, window: &mut Window, cx: &mut Context<Self>) {
+        let entity = cx.entity();
+
+        window.open_modal(cx, move |modal, _, _| {
+            let rows = replays.iter().enumerate().map(|(i, replay)| {
+                let start_time = chrono::DateTime::from_timestamp(replay.start_time, 0)
+                    .map(|time| {
+                        time.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_default();
+                let entity = entity.clone();
+                let replay = replay.clone();
+
+                h_flex()
+                    .gap_4()
+                    .justify_between()
+                    .child(format!(
+                        "{start_time} {} ({})",
+                        replay.live_info.title,
+                        pretty_duration(replay.video_info.duration)
+                    ))
+                    .child(
+                        Button::new(("replay-download", i))
+                            .primary()
+                            .label("补录")
+                            .on_click(move |_, window, cx| {
+                                window.close_modal(cx);
+                                entity.update(cx, |this, cx| {
+                                    this.download_replay(replay.clone(), window, cx)
+                                });
+                            }),
+                    )
+            });
+
+            modal
+                .rounded_lg()
+                .title(
+                    div()
+                        .font_bold()
+                        .text_2xl()
+                        .child("直播回放".into_element()),
+                )
+                .child(v_flex().gap_2().children(rows))
+        });
+    }
+
+    /// 下载回放的各个分段到房间的录制目录，文件名沿用房间的命名模板
+    fn download_replay(&mut self, replay: Replay, window: &mut Window, cx: &mut Context<Self>) {
+        let room_id = self.settings.room_id;
+        let state = AppState::global(cx);
+        let client = state.client.clone();
+        let settings = state
+            .get_room_settings(room_id)
+            .cloned()
+            .unwrap_or_else(|| self.settings.clone())
+            .merge_global(&state.settings);
+        let up_name = self
+            .get_room_state(cx)
+            .and_then(|state| state.user_info)
+            .map(|user_info| user_info.uname)
+            .unwrap_or_default();
+
+        log_user_action(
+            "补录直播回放",
+            Some(&format!("房间号: {room_id}, 场次: {}", replay.live_key)),
+        );
+        window.push_notification(
+            Notification::info(format!("开始补录回放: {}", replay.live_info.title)),
+            cx,
+        );
+
+        cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let streams = client.get_replay_streams(&replay).await?;
+                if streams.is_empty() {
+                    anyhow::bail!("回放没有可下载的分段");
+                }
+
+                let record_dir = settings.record_dir.clone().unwrap_or_default();
+                let metadata = RecordingMetadata {
+                    title: replay.live_info.title.clone(),
+                    artist: up_name.clone(),
+                    date: chrono::DateTime::from_timestamp(replay.start_time, 0)
+                        .unwrap_or_default()
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                    room_id,
+                    description: String::new(),
+                };
+
+                let mut saved = vec![];
+                for (i, stream) in streams.iter().enumerate() {
+                    let filename = replay_filename(
+                        &settings.record_name,
+                        &up_name,
+                        settings.alias().unwrap_or_default(),
+                        room_id,
+                        &replay,
+                        i + 1,
+                        streams.len(),
+                    );
+                    let (dir, filename) = split_subdirectory(&record_dir, &filename);
+                    std::fs::create_dir_all(&dir)?;
+
+                    let output_path = PathBuf::from(dir).join(format!("{filename}.mp4"));
+                    if output_path.exists() {
+                        tracing::info!("回放文件已存在，跳过: {}", output_path.display());
+                        continue;
+                    }
+
+                    let url = stream.stream.clone();
+                    let metadata = metadata.clone();
+                    let path = output_path.clone();
+                    cx.background_executor()
+                        .spawn(async move { download_replay(&url, &path, &metadata) })
+                        .await?;
+
+                    saved.push(output_path);
+                }
+
+                anyhow::Ok(saved)
+            }
+            .await;
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(saved) => {
+                    tracing::info!("回放补录完成 - 房间: {}, 文件数: {}", room_id, saved.len());
+                    window.push_notification(
+                        Notification::success(format!("回放补录完成，共 {} 个文件", saved.len())),
+                        cx,
+                    )
+                }
+                Err(e) => {
+                    tracing::error!("回放补录失败 - 房间: {}, 错误: {}", room_id, e);
+                    window.push_notification(Notification::error(format!("回放补录失败: {e}")), cx)
+                }
+            });
+        })
+        .detach();
+    }
+
     fn on_delete(&mut self, _: &ClickEvent, _window: &mut Window, cx: &mut Context<Self>) {
         let room_id = self.settings.room_id;
         log_user_action("删除房间", Some(&format!("房间号: {room_id}")));
@@ -201,6 +477,32 @@ impl RoomCard {
         cx.emit(RoomCardEvent::WillDeleted(room_id));
     }
 
+    fn on_toggle_pinned(&mut self, _: &ClickEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let room_id = self.settings.room_id;
+        let pinned = !self.settings.pinned;
+        self.settings.pinned = pinned;
+        self.settings_modal
+            .update(cx, |modal, _| modal.set_pinned(pinned));
+        log_user_action(
+            if pinned {
+                "置顶房间"
+            } else {
+                "取消置顶房间"
+            },
+            Some(&format!("房间号: {room_id}")),
+        );
+
+        cx.update_global(|state: &mut AppState, _| {
+            if let Some(settings) = state.get_room_settings_mut(room_id) {
+                settings.pinned = pinned;
+            }
+            state.save_settings();
+        });
+
+        cx.emit(RoomCardEvent::PinnedChanged);
+        cx.notify();
+    }
+
     fn on_open_settings(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
         let room_id = self.settings.room_id;
         log_user_action("打开房间设置", Some(&format!("房间号: {room_id}")));
@@ -220,11 +522,37 @@ impl RoomCard {
         });
     }
 
+    /// 切换本场直播结束后关闭自动录制
+    fn on_toggle_stop_after_live(
+        &mut self,
+        _: &ClickEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let room_id = self.settings.room_id;
+
+        cx.update_global(|state: &mut AppState, _| {
+            if let Some(room_state) = state.get_room_state_mut(room_id) {
+                room_state.stop_after_live = !room_state.stop_after_live;
+                log_user_action(
+                    if room_state.stop_after_live {
+                        "设置本场结束后停止"
+                    } else {
+                        "取消本场结束后停止"
+                    },
+                    Some(&format!("房间号: {room_id}")),
+                );
+            }
+        });
+
+        cx.notify();
+    }
+
     fn on_event(
         &mut self,
         this: &Entity<Self>,
         event: &RoomCardEvent,
-        _window: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
         match event {
@@ -247,6 +575,10 @@ impl RoomCard {
                         if let Some(settings) = state.get_room_settings_mut(room_id) {
                             settings.auto_record = false;
                         }
+                        // 已手动停止，无需在下播后再提示
+                        if let Some(room_state) = state.get_room_state_mut(room_id) {
+                            room_state.stop_after_live = false;
+                        }
                     });
                 }
 
@@ -266,18 +598,68 @@ impl RoomCard {
                     self.downloader = None;
                 }
 
-                // 刷新窗口
-                cx.refresh_windows();
+                // 只影响当前卡片，无需刷新整个窗口
+                cx.notify();
             }
             RoomCardEvent::WillDeleted(room_id) => {
                 cx.emit(RoomCardEvent::Deleted(this.entity_id()));
 
                 cx.update_global(|state: &mut AppState, _| {
                     state.remove_room_state(*room_id);
-                    state.settings.rooms.retain(|d| d.room_id != *room_id);
+                    state.update_settings(|settings| {
+                        settings.rooms.retain(|d| d.room_id != *room_id)
+                    });
                     log_user_action("房间删除完成", Some(&format!("房间号: {room_id}")));
                 });
             }
+            RoomCardEvent::QuotaExceeded => {
+                let room_id = self.settings.room_id;
+                // 已关闭自动录制时不重复提示
+                if !AppState::global(cx)
+                    .get_room_settings(room_id)
+                    .is_some_and(|settings| settings.auto_record)
+                {
+                    return;
+                }
+
+                self.settings.auto_record = false;
+
+                cx.update_global(|state: &mut AppState, _| {
+                    if let Some(settings) = state.get_room_settings_mut(room_id) {
+                        settings.auto_record = false;
+                    }
+                    state.save_settings();
+                });
+
+                tracing::warn!("房间录像超出配额，关闭自动录制 - 房间: {}", room_id);
+                window.push_notification(
+                    Notification::warning(
+                        "录像超出房间配额，已关闭自动录制，清理录像或调整配额后可重新开启",
+                    )
+                    .title(format!("{} 超出配额", self.display_name(cx))),
+                    cx,
+                );
+                cx.notify();
+            }
+            RoomCardEvent::StoppedAfterLive => {
+                let room_id = self.settings.room_id;
+                self.settings.auto_record = false;
+
+                cx.update_global(|state: &mut AppState, _| {
+                    if let Some(settings) = state.get_room_settings_mut(room_id) {
+                        settings.auto_record = false;
+                    }
+                    state.save_settings();
+                });
+
+                tracing::info!("本场直播已结束，关闭自动录制 - 房间: {}", room_id);
+                window.push_notification(
+                    Notification::info("本场直播已结束，已关闭自动录制，下次开播不会自动录制")
+                        .title(format!("{} 已停止监控", self.display_name(cx))),
+                    cx,
+                );
+                cx.notify();
+            }
             _ => {}
         }
     }
@@ -286,7 +668,7 @@ impl RoomCard {
         &mut self,
         _: &Entity<Self>,
         event: &DownloaderEvent,
-        _window: &mut Window,
+        window: &mut Window,
         cx: &mut Context<Self>,
     ) {
         match event {
@@ -298,16 +680,81 @@ impl RoomCard {
                 ..
             } => {
                 self.downloader_speed = Some(*download_speed_kbps);
+
+                // 进度更新节流，状态变更类事件仍即时重绘
+                let now = Instant::now();
+                if self
+                    .last_progress_notify
+                    .is_some_and(|last| now.duration_since(last) < PROGRESS_NOTIFY_INTERVAL)
+                {
+                    return;
+                }
+                self.last_progress_notify = Some(now);
             }
             DownloaderEvent::Completed { .. } => {
                 self.downloader_speed = None;
+
+                // 自动开始的录制只把下载器保存在房间状态中，停止录制时取不到，需在此释放录制锁
+                if let Some(downloader) = AppState::global(cx)
+                    .get_room_state(self.settings.room_id)
+                    .and_then(|state| state.downloader.clone())
+                    && !downloader.is_running()
+                {
+                    downloader.release_lock();
+                }
+
                 cx.emit(RoomCardEvent::StopRecording(false));
             }
             DownloaderEvent::Reconnecting => {
                 self.downloader_speed = None;
             }
-            DownloaderEvent::Error { .. } => {
+            DownloaderEvent::Error { error } => {
                 self.downloader_speed = None;
+                let name = self.display_name(cx);
+                push_room_notification(
+                    window,
+                    cx,
+                    self.settings.room_id,
+                    NotificationKind::RecordingError,
+                    error.to_string(),
+                    |message| Notification::error(message).title(format!("{name} 录制出错")),
+                );
+            }
+            DownloaderEvent::OutputCorrupted { file_path, reason } => {
+                let path = PathBuf::from(file_path);
+                let name = self.display_name(cx);
+                push_room_notification(
+                    window,
+                    cx,
+                    self.settings.room_id,
+                    NotificationKind::OutputCorrupted,
+                    format!("{file_path}: {reason}"),
+                    |message| {
+                        Notification::warning(message)
+                            .title(format!("{name} 录制产物可能损坏"))
+                            .on_click(move |_, _, cx| {
+                                cx.reveal_path(&path);
+                            })
+                    },
+                );
+            }
+            DownloaderEvent::RecordDirSwitched { from, to } => {
+                let path = PathBuf::from(to);
+                let name = self.display_name(cx);
+                push_room_notification(
+                    window,
+                    cx,
+                    self.settings.room_id,
+                    NotificationKind::RecordDirSwitched,
+                    format!("{from} 空间不足或不可写，已切换到 {to} 继续录制"),
+                    |message| {
+                        Notification::warning(message)
+                            .title(format!("{name} 切换了录制目录"))
+                            .on_click(move |_, _, cx| {
+                                cx.reveal_path(&path);
+                            })
+                    },
+                );
             }
         }
 
@@ -321,6 +768,8 @@ impl EventEmitter<DownloaderEvent> for RoomCard {}
 
 impl Render for RoomCard {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        profile_scope!("render.room_card");
+
         let room_state = self.get_room_state(cx).unwrap_or_default().clone();
 
         let room_info = &room_state.room_info;
@@ -342,15 +791,26 @@ impl Render for RoomCard {
                                 h_flex()
                                     .gap_3()
                                     .items_start()
-                                    .child(
-                                        Skeleton::new()
-                                            .rounded_lg()
-                                            .w_40()
-                                            .p_4()
-                                            .border(px(1.0))
-                                            .border_color(cx.theme().border)
-                                            .h_full(),
-                                    )
+                                    // 直播间信息获取失败时仍显示缓存的封面
+                                    .map(|this| match cover::room_cover(self.settings.room_id) {
+                                        Some(path) => this.child(
+                                            img(path)
+                                                .block()
+                                                .w_40()
+                                                .rounded(cx.theme().radius_lg)
+                                                .overflow_hidden()
+                                                .object_fit(ObjectFit::Cover),
+                                        ),
+                                        None => this.child(
+                                            Skeleton::new()
+                                                .rounded_lg()
+                                                .w_40()
+                                                .p_4()
+                                                .border(px(1.0))
+                                                .border_color(cx.theme().border)
+                                                .h_full(),
+                                        ),
+                                    })
                                     .child(
                                         v_flex()
                                             .gap_1()
@@ -431,6 +891,8 @@ impl Render for RoomCard {
         let user_info = user_info.clone().unwrap_or_default();
 
         let live_time = room_info.live_time.rsplit(" ").next().unwrap_or_default();
+        let danmaku_filter = AppState::global(cx).danmaku_filter.clone();
+        let relay_port = AppState::global(cx).settings.relay_port;
 
         div()
             .rounded_lg()
@@ -440,7 +902,7 @@ impl Render for RoomCard {
             .when(
                 matches!(
                     room_state.downloader_status,
-                    Some(DownloaderStatus::Error { .. })
+                    Some(DownloaderStatus::Error { .. } | DownloaderStatus::Corrupted { .. })
                 ),
                 |div| div.border_color(cx.theme().red),
             )
@@ -482,14 +944,23 @@ impl Render for RoomCard {
                                                     .rounded(cx.theme().radius_lg)
                                                     .overflow_hidden()
                                                     .size_full()
-                                                    .child(
-                                                        img(room_info.user_cover.clone())
-                                                            .block()
-                                                            .size_full()
-                                                            .rounded(cx.theme().radius_lg)
-                                                            .overflow_hidden()
-                                                            .object_fit(ObjectFit::Cover),
-                                                    ),
+                                                    // 直播中优先显示关键帧截图，封面下载到本地缓存前先显示占位
+                                                    .map(|this| match cover::keyframe(room_info.room_id)
+                                                        .filter(|_| room_info.live_status == LiveStatus::Live)
+                                                        .or_else(|| cover::cached_cover(&room_info.user_cover))
+                                                    {
+                                                        Some(path) => this.child(
+                                                            img(path)
+                                                                .block()
+                                                                .size_full()
+                                                                .rounded(cx.theme().radius_lg)
+                                                                .overflow_hidden()
+                                                                .object_fit(ObjectFit::Cover),
+                                                        ),
+                                                        None => this.child(
+                                                            Skeleton::new().size_full().rounded_lg(),
+                                                        ),
+                                                    }),
                                             ),
                                         )
                                         .child(
@@ -499,9 +970,32 @@ impl Render for RoomCard {
                                                     h_flex()
                                                         .gap_2()
                                                         .child(room_info.title.clone().into_element())
-                                                        .child(div().font_bold().child(
-                                                            user_info.uname.clone().into_element(),
-                                                        )),
+                                                        .child(
+                                                            div().font_bold().child(
+                                                                self.settings
+                                                                    .display_name(&user_info.uname)
+                                                                    .to_string()
+                                                                    .into_element(),
+                                                            ),
+                                                        )
+                                                        .when(
+                                                            self.settings.alias().is_some(),
+                                                            |this| {
+                                                                this.child(
+                                                                    div()
+                                                                        .text_color(
+                                                                            cx.theme()
+                                                                                .muted_foreground,
+                                                                        )
+                                                                        .child(
+                                                                            user_info
+                                                                                .uname
+                                                                                .clone()
+                                                                                .into_element(),
+                                                                        ),
+                                                                )
+                                                            },
+                                                        ),
                                                 )
                                                 .child(
                                                     format!(
@@ -558,7 +1052,7 @@ impl Render for RoomCard {
                                                             |div| {
                                                                 div.child(
                                                                     Tag::color(self.area_tag_color)
-                                                                        .child(room_info.area_name),
+                                                                        .child(room_info.area_path()),
                                                                 )
                                                             },
                                                         )
@@ -575,7 +1069,49 @@ impl Render for RoomCard {
                                                                     .child(live_time.to_owned()),
                                                                 )
                                                             },
-                                                        ),
+                                                        )
+                                                        .when_some(
+                                                            room_state.locked_by.filter(|_| {
+                                                                matches!(
+                                                                    room_info.live_status,
+                                                                    LiveStatus::Live
+                                                                )
+                                                            }),
+                                                            |div, pid| {
+                                                                div.child(
+                                                                    Tag::color(ColorName::Orange)
+                                                                        .child(format!(
+                                                                            "由其他实例录制中 (PID {pid})"
+                                                                        )),
+                                                                )
+                                                            },
+                                                        )
+                                                        .when(self.settings.audio_only, |div| {
+                                                            div.child(
+                                                                Tag::color(ColorName::Purple)
+                                                                    .child(
+                                                                        h_flex()
+                                                                            .gap_1()
+                                                                            .child(
+                                                                                Icon::default()
+                                                                                    .path("icons/music.svg")
+                                                                                    .into_element(),
+                                                                            )
+                                                                            .child(
+                                                                                self.settings
+                                                                                    .audio_format
+                                                                                    .unwrap_or_default()
+                                                                                    .to_string(),
+                                                                            ),
+                                                                    ),
+                                                            )
+                                                        })
+                                                        .children(self.settings.tags.iter().map(
+                                                            |tag| {
+                                                                Tag::color(ColorName::Gray)
+                                                                    .child(tag.clone())
+                                                            },
+                                                        )),
                                                 ),
                                         ),
                                     )
@@ -616,6 +1152,22 @@ impl Render for RoomCard {
                                                                 pretty_duration(*duration),
                                                             )),
                                                         ],
+                                                        DownloaderStatus::Corrupted {
+                                                            ref file_path,
+                                                            ref reason,
+                                                        } => vec![
+                                                            Tag::color(ColorName::Red).child(format!(
+                                                                "产物可能损坏: {}",
+                                                                reason,
+                                                            )),
+                                                            Tag::color(self.downloader_speed_tag_color).child(
+                                                                Path::new(file_path)
+                                                                    .file_name()
+                                                                    .unwrap_or_default()
+                                                                    .to_string_lossy()
+                                                                    .to_string()
+                                                            ),
+                                                        ],
                                                         DownloaderStatus::Error { ref cause } => {
                                                             vec![
                                                                 #[cfg(debug_assertions)]
@@ -625,6 +1177,23 @@ impl Render for RoomCard {
                                                     }
                                                 })
                                             })
+                                            .when_some(
+                                                room_state
+                                                    .stream_quality
+                                                    .filter(|_| matches!(room_state.status, RoomCardStatus::LiveRecording)),
+                                                |div, quality| {
+                                                // 实际画质低于设置时以警告色提示
+                                                let expected = self.settings.quality.unwrap_or_default();
+                                                div.child(
+                                                    Tag::color(if quality.to_quality() < expected.to_quality() {
+                                                        ColorName::Orange
+                                                    } else {
+                                                        self.downloader_speed_tag_color
+                                                    })
+                                                    .child(format!("画质: {quality}")),
+                                                )
+                                            },
+                                            )
                                             .when_some(self.downloader_speed, |div, speed| {
                                                 div.child(
                                                     Tag::color(self.downloader_speed_tag_color)
@@ -641,6 +1210,59 @@ impl Render for RoomCard {
                                                 )
                                             })
                                     )
+                                    .when_some(
+                                        room_state
+                                            .media_info
+                                            .clone()
+                                            .filter(|_| matches!(room_state.status, RoomCardStatus::LiveRecording)),
+                                        |div, media_info| {
+                                            div.child(
+                                                h_flex()
+                                                    .gap_x_2()
+                                                    .text_xs()
+                                                    .font_bold()
+                                                    .when_some(media_info.resolution(), |div, resolution| {
+                                                        div.child(Tag::secondary().child(resolution))
+                                                    })
+                                                    .when_some(media_info.frame_rate, |div, frame_rate| {
+                                                        div.child(Tag::secondary().child(format!("{frame_rate:.0} fps")))
+                                                    })
+                                                    .when_some(media_info.video_codec.clone(), |div, codec| {
+                                                        div.child(Tag::secondary().child(codec))
+                                                    })
+                                                    .when_some(media_info.bitrate_kbps, |div, bitrate| {
+                                                        div.child(Tag::secondary().child(format!("{bitrate} kbps")))
+                                                    })
+                                                    .when_some(media_info.audio_codec.clone(), |div, codec| {
+                                                        div.child(Tag::secondary().child(codec))
+                                                    })
+                                                    .when_some(media_info.audio_sample_rate, |div, sample_rate| {
+                                                        div.child(Tag::secondary().child(format!(
+                                                            "{:.1} kHz",
+                                                            sample_rate as f32 / 1000.0
+                                                        )))
+                                                    }),
+                                            )
+                                        },
+                                    )
+                                    .when_some(
+                                        room_state
+                                            .process_usage
+                                            .filter(|_| matches!(room_state.status, RoomCardStatus::LiveRecording)),
+                                        |div, usage| {
+                                            div.child(
+                                                h_flex()
+                                                    .gap_x_2()
+                                                    .text_xs()
+                                                    .font_bold()
+                                                    .child(Tag::secondary().child(format!("CPU {:.1}%", usage.cpu_usage)))
+                                                    .child(Tag::secondary().child(format!(
+                                                        "内存 {}",
+                                                        pretty_bytes(usage.memory)
+                                                    ))),
+                                            )
+                                        },
+                                    )
                             )
                             .child(
                                 h_flex()
@@ -700,6 +1322,47 @@ impl Render for RoomCard {
                                                 };
                                             })),
                                     )
+                                    .when(
+                                        matches!(room_state.status, RoomCardStatus::LiveRecording),
+                                        |this| {
+                                            this.child(
+                                                Button::new("stop_after_live")
+                                                    .label("本场结束后停止")
+                                                    .map(|button| {
+                                                        if room_state.stop_after_live {
+                                                            button.warning()
+                                                        } else {
+                                                            button.ghost()
+                                                        }
+                                                    })
+                                                    .tooltip(if room_state.stop_after_live {
+                                                        "已设置，本场直播结束后关闭自动录制"
+                                                    } else {
+                                                        "录完本场后关闭自动录制，不再录制下一场"
+                                                    })
+                                                    .on_click(
+                                                        cx.listener(Self::on_toggle_stop_after_live),
+                                                    ),
+                                            )
+                                        },
+                                    )
+                                    .child(
+                                        Button::new("pin")
+                                            .icon(IconName::Star)
+                                            .map(|button| {
+                                                if self.settings.pinned {
+                                                    button.warning()
+                                                } else {
+                                                    button.ghost()
+                                                }
+                                            })
+                                            .tooltip(if self.settings.pinned {
+                                                "取消置顶"
+                                            } else {
+                                                "置顶，并在托盘菜单中列出"
+                                            })
+                                            .on_click(cx.listener(Self::on_toggle_pinned)),
+                                    )
                                     .child(
                                         Button::new("settings")
                                             .primary()
@@ -731,9 +1394,104 @@ impl Render for RoomCard {
                                                     ));
                                                 }
                                             })),
+                                    )
+                                    .child(
+                                        Button::new("player")
+                                            .icon(IconName::ExternalLink)
+                                            .label("用播放器打开")
+                                            .tooltip("直播中打开直播流，否则打开最近的录制文件")
+                                            .on_click(cx.listener(Self::on_open_player)),
+                                    )
+                                    .child(
+                                        Button::new("replay")
+                                            .icon(IconName::ArrowDown)
+                                            .label("补录回放")
+                                            .tooltip("下载官方直播回放，补齐漏录的场次")
+                                            .on_click(cx.listener(Self::on_find_replays)),
+                                    )
+                                    .child(
+                                        Button::new("danmaku")
+                                            .icon(if self.show_danmaku {
+                                                IconName::ChevronUp
+                                            } else {
+                                                IconName::ChevronDown
+                                            })
+                                            .label("弹幕")
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.show_danmaku = !this.show_danmaku;
+                                                cx.notify();
+                                            })),
+                                    )
+                                    .child(
+                                        Button::new("timeline")
+                                            .icon(if self.show_timeline {
+                                                IconName::ChevronUp
+                                            } else {
+                                                IconName::ChevronDown
+                                            })
+                                            .label("时间线")
+                                            .on_click(cx.listener(|this, _, _, cx| {
+                                                this.show_timeline = !this.show_timeline;
+                                                cx.notify();
+                                            })),
+                                    )
+                                    .when(
+                                        relay_port > 0
+                                            && matches!(
+                                                room_state.status,
+                                                RoomCardStatus::LiveRecording
+                                            ),
+                                        |this| {
+                                            let url = relay::relay_url(relay_port, room_info.room_id);
+
+                                            this.child(
+                                                Button::new("relay")
+                                                    .icon(IconName::Copy)
+                                                    .label("边录边看")
+                                                    .tooltip("复制本地播放地址，可用 PotPlayer/mpv 打开")
+                                                    .on_click(move |_, window, cx| {
+                                                        cx.write_to_clipboard(ClipboardItem::new_string(
+                                                            url.clone(),
+                                                        ));
+                                                        window.push_notification(
+                                                            Notification::success("播放地址已复制"),
+                                                            cx,
+                                                        );
+                                                    }),
+                                            )
+                                        },
                                     ),
                             ),
                     )
+                    .when_some(self.settings.note().map(str::to_string), |this, note| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!("备注: {note}")),
+                        )
+                    })
+                    .when(self.show_danmaku, |this| {
+                        this.child(DanmakuPanel::new(room_state.danmaku.clone(), danmaku_filter))
+                    })
+                    .when(self.show_timeline, |this| {
+                        let (timeline, storyboards) = room_state
+                            .downloader
+                            .as_ref()
+                            .map(|downloader| {
+                                (
+                                    downloader.context.timeline(),
+                                    downloader.context.storyboards(),
+                                )
+                            })
+                            .unwrap_or_default();
+
+                        this.child(TimelinePanel::new(
+                            timeline,
+                            storyboards,
+                            live_start_millis(&room_info.live_time),
+                        ))
+                    })
             )
     }
 }
@@ -1,17 +1,23 @@
-use gpui::{App, Entity, EventEmitter, Subscription, Window, div, prelude::*, px};
+use gpui::{App, ClickEvent, Entity, EventEmitter, Subscription, Window, div, prelude::*, px};
 use gpui_component::{
-    ActiveTheme, Disableable, StyledExt,
+    ActiveTheme, ContextModal, Disableable, StyledExt,
     button::{Button, ButtonVariants},
     h_flex,
     input::{InputEvent, InputState, TextInput},
+    notification::Notification,
     v_flex,
 };
 
-use crate::state::AppState;
+use crate::{
+    settings::{RoomSettings, import::import_bililive_recorder},
+    state::AppState,
+};
 
 #[derive(Debug, Clone)]
 pub enum RoomInputEvent {
     RoomInputSubmit(u64),
+    /// 从其他录制工具的配置中批量导入
+    ImportRooms(Vec<RoomSettings>),
 }
 
 pub struct RoomInput {
@@ -74,6 +80,38 @@ impl RoomInput {
             .detach();
         }
     }
+
+    fn import_rooms(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(handle) = rfd::AsyncFileDialog::new()
+                .set_title("选择录播姬配置文件 config.json")
+                .add_filter("录播姬配置", &["json"])
+                .pick_file()
+                .await
+            else {
+                return;
+            };
+
+            let content = handle.read().await;
+            let result = std::str::from_utf8(&content)
+                .map_err(|e| e.to_string())
+                .and_then(|content| import_bililive_recorder(content).map_err(|e| e.to_string()));
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(rooms) if rooms.is_empty() => window
+                    .push_notification(Notification::warning("配置文件中没有可导入的房间"), cx),
+                Ok(rooms) => cx.emit(RoomInputEvent::ImportRooms(rooms)),
+                Err(e) => {
+                    tracing::warn!("解析录播姬配置失败: {}", e);
+                    window.push_notification(
+                        Notification::error(format!("无法识别录播姬配置: {e}")),
+                        cx,
+                    )
+                }
+            });
+        })
+        .detach();
+    }
 }
 
 impl EventEmitter<RoomInputEvent> for RoomInput {}
@@ -90,7 +128,18 @@ impl Render for RoomInput {
             .child(
                 v_flex()
                     .gap_6()
-                    .child(div().font_bold().text_lg().child("添加录制房间"))
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .child(div().font_bold().text_lg().child("添加录制房间"))
+                            .child(
+                                Button::new("从录播姬导入")
+                                    .label("从录播姬导入")
+                                    .ghost()
+                                    .tooltip("读取 B 站录播姬的 config.json，批量添加其中的房间")
+                                    .on_click(cx.listener(Self::import_rooms)),
+                            ),
+                    )
                     .child(
                         div().rounded_lg().p_4().bg(cx.theme().background).child(
                             v_flex()
@@ -1,23 +1,46 @@
-use crate::settings::{Quality, RoomSettings, Strategy, StreamCodec, VideoContainer};
-use gpui::{App, ClickEvent, Entity, EventEmitter, Subscription, Window, prelude::*};
+use crate::{
+    core::{
+        disk::WINDOWS_MAX_PATH,
+        downloader::template::{longest_record_path, preview_filename},
+    },
+    settings::{
+        AudioFormat, Quality, RecordNamePreset, RoomQuota, RoomSettings, StorageLimitAction,
+        Strategy, StreamCodec, VideoContainer, WebhookSettings, parse_tags,
+    },
+    state::AppState,
+};
+use gpui::{App, ClickEvent, Entity, EventEmitter, Subscription, Window, div, prelude::*};
 use gpui_component::{
-    ContextModal, IndexPath, StyledExt,
+    ActiveTheme, ContextModal, IndexPath, StyledExt,
     button::{Button, ButtonVariants},
-    dropdown::{Dropdown, DropdownState},
+    dropdown::{Dropdown, DropdownEvent, DropdownState},
     h_flex,
-    input::{InputState, TextInput},
+    input::{InputEvent, InputState, TextInput},
     notification::Notification,
     switch::Switch,
     v_flex,
 };
 
+/// 命名方案下拉框中表示自定义模板的选项
+const CUSTOM_RECORD_NAME: &str = "自定义";
+
 pub struct RoomSettingsModal {
     settings: RoomSettings,
+    alias_input: Entity<InputState>,
+    note_input: Entity<InputState>,
+    tags_input: Entity<InputState>,
     record_name_input: Entity<InputState>,
+    record_name_preset_input: Entity<DropdownState<Vec<String>>>,
     strategy_input: Entity<DropdownState<Vec<String>>>,
     quality_input: Entity<DropdownState<Vec<String>>>,
     format_input: Entity<DropdownState<Vec<String>>>,
     codec_input: Entity<DropdownState<Vec<String>>>,
+    audio_format_input: Entity<DropdownState<Vec<String>>>,
+    quota_gb_input: Entity<InputState>,
+    quota_sessions_input: Entity<InputState>,
+    quota_action_input: Entity<DropdownState<Vec<String>>>,
+    webhook_urls_input: Entity<InputState>,
+    webhook_events_input: Entity<InputState>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -31,12 +54,46 @@ impl EventEmitter<RoomSettingsModalEvent> for RoomSettingsModal {}
 
 impl RoomSettingsModal {
     pub fn new(settings: RoomSettings, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let alias_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("留空使用主播名")
+                .default_value(settings.alias.clone().unwrap_or_default())
+        });
+
+        let note_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("如：只录周五的歌回")
+                .default_value(settings.note.clone().unwrap_or_default())
+        });
+
+        let tags_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("多个标签用逗号分隔，如：vup,游戏")
+                .default_value(settings.tags.join(","))
+        });
+
         let record_name_input = cx.new(|cx| {
             InputState::new(window, cx)
                 .placeholder("录制文件名")
                 .default_value(settings.record_name.clone())
         });
 
+        let record_name_preset_input = cx.new(|cx| {
+            let mut presets = RecordNamePreset::ALL
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            presets.push(CUSTOM_RECORD_NAME.to_string());
+
+            let mut state = DropdownState::new(presets, Some(IndexPath::new(0)), window, cx);
+            let selected = RecordNamePreset::from_template(&settings.record_name)
+                .map(|preset| preset.to_string())
+                .unwrap_or_else(|| CUSTOM_RECORD_NAME.to_string());
+            state.set_selected_value(&selected, window, cx);
+
+            state
+        });
+
         let strategy_input = cx.new(|cx| {
             let mut state = DropdownState::new(
                 vec![
@@ -112,19 +169,190 @@ impl RoomSettingsModal {
             state
         });
 
-        let _subscriptions = vec![];
+        let audio_format_input = cx.new(|cx| {
+            let mut state = DropdownState::new(
+                AudioFormat::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+                Some(IndexPath::new(0)),
+                window,
+                cx,
+            );
+
+            state.set_selected_value(
+                &settings.audio_format.unwrap_or_default().to_string(),
+                window,
+                cx,
+            );
+
+            state
+        });
+
+        let quota = settings.quota.clone().unwrap_or_default();
+        let quota_gb_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("GB，0 表示不限制")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(quota.max_gb.to_string())
+        });
+
+        let quota_sessions_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("场次，0 表示不限制")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(quota.max_sessions.to_string())
+        });
+
+        let quota_action_input = cx.new(|cx| {
+            let mut state = DropdownState::new(
+                vec![
+                    StorageLimitAction::Pause.to_string(),
+                    StorageLimitAction::DeleteOldest.to_string(),
+                ],
+                Some(IndexPath::new(0)),
+                window,
+                cx,
+            );
+
+            state.set_selected_value(&quota.action.to_string(), window, cx);
+
+            state
+        });
+
+        let webhook = settings.webhook.clone().unwrap_or_default();
+        let webhook_urls_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("与全局 Webhook 一并推送，多个地址用逗号分隔")
+                .default_value(webhook.urls.join(","))
+        });
+
+        let webhook_events_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("留空推送全部：开播,下播,开始录制,录制完成,录制出错")
+                .default_value(webhook.events_text())
+        });
+
+        let _subscriptions = vec![
+            cx.subscribe_in(
+                &record_name_preset_input,
+                window,
+                Self::on_record_name_preset_change,
+            ),
+            cx.subscribe(&record_name_input, Self::on_record_name_input_change),
+            cx.subscribe(&alias_input, Self::on_record_name_input_change),
+        ];
 
         Self {
             settings,
+            alias_input,
+            note_input,
+            tags_input,
             record_name_input,
+            record_name_preset_input,
             strategy_input,
             quality_input,
             format_input,
             codec_input,
+            audio_format_input,
+            quota_gb_input,
+            quota_sessions_input,
+            quota_action_input,
+            webhook_urls_input,
+            webhook_events_input,
             _subscriptions,
         }
     }
 
+    /// 批量修改标签后同步到弹窗，避免再次保存时写回旧标签
+    pub fn set_tags(&mut self, tags: Vec<String>, window: &mut Window, cx: &mut Context<Self>) {
+        self.tags_input.update(cx, |input, cx| {
+            input.set_value(tags.join(","), window, cx);
+        });
+        self.settings.tags = tags;
+    }
+
+    /// 卡片上切换置顶后同步到弹窗，避免再次保存时写回旧的置顶状态
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.settings.pinned = pinned;
+    }
+
+    /// 选择预设时把模板填入文件名输入框，之后仍可手动修改
+    fn on_record_name_preset_change(
+        &mut self,
+        _: &Entity<DropdownState<Vec<String>>>,
+        event: &DropdownEvent<Vec<String>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let DropdownEvent::Confirm(Some(selected)) = event else {
+            return;
+        };
+
+        if let Some(preset) = RecordNamePreset::ALL
+            .into_iter()
+            .find(|preset| preset.to_string() == *selected)
+        {
+            self.record_name_input.update(cx, |input, cx| {
+                input.set_value(preset.template(), window, cx);
+            });
+        }
+    }
+
+    /// 文件名模板或别名变化时刷新路径预览
+    fn on_record_name_input_change(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change(_) = event {
+            cx.notify();
+        }
+    }
+
+    /// 按当前模板预计的最长录制路径，Windows 上超过 260 字符时提示
+    fn render_path_preview(&self, cx: &Context<Self>) -> impl IntoElement {
+        let state = AppState::global(cx);
+        let room_id = self.settings.room_id;
+        let record_dir = self
+            .settings
+            .record_dir
+            .clone()
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| state.settings.record_dir.clone());
+        let up_name = state
+            .get_room_state(room_id)
+            .and_then(|room| room.user_info.as_ref())
+            .map(|user| user.uname.clone())
+            .unwrap_or_else(|| "主播".to_string());
+
+        let filename = preview_filename(
+            &self.record_name_input.read(cx).value(),
+            &up_name,
+            self.alias_input.read(cx).value().trim(),
+            room_id,
+        );
+        let ext = self.settings.format.unwrap_or_default().ext().to_string();
+        let path = longest_record_path(&record_dir, &filename, &ext);
+        let len = path.encode_utf16().count();
+
+        let too_long = cfg!(windows) && len > WINDOWS_MAX_PATH;
+        div()
+            .text_sm()
+            .font_normal()
+            .text_color(if too_long {
+                cx.theme().warning
+            } else {
+                cx.theme().muted_foreground
+            })
+            .child(if too_long {
+                format!("预计最长路径 {len} 字符，超过 {WINDOWS_MAX_PATH} 字符，将使用扩展路径录制，部分播放器可能无法打开：{path}")
+            } else {
+                format!("预计最长路径 {len} 字符：{path}")
+            })
+    }
+
     pub fn view(settings: RoomSettings, window: &mut Window, cx: &mut App) -> Entity<Self> {
         cx.new(|cx| Self::new(settings, window, cx))
     }
@@ -134,6 +362,18 @@ impl RoomSettingsModal {
         let quality_str = self.quality_input.read(cx).selected_value();
         let format = self.format_input.read(cx).selected_value();
         let codec = self.codec_input.read(cx).selected_value();
+        let audio_format = self.audio_format_input.read(cx).selected_value();
+        let record_name = self.record_name_input.read(cx).value();
+        let alias = self.alias_input.read(cx).value();
+        let note = self.note_input.read(cx).value();
+        let tags = self.tags_input.read(cx).value();
+
+        if !record_name.trim().is_empty() {
+            self.settings.record_name = record_name.trim().to_string();
+        }
+        self.settings.alias = Some(alias.trim().to_string()).filter(|alias| !alias.is_empty());
+        self.settings.note = Some(note.trim().to_string()).filter(|note| !note.is_empty());
+        self.settings.tags = parse_tags(&tags);
 
         // 策略设置
         if let Some(strategy_str) = strategy_str {
@@ -178,6 +418,41 @@ impl RoomSettingsModal {
             };
         }
 
+        // 仅在纯音频模式下保存音频格式
+        self.settings.audio_format = audio_format
+            .filter(|_| self.settings.audio_only)
+            .and_then(|format| format.parse::<AudioFormat>().ok());
+
+        // 两项都不限制时不保留配额
+        let quota = RoomQuota {
+            max_gb: self
+                .quota_gb_input
+                .read(cx)
+                .value()
+                .parse()
+                .unwrap_or_default(),
+            max_sessions: self
+                .quota_sessions_input
+                .read(cx)
+                .value()
+                .parse()
+                .unwrap_or_default(),
+            action: self
+                .quota_action_input
+                .read(cx)
+                .selected_value()
+                .and_then(|action| action.parse().ok())
+                .unwrap_or_default(),
+        };
+        self.settings.quota = (!quota.is_empty()).then_some(quota);
+
+        // 没有填写地址时不保留房间的 Webhook
+        let webhook = WebhookSettings::parse(
+            &self.webhook_urls_input.read(cx).value(),
+            &self.webhook_events_input.read(cx).value(),
+        );
+        self.settings.webhook = (!webhook.urls.is_empty()).then_some(webhook);
+
         cx.emit(RoomSettingsModalEvent::SaveSettings(self.settings.clone()));
         window.push_notification(Notification::success("设置保存成功"), cx);
     }
@@ -199,8 +474,31 @@ impl Render for RoomSettingsModal {
                             v_flex()
                                 .gap_y_2()
                                 .font_bold()
-                                .child("录制文件名")
-                                .child(TextInput::new(&self.record_name_input)),
+                                .child("别名（文件名模板中为 {alias}）")
+                                .child(TextInput::new(&self.alias_input).max_w_48()),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_y_2()
+                                .font_bold()
+                                .child("备注")
+                                .child(TextInput::new(&self.note_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_y_2()
+                                .font_bold()
+                                .child("标签")
+                                .child(TextInput::new(&self.tags_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .gap_y_2()
+                                .font_bold()
+                                .child("录制文件名（/ 表示子目录）")
+                                .child(Dropdown::new(&self.record_name_preset_input).max_w_48())
+                                .child(TextInput::new(&self.record_name_input))
+                                .child(self.render_path_preview(cx)),
                         )
                         .child(
                             h_flex().font_bold().gap_4().child("自动录制").child(
@@ -210,6 +508,30 @@ impl Render for RoomSettingsModal {
                                     .max_w_32(),
                             ),
                         )
+                        .child(
+                            h_flex().font_bold().gap_4().child("按标题分段").child(
+                                Switch::new("split_on_title_change")
+                                    .checked(self.settings.split_on_title_change)
+                                    .tooltip("直播标题变化时结束当前文件，按新标题开始录制")
+                                    .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                        this.settings.split_on_title_change = *checked;
+                                        cx.notify();
+                                    })),
+                            ),
+                        )
+                        .child(
+                            h_flex().font_bold().gap_4().child("录制弹幕").child(
+                                Switch::new("record_danmaku")
+                                    .checked(self.settings.record_danmaku)
+                                    .tooltip(
+                                        "录制时连接弹幕服务器，在视频旁保存弹幕、礼物与醒目留言",
+                                    )
+                                    .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                        this.settings.record_danmaku = *checked;
+                                        cx.notify();
+                                    })),
+                            ),
+                        )
                         .child(
                             v_flex()
                                 .font_bold()
@@ -237,6 +559,49 @@ impl Render for RoomSettingsModal {
                                 .gap_2()
                                 .child("录制编码")
                                 .child(Dropdown::new(&self.codec_input).max_w_32()),
+                        )
+                        .child(
+                            h_flex().font_bold().gap_4().child("仅录音频").child(
+                                Switch::new("audio_only")
+                                    .checked(self.settings.audio_only)
+                                    .tooltip("录制结束后去除视频轨，只保留音频文件")
+                                    .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                        this.settings.audio_only = *checked;
+                                        cx.notify();
+                                    })),
+                            ),
+                        )
+                        .when(self.settings.audio_only, |this| {
+                            this.child(
+                                v_flex()
+                                    .font_bold()
+                                    .gap_2()
+                                    .child("音频格式（flac 需转码）")
+                                    .child(Dropdown::new(&self.audio_format_input).max_w_32()),
+                            )
+                        })
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child("存储配额（GB / 场次）/ 超出后")
+                                .child(
+                                    h_flex()
+                                        .gap_x_2()
+                                        .child(TextInput::new(&self.quota_gb_input).max_w_32())
+                                        .child(
+                                            TextInput::new(&self.quota_sessions_input).max_w_32(),
+                                        )
+                                        .child(Dropdown::new(&self.quota_action_input).max_w_32()),
+                                ),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child("Webhook 地址 / 推送事件")
+                                .child(TextInput::new(&self.webhook_urls_input))
+                                .child(TextInput::new(&self.webhook_events_input)),
                         ),
                 ),
             )
@@ -1,5 +1,17 @@
 use crate::{
-    settings::{GlobalSettings, Quality, Strategy, StreamCodec, VideoContainer},
+    core::{
+        danmaku::{archive, srt},
+        dns, history,
+    },
+    diagnostics::{default_diagnostics_file_name, export_diagnostics},
+    hotkey::parse_hotkey,
+    logger::LogLevel,
+    settings::{
+        DEFAULT_MQTT_TOPIC, DEFAULT_PLAYER_COMMAND, DEFAULT_QUIET_HOURS_END,
+        DEFAULT_QUIET_HOURS_START, GlobalSettings, METERED_QUALITY, MeteredAction, Quality,
+        StorageLimitAction, Strategy, StreamCodec, VideoContainer, WebhookSettings,
+        WhisperSettings, parse_clock_time,
+    },
     state::AppState,
 };
 use gpui::{App, ClickEvent, Entity, EventEmitter, Subscription, Window, prelude::*};
@@ -10,6 +22,7 @@ use gpui_component::{
     h_flex,
     input::{InputEvent, InputState, TextInput},
     notification::Notification,
+    switch::Switch,
     text::Text,
     v_flex,
 };
@@ -17,10 +30,50 @@ use gpui_component::{
 pub struct SettingsModal {
     global_settings: GlobalSettings,
     record_dir_input: Entity<InputState>,
+    fallback_record_dirs_input: Entity<InputState>,
     strategy_input: Entity<DropdownState<Vec<String>>>,
     quality_input: Entity<DropdownState<Vec<String>>>,
     format_input: Entity<DropdownState<Vec<String>>>,
     codec_input: Entity<DropdownState<Vec<String>>>,
+    danmaku_highlight_input: Entity<InputState>,
+    danmaku_block_input: Entity<InputState>,
+    log_retention_input: Entity<InputState>,
+    reconnect_reset_input: Entity<InputState>,
+    memory_warn_input: Entity<InputState>,
+    offline_grace_input: Entity<InputState>,
+    storyboard_interval_input: Entity<InputState>,
+    blank_detect_input: Entity<InputState>,
+    healthz_port_input: Entity<InputState>,
+    relay_port_input: Entity<InputState>,
+    storage_limit_input: Entity<InputState>,
+    storage_limit_action_input: Entity<DropdownState<Vec<String>>>,
+    metered_action_input: Entity<DropdownState<Vec<String>>>,
+    pool_size_input: Entity<InputState>,
+    keep_alive_input: Entity<InputState>,
+    read_buffer_input: Entity<InputState>,
+    resolution_input: Entity<InputState>,
+    loudnorm_integrated_input: Entity<InputState>,
+    loudnorm_true_peak_input: Entity<InputState>,
+    loudnorm_lra_input: Entity<InputState>,
+    quiet_hours_start_input: Entity<InputState>,
+    quiet_hours_end_input: Entity<InputState>,
+    dns_servers_input: Entity<InputState>,
+    player_command_input: Entity<InputState>,
+    stop_all_hotkey_input: Entity<InputState>,
+    whisper_binary_input: Entity<InputState>,
+    whisper_model_input: Entity<InputState>,
+    whisper_language_input: Entity<InputState>,
+    webhook_urls_input: Entity<InputState>,
+    webhook_events_input: Entity<InputState>,
+    mqtt_broker_input: Entity<InputState>,
+    mqtt_topic_input: Entity<InputState>,
+    mqtt_username_input: Entity<InputState>,
+    mqtt_password_input: Entity<InputState>,
+    host_overrides_input: Entity<InputState>,
+    log_level_input: Entity<DropdownState<Vec<String>>>,
+    api_log_level_input: Entity<DropdownState<Vec<String>>>,
+    downloader_log_level_input: Entity<DropdownState<Vec<String>>>,
+    ui_log_level_input: Entity<DropdownState<Vec<String>>>,
     _subscriptions: Vec<Subscription>,
     lock: bool,
 }
@@ -35,7 +88,8 @@ impl EventEmitter<SettingsModalEvent> for SettingsModal {}
 
 impl SettingsModal {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let global_settings = AppState::global(cx).settings.clone();
+        // 设置面板编辑的是一份独立副本，保存时整体替换
+        let global_settings = GlobalSettings::clone(&AppState::global(cx).settings);
 
         let record_dir_input = cx.new(|cx| {
             InputState::new(window, cx)
@@ -43,6 +97,12 @@ impl SettingsModal {
                 .default_value(global_settings.record_dir.clone())
         });
 
+        let fallback_record_dirs_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("多个目录用分号分隔，按顺序回退")
+                .default_value(global_settings.fallback_record_dirs.join(";"))
+        });
+
         let strategy_input = cx.new(|cx| {
             let mut state = DropdownState::new(
                 vec![
@@ -110,16 +170,333 @@ impl SettingsModal {
             state
         });
 
+        let danmaku_highlight_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("多个关键词用逗号分隔")
+                .default_value(global_settings.danmaku.highlight_keywords.join(","))
+        });
+
+        let danmaku_block_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("多个关键词用逗号分隔，/正则/ 按正则匹配")
+                .default_value(global_settings.danmaku.block_keywords.join(","))
+        });
+
+        let log_retention_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("日志保留天数")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.log_retention_days.to_string())
+        });
+
+        let reconnect_reset_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不重置")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.reconnect_reset_minutes.to_string())
+        });
+
+        let memory_warn_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不告警")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.memory_warn_mb.to_string())
+        });
+
+        let offline_grace_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示立即停止")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.offline_grace_seconds.to_string())
+        });
+
+        let storyboard_interval_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不截图")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.storyboard_interval_minutes.to_string())
+        });
+
+        let blank_detect_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不检测")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.blank_detect_minutes.to_string())
+        });
+
+        let healthz_port_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不启用")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.healthz_port.to_string())
+        });
+
+        let relay_port_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不启用")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.relay_port.to_string())
+        });
+
+        let storage_limit_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不限制")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.storage_limit_gb.to_string())
+        });
+
+        let storage_limit_action_input = cx.new(|cx| {
+            let mut state = DropdownState::new(
+                vec![
+                    StorageLimitAction::Pause.to_string(),
+                    StorageLimitAction::DeleteOldest.to_string(),
+                ],
+                Some(IndexPath::new(0)),
+                window,
+                cx,
+            );
+
+            state.set_selected_value(
+                &global_settings.storage_limit_action.to_string(),
+                window,
+                cx,
+            );
+
+            state
+        });
+
+        let metered_action_input = cx.new(|cx| {
+            let mut state = DropdownState::new(
+                vec![
+                    MeteredAction::Ignore.to_string(),
+                    MeteredAction::PauseAutoRecord.to_string(),
+                    MeteredAction::LowQuality.to_string(),
+                ],
+                Some(IndexPath::new(0)),
+                window,
+                cx,
+            );
+
+            state.set_selected_value(&global_settings.metered_action.to_string(), window, cx);
+
+            state
+        });
+
+        let pool_size_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不复用连接")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.network.pool_max_idle_per_host.to_string())
+        });
+
+        let keep_alive_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("0 表示不保持空闲连接")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.network.keep_alive_seconds.to_string())
+        });
+
+        let resolution_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("留空保持原分辨率，如 1920x1080")
+                .default_value(
+                    global_settings
+                        .transcode
+                        .resolution
+                        .map(|resolution| resolution.to_string())
+                        .unwrap_or_default(),
+                )
+        });
+
+        let loudnorm_integrated_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("目标响度 LUFS")
+                .pattern(regex::Regex::new(r"^-?\d*\.?\d*$").unwrap())
+                .default_value(global_settings.loudnorm.integrated.to_string())
+        });
+
+        let loudnorm_true_peak_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("真峰值 dBTP")
+                .pattern(regex::Regex::new(r"^-?\d*\.?\d*$").unwrap())
+                .default_value(global_settings.loudnorm.true_peak.to_string())
+        });
+
+        let loudnorm_lra_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("响度范围 LU")
+                .pattern(regex::Regex::new(r"^\d*\.?\d*$").unwrap())
+                .default_value(global_settings.loudnorm.lra.to_string())
+        });
+
+        let quiet_hours_start_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(DEFAULT_QUIET_HOURS_START)
+                .default_value(global_settings.quiet_hours.start.clone())
+        });
+
+        let quiet_hours_end_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(DEFAULT_QUIET_HOURS_END)
+                .default_value(global_settings.quiet_hours.end.clone())
+        });
+
+        let read_buffer_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("16 ~ 1024")
+                .pattern(regex::Regex::new(r"^\d*$").unwrap())
+                .default_value(global_settings.read_buffer_kb.to_string())
+        });
+
+        let whisper_binary_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("whisper-cli")
+                .default_value(global_settings.whisper.binary.clone())
+        });
+
+        let whisper_model_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("为空时不转写，如 ggml-base.bin")
+                .default_value(global_settings.whisper.model_path.clone())
+        });
+
+        let whisper_language_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("zh")
+                .default_value(global_settings.whisper.language.clone())
+        });
+
+        let webhook_urls_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("多个地址用逗号分隔")
+                .default_value(global_settings.webhook.urls.join(","))
+        });
+
+        let webhook_events_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("留空推送全部：开播,下播,开始录制,录制完成,录制出错")
+                .default_value(global_settings.webhook.events_text())
+        });
+
+        let mqtt_broker_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("为空时不发布，如 mqtt://192.168.1.2:1883")
+                .default_value(global_settings.mqtt.broker.clone())
+        });
+
+        let mqtt_topic_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(DEFAULT_MQTT_TOPIC)
+                .default_value(global_settings.mqtt.topic.clone())
+        });
+
+        let mqtt_username_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("用户名")
+                .default_value(global_settings.mqtt.username.clone())
+        });
+
+        let mqtt_password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("密码")
+                .masked(true)
+                .default_value(global_settings.mqtt.password.clone())
+        });
+
+        let player_command_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(DEFAULT_PLAYER_COMMAND)
+                .default_value(global_settings.player_command.clone())
+        });
+
+        let stop_all_hotkey_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("留空不注册，如 CmdOrCtrl+Alt+Shift+S")
+                .default_value(global_settings.stop_all_hotkey.clone())
+        });
+
+        let dns_servers_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("留空使用系统 DNS，多个服务器用逗号分隔")
+                .default_value(global_settings.network.dns_servers.join(","))
+        });
+
+        let host_overrides_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("域名=IP，多条用逗号分隔")
+                .default_value(dns::format_host_overrides(
+                    &global_settings.network.host_overrides,
+                ))
+        });
+
+        let log_levels = &global_settings.log_levels;
+        let log_level_input = cx.new(|cx| {
+            let mut state = DropdownState::new(
+                LogLevel::ALL.iter().map(ToString::to_string).collect(),
+                Some(IndexPath::new(2)),
+                window,
+                cx,
+            );
+
+            state.set_selected_value(&log_levels.default.to_string(), window, cx);
+
+            state
+        });
+        let api_log_level_input = cx.new(|cx| module_log_level_state(log_levels.api, window, cx));
+        let downloader_log_level_input =
+            cx.new(|cx| module_log_level_state(log_levels.downloader, window, cx));
+        let ui_log_level_input = cx.new(|cx| module_log_level_state(log_levels.ui, window, cx));
+
         let _subscriptions =
             vec![cx.subscribe_in(&record_dir_input, window, Self::on_record_dir_input_change)];
 
         Self {
             global_settings,
             record_dir_input,
+            fallback_record_dirs_input,
             strategy_input,
             quality_input,
             format_input,
             codec_input,
+            danmaku_highlight_input,
+            danmaku_block_input,
+            log_retention_input,
+            reconnect_reset_input,
+            memory_warn_input,
+            offline_grace_input,
+            storyboard_interval_input,
+            blank_detect_input,
+            healthz_port_input,
+            relay_port_input,
+            storage_limit_input,
+            storage_limit_action_input,
+            metered_action_input,
+            pool_size_input,
+            keep_alive_input,
+            read_buffer_input,
+            resolution_input,
+            loudnorm_integrated_input,
+            loudnorm_true_peak_input,
+            loudnorm_lra_input,
+            quiet_hours_start_input,
+            quiet_hours_end_input,
+            dns_servers_input,
+            player_command_input,
+            stop_all_hotkey_input,
+            whisper_binary_input,
+            whisper_model_input,
+            whisper_language_input,
+            webhook_urls_input,
+            webhook_events_input,
+            mqtt_broker_input,
+            mqtt_topic_input,
+            mqtt_username_input,
+            mqtt_password_input,
+            host_overrides_input,
+            log_level_input,
+            api_log_level_input,
+            downloader_log_level_input,
+            ui_log_level_input,
             _subscriptions,
             lock: false,
         }
@@ -155,8 +532,166 @@ impl SettingsModal {
         let quality_str = self.quality_input.read(cx).selected_value();
         let format = self.format_input.read(cx).selected_value();
         let codec = self.codec_input.read(cx).selected_value();
+        let danmaku_highlight = self.danmaku_highlight_input.read(cx).value();
+        let danmaku_block = self.danmaku_block_input.read(cx).value();
+        let log_retention = self.log_retention_input.read(cx).value();
 
         self.global_settings.record_dir = record_dir.to_string();
+        self.global_settings.fallback_record_dirs =
+            split_dirs(&self.fallback_record_dirs_input.read(cx).value());
+        self.global_settings.danmaku.highlight_keywords = split_keywords(&danmaku_highlight);
+        self.global_settings.danmaku.block_keywords = split_keywords(&danmaku_block);
+
+        if let Ok(days) = log_retention.parse::<u32>()
+            && days > 0
+        {
+            self.global_settings.log_retention_days = days;
+        }
+
+        if let Ok(minutes) = self.reconnect_reset_input.read(cx).value().parse::<u32>() {
+            self.global_settings.reconnect_reset_minutes = minutes;
+        }
+
+        if let Ok(memory_warn_mb) = self.memory_warn_input.read(cx).value().parse::<u32>() {
+            self.global_settings.memory_warn_mb = memory_warn_mb;
+        }
+
+        if let Ok(seconds) = self.offline_grace_input.read(cx).value().parse::<u32>() {
+            self.global_settings.offline_grace_seconds = seconds;
+        }
+
+        if let Ok(minutes) = self
+            .storyboard_interval_input
+            .read(cx)
+            .value()
+            .parse::<u32>()
+        {
+            self.global_settings.storyboard_interval_minutes = minutes;
+        }
+
+        if let Ok(minutes) = self.blank_detect_input.read(cx).value().parse::<u32>() {
+            self.global_settings.blank_detect_minutes = minutes;
+        }
+
+        if let Ok(port) = self.healthz_port_input.read(cx).value().parse::<u16>() {
+            self.global_settings.healthz_port = port;
+        }
+
+        if let Ok(port) = self.relay_port_input.read(cx).value().parse::<u16>() {
+            self.global_settings.relay_port = port;
+        }
+
+        if let Ok(limit) = self.storage_limit_input.read(cx).value().parse::<u32>() {
+            self.global_settings.storage_limit_gb = limit;
+        }
+        if let Some(action) = self.storage_limit_action_input.read(cx).selected_value() {
+            self.global_settings.storage_limit_action = action.parse().unwrap_or_default();
+        }
+        if let Some(action) = self.metered_action_input.read(cx).selected_value() {
+            self.global_settings.metered_action = action.parse().unwrap_or_default();
+        }
+
+        if let Ok(pool_size) = self.pool_size_input.read(cx).value().parse::<u32>() {
+            self.global_settings.network.pool_max_idle_per_host = pool_size;
+        }
+
+        if let Ok(seconds) = self.keep_alive_input.read(cx).value().parse::<u32>() {
+            self.global_settings.network.keep_alive_seconds = seconds;
+        }
+
+        if let Ok(read_buffer_kb) = self.read_buffer_input.read(cx).value().parse::<u32>() {
+            self.global_settings.read_buffer_kb = read_buffer_kb;
+        }
+
+        // 无法解析的分辨率视为保持原分辨率
+        self.global_settings.transcode.resolution =
+            self.resolution_input.read(cx).value().parse().ok();
+
+        if let Ok(integrated) = self.loudnorm_integrated_input.read(cx).value().parse() {
+            self.global_settings.loudnorm.integrated = integrated;
+        }
+
+        if let Ok(true_peak) = self.loudnorm_true_peak_input.read(cx).value().parse() {
+            self.global_settings.loudnorm.true_peak = true_peak;
+        }
+
+        if let Ok(lra) = self.loudnorm_lra_input.read(cx).value().parse() {
+            self.global_settings.loudnorm.lra = lra;
+        }
+
+        // 无法解析的时间保留原设置
+        let quiet_hours_start = self.quiet_hours_start_input.read(cx).value();
+        if let Some(start) = parse_clock_time(&quiet_hours_start) {
+            self.global_settings.quiet_hours.start = start.format("%H:%M").to_string();
+        }
+        let quiet_hours_end = self.quiet_hours_end_input.read(cx).value();
+        if let Some(end) = parse_clock_time(&quiet_hours_end) {
+            self.global_settings.quiet_hours.end = end.format("%H:%M").to_string();
+        }
+
+        let whisper_binary = self.whisper_binary_input.read(cx).value();
+        self.global_settings.whisper.binary = match whisper_binary.trim() {
+            "" => WhisperSettings::default().binary,
+            binary => binary.to_string(),
+        };
+        self.global_settings.whisper.model_path =
+            self.whisper_model_input.read(cx).value().trim().to_string();
+        self.global_settings.whisper.language = self
+            .whisper_language_input
+            .read(cx)
+            .value()
+            .trim()
+            .to_string();
+
+        self.global_settings.webhook = WebhookSettings::parse(
+            &self.webhook_urls_input.read(cx).value(),
+            &self.webhook_events_input.read(cx).value(),
+        );
+
+        self.global_settings.mqtt.broker =
+            self.mqtt_broker_input.read(cx).value().trim().to_string();
+        let mqtt_topic = self.mqtt_topic_input.read(cx).value();
+        self.global_settings.mqtt.topic = match mqtt_topic.trim() {
+            "" => DEFAULT_MQTT_TOPIC.to_string(),
+            topic => topic.to_string(),
+        };
+        self.global_settings.mqtt.username =
+            self.mqtt_username_input.read(cx).value().trim().to_string();
+        self.global_settings.mqtt.password = self.mqtt_password_input.read(cx).value().to_string();
+
+        let player_command = self.player_command_input.read(cx).value();
+        self.global_settings.player_command = match player_command.trim() {
+            "" => DEFAULT_PLAYER_COMMAND.to_string(),
+            command => command.to_string(),
+        };
+
+        // 无法解析的热键保留原设置
+        let stop_all_hotkey = self.stop_all_hotkey_input.read(cx).value();
+        let stop_all_hotkey = stop_all_hotkey.trim();
+        if stop_all_hotkey.is_empty() || parse_hotkey(stop_all_hotkey).is_ok() {
+            self.global_settings.stop_all_hotkey = stop_all_hotkey.to_string();
+        } else {
+            window.push_notification(
+                Notification::warning(format!("无效的热键: {stop_all_hotkey}")),
+                cx,
+            );
+        }
+
+        let dns_servers = self.dns_servers_input.read(cx).value();
+        self.global_settings.network.dns_servers = split_keywords(&dns_servers);
+        let host_overrides = self.host_overrides_input.read(cx).value();
+        self.global_settings.network.host_overrides = dns::parse_host_overrides(&host_overrides);
+
+        // 日志级别
+        let log_levels = &mut self.global_settings.log_levels;
+        if let Some(level) = self.log_level_input.read(cx).selected_value()
+            && let Ok(level) = level.parse()
+        {
+            log_levels.default = level;
+        }
+        log_levels.api = module_log_level(&self.api_log_level_input, cx);
+        log_levels.downloader = module_log_level(&self.downloader_log_level_input, cx);
+        log_levels.ui = module_log_level(&self.ui_log_level_input, cx);
 
         // 策略设置
         if let Some(strategy_str) = strategy_str {
@@ -225,6 +760,182 @@ impl SettingsModal {
         })
         .detach();
     }
+
+    fn export_diagnostics(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let settings = AppState::global(cx).settings.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(handle) = rfd::AsyncFileDialog::new()
+                .set_file_name(default_diagnostics_file_name())
+                .add_filter("zip", &["zip"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+
+            let target = handle.path().to_path_buf();
+            let result = cx
+                .background_executor()
+                .spawn(async move { export_diagnostics(&settings, &target) })
+                .await;
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(path) => window.push_notification(
+                    Notification::success(format!("诊断包已导出到 {}", path.display())),
+                    cx,
+                ),
+                Err(e) => {
+                    window.push_notification(Notification::error(format!("导出失败: {e}")), cx)
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn export_history(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let file_name = format!(
+                "blive-history-{}.csv",
+                chrono::Local::now().format("%Y%m%d")
+            );
+            let Some(handle) = rfd::AsyncFileDialog::new()
+                .set_file_name(file_name)
+                .add_filter("CSV", &["csv"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+
+            let target = handle.path().to_path_buf();
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let target = target.clone();
+                    async move { history::export_csv(&target) }
+                })
+                .await;
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(rows) => window.push_notification(
+                    Notification::success(format!(
+                        "已导出 {rows} 条录制记录到 {}",
+                        target.display()
+                    )),
+                    cx,
+                ),
+                Err(e) => {
+                    window.push_notification(Notification::error(format!("导出失败: {e}")), cx)
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn export_monthly_report(
+        &mut self,
+        _: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.spawn_in(window, async move |this, cx| {
+            let file_name = format!("blive-monthly-{}.md", chrono::Local::now().format("%Y%m"));
+            let Some(handle) = rfd::AsyncFileDialog::new()
+                .set_file_name(file_name)
+                .add_filter("Markdown", &["md"])
+                .add_filter("CSV", &["csv"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+
+            // 按保存的扩展名决定导出格式
+            let target = handle.path().to_path_buf();
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let target = target.clone();
+                    async move { history::export_monthly_report(&target) }
+                })
+                .await;
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(rows) => window.push_notification(
+                    Notification::success(format!(
+                        "已导出 {rows} 条月度统计到 {}",
+                        target.display()
+                    )),
+                    cx,
+                ),
+                Err(e) => {
+                    window.push_notification(Notification::error(format!("导出失败: {e}")), cx)
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn export_danmaku(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(source) = rfd::AsyncFileDialog::new()
+                .add_filter("弹幕归档", &["zst", "jsonl"])
+                .pick_file()
+                .await
+            else {
+                return;
+            };
+
+            let source = source.path().to_path_buf();
+            let file_name = source
+                .file_name()
+                .map(|name| name.to_string_lossy().trim_end_matches(".zst").to_string())
+                .unwrap_or_else(|| "danmaku.jsonl".to_string());
+
+            let Some(target) = rfd::AsyncFileDialog::new()
+                .set_file_name(file_name)
+                .add_filter("jsonl", &["jsonl"])
+                .add_filter("SRT 字幕", &["srt"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+
+            // 按保存的扩展名决定导出格式，SRT 会按密度抽稀
+            let target = target.path().to_path_buf();
+            let as_srt = target.extension().is_some_and(|ext| ext == "srt");
+            let result = cx
+                .background_executor()
+                .spawn({
+                    let target = target.clone();
+                    async move {
+                        if as_srt {
+                            srt::export_srt(&source, &target)
+                        } else {
+                            archive::export_archive(&source, &target)
+                        }
+                    }
+                })
+                .await;
+
+            let _ = this.update_in(cx, |_, window, cx| match result {
+                Ok(count) => window.push_notification(
+                    Notification::success(format!(
+                        "已导出 {count} 条{}到 {}",
+                        if as_srt { "字幕" } else { "弹幕" },
+                        target.display()
+                    )),
+                    cx,
+                ),
+                Err(e) => {
+                    window.push_notification(Notification::error(format!("导出失败: {e}")), cx)
+                }
+            });
+        })
+        .detach();
+    }
 }
 
 impl Render for SettingsModal {
@@ -253,6 +964,15 @@ impl Render for SettingsModal {
                                         ),
                                 ),
                         )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String(
+                                    "备用录制目录（空间不足或不可写时切换）".into(),
+                                ))
+                                .child(TextInput::new(&self.fallback_record_dirs_input)),
+                        )
                         .child(
                             v_flex()
                                 .font_bold()
@@ -260,6 +980,139 @@ impl Render for SettingsModal {
                                 .child(Text::String("录制策略".into()))
                                 .child(Dropdown::new(&self.strategy_input).max_w_32()),
                         )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("重编码".into()))
+                                .child(
+                                    Switch::new("transcode_reencode")
+                                        .checked(self.global_settings.transcode.reencode)
+                                        .tooltip(
+                                            "仅 PriorityConfig 策略生效，关闭时只转封装（-c copy）",
+                                        )
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.transcode.reencode = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .when(self.global_settings.transcode.reencode, |this| {
+                            this.child(
+                                v_flex()
+                                    .font_bold()
+                                    .gap_2()
+                                    .child(Text::String("目标分辨率".into()))
+                                    .child(TextInput::new(&self.resolution_input).max_w_64()),
+                            )
+                        })
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("写入元数据".into()))
+                                .child(
+                                    Switch::new("embed_metadata")
+                                        .checked(self.global_settings.embed_metadata)
+                                        .tooltip("LowCost 录制完成后重新封装，写入标题、主播与日期")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.embed_metadata = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("响度标准化".into()))
+                                .child(
+                                    Switch::new("loudnorm_enabled")
+                                        .checked(self.global_settings.loudnorm.enabled)
+                                        .tooltip("录制完成后按 EBU R128 标准化音量，只重编码音轨")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.loudnorm.enabled = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("静默时段".into()))
+                                .child(
+                                    Switch::new("quiet_hours_enabled")
+                                        .checked(self.global_settings.quiet_hours.enabled)
+                                        .tooltip("时段内新开播只记录不录制、不弹通知，时段结束后自动开始录制")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.quiet_hours.enabled = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .when(self.global_settings.quiet_hours.enabled, |this| {
+                            this.child(
+                                v_flex()
+                                    .font_bold()
+                                    .gap_2()
+                                    .child(Text::String(
+                                        "开始 / 结束时间（HH:MM，结束早于开始表示跨零点）".into(),
+                                    ))
+                                    .child(
+                                        h_flex()
+                                            .gap_x_2()
+                                            .child(
+                                                TextInput::new(&self.quiet_hours_start_input)
+                                                    .max_w_32(),
+                                            )
+                                            .child(
+                                                TextInput::new(&self.quiet_hours_end_input)
+                                                    .max_w_32(),
+                                            )
+                                            .child(Text::String("仅工作日".into()))
+                                            .child(
+                                                Switch::new("quiet_hours_weekdays_only")
+                                                    .checked(
+                                                        self.global_settings.quiet_hours.weekdays_only,
+                                                    )
+                                                    .on_click(cx.listener(
+                                                        |this, checked: &bool, _, cx| {
+                                                            this.global_settings
+                                                                .quiet_hours
+                                                                .weekdays_only = *checked;
+                                                            cx.notify();
+                                                        },
+                                                    )),
+                                            ),
+                                    ),
+                            )
+                        })
+                        .when(self.global_settings.loudnorm.enabled, |this| {
+                            this.child(
+                                v_flex()
+                                    .font_bold()
+                                    .gap_2()
+                                    .child(Text::String(
+                                        "目标响度 LUFS / 真峰值 dBTP / 响度范围 LU".into(),
+                                    ))
+                                    .child(
+                                        h_flex()
+                                            .gap_x_2()
+                                            .child(
+                                                TextInput::new(&self.loudnorm_integrated_input)
+                                                    .max_w_32(),
+                                            )
+                                            .child(
+                                                TextInput::new(&self.loudnorm_true_peak_input)
+                                                    .max_w_32(),
+                                            )
+                                            .child(
+                                                TextInput::new(&self.loudnorm_lra_input).max_w_32(),
+                                            ),
+                                    ),
+                            )
+                        })
                         .child(
                             v_flex()
                                 .font_bold()
@@ -280,10 +1133,359 @@ impl Render for SettingsModal {
                                 .gap_2()
                                 .child(Text::String("录制编码".into()))
                                 .child(Dropdown::new(&self.codec_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("弹幕高亮关键词".into()))
+                                .child(TextInput::new(&self.danmaku_highlight_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("弹幕屏蔽关键词".into()))
+                                .child(TextInput::new(&self.danmaku_block_input)),
+                        )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("弹幕归档".into()))
+                                .child(
+                                    Switch::new("danmaku_archive")
+                                        .checked(self.global_settings.danmaku.archive)
+                                        .tooltip("录制时在视频旁保存弹幕（JSONL）")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.danmaku.archive = *checked;
+                                            cx.notify();
+                                        })),
+                                )
+                                .child(Text::String("zstd 压缩".into()))
+                                .child(
+                                    Switch::new("danmaku_compress_archive")
+                                        .checked(self.global_settings.danmaku.compress_archive)
+                                        .tooltip("保存为 .jsonl.zst，可通过“导出弹幕”解压")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.danmaku.compress_archive =
+                                                *checked;
+                                            cx.notify();
+                                        })),
+                                )
+                                .child(Text::String("原始数据包".into()))
+                                .child(
+                                    Switch::new("danmaku_raw_dump")
+                                        .checked(self.global_settings.danmaku.raw_dump)
+                                        .tooltip("录制时在视频旁保存带时间戳的弹幕原始数据包，便于重放")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.danmaku.raw_dump = *checked;
+                                            cx.notify();
+                                        })),
+                                )
+                                .child(Text::String("归档屏蔽弹幕".into()))
+                                .child(
+                                    Switch::new("danmaku_archive_blocked")
+                                        .checked(self.global_settings.danmaku.archive_blocked)
+                                        .tooltip("关闭后命中屏蔽词的弹幕不写入归档")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.danmaku.archive_blocked = *checked;
+                                            cx.notify();
+                                        })),
+                                )
+                                .child(Text::String("ASS 字幕".into()))
+                                .child(
+                                    Switch::new("danmaku_ass_sidecar")
+                                        .checked(self.global_settings.danmaku.ass_sidecar)
+                                        .tooltip("开启录制弹幕的房间保存为 ASS 字幕，关闭时为 B 站 XML")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.danmaku.ass_sidecar = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("稳定录制多少分钟后重置重连计数".into()))
+                                .child(TextInput::new(&self.reconnect_reset_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("下播宽限期（秒）".into()))
+                                .child(TextInput::new(&self.offline_grace_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("录制截图间隔（分钟）".into()))
+                                .child(TextInput::new(&self.storyboard_interval_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("黑屏/静音检测阈值（分钟）".into()))
+                                .child(TextInput::new(&self.blank_detect_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("录像总占用上限（GB）/ 达到上限后".into()))
+                                .child(
+                                    h_flex()
+                                        .gap_x_2()
+                                        .child(TextInput::new(&self.storage_limit_input).max_w_32())
+                                        .child(
+                                            Dropdown::new(&self.storage_limit_action_input)
+                                                .max_w_32(),
+                                        ),
+                                ),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String(
+                                    format!("计费网络下的自动录制（限制画质时不高于{METERED_QUALITY}）").into(),
+                                ))
+                                .child(Dropdown::new(&self.metered_action_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("内存占用告警阈值（MB）".into()))
+                                .child(TextInput::new(&self.memory_warn_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("日志保留天数".into()))
+                                .child(TextInput::new(&self.log_retention_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("日志级别（全局 / 接口 / 录制 / 界面）".into()))
+                                .child(
+                                    h_flex()
+                                        .gap_x_2()
+                                        .child(Dropdown::new(&self.log_level_input).max_w_32())
+                                        .child(Dropdown::new(&self.api_log_level_input).max_w_32())
+                                        .child(
+                                            Dropdown::new(&self.downloader_log_level_input)
+                                                .max_w_32(),
+                                        )
+                                        .child(Dropdown::new(&self.ui_log_level_input).max_w_32()),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("监听剪贴板".into()))
+                                .child(
+                                    Switch::new("clipboard_watch")
+                                        .checked(self.global_settings.clipboard_watch)
+                                        .tooltip("复制直播间链接后提示添加房间")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.clipboard_watch = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("严格画质".into()))
+                                .child(
+                                    Switch::new("strict_quality")
+                                        .checked(self.global_settings.strict_quality)
+                                        .tooltip("拿不到设置的画质/编码时不降级录制，报错等待重试")
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.strict_quality = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .font_bold()
+                                .gap_4()
+                                .child(Text::String("心跳文件".into()))
+                                .child(
+                                    Switch::new("heartbeat_file")
+                                        .checked(self.global_settings.heartbeat_file)
+                                        .tooltip(
+                                            "定期更新配置目录下的 heartbeat.json，供外部监控检查",
+                                        )
+                                        .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                            this.global_settings.heartbeat_file = *checked;
+                                            cx.notify();
+                                        })),
+                                ),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("健康检查端口（/healthz，重启后生效）".into()))
+                                .child(TextInput::new(&self.healthz_port_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("本地转发端口（边录边看，重启后生效）".into()))
+                                .child(TextInput::new(&self.relay_port_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String(
+                                    "外部播放器命令（{url}、{user_agent}、{referer}）".into(),
+                                ))
+                                .child(TextInput::new(&self.player_command_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("停止全部录制的全局热键".into()))
+                                .child(TextInput::new(&self.stop_all_hotkey_input).max_w_48()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("Webhook 地址 / 推送事件".into()))
+                                .child(TextInput::new(&self.webhook_urls_input))
+                                .child(TextInput::new(&self.webhook_events_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(
+                                    h_flex()
+                                        .gap_4()
+                                        .child(Text::String(
+                                            "MQTT broker / 主题（{room_id}、{event}、{up_name}）"
+                                                .into(),
+                                        ))
+                                        .child(Text::String("保留消息".into()))
+                                        .child(
+                                            Switch::new("mqtt_retain")
+                                                .checked(self.global_settings.mqtt.retain)
+                                                .tooltip("保留消息，订阅者上线时能收到最近一次事件")
+                                                .on_click(cx.listener(
+                                                    |this, checked: &bool, _, cx| {
+                                                        this.global_settings.mqtt.retain = *checked;
+                                                        cx.notify();
+                                                    },
+                                                )),
+                                        ),
+                                )
+                                .child(TextInput::new(&self.mqtt_broker_input))
+                                .child(TextInput::new(&self.mqtt_topic_input))
+                                .child(
+                                    h_flex()
+                                        .gap_x_2()
+                                        .child(TextInput::new(&self.mqtt_username_input).max_w_48())
+                                        .child(
+                                            TextInput::new(&self.mqtt_password_input).max_w_48(),
+                                        ),
+                                ),
+                        )
+                        .when(cfg!(feature = "whisper"), |this| {
+                            this.child(
+                                v_flex()
+                                    .font_bold()
+                                    .gap_2()
+                                    .child(Text::String(
+                                        "语音转写（whisper.cpp 可执行文件 / 模型 / 语言）".into(),
+                                    ))
+                                    .child(
+                                        h_flex()
+                                            .gap_x_2()
+                                            .child(
+                                                TextInput::new(&self.whisper_binary_input)
+                                                    .max_w_32(),
+                                            )
+                                            .child(TextInput::new(&self.whisper_model_input))
+                                            .child(
+                                                TextInput::new(&self.whisper_language_input)
+                                                    .max_w_32(),
+                                            ),
+                                    ),
+                            )
+                        })
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String(
+                                    "连接池大小 / 空闲连接保持时长（秒，重启后生效）".into(),
+                                ))
+                                .child(
+                                    h_flex()
+                                        .gap_x_2()
+                                        .child(TextInput::new(&self.pool_size_input).max_w_32())
+                                        .child(TextInput::new(&self.keep_alive_input).max_w_32()),
+                                ),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String(
+                                    "读缓冲区大小（KB，下次开始录制时生效）".into(),
+                                ))
+                                .child(TextInput::new(&self.read_buffer_input).max_w_32()),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("DNS 服务器".into()))
+                                .child(TextInput::new(&self.dns_servers_input)),
+                        )
+                        .child(
+                            v_flex()
+                                .font_bold()
+                                .gap_2()
+                                .child(Text::String("域名直连（域名=IP）".into()))
+                                .child(TextInput::new(&self.host_overrides_input)),
                         ),
                 ),
             )
             .child(h_flex().justify_end().gap_x_4().children(vec![
+                    Button::new("export_diagnostics")
+                        .label("导出诊断信息")
+                        .tooltip("打包最近日志、脱敏后的配置与系统信息，便于反馈问题")
+                        .on_click(cx.listener(Self::export_diagnostics)),
+                    Button::new("export_danmaku")
+                        .label("导出弹幕")
+                        .tooltip("将弹幕归档导出为 JSONL，或抽稀后导出为 SRT 字幕")
+                        .on_click(cx.listener(Self::export_danmaku)),
+                    Button::new("export_history")
+                        .label("导出录制记录")
+                        .tooltip("将历史录制记录导出为 CSV，便于在 Excel 中统计")
+                        .on_click(cx.listener(Self::export_history)),
+                    Button::new("export_monthly_report")
+                        .label("导出月度统计")
+                        .tooltip("按主播汇总每月的录制场次、时长、大小与平均码率，导出为 Markdown 或 CSV")
+                        .on_click(cx.listener(Self::export_monthly_report)),
                     Button::new("save")
                         .label("保存设置")
                         .primary()
@@ -295,3 +1497,52 @@ impl Render for SettingsModal {
                 ]))
     }
 }
+
+/// 模块日志级别下拉框中表示跟随全局级别的选项
+const FOLLOW_GLOBAL_LOG_LEVEL: &str = "跟随全局";
+
+fn module_log_level_state(
+    level: Option<LogLevel>,
+    window: &mut Window,
+    cx: &mut Context<DropdownState<Vec<String>>>,
+) -> DropdownState<Vec<String>> {
+    let mut state = DropdownState::new(
+        std::iter::once(FOLLOW_GLOBAL_LOG_LEVEL.to_string())
+            .chain(LogLevel::ALL.iter().map(ToString::to_string))
+            .collect(),
+        Some(IndexPath::new(0)),
+        window,
+        cx,
+    );
+
+    if let Some(level) = level {
+        state.set_selected_value(&level.to_string(), window, cx);
+    }
+
+    state
+}
+
+fn module_log_level(input: &Entity<DropdownState<Vec<String>>>, cx: &App) -> Option<LogLevel> {
+    input
+        .read(cx)
+        .selected_value()
+        .and_then(|level| level.parse().ok())
+}
+
+/// 路径中可能带逗号，目录列表用分号分隔
+fn split_dirs(value: &str) -> Vec<String> {
+    value
+        .split([';', '；'])
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty())
+        .collect()
+}
+
+/// 按中英文逗号拆分关键词列表
+fn split_keywords(value: &str) -> Vec<String> {
+    value
+        .split([',', '，'])
+        .map(|keyword| keyword.trim().to_string())
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
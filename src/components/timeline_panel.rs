@@ -0,0 +1,250 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Local};
+use gpui::{App, Axis, IntoElement, ObjectFit, RenderOnce, Window, div, img, prelude::*, px};
+use gpui_component::{
+    ActiveTheme as _, ContextModal, Sizable, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    notification::Notification,
+    text::Text,
+    v_flex,
+};
+
+use crate::{
+    components::ClipModal,
+    core::{
+        downloader::{
+            timeline::{SessionEvent, SessionEventKind, SessionTimeline, output_path},
+            utils::pretty_duration,
+        },
+        player::open_in_player,
+    },
+    state::AppState,
+};
+
+/// 录制会话事件时间线，按时间顺序展示开始、重连、分 P 切换、错误、高光时刻与完成，
+/// 会话结束后在顶部给出覆盖率结论
+#[derive(IntoElement)]
+pub struct TimelinePanel {
+    timeline: SessionTimeline,
+    /// 已完成录像的故事板截图，按录像路径索引
+    storyboards: HashMap<String, Vec<(Duration, PathBuf)>>,
+    live_start: Option<i64>,
+}
+
+impl TimelinePanel {
+    pub fn new(
+        timeline: SessionTimeline,
+        storyboards: HashMap<String, Vec<(Duration, PathBuf)>>,
+        live_start: Option<i64>,
+    ) -> Self {
+        Self {
+            timeline,
+            storyboards,
+            live_start,
+        }
+    }
+}
+
+impl RenderOnce for TimelinePanel {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let events = self
+            .timeline
+            .events()
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let time = DateTime::from_timestamp_millis(event.timestamp)
+                    .map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string())
+                    .unwrap_or_default();
+                let color = match event.kind {
+                    SessionEventKind::Error { .. } => cx.theme().red,
+                    SessionEventKind::Reconnecting | SessionEventKind::BlankSegments { .. } => {
+                        cx.theme().warning
+                    }
+                    SessionEventKind::Highlights { .. } => cx.theme().primary,
+                    _ => cx.theme().foreground,
+                };
+
+                h_flex()
+                    .gap_2()
+                    .text_sm()
+                    .child(
+                        div()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String(time.into())),
+                    )
+                    .child(
+                        div()
+                            .text_color(color)
+                            .child(Text::String(event.kind.describe().into())),
+                    )
+                    .when_some(
+                        completed_file(self.timeline.events(), &event.kind),
+                        |this, (recorded_path, file_path)| {
+                            let clip_file = file_path.clone();
+                            let snapshots = self
+                                .storyboards
+                                .get(&recorded_path)
+                                .cloned()
+                                .unwrap_or_default();
+
+                            this.child(
+                                Button::new(("timeline-play", i))
+                                    .ghost()
+                                    .xsmall()
+                                    .label("播放")
+                                    .on_click(move |_, window, cx| {
+                                        let command =
+                                            AppState::global(cx).settings.player_command.clone();
+                                        if let Err(e) = open_in_player(&command, &file_path) {
+                                            window.push_notification(
+                                                Notification::error(format!("打开播放器失败: {e}")),
+                                                cx,
+                                            );
+                                        }
+                                    }),
+                            )
+                            .child(
+                                Button::new(("timeline-clip", i))
+                                    .ghost()
+                                    .xsmall()
+                                    .label("切片")
+                                    .on_click(move |_, window, cx| {
+                                        let clip =
+                                            ClipModal::view(clip_file.clone().into(), window, cx);
+                                        window.open_modal(cx, move |modal, _, _| {
+                                            modal
+                                                .rounded_lg()
+                                                .title(
+                                                    div()
+                                                        .font_bold()
+                                                        .text_2xl()
+                                                        .child(Text::String("导出切片".into())),
+                                                )
+                                                .child(clip.clone())
+                                        });
+                                    }),
+                            )
+                            .when(!snapshots.is_empty(), |this| {
+                                this.child(
+                                    Button::new(("timeline-storyboard", i))
+                                        .ghost()
+                                        .xsmall()
+                                        .label("故事板")
+                                        .on_click(move |_, window, cx| {
+                                            let snapshots = snapshots.clone();
+                                            window.open_modal(cx, move |modal, _, cx| {
+                                                modal
+                                                    .rounded_lg()
+                                                    .title(
+                                                        div()
+                                                            .font_bold()
+                                                            .text_2xl()
+                                                            .child(Text::String("故事板".into())),
+                                                    )
+                                                    .child(render_storyboard(&snapshots, cx))
+                                            });
+                                        }),
+                                )
+                            })
+                        },
+                    )
+            })
+            .collect::<Vec<_>>();
+        let coverage = self.timeline.coverage(self.live_start);
+
+        v_flex()
+            .rounded_lg()
+            .p_3()
+            .max_h(px(240.))
+            .border(px(1.0))
+            .border_color(cx.theme().border)
+            .bg(cx.theme().background)
+            .map(|this| {
+                if events.is_empty() {
+                    this.items_center().child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(Text::String("暂无录制事件".into())),
+                    )
+                } else {
+                    this.when_some(coverage, |this, coverage| {
+                        this.child(
+                            v_flex()
+                                .pb_2()
+                                .mb_2()
+                                .border_b_1()
+                                .border_color(cx.theme().border)
+                                .text_sm()
+                                .child(
+                                    div()
+                                        .font_bold()
+                                        .text_color(if coverage.gaps.is_empty() {
+                                            cx.theme().foreground
+                                        } else {
+                                            cx.theme().warning
+                                        })
+                                        .child(Text::String(coverage.describe().into())),
+                                )
+                                .children(coverage.describe_gaps().into_iter().map(|gap| {
+                                    div()
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground)
+                                        .child(Text::String(format!("缺口: {gap}").into()))
+                                })),
+                        )
+                    })
+                    .child(
+                        v_flex()
+                            .size_full()
+                            .gap_1()
+                            .font_medium()
+                            .scrollable(Axis::Vertical)
+                            .children(events),
+                    )
+                }
+            })
+    }
+}
+
+/// 录制期间的定时截图，按时间排列
+fn render_storyboard(snapshots: &[(Duration, PathBuf)], cx: &App) -> impl IntoElement {
+    h_flex()
+        .flex_wrap()
+        .gap_2()
+        .max_h(px(480.))
+        .scrollable(Axis::Vertical)
+        .children(snapshots.iter().map(|(offset, path)| {
+            v_flex()
+                .gap_1()
+                .w(px(160.))
+                .child(
+                    img(path.clone())
+                        .block()
+                        .w_full()
+                        .rounded(cx.theme().radius)
+                        .overflow_hidden()
+                        .object_fit(ObjectFit::Cover),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(Text::String(pretty_duration(offset.as_secs()).into())),
+                )
+        }))
+}
+
+/// 已写入完成的录制文件，返回录制时的路径与可用播放器打开的路径，提取音频后后者指向音频文件
+fn completed_file(events: &[SessionEvent], kind: &SessionEventKind) -> Option<(String, String)> {
+    match kind {
+        SessionEventKind::Completed { file_path, .. } => Some((
+            file_path.clone(),
+            output_path(events, file_path).to_string(),
+        )),
+        _ => None,
+    }
+}
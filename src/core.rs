@@ -1,4 +1,32 @@
+pub mod audio;
+pub mod auth;
+pub mod blank;
+pub mod clip;
+pub mod cover;
+pub mod danmaku;
+pub mod disk;
+pub mod dns;
 pub mod downloader;
+pub mod ffmpeg;
+pub mod ffprobe;
+pub mod history;
 pub mod http_client;
+pub mod hwaccel;
+pub mod loudnorm;
+pub mod metered;
+pub mod monitor;
+pub mod mqtt;
+pub mod netwatch;
+pub mod playback;
+pub mod player;
+pub mod probe;
+pub mod record_lock;
+pub mod relay;
+pub mod repair;
+pub mod scheduler;
+pub mod storage;
+pub mod webhook;
+#[cfg(feature = "whisper")]
+pub mod whisper;
 
 pub use http_client::HttpClient;
@@ -0,0 +1,85 @@
+//! 纯音频录制：直播流只能整体拉取，录制结束后用 ffmpeg 去除视频轨，按设置复制或转码音轨
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    core::{disk::extended_path, repair::ffmpeg_path},
+    settings::AudioFormat,
+};
+
+/// 音频文件与录制文件同名，仅替换扩展名
+pub fn audio_path(path: &Path, format: AudioFormat) -> PathBuf {
+    path.with_extension(format.ext())
+}
+
+/// 输出文件之前的 ffmpeg 参数，aac/m4a 直接复制音轨，flac 需要转码
+pub fn extract_args(format: AudioFormat) -> Vec<String> {
+    let args: &[&str] = match format {
+        AudioFormat::Aac => &["-vn", "-sn", "-dn", "-c:a", "copy", "-f", "adts"],
+        AudioFormat::M4a => &[
+            "-vn",
+            "-sn",
+            "-dn",
+            "-c:a",
+            "copy",
+            "-movflags",
+            "+faststart",
+        ],
+        AudioFormat::Flac => &["-vn", "-sn", "-dn", "-c:a", "flac"],
+    };
+
+    args.iter().map(ToString::to_string).collect()
+}
+
+/// 提取音轨并删除原录制文件，返回音频文件路径
+pub fn extract_file(path: &Path, format: AudioFormat) -> Result<PathBuf> {
+    let output_path = audio_path(path, format);
+    let input = extended_path(&path.to_string_lossy());
+    let output_file = extended_path(&output_path.to_string_lossy());
+
+    let output = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-y", "-i"])
+        .arg(&input)
+        .args(extract_args(format))
+        .arg(&output_file)
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&output_file);
+        anyhow::bail!(
+            "ffmpeg 提取音频失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    std::fs::remove_file(&input).context("无法删除原录制文件")?;
+
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_args() {
+        assert_eq!(
+            audio_path(Path::new("/录制/a.flv"), AudioFormat::M4a),
+            PathBuf::from("/录制/a.m4a")
+        );
+
+        assert!(extract_args(AudioFormat::Aac).ends_with(&["-f".to_string(), "adts".to_string()]));
+        assert!(extract_args(AudioFormat::Flac).contains(&"flac".to_string()));
+        assert!(
+            AudioFormat::ALL
+                .iter()
+                .all(|format| extract_args(*format).contains(&"-vn".to_string()))
+        );
+    }
+}
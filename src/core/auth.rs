@@ -0,0 +1,306 @@
+//! 登录账号
+//!
+//! 支持粘贴 Cookie 或扫码登录。凭据以 ChaCha20-Poly1305 加密后保存在配置目录的 `credentials.bin`，
+//! 密钥单独保存在 `credentials.key`，导出设置与诊断包都不会带上登录信息。
+//! 登录后 [`crate::core::HttpClient`] 向 B 站接口的请求自动携带 Cookie，4K、杜比等需要登录的画质才能解析
+
+use std::{io, path::PathBuf, time::Duration};
+
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce, aead::Aead};
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{config_dir, write_private};
+
+/// 定期检查登录状态的间隔
+pub const LOGIN_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 距离过期不足该时长（秒）时在诊断中提示
+const EXPIRY_WARNING_SECS: i64 = 7 * 24 * 60 * 60;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// 当前登录的凭据
+static CREDENTIALS: RwLock<Option<Credentials>> = RwLock::new(None);
+
+/// 登录凭据，各字段保持 Cookie 中的原样（SESSDATA 含 URL 编码）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credentials {
+    pub sessdata: String,
+    #[serde(default)]
+    pub bili_jct: String,
+    /// 账号 uid，只粘贴 SESSDATA 时为 0
+    #[serde(default)]
+    pub uid: u64,
+    /// 过期时间（秒级时间戳）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+impl Credentials {
+    /// 解析粘贴的内容，可以是浏览器中复制的完整 Cookie，也可以只有 SESSDATA 的值
+    pub fn parse_cookie(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches("Cookie:").trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        if !text.contains('=') {
+            return Self::new(text, "", 0);
+        }
+
+        let field = |name: &str| {
+            text.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                (key.trim() == name).then(|| value.trim())
+            })
+        };
+
+        Self::new(
+            field("SESSDATA")?,
+            field("bili_jct").unwrap_or_default(),
+            field("DedeUserID")
+                .and_then(|uid| uid.parse().ok())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// 扫码登录成功后，跳转地址的查询参数中带有 Cookie
+    pub fn from_login_url(url: &str) -> Option<Self> {
+        let (_, query) = url.split_once('?')?;
+        let field = |name: &str| {
+            query.split('&').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                (key == name).then_some(value)
+            })
+        };
+
+        let mut credentials = Self::new(
+            field("SESSDATA")?,
+            field("bili_jct").unwrap_or_default(),
+            field("DedeUserID")
+                .and_then(|uid| uid.parse().ok())
+                .unwrap_or_default(),
+        )?;
+        if let Some(expires_at) = field("Expires").and_then(|expires| expires.parse().ok()) {
+            credentials.expires_at = Some(expires_at);
+        }
+
+        Some(credentials)
+    }
+
+    fn new(sessdata: &str, bili_jct: &str, uid: u64) -> Option<Self> {
+        // Cookie 值中不能有分隔符与空白
+        if sessdata.is_empty()
+            || sessdata
+                .chars()
+                .any(|c| c.is_whitespace() || c == ';' || c.is_control())
+        {
+            return None;
+        }
+
+        Some(Self {
+            sessdata: sessdata.to_string(),
+            bili_jct: bili_jct.to_string(),
+            uid,
+            expires_at: sessdata_expires_at(sessdata),
+        })
+    }
+
+    /// 请求头中的 Cookie
+    pub fn cookie_header(&self) -> String {
+        let mut cookie = format!("SESSDATA={}", self.sessdata);
+        if !self.bili_jct.is_empty() {
+            cookie.push_str(&format!("; bili_jct={}", self.bili_jct));
+        }
+        if self.uid > 0 {
+            cookie.push_str(&format!("; DedeUserID={}", self.uid));
+        }
+
+        cookie
+    }
+
+    /// 在 `now`（秒级时间戳）之后不到一周过期
+    pub fn expires_soon(&self, now: i64) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at - now < EXPIRY_WARNING_SECS)
+    }
+}
+
+/// SESSDATA 形如 `xxx%2C1700000000%2Cyyy`，第二段为过期时间
+fn sessdata_expires_at(sessdata: &str) -> Option<i64> {
+    sessdata
+        .replace("%2C", ",")
+        .replace("%2c", ",")
+        .split(',')
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+fn credentials_path() -> PathBuf {
+    config_dir().join("credentials.bin")
+}
+
+fn key_path() -> PathBuf {
+    config_dir().join("credentials.key")
+}
+
+fn load_or_create_key() -> io::Result<[u8; KEY_LEN]> {
+    if let Ok(key) = std::fs::read(key_path())
+        && let Ok(key) = key.try_into()
+    {
+        return Ok(key);
+    }
+
+    let mut key = [0u8; KEY_LEN];
+    rand::rng().fill(&mut key);
+    write_private(&key_path(), &key)?;
+
+    Ok(key)
+}
+
+/// 加密结果为随机 nonce 加密文
+fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill(&mut nonce);
+
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| io::Error::other("加密登录信息失败"))?;
+
+    Ok([nonce.as_slice(), ciphertext.as_slice()].concat())
+}
+
+fn decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < NONCE_LEN {
+        return None;
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .ok()
+}
+
+/// 启动时读取保存的登录信息，无法解密时视为未登录
+pub fn load() {
+    let Ok(data) = std::fs::read(credentials_path()) else {
+        return;
+    };
+
+    let credentials = std::fs::read(key_path())
+        .ok()
+        .and_then(|key| <[u8; KEY_LEN]>::try_from(key).ok())
+        .and_then(|key| decrypt(&key, &data))
+        .and_then(|plaintext| serde_json::from_slice::<Credentials>(&plaintext).ok());
+
+    match credentials {
+        Some(credentials) => {
+            tracing::info!("已加载登录信息 - uid: {}", credentials.uid);
+            *CREDENTIALS.write() = Some(credentials);
+        }
+        None => tracing::warn!("登录信息无法解密，需要重新登录"),
+    }
+}
+
+/// 保存并启用登录信息
+pub fn login(credentials: Credentials) -> io::Result<()> {
+    let key = load_or_create_key()?;
+    let plaintext = serde_json::to_vec(&credentials).map_err(io::Error::other)?;
+    write_private(&credentials_path(), &encrypt(&key, &plaintext)?)?;
+
+    tracing::info!("已登录 - uid: {}", credentials.uid);
+    *CREDENTIALS.write() = Some(credentials);
+
+    Ok(())
+}
+
+/// 退出登录并删除保存的凭据
+pub fn logout() {
+    *CREDENTIALS.write() = None;
+    let _ = std::fs::remove_file(credentials_path());
+    tracing::info!("已退出登录");
+}
+
+pub fn current() -> Option<Credentials> {
+    CREDENTIALS.read().clone()
+}
+
+/// 已登录时请求需要携带的 Cookie
+pub fn cookie_header() -> Option<String> {
+    CREDENTIALS.read().as_ref().map(Credentials::cookie_header)
+}
+
+/// 已登录账号的 uid，未登录时为 0
+pub fn uid() -> u64 {
+    CREDENTIALS
+        .read()
+        .as_ref()
+        .map(|credentials| credentials.uid)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cookie() {
+        let credentials = Credentials::parse_cookie(
+            "Cookie: buvid3=x; SESSDATA=abc%2C1700000000%2Cdef*11; bili_jct=c0ffee; DedeUserID=42",
+        )
+        .unwrap();
+        assert_eq!(credentials.sessdata, "abc%2C1700000000%2Cdef*11");
+        assert_eq!(credentials.bili_jct, "c0ffee");
+        assert_eq!(credentials.uid, 42);
+        assert_eq!(credentials.expires_at, Some(1700000000));
+        assert_eq!(
+            credentials.cookie_header(),
+            "SESSDATA=abc%2C1700000000%2Cdef*11; bili_jct=c0ffee; DedeUserID=42"
+        );
+
+        // 只粘贴 SESSDATA 的值
+        let credentials = Credentials::parse_cookie(" abc%2C1700000000%2Cdef ").unwrap();
+        assert_eq!(
+            credentials.cookie_header(),
+            "SESSDATA=abc%2C1700000000%2Cdef"
+        );
+
+        assert!(Credentials::parse_cookie("").is_none());
+        assert!(Credentials::parse_cookie("buvid3=x").is_none());
+        assert!(Credentials::parse_cookie("abc def").is_none());
+    }
+
+    #[test]
+    fn test_from_login_url() {
+        let credentials = Credentials::from_login_url(
+            "https://passport.biligame.com/x/passport-login/web/crossDomain?DedeUserID=42&DedeUserID__ckMd5=x&Expires=1700000000&SESSDATA=abc%2C1700000000%2Cdef&bili_jct=c0ffee&gourl=https%3A%2F%2Fwww.bilibili.com",
+        )
+        .unwrap();
+        assert_eq!(credentials.uid, 42);
+        assert_eq!(credentials.bili_jct, "c0ffee");
+        assert_eq!(credentials.expires_at, Some(1700000000));
+        assert!(credentials.expires_soon(1700000000 - 60));
+        assert!(!credentials.expires_soon(1700000000 - EXPIRY_WARNING_SECS));
+
+        assert!(Credentials::from_login_url("https://www.bilibili.com").is_none());
+    }
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let key = [7u8; KEY_LEN];
+        let data = encrypt(&key, b"SESSDATA=abc").unwrap();
+        assert_ne!(&data[NONCE_LEN..], b"SESSDATA=abc");
+        assert_eq!(decrypt(&key, &data).unwrap(), b"SESSDATA=abc");
+
+        // 密钥不对或数据被篡改时无法解密
+        assert!(decrypt(&[8u8; KEY_LEN], &data).is_none());
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&key, &tampered).is_none());
+        assert!(decrypt(&key, &data[..4]).is_none());
+    }
+}
@@ -0,0 +1,218 @@
+//! 黑屏/静音段检测：用 ffmpeg blackdetect 与 silencedetect 找出挂机录到的待机画面
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{disk::extended_path, repair::ffmpeg_path};
+
+/// 判定为黑屏的像素亮度阈值
+const BLACK_PIXEL_THRESHOLD: f64 = 0.10;
+
+/// 判定为静音的音量阈值
+const SILENCE_NOISE: &str = "-50dB";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlankKind {
+    Black,
+    Silence,
+}
+
+/// 一段可疑的黑屏或静音，时间为相对录像开头的秒数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlankSegment {
+    pub kind: BlankKind,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl BlankSegment {
+    pub fn describe(&self) -> String {
+        let kind = match self.kind {
+            BlankKind::Black => "黑屏",
+            BlankKind::Silence => "静音",
+        };
+
+        format!(
+            "{kind} {}-{}",
+            format_offset(self.start),
+            format_offset(self.end)
+        )
+    }
+}
+
+fn format_offset(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// 取 `key:value` 或 `key: value` 中的数值
+fn field(line: &str, key: &str) -> Option<f64> {
+    let rest = &line[line.find(key)? + key.len()..];
+    rest.trim_start()
+        .split(|c: char| c.is_whitespace() || c == '|')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// 解析 ffmpeg 输入信息中的 `Duration: 01:02:03.45`
+fn parse_duration(line: &str) -> Option<f64> {
+    let rest = &line[line.find("Duration:")? + "Duration:".len()..];
+    let value = rest.trim_start().split(',').next()?;
+
+    let mut total = 0.0;
+    for part in value.split(':') {
+        total = total * 60.0 + part.parse::<f64>().ok()?;
+    }
+
+    Some(total)
+}
+
+/// 从 ffmpeg 的日志中解析检测结果，持续到结尾的静音以文件时长收尾
+pub fn parse_detect_output(stderr: &str) -> Vec<BlankSegment> {
+    let mut segments = vec![];
+    let mut duration = None;
+    let mut silence_start = None;
+
+    for line in stderr.lines() {
+        if duration.is_none() && line.trim_start().starts_with("Duration:") {
+            duration = parse_duration(line);
+        } else if line.contains("[blackdetect") {
+            if let (Some(start), Some(end)) =
+                (field(line, "black_start:"), field(line, "black_end:"))
+            {
+                segments.push(BlankSegment {
+                    kind: BlankKind::Black,
+                    start,
+                    end,
+                });
+            }
+        } else if line.contains("[silencedetect") {
+            if let Some(start) = field(line, "silence_start:") {
+                silence_start = Some(start);
+            } else if let Some(end) = field(line, "silence_end:")
+                && let Some(start) = silence_start.take()
+            {
+                segments.push(BlankSegment {
+                    kind: BlankKind::Silence,
+                    start,
+                    end,
+                });
+            }
+        }
+    }
+
+    if let (Some(start), Some(end)) = (silence_start, duration)
+        && end > start
+    {
+        segments.push(BlankSegment {
+            kind: BlankKind::Silence,
+            start,
+            end,
+        });
+    }
+
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    segments
+}
+
+/// 检测录像中持续超过 `min_duration` 秒的黑屏与静音段
+///
+/// 视频只解码关键帧，精度为一个 GOP，足以找出以分钟计的待机画面
+pub fn detect_blank(path: &Path, min_duration: u64) -> Result<Vec<BlankSegment>> {
+    let output = Command::new(ffmpeg_path())
+        .args(["-hide_banner", "-nostats", "-skip_frame", "nokey", "-i"])
+        .arg(extended_path(&path.to_string_lossy()))
+        .arg("-vf")
+        .arg(format!(
+            "blackdetect=d={min_duration}:pix_th={BLACK_PIXEL_THRESHOLD}"
+        ))
+        .arg("-af")
+        .arg(format!("silencedetect=n={SILENCE_NOISE}:d={min_duration}"))
+        .args(["-f", "null", "-"])
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        anyhow::bail!(
+            "ffmpeg 黑屏/静音检测失败: {}",
+            stderr.lines().last().unwrap_or_default().trim()
+        );
+    }
+
+    Ok(parse_detect_output(&stderr))
+}
+
+pub fn blank_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("blank.json")
+}
+
+/// 把检测结果写入录制文件旁
+pub fn write_blank_segments(video_path: &Path, segments: &[BlankSegment]) -> io::Result<()> {
+    let content = serde_json::to_string_pretty(segments).map_err(io::Error::other)?;
+    std::fs::write(
+        extended_path(&blank_path(video_path).to_string_lossy()),
+        content,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_detect_output() {
+        let stderr = "\
+Input #0, flv, from 'a.flv':
+  Duration: 02:00:00.00, start: 0.000000, bitrate: 6000 kb/s
+[blackdetect @ 0x1] black_start:600 black_end:3600.5 black_duration:3000.5
+[silencedetect @ 0x2] silence_start: 610.25
+[silencedetect @ 0x2] silence_end: 3590 | silence_duration: 2979.75
+[silencedetect @ 0x2] silence_start: 6000
+";
+
+        assert_eq!(
+            parse_detect_output(stderr),
+            vec![
+                BlankSegment {
+                    kind: BlankKind::Black,
+                    start: 600.0,
+                    end: 3600.5,
+                },
+                BlankSegment {
+                    kind: BlankKind::Silence,
+                    start: 610.25,
+                    end: 3590.0,
+                },
+                BlankSegment {
+                    kind: BlankKind::Silence,
+                    start: 6000.0,
+                    end: 7200.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let segment = BlankSegment {
+            kind: BlankKind::Black,
+            start: 600.0,
+            end: 3661.0,
+        };
+        assert_eq!(segment.describe(), "黑屏 00:10:00-01:01:01");
+    }
+}
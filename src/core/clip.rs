@@ -0,0 +1,120 @@
+//! 录像切片：用 ffmpeg 流复制截取一段，不重新编码
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+
+use crate::core::repair::ffmpeg_path;
+
+/// 解析 `时:分:秒`、`分:秒` 或秒数，秒可以带小数
+pub fn parse_timestamp(value: &str) -> Option<f64> {
+    let parts = value.trim().split(':').collect::<Vec<_>>();
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+
+    let (seconds, rest) = parts.split_last()?;
+    let seconds = seconds.parse::<f64>().ok()?;
+    if !seconds.is_finite() || seconds < 0.0 || (!rest.is_empty() && seconds >= 60.0) {
+        return None;
+    }
+
+    let mut total = 0u64;
+    for (i, part) in rest.iter().enumerate() {
+        let value = part.parse::<u64>().ok()?;
+        // 最高位不限制，其余的分钟不能超过 59
+        if i > 0 && value >= 60 {
+            return None;
+        }
+        total = total * 60 + value;
+    }
+
+    Some(total as f64 * 60.0 + seconds)
+}
+
+/// `HHMMSS`，用于切片文件名
+fn compact_timestamp(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!(
+        "{:02}{:02}{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// 切片保存在原文件旁，如 `录像_切片_010000-010500.flv`
+pub fn clip_path(input: &Path, start: f64, end: f64) -> PathBuf {
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = format!(
+        "{stem}_切片_{}-{}",
+        compact_timestamp(start),
+        compact_timestamp(end)
+    );
+
+    match input.extension() {
+        Some(ext) => input.with_file_name(format!("{name}.{}", ext.to_string_lossy())),
+        None => input.with_file_name(name),
+    }
+}
+
+/// 导出 `[start, end)` 区间的切片，流复制从 `start` 之前最近的关键帧开始
+pub fn export_clip(input: &Path, start: f64, end: f64, output: &Path) -> Result<()> {
+    if end <= start {
+        anyhow::bail!("结束时间必须晚于开始时间");
+    }
+
+    let output_result = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-y", "-ss"])
+        .arg(format!("{start:.3}"))
+        .arg("-to")
+        .arg(format!("{end:.3}"))
+        .arg("-i")
+        .arg(input)
+        .args(["-map", "0", "-c", "copy"])
+        .arg(output)
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output_result.status.success() {
+        let _ = std::fs::remove_file(output);
+        anyhow::bail!(
+            "ffmpeg 切片失败: {}",
+            String::from_utf8_lossy(&output_result.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("01:02:03"), Some(3723.0));
+        assert_eq!(parse_timestamp("62:03"), Some(3723.0));
+        assert_eq!(parse_timestamp(" 90.5 "), Some(90.5));
+        assert_eq!(parse_timestamp("1:00:00.25"), Some(3600.25));
+        assert_eq!(parse_timestamp("1:60:00"), None);
+        assert_eq!(parse_timestamp("1:00:60"), None);
+        assert_eq!(parse_timestamp("1:2:3:4"), None);
+        assert_eq!(parse_timestamp("-1"), None);
+        assert_eq!(parse_timestamp(""), None);
+    }
+
+    #[test]
+    fn test_clip_path() {
+        assert_eq!(
+            clip_path(Path::new("/录制/主播_标题.flv"), 3600.0, 3900.5),
+            PathBuf::from("/录制/主播_标题_切片_010000-010500.flv")
+        );
+    }
+}
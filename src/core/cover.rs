@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use gpui::BackgroundExecutor;
+use parking_lot::Mutex;
+
+use crate::{core::HttpClient, settings::config_dir};
+
+/// 房间号到最近一次封面 URL 的索引，离线时据此找到缓存的封面
+static COVER_INDEX: LazyLock<Mutex<HashMap<u64, String>>> =
+    LazyLock::new(|| Mutex::new(load_index()));
+
+/// 直播中关键帧截图的刷新间隔
+pub const KEYFRAME_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Keyframe {
+    url: String,
+    path: PathBuf,
+    fetched_at: Instant,
+}
+
+/// 各房间当前的关键帧截图，只保留最新一张
+static KEYFRAMES: LazyLock<Mutex<HashMap<u64, Keyframe>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 封面缓存目录
+pub fn cover_cache_dir() -> PathBuf {
+    config_dir().join("covers")
+}
+
+fn keyframe_dir() -> PathBuf {
+    cover_cache_dir().join("keyframes")
+}
+
+fn index_file() -> PathBuf {
+    cover_cache_dir().join("index.json")
+}
+
+fn load_index() -> HashMap<u64, String> {
+    std::fs::read_to_string(index_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &HashMap<u64, String>) {
+    let result = serde_json::to_string_pretty(index)
+        .map_err(std::io::Error::other)
+        .and_then(|content| {
+            std::fs::create_dir_all(cover_cache_dir())?;
+            std::fs::write(index_file(), content)
+        });
+
+    if let Err(e) = result {
+        tracing::warn!("写入封面缓存索引失败: {}", e);
+    }
+}
+
+/// FNV-1a 哈希，保证缓存文件名在不同版本之间稳定
+fn url_hash(url: &str) -> u64 {
+    url.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// 封面 URL 对应的缓存文件，按 URL 哈希命名并保留图片扩展名
+pub fn cover_cache_path(url: &str) -> PathBuf {
+    let ext = Path::new(url.split(['?', '#']).next().unwrap_or_default())
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .filter(|ext| matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp" | "gif"))
+        .unwrap_or_else(|| "jpg".to_string());
+
+    cover_cache_dir().join(format!("{:016x}.{ext}", url_hash(url)))
+}
+
+/// 已缓存的封面文件
+pub fn cached_cover(url: &str) -> Option<PathBuf> {
+    let path = cover_cache_path(url);
+    path.exists().then_some(path)
+}
+
+/// 房间最近一次缓存的封面，直播间信息获取失败时使用
+pub fn room_cover(room_id: u64) -> Option<PathBuf> {
+    let url = COVER_INDEX.lock().get(&room_id).cloned()?;
+    cached_cover(&url)
+}
+
+/// 确保封面已缓存，仅在 URL 变化且本地没有缓存时下载
+pub async fn ensure_cover(client: &HttpClient, room_id: u64, url: &str) -> Result<PathBuf> {
+    let path = match cached_cover(url) {
+        Some(path) => path,
+        None => {
+            let bytes = client.get_bytes(url).await?;
+            let path = cover_cache_path(url);
+            std::fs::create_dir_all(cover_cache_dir()).context("无法创建封面缓存目录")?;
+
+            // 先写临时文件，避免渲染时读到不完整的图片
+            let temp = path.with_extension("tmp");
+            std::fs::write(&temp, bytes).context("无法写入封面缓存")?;
+            std::fs::rename(&temp, &path).context("无法写入封面缓存")?;
+            path
+        }
+    };
+
+    let mut index = COVER_INDEX.lock();
+    if index.get(&room_id).map(String::as_str) != Some(url) {
+        index.insert(room_id, url.to_string());
+        save_index(&index);
+    }
+
+    Ok(path)
+}
+
+/// 截图地址变化且距上次下载超过刷新间隔时才需要重新下载
+fn keyframe_due(previous: Option<(&str, Instant)>, url: &str, now: Instant) -> bool {
+    match previous {
+        Some((previous_url, fetched_at)) => {
+            previous_url != url
+                && now.saturating_duration_since(fetched_at) >= KEYFRAME_REFRESH_INTERVAL
+        }
+        None => true,
+    }
+}
+
+/// 启动时清理上次遗留的关键帧截图，须在开始轮询房间之前调用
+pub fn clear_stale_keyframes() {
+    if let Err(e) = std::fs::remove_dir_all(keyframe_dir())
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("清理关键帧截图失败: {}", e);
+    }
+}
+
+/// 房间当前的关键帧截图
+pub fn keyframe(room_id: u64) -> Option<PathBuf> {
+    KEYFRAMES
+        .lock()
+        .get(&room_id)
+        .map(|keyframe| keyframe.path.clone())
+}
+
+/// 下载直播中的关键帧截图，替换并删除上一张
+///
+/// 截图按地址命名，渲染层按路径缓存图片，复用同一路径不会刷新画面
+pub async fn refresh_keyframe(
+    executor: &BackgroundExecutor,
+    client: &HttpClient,
+    room_id: u64,
+    url: &str,
+) -> Result<()> {
+    let now = Instant::now();
+    let due = {
+        let keyframes = KEYFRAMES.lock();
+        let previous = keyframes
+            .get(&room_id)
+            .map(|keyframe| (keyframe.url.as_str(), keyframe.fetched_at));
+        keyframe_due(previous, url, now)
+    };
+    if !due {
+        return Ok(());
+    }
+
+    let bytes = client.get_bytes(url).await?;
+    let file_name = cover_cache_path(url)
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    let path = keyframe_dir().join(file_name);
+
+    // 写盘交给后台线程，避免阻塞轮询任务所在的线程
+    let path = executor
+        .spawn(async move {
+            std::fs::create_dir_all(keyframe_dir()).context("无法创建封面缓存目录")?;
+
+            let temp = path.with_extension("tmp");
+            std::fs::write(&temp, bytes).context("无法写入关键帧截图")?;
+            std::fs::rename(&temp, &path).context("无法写入关键帧截图")?;
+            anyhow::Ok(path)
+        })
+        .await?;
+
+    let previous = KEYFRAMES.lock().insert(
+        room_id,
+        Keyframe {
+            url: url.to_string(),
+            path,
+            fetched_at: now,
+        },
+    );
+    if let Some(previous) = previous {
+        let _ = std::fs::remove_file(previous.path);
+    }
+
+    Ok(())
+}
+
+/// 下播后移除关键帧截图，卡片回到房间封面
+pub fn clear_keyframe(room_id: u64) {
+    if let Some(keyframe) = KEYFRAMES.lock().remove(&room_id) {
+        let _ = std::fs::remove_file(keyframe.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cover_cache_path() {
+        let url = "https://i0.hdslb.com/bfs/live/new_room_cover/abc.jpg";
+        let path = cover_cache_path(url);
+
+        assert_eq!(path, cover_cache_path(url));
+        assert_eq!(path.extension().unwrap(), "jpg");
+        assert_ne!(
+            path,
+            cover_cache_path("https://i0.hdslb.com/bfs/live/def.jpg")
+        );
+        assert_eq!(
+            cover_cache_path("https://i0.hdslb.com/cover.PNG?size=large")
+                .extension()
+                .unwrap(),
+            "png"
+        );
+        assert_eq!(
+            cover_cache_path("https://example.com/cover")
+                .extension()
+                .unwrap(),
+            "jpg"
+        );
+    }
+
+    #[test]
+    fn test_keyframe_due() {
+        let now = Instant::now();
+        let url = "https://i0.hdslb.com/bfs/live-key-frame/keyframe1.jpg";
+        let later = now + KEYFRAME_REFRESH_INTERVAL;
+
+        assert!(keyframe_due(None, url, now));
+        assert!(!keyframe_due(Some((url, now)), url, later));
+        assert!(!keyframe_due(
+            Some((url, now)),
+            "https://i0.hdslb.com/bfs/live-key-frame/keyframe2.jpg",
+            now + Duration::from_secs(30)
+        ));
+        assert!(keyframe_due(
+            Some((url, now)),
+            "https://i0.hdslb.com/bfs/live-key-frame/keyframe2.jpg",
+            later
+        ));
+    }
+}
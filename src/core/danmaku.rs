@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::DanmakuSettings;
+
+pub mod archive;
+pub mod client;
+pub mod highlights;
+pub mod protocol;
+pub mod raw;
+pub mod sidecar;
+pub mod srt;
+
+/// 每个房间在内存中保留的弹幕条数
+pub const DANMAKU_BUFFER_SIZE: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DanmakuMessage {
+    /// 发送时间（毫秒时间戳）
+    pub timestamp: i64,
+    /// 发送者 uid
+    pub uid: u64,
+    /// 发送者昵称
+    pub uname: String,
+    /// 弹幕内容
+    pub content: String,
+}
+
+/// 房间弹幕缓冲区，只保留最近的 [`DANMAKU_BUFFER_SIZE`] 条
+#[derive(Debug, Clone, Default)]
+pub struct DanmakuBuffer {
+    messages: VecDeque<DanmakuMessage>,
+}
+
+impl DanmakuBuffer {
+    pub fn push(&mut self, message: DanmakuMessage) {
+        if self.messages.len() >= DANMAKU_BUFFER_SIZE {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &DanmakuMessage> {
+        self.messages.iter()
+    }
+}
+
+/// 关键词，`/.../` 包裹的按正则匹配，其余按子串匹配
+#[derive(Debug, Clone)]
+enum Keyword {
+    Text(String),
+    Regex(Regex),
+}
+
+impl Keyword {
+    fn parse(keyword: &str) -> Option<Self> {
+        let keyword = keyword.trim();
+        if keyword.is_empty() {
+            return None;
+        }
+
+        if let Some(pattern) = keyword
+            .strip_prefix('/')
+            .and_then(|keyword| keyword.strip_suffix('/'))
+            .filter(|pattern| !pattern.is_empty())
+        {
+            match Regex::new(pattern) {
+                Ok(regex) => return Some(Keyword::Regex(regex)),
+                Err(e) => tracing::warn!("弹幕关键词正则无效，按普通文本匹配: {} - {}", keyword, e),
+            }
+        }
+
+        Some(Keyword::Text(keyword.to_string()))
+    }
+
+    fn matches(&self, content: &str) -> bool {
+        match self {
+            Keyword::Text(text) => content.contains(text.as_str()),
+            Keyword::Regex(regex) => regex.is_match(content),
+        }
+    }
+}
+
+/// 弹幕过滤器，负责关键词高亮与屏蔽
+#[derive(Debug, Clone, Default)]
+pub struct DanmakuFilter {
+    highlight_keywords: Vec<Keyword>,
+    block_keywords: Vec<Keyword>,
+    archive_blocked: bool,
+}
+
+impl DanmakuFilter {
+    pub fn new(settings: &DanmakuSettings) -> Self {
+        let parse = |keywords: &[String]| {
+            keywords
+                .iter()
+                .filter_map(|keyword| Keyword::parse(keyword))
+                .collect()
+        };
+
+        Self {
+            highlight_keywords: parse(&settings.highlight_keywords),
+            block_keywords: parse(&settings.block_keywords),
+            archive_blocked: settings.archive_blocked,
+        }
+    }
+
+    /// 弹幕是否命中屏蔽词
+    pub fn is_blocked(&self, message: &DanmakuMessage) -> bool {
+        self.block_keywords
+            .iter()
+            .any(|keyword| keyword.matches(&message.content))
+    }
+
+    /// 弹幕是否命中高亮词
+    pub fn is_highlighted(&self, message: &DanmakuMessage) -> bool {
+        self.highlight_keywords
+            .iter()
+            .any(|keyword| keyword.matches(&message.content))
+    }
+
+    /// 弹幕是否写入归档
+    pub fn should_archive(&self, message: &DanmakuMessage) -> bool {
+        self.archive_blocked || !self.is_blocked(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(content: &str) -> DanmakuMessage {
+        DanmakuMessage {
+            timestamp: 0,
+            uid: 1,
+            uname: "test".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_buffer_keeps_latest_messages() {
+        let mut buffer = DanmakuBuffer::default();
+        for i in 0..DANMAKU_BUFFER_SIZE + 10 {
+            buffer.push(message(&i.to_string()));
+        }
+
+        assert_eq!(buffer.iter().count(), DANMAKU_BUFFER_SIZE);
+        assert_eq!(buffer.iter().next().unwrap().content, "10");
+    }
+
+    #[test]
+    fn test_filter_keywords() {
+        let filter = DanmakuFilter::new(&DanmakuSettings {
+            highlight_keywords: vec!["歌回".to_string(), " ".to_string()],
+            block_keywords: vec!["广告".to_string()],
+            ..Default::default()
+        });
+
+        assert!(filter.is_highlighted(&message("今天是歌回吗")));
+        assert!(!filter.is_highlighted(&message("晚上好")));
+        assert!(filter.is_blocked(&message("加群领广告")));
+        assert!(!filter.is_blocked(&message("晚上好")));
+    }
+
+    #[test]
+    fn test_filter_regex() {
+        let filter = DanmakuFilter::new(&DanmakuSettings {
+            block_keywords: vec![
+                r"/^\d{6,}$/".to_string(),
+                "/[/".to_string(),
+                "//".to_string(),
+            ],
+            archive_blocked: false,
+            ..Default::default()
+        });
+
+        assert!(filter.is_blocked(&message("12345678")));
+        assert!(!filter.is_blocked(&message("房间号 12345678")));
+        assert!(filter.is_blocked(&message("无效正则 /[/ 按文本匹配")));
+        assert!(filter.is_blocked(&message("https://")));
+        assert!(!filter.should_archive(&message("12345678")));
+        assert!(filter.should_archive(&message("晚上好")));
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{danmaku::DanmakuMessage, disk::extended_path};
+
+/// zstd 压缩级别，弹幕文本重复度高，默认级别已足够
+const ZSTD_LEVEL: i32 = 3;
+
+/// 每写入这么多条刷新一次，程序异常退出时最多丢失这部分弹幕
+const FLUSH_EVERY: usize = 64;
+
+/// 归档首行，记录录制开始时间，用于换算弹幕在视频中的位置
+///
+/// 旧版本读取时会把它当作无法解析的行跳过
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveHeader {
+    /// 录制开始时间（毫秒时间戳）
+    started_at: i64,
+}
+
+enum ArchiveWriter {
+    Plain(BufWriter<File>),
+    Zstd(zstd::stream::write::Encoder<'static, BufWriter<File>>),
+}
+
+impl ArchiveWriter {
+    fn inner(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(writer) => writer,
+            Self::Zstd(encoder) => encoder,
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut writer) => writer.flush(),
+            Self::Zstd(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+/// 弹幕归档，每行一条 JSON，可选 zstd 压缩（`.jsonl.zst`）
+pub struct DanmakuArchive {
+    path: PathBuf,
+    writer: Option<ArchiveWriter>,
+    pending: usize,
+}
+
+impl std::fmt::Debug for DanmakuArchive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DanmakuArchive")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl DanmakuArchive {
+    /// 在录制文件旁创建归档，`started_at` 为录制开始时间（毫秒时间戳）
+    pub fn create(video_path: &Path, compress: bool, started_at: i64) -> io::Result<Self> {
+        let path = archive_path(video_path, compress);
+        let file = BufWriter::new(File::create(extended_path(&path.to_string_lossy()))?);
+
+        let mut writer = if compress {
+            ArchiveWriter::Zstd(zstd::stream::write::Encoder::new(file, ZSTD_LEVEL)?)
+        } else {
+            ArchiveWriter::Plain(file)
+        };
+
+        let inner = writer.inner();
+        serde_json::to_writer(&mut *inner, &ArchiveHeader { started_at })?;
+        inner.write_all(b"\n")?;
+
+        Ok(Self {
+            path,
+            writer: Some(writer),
+            pending: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&mut self, message: &DanmakuMessage) -> io::Result<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+
+        let writer = writer.inner();
+        serde_json::to_writer(&mut *writer, message)?;
+        writer.write_all(b"\n")?;
+
+        // zstd 刷新后已写入的部分即可独立解压
+        self.pending += 1;
+        if self.pending >= FLUSH_EVERY {
+            self.pending = 0;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// 写入结尾并关闭文件
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.writer.take() {
+            Some(writer) => writer.finish(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for DanmakuArchive {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take()
+            && let Err(e) = writer.finish()
+        {
+            tracing::warn!("弹幕归档写入失败: {} - {}", self.path.display(), e);
+        }
+    }
+}
+
+/// 录制文件对应的弹幕归档路径
+pub fn archive_path(video_path: &Path, compress: bool) -> PathBuf {
+    video_path.with_extension(if compress { "jsonl.zst" } else { "jsonl" })
+}
+
+fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "zst")
+}
+
+/// 读取归档中的全部弹幕
+pub fn read_archive(path: &Path) -> io::Result<Vec<DanmakuMessage>> {
+    read_archive_with_start(path).map(|(_, messages)| messages)
+}
+
+/// 读取录制开始时间与全部弹幕，没有记录开始时间的旧归档返回 `None`
+///
+/// 程序异常退出时压缩归档可能缺少结尾，此时保留已能解出的部分
+pub fn read_archive_with_start(path: &Path) -> io::Result<(Option<i64>, Vec<DanmakuMessage>)> {
+    let file = File::open(extended_path(&path.to_string_lossy()))?;
+    let reader: Box<dyn Read> = if is_compressed(path) {
+        Box::new(zstd::stream::read::Decoder::new(file)?)
+    } else {
+        Box::new(file)
+    };
+
+    let mut started_at = None;
+    let mut messages = vec![];
+    for line in BufReader::new(reader).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(
+                    "弹幕归档不完整，已读取 {} 条: {} - {}",
+                    messages.len(),
+                    path.display(),
+                    e
+                );
+                break;
+            }
+        };
+
+        // 跳过写到一半的行
+        if let Ok(message) = serde_json::from_str(&line) {
+            messages.push(message);
+        } else if started_at.is_none()
+            && messages.is_empty()
+            && let Ok(header) = serde_json::from_str::<ArchiveHeader>(&line)
+        {
+            started_at = Some(header.started_at);
+        }
+    }
+
+    Ok((started_at, messages))
+}
+
+/// 解压导出为普通 JSONL，返回导出的条数
+pub fn export_archive(source: &Path, target: &Path) -> io::Result<usize> {
+    let messages = read_archive(source)?;
+
+    let mut writer = BufWriter::new(File::create(extended_path(&target.to_string_lossy()))?);
+    for message in &messages {
+        serde_json::to_writer(&mut writer, message)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(messages.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(i: usize) -> DanmakuMessage {
+        DanmakuMessage {
+            timestamp: i as i64,
+            uid: 1,
+            uname: "test".to_string(),
+            content: format!("弹幕 {i}"),
+        }
+    }
+
+    #[test]
+    fn test_compressed_archive_roundtrip() {
+        let video = std::env::temp_dir().join(format!("blive-archive-{}.flv", std::process::id()));
+        let mut archive = DanmakuArchive::create(&video, true, 42).unwrap();
+        let path = archive.path().to_path_buf();
+        assert!(path.to_string_lossy().ends_with(".jsonl.zst"));
+
+        for i in 0..FLUSH_EVERY * 3 {
+            archive.append(&message(i)).unwrap();
+        }
+        archive.finish().unwrap();
+
+        let (started_at, messages) = read_archive_with_start(&path).unwrap();
+        assert_eq!(started_at, Some(42));
+        assert_eq!(messages.len(), FLUSH_EVERY * 3);
+        assert_eq!(messages[10], message(10));
+
+        let target = path.with_extension("export");
+        assert_eq!(export_archive(&path, &target).unwrap(), FLUSH_EVERY * 3);
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap().lines().count(),
+            FLUSH_EVERY * 3
+        );
+
+        // 缺少结尾的归档仍能读出已刷新的部分
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+        assert!(!read_archive(&path).unwrap().is_empty());
+
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(target);
+    }
+}
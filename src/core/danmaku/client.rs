@@ -0,0 +1,241 @@
+//! 直播弹幕 WebSocket 连接
+//!
+//! 每个录制中的房间一条连接，运行在独立线程上：鉴权后每 30 秒发送心跳，断线后按递增间隔重连并轮换服务器，
+//! 解析出的消息经 channel 交给前台写入房间状态
+
+use std::{
+    net::TcpStream,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, bail};
+use tungstenite::{Message, WebSocket, stream::MaybeTlsStream};
+
+use crate::core::{
+    danmaku::{
+        protocol::{self, LiveMessage, OP_AUTH_REPLY, OP_MESSAGE},
+        raw::RawDumpSink,
+    },
+    http_client::room::DanmuInfo,
+};
+
+/// 获取不到服务器列表时使用的默认地址
+const DEFAULT_ENDPOINT: &str = "wss://broadcastlv.chat.bilibili.com:443/sub";
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 读超时，同时也是响应停止的最长延迟
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 超过该时长没有收到任何数据（包括心跳回复）视为连接已失效
+const IDLE_TIMEOUT: Duration = Duration::from_secs(70);
+
+/// 重连间隔上限
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// 弹幕连接句柄，克隆后共享同一个停止标记
+#[derive(Debug, Clone, Default)]
+pub struct DanmakuConnection {
+    stopped: Arc<AtomicBool>,
+}
+
+impl DanmakuConnection {
+    /// 在后台线程中连接，调用 [`Self::stop`] 前断线会自动重连；线程退出后接收端随之关闭
+    ///
+    /// 未登录时 `uid` 为 0，服务器下发的昵称会被打码；收到的每一帧在解析前原样交给 `raw_dump`
+    pub fn run(
+        &self,
+        room_id: u64,
+        uid: u64,
+        info: Option<DanmuInfo>,
+        raw_dump: RawDumpSink,
+    ) -> std::io::Result<flume::Receiver<LiveMessage>> {
+        let (tx, rx) = flume::unbounded();
+        let stopped = self.stopped.clone();
+
+        std::thread::Builder::new()
+            .name(format!("blive-danmaku-{room_id}"))
+            .spawn(move || {
+                let (endpoints, token) = endpoints(info);
+                let mut failures = 0u32;
+
+                for endpoint in endpoints.iter().cycle() {
+                    if stopped.load(Ordering::Relaxed) {
+                        break;
+                    }
+
+                    let mut authenticated = false;
+                    match session(
+                        endpoint,
+                        room_id,
+                        uid,
+                        &token,
+                        &stopped,
+                        &tx,
+                        &raw_dump,
+                        &mut authenticated,
+                    ) {
+                        Ok(()) => break,
+                        Err(e) => tracing::warn!(
+                            "弹幕连接断开，稍后重连 - 房间: {}, 服务器: {}, 错误: {}",
+                            room_id,
+                            endpoint,
+                            e
+                        ),
+                    }
+
+                    failures = if authenticated { 1 } else { failures + 1 };
+                    let delay = Duration::from_secs(1 << failures.min(5)).min(MAX_RECONNECT_DELAY);
+                    let deadline = Instant::now() + delay;
+                    while Instant::now() < deadline && !stopped.load(Ordering::Relaxed) {
+                        std::thread::sleep(READ_TIMEOUT);
+                    }
+                }
+
+                tracing::info!("弹幕连接已关闭 - 房间: {}", room_id);
+            })?;
+
+        Ok(rx)
+    }
+
+    /// 停止连接，后台线程在一个读超时内退出
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+/// 服务器地址列表与鉴权 token，接口失败时匿名连接默认服务器
+fn endpoints(info: Option<DanmuInfo>) -> (Vec<String>, String) {
+    let info = info.unwrap_or_default();
+    let mut endpoints: Vec<String> = info
+        .host_list
+        .iter()
+        .filter(|host| !host.host.is_empty())
+        .map(|host| format!("wss://{}:{}/sub", host.host, host.wss_port))
+        .collect();
+
+    if endpoints.is_empty() {
+        endpoints.push(DEFAULT_ENDPOINT.to_string());
+    }
+
+    (endpoints, info.token)
+}
+
+fn set_read_timeout(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> std::io::Result<()> {
+    match socket.get_mut() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(READ_TIMEOUT)),
+        MaybeTlsStream::Rustls(stream) => stream.sock.set_read_timeout(Some(READ_TIMEOUT)),
+        _ => Ok(()),
+    }
+}
+
+/// 一次连接的完整生命周期，主动停止或接收端关闭时返回 `Ok`
+#[allow(clippy::too_many_arguments)]
+fn session(
+    endpoint: &str,
+    room_id: u64,
+    uid: u64,
+    token: &str,
+    stopped: &AtomicBool,
+    tx: &flume::Sender<LiveMessage>,
+    raw_dump: &RawDumpSink,
+    authenticated: &mut bool,
+) -> Result<()> {
+    let (mut socket, _) = tungstenite::connect(endpoint)?;
+    set_read_timeout(&mut socket)?;
+    socket.send(Message::binary(protocol::auth_packet(room_id, uid, token)))?;
+
+    let mut last_heartbeat: Option<Instant> = None;
+    let mut last_received = Instant::now();
+
+    loop {
+        if stopped.load(Ordering::Relaxed) {
+            let _ = socket.close(None);
+            return Ok(());
+        }
+
+        if *authenticated && last_heartbeat.is_none_or(|at| at.elapsed() >= HEARTBEAT_INTERVAL) {
+            socket.send(Message::binary(protocol::heartbeat_packet()))?;
+            last_heartbeat = Some(Instant::now());
+        }
+
+        if last_received.elapsed() >= IDLE_TIMEOUT {
+            bail!("超过 {} 秒未收到数据", IDLE_TIMEOUT.as_secs());
+        }
+
+        let data = match socket.read() {
+            Ok(Message::Binary(data)) => data,
+            Ok(Message::Close(_)) => bail!("服务器关闭了连接"),
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        last_received = Instant::now();
+        raw_dump.write(chrono::Local::now().timestamp_millis(), &data);
+
+        for packet in protocol::decode(&data)? {
+            match packet.op {
+                OP_AUTH_REPLY => {
+                    if !protocol::auth_succeeded(&packet.body) {
+                        bail!("鉴权失败: {}", String::from_utf8_lossy(&packet.body));
+                    }
+
+                    *authenticated = true;
+                    tracing::info!("弹幕服务器已连接 - 房间: {}, 服务器: {}", room_id, endpoint);
+                }
+                OP_MESSAGE => {
+                    if let Some(message) = protocol::parse_message(&packet.body)
+                        && tx.send(message).is_err()
+                    {
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::http_client::room::DanmuHost;
+
+    #[test]
+    fn test_endpoints() {
+        let (endpoints, token) = endpoints(None);
+        assert_eq!(endpoints, vec![DEFAULT_ENDPOINT.to_string()]);
+        assert!(token.is_empty());
+
+        let info = DanmuInfo {
+            token: "token".to_string(),
+            host_list: vec![DanmuHost {
+                host: "zj-cn-live-comet.chat.bilibili.com".to_string(),
+                port: 2243,
+                wss_port: 443,
+                ws_port: 2244,
+            }],
+        };
+        let (endpoints, token) = endpoints(Some(info));
+        assert_eq!(
+            endpoints,
+            vec!["wss://zj-cn-live-comet.chat.bilibili.com:443/sub".to_string()]
+        );
+        assert_eq!(token, "token");
+    }
+}
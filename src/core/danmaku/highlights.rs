@@ -0,0 +1,274 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{danmaku::DanmakuMessage, disk::extended_path};
+
+/// 每个录制文件最多保留的高光时刻
+pub const MAX_HIGHLIGHTS: usize = 10;
+
+/// 成为高光时刻的最低每分钟弹幕数，避免冷清直播间的小波动被当成高光
+const MIN_PEAK_COUNT: u32 = 10;
+
+/// 峰值至少为平均密度的倍数
+const PEAK_RATIO: f64 = 2.0;
+
+/// 每个高光时刻展示的热词数
+const KEYWORDS_PER_HIGHLIGHT: usize = 3;
+
+/// 热词最长字符数，过长的弹幕截断后再统计
+const MAX_KEYWORD_CHARS: usize = 16;
+
+/// 最多统计的分钟数，时间戳异常的弹幕不应导致无限分配
+const MAX_MINUTES: usize = 7 * 24 * 60;
+
+/// 弹幕峰值所在的一分钟
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlight {
+    /// 相对录制开始的偏移（秒），取该分钟的起点
+    pub offset_secs: u64,
+    /// 该分钟的弹幕数
+    pub count: u32,
+    /// 该分钟重复最多的弹幕
+    pub keywords: Vec<String>,
+}
+
+impl Highlight {
+    /// 历史记录中展示的描述，如 `01:02:00 86条 [草/哈哈哈]`
+    pub fn describe(&self) -> String {
+        let time = format!(
+            "{:02}:{:02}:{:02}",
+            self.offset_secs / 3600,
+            self.offset_secs / 60 % 60,
+            self.offset_secs % 60
+        );
+
+        if self.keywords.is_empty() {
+            format!("{time} {}条", self.count)
+        } else {
+            format!("{time} {}条 [{}]", self.count, self.keywords.join("/"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Minute {
+    count: u32,
+    words: HashMap<String, u32>,
+}
+
+/// 录制期间按分钟统计弹幕数与重复弹幕
+#[derive(Debug, Clone)]
+pub struct HighlightTracker {
+    /// 录制开始时间（毫秒时间戳）
+    started_at: i64,
+    minutes: Vec<Minute>,
+}
+
+impl HighlightTracker {
+    pub fn new(started_at: i64) -> Self {
+        Self {
+            started_at,
+            minutes: vec![],
+        }
+    }
+
+    pub fn observe(&mut self, message: &DanmakuMessage) {
+        if message.timestamp < self.started_at {
+            return;
+        }
+
+        let index = ((message.timestamp - self.started_at) / 60_000) as usize;
+        if index >= MAX_MINUTES {
+            return;
+        }
+        if self.minutes.len() <= index {
+            self.minutes.resize_with(index + 1, Minute::default);
+        }
+
+        let minute = &mut self.minutes[index];
+        minute.count += 1;
+        if let Some(word) = normalize_keyword(&message.content) {
+            *minute.words.entry(word).or_default() += 1;
+        }
+    }
+
+    /// 每分钟的弹幕数
+    pub fn per_minute(&self) -> Vec<u32> {
+        self.minutes.iter().map(|minute| minute.count).collect()
+    }
+
+    /// 录制结束时挑出弹幕峰值，按时间排序
+    ///
+    /// 只保留局部峰值，连续多分钟的高密度只算一次
+    pub fn finish(self) -> Vec<Highlight> {
+        if self.minutes.is_empty() {
+            return vec![];
+        }
+
+        let total: u32 = self.minutes.iter().map(|minute| minute.count).sum();
+        let average = total as f64 / self.minutes.len() as f64;
+        let threshold = (average * PEAK_RATIO).max(MIN_PEAK_COUNT as f64);
+
+        let mut peaks = self
+            .minutes
+            .iter()
+            .enumerate()
+            .filter(|(i, minute)| {
+                let count = minute.count;
+                count as f64 >= threshold
+                    && (*i == 0 || self.minutes[i - 1].count < count)
+                    && self
+                        .minutes
+                        .get(i + 1)
+                        .is_none_or(|next| next.count <= count)
+            })
+            .collect::<Vec<_>>();
+
+        peaks.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(&b.0)));
+        peaks.truncate(MAX_HIGHLIGHTS);
+        peaks.sort_by_key(|(i, _)| *i);
+
+        peaks
+            .into_iter()
+            .map(|(i, minute)| Highlight {
+                offset_secs: i as u64 * 60,
+                count: minute.count,
+                keywords: top_keywords(&minute.words),
+            })
+            .collect()
+    }
+}
+
+/// 出现至少两次的弹幕按次数排序
+fn top_keywords(words: &HashMap<String, u32>) -> Vec<String> {
+    let mut words = words
+        .iter()
+        .filter(|(_, count)| **count >= 2)
+        .collect::<Vec<_>>();
+    words.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+    words
+        .into_iter()
+        .take(KEYWORDS_PER_HIGHLIGHT)
+        .map(|(word, _)| word.clone())
+        .collect()
+}
+
+/// 统一大小写，把超过三个的连续重复字符压缩为三个，使“哈哈哈哈哈”与“哈哈哈”计为同一热词
+fn normalize_keyword(content: &str) -> Option<String> {
+    let mut word = String::new();
+    let mut last = None;
+    let mut repeat = 0;
+
+    for c in content.trim().chars().flat_map(char::to_lowercase) {
+        if last == Some(c) {
+            repeat += 1;
+        } else {
+            last = Some(c);
+            repeat = 1;
+        }
+
+        if repeat <= 3 {
+            word.push(c);
+        }
+    }
+
+    let word = word.chars().take(MAX_KEYWORD_CHARS).collect::<String>();
+    (!word.is_empty()).then_some(word)
+}
+
+/// 录制文件对应的高光时刻文件路径
+pub fn highlights_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("highlights.json")
+}
+
+/// 把高光时刻写入录制文件旁，供切片工具读取
+pub fn write_highlights(video_path: &Path, highlights: &[Highlight]) -> io::Result<()> {
+    let content = serde_json::to_string_pretty(highlights).map_err(io::Error::other)?;
+    std::fs::write(
+        extended_path(&highlights_path(video_path).to_string_lossy()),
+        content,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(timestamp: i64, content: &str) -> DanmakuMessage {
+        DanmakuMessage {
+            timestamp,
+            uid: 1,
+            uname: "test".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_keyword() {
+        assert_eq!(
+            normalize_keyword(" 哈哈哈哈哈哈 "),
+            Some("哈哈哈".to_string())
+        );
+        assert_eq!(normalize_keyword("AWSL"), Some("awsl".to_string()));
+        assert_eq!(normalize_keyword("  "), None);
+    }
+
+    #[test]
+    fn test_observe_ignores_far_future() {
+        let mut tracker = HighlightTracker::new(0);
+        tracker.observe(&message(i64::MAX, "时间戳异常"));
+        tracker.observe(&message(MAX_MINUTES as i64 * 60_000, "超出统计范围"));
+        assert!(tracker.per_minute().is_empty());
+
+        tracker.observe(&message(60_000, "正常"));
+        assert_eq!(tracker.per_minute(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_highlights() {
+        let started_at = 1_000_000;
+        let mut tracker = HighlightTracker::new(started_at);
+        let mut feed = |minute: i64, count: usize, content: &str| {
+            for i in 0..count {
+                tracker.observe(&message(started_at + minute * 60_000 + i as i64, content));
+            }
+        };
+
+        // 录制前的弹幕不计入
+        feed(-1, 50, "录制前");
+        feed(0, 2, "开播");
+        feed(1, 3, "晚上好");
+        feed(2, 30, "哈哈哈哈");
+        feed(2, 10, "草");
+        // 紧随峰值的高密度分钟不重复计入
+        feed(3, 20, "哈哈");
+        feed(5, 30, "awsl");
+        feed(10, 1, "晚安");
+
+        assert_eq!(tracker.per_minute(), [2, 3, 40, 20, 0, 30, 0, 0, 0, 0, 1]);
+
+        let highlights = tracker.finish();
+        assert_eq!(
+            highlights,
+            [
+                Highlight {
+                    offset_secs: 120,
+                    count: 40,
+                    keywords: vec!["哈哈哈".to_string(), "草".to_string()],
+                },
+                Highlight {
+                    offset_secs: 300,
+                    count: 30,
+                    keywords: vec!["awsl".to_string()],
+                },
+            ]
+        );
+        assert_eq!(highlights[0].describe(), "00:02:00 40条 [哈哈哈/草]");
+    }
+}
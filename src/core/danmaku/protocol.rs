@@ -0,0 +1,281 @@
+//! 直播弹幕服务器的二进制协议
+//!
+//! 每个包由 16 字节大端头部（总长度、头部长度、协议版本、操作码、序号）加正文组成。
+//! 鉴权时声明协议版本 2，服务器推送的消息以 zlib 压缩，解压后是若干个完整的包
+
+use std::io::{self, Read};
+
+use flate2::read::ZlibDecoder;
+use serde_json::Value;
+
+use crate::core::danmaku::DanmakuMessage;
+
+const HEADER_LEN: usize = 16;
+
+/// 正文为 JSON
+const PROTO_JSON: u16 = 0;
+/// 正文为大端整数（心跳回复中的人气值）
+const PROTO_INT: u16 = 1;
+/// 正文为 zlib 压缩的若干个包
+const PROTO_ZLIB: u16 = 2;
+
+pub const OP_HEARTBEAT: u32 = 2;
+pub const OP_HEARTBEAT_REPLY: u32 = 3;
+pub const OP_MESSAGE: u32 = 5;
+pub const OP_AUTH: u32 = 7;
+pub const OP_AUTH_REPLY: u32 = 8;
+
+/// 解出的单个包
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet {
+    pub op: u32,
+    pub body: Vec<u8>,
+}
+
+/// 礼物
+#[derive(Debug, Clone, PartialEq)]
+pub struct GiftMessage {
+    /// 赠送时间（毫秒时间戳）
+    pub timestamp: i64,
+    pub uid: u64,
+    pub uname: String,
+    pub gift_name: String,
+    pub count: u32,
+}
+
+/// 醒目留言
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuperChatMessage {
+    /// 发送时间（毫秒时间戳）
+    pub timestamp: i64,
+    pub uid: u64,
+    pub uname: String,
+    pub content: String,
+    /// 金额（元）
+    pub price: u32,
+}
+
+/// 录制关心的直播间消息
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveMessage {
+    Danmaku(DanmakuMessage),
+    Gift(GiftMessage),
+    SuperChat(SuperChatMessage),
+}
+
+/// 编码一个协议版本 1 的包，客户端发出的鉴权与心跳都不压缩
+pub fn encode(op: u32, body: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_LEN + body.len());
+    packet.extend_from_slice(&((HEADER_LEN + body.len()) as u32).to_be_bytes());
+    packet.extend_from_slice(&(HEADER_LEN as u16).to_be_bytes());
+    packet.extend_from_slice(&PROTO_INT.to_be_bytes());
+    packet.extend_from_slice(&op.to_be_bytes());
+    packet.extend_from_slice(&1u32.to_be_bytes());
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// 鉴权包，未登录时 `uid` 为 0
+pub fn auth_packet(room_id: u64, uid: u64, token: &str) -> Vec<u8> {
+    let body = serde_json::json!({
+        "uid": uid,
+        "roomid": room_id,
+        "protover": PROTO_ZLIB,
+        "platform": "web",
+        "type": 2,
+        "key": token,
+    });
+
+    encode(OP_AUTH, body.to_string().as_bytes())
+}
+
+pub fn heartbeat_packet() -> Vec<u8> {
+    encode(OP_HEARTBEAT, b"")
+}
+
+/// 解出一帧中的全部包，压缩的包展开为其中的子包
+pub fn decode(mut data: &[u8]) -> io::Result<Vec<Packet>> {
+    let mut packets = vec![];
+
+    while !data.is_empty() {
+        if data.len() < HEADER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "弹幕包头部不完整",
+            ));
+        }
+
+        let total_len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        let header_len = u16::from_be_bytes([data[4], data[5]]) as usize;
+        let proto = u16::from_be_bytes([data[6], data[7]]);
+        let op = u32::from_be_bytes([data[8], data[9], data[10], data[11]]);
+
+        if header_len < HEADER_LEN || total_len < header_len || total_len > data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("弹幕包长度无效: {total_len}"),
+            ));
+        }
+
+        let body = &data[header_len..total_len];
+        match proto {
+            PROTO_ZLIB => {
+                let mut inflated = vec![];
+                ZlibDecoder::new(body).read_to_end(&mut inflated)?;
+                packets.extend(decode(&inflated)?);
+            }
+            PROTO_JSON | PROTO_INT => packets.push(Packet {
+                op,
+                body: body.to_vec(),
+            }),
+            _ => tracing::debug!("跳过不支持的弹幕协议版本: {}", proto),
+        }
+
+        data = &data[total_len..];
+    }
+
+    Ok(packets)
+}
+
+/// 鉴权回复是否成功
+pub fn auth_succeeded(body: &[u8]) -> bool {
+    serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("code").and_then(Value::as_i64))
+        == Some(0)
+}
+
+/// 解析消息包，只保留弹幕、礼物与醒目留言
+pub fn parse_message(body: &[u8]) -> Option<LiveMessage> {
+    let value: Value = serde_json::from_slice(body).ok()?;
+    // 部分消息的 cmd 带有 `DANMU_MSG:4:0:2:2:2:0` 形式的后缀
+    let cmd = value.get("cmd")?.as_str()?.split(':').next()?;
+
+    match cmd {
+        "DANMU_MSG" => {
+            let info = value.get("info")?;
+            let user = info.get(2)?;
+
+            Some(LiveMessage::Danmaku(DanmakuMessage {
+                timestamp: info.get(0)?.get(4)?.as_i64()?,
+                uid: user.get(0)?.as_u64()?,
+                uname: user.get(1)?.as_str()?.to_string(),
+                content: info.get(1)?.as_str()?.to_string(),
+            }))
+        }
+        "SEND_GIFT" => {
+            let data = value.get("data")?;
+
+            Some(LiveMessage::Gift(GiftMessage {
+                timestamp: data.get("timestamp")?.as_i64()? * 1000,
+                uid: data.get("uid")?.as_u64()?,
+                uname: data.get("uname")?.as_str()?.to_string(),
+                gift_name: data.get("giftName")?.as_str()?.to_string(),
+                count: data.get("num")?.as_u64()? as u32,
+            }))
+        }
+        "SUPER_CHAT_MESSAGE" => {
+            let data = value.get("data")?;
+
+            Some(LiveMessage::SuperChat(SuperChatMessage {
+                timestamp: data.get("start_time")?.as_i64()? * 1000,
+                uid: data.get("uid")?.as_u64()?,
+                uname: data.get("user_info")?.get("uname")?.as_str()?.to_string(),
+                content: data.get("message")?.as_str()?.to_string(),
+                price: data.get("price")?.as_u64()? as u32,
+            }))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::ZlibEncoder};
+
+    use super::*;
+
+    fn raw_packet(proto: u16, op: u32, body: &[u8]) -> Vec<u8> {
+        let mut packet = encode(op, body);
+        packet[6..8].copy_from_slice(&proto.to_be_bytes());
+        packet
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let packet = auth_packet(1000, 0, "token");
+        assert_eq!(&packet[..4], &(packet.len() as u32).to_be_bytes());
+
+        let decoded = decode(&packet).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].op, OP_AUTH);
+        let body: Value = serde_json::from_slice(&decoded[0].body).unwrap();
+        assert_eq!(body["roomid"], 1000);
+        assert_eq!(body["key"], "token");
+
+        assert!(auth_succeeded(br#"{"code":0}"#));
+        assert!(!auth_succeeded(br#"{"code":-101}"#));
+        assert!(decode(&packet[..10]).is_err());
+    }
+
+    #[test]
+    fn test_decode_zlib() {
+        let first = raw_packet(PROTO_JSON, OP_MESSAGE, br#"{"cmd":"A"}"#);
+        let second = raw_packet(PROTO_JSON, OP_MESSAGE, br#"{"cmd":"B"}"#);
+
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&first).unwrap();
+        encoder.write_all(&second).unwrap();
+        let compressed = raw_packet(PROTO_ZLIB, OP_MESSAGE, &encoder.finish().unwrap());
+
+        // 一帧里可能同时有压缩包与心跳回复
+        let mut frame = compressed;
+        frame.extend(raw_packet(
+            PROTO_INT,
+            OP_HEARTBEAT_REPLY,
+            &7u32.to_be_bytes(),
+        ));
+
+        let packets = decode(&frame).unwrap();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[1].body, br#"{"cmd":"B"}"#);
+        assert_eq!(packets[2].op, OP_HEARTBEAT_REPLY);
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let danmaku = r#"{"cmd":"DANMU_MSG:4:0:2:2:2:0","info":[[0,1,25,16777215,1700000000123,0],"晚上好",[42,"观众",0,0]]}"#.as_bytes();
+        assert_eq!(
+            parse_message(danmaku),
+            Some(LiveMessage::Danmaku(DanmakuMessage {
+                timestamp: 1700000000123,
+                uid: 42,
+                uname: "观众".to_string(),
+                content: "晚上好".to_string(),
+            }))
+        );
+
+        let gift = r#"{"cmd":"SEND_GIFT","data":{"timestamp":1700000000,"uid":42,"uname":"观众","giftName":"小心心","num":3}}"#.as_bytes();
+        assert_eq!(
+            parse_message(gift),
+            Some(LiveMessage::Gift(GiftMessage {
+                timestamp: 1700000000000,
+                uid: 42,
+                uname: "观众".to_string(),
+                gift_name: "小心心".to_string(),
+                count: 3,
+            }))
+        );
+
+        let super_chat = r#"{"cmd":"SUPER_CHAT_MESSAGE","data":{"start_time":1700000000,"uid":42,"user_info":{"uname":"观众"},"message":"加油","price":30}}"#.as_bytes();
+        assert!(matches!(
+            parse_message(super_chat),
+            Some(LiveMessage::SuperChat(SuperChatMessage { price: 30, .. }))
+        ));
+
+        assert_eq!(parse_message(br#"{"cmd":"INTERACT_WORD","data":{}}"#), None);
+        assert_eq!(parse_message(b"not json"), None);
+    }
+}
@@ -0,0 +1,147 @@
+//! 弹幕原始数据包转储
+//!
+//! 按收到的顺序保存服务器下发的每一帧，不解压也不解析，便于用自己的工具重放。
+//! 每条记录为 8 字节大端毫秒时间戳、4 字节大端帧长度加原始帧，帧格式见 [`super::protocol`]
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+
+use crate::core::disk::extended_path;
+
+/// 每写入这么多帧刷新一次，程序异常退出时最多丢失这部分数据
+const FLUSH_EVERY: usize = 32;
+
+const RECORD_HEADER_LEN: usize = 12;
+
+/// 录制文件旁的原始数据包转储（`.danmaku.bin`）
+pub struct RawDump {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    pending: usize,
+}
+
+impl std::fmt::Debug for RawDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawDump").field("path", &self.path).finish()
+    }
+}
+
+impl RawDump {
+    pub fn create(video_path: &Path) -> io::Result<Self> {
+        let path = raw_dump_path(video_path);
+        let writer = BufWriter::new(File::create(extended_path(&path.to_string_lossy()))?);
+
+        Ok(Self {
+            path,
+            writer,
+            pending: 0,
+        })
+    }
+
+    /// 追加一帧，`timestamp` 为收到的时间（毫秒时间戳）
+    pub fn append(&mut self, timestamp: i64, frame: &[u8]) -> io::Result<()> {
+        self.writer.write_all(&timestamp.to_be_bytes())?;
+        self.writer.write_all(&(frame.len() as u32).to_be_bytes())?;
+        self.writer.write_all(frame)?;
+
+        self.pending += 1;
+        if self.pending >= FLUSH_EVERY {
+            self.writer.flush()?;
+            self.pending = 0;
+        }
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// 连接线程与录制流程共享的转储目标，每个分 P 换一个文件，为 `None` 时不转储
+#[derive(Debug, Clone, Default)]
+pub struct RawDumpSink(Arc<Mutex<Option<RawDump>>>);
+
+impl RawDumpSink {
+    pub fn replace(&self, dump: Option<RawDump>) {
+        *self.0.lock() = dump;
+    }
+
+    /// 写入失败时停止转储，不影响弹幕连接
+    pub fn write(&self, timestamp: i64, frame: &[u8]) {
+        let mut dump = self.0.lock();
+        if let Some(writer) = dump.as_mut()
+            && let Err(e) = writer.append(timestamp, frame)
+        {
+            tracing::warn!(
+                "弹幕原始数据包写入失败，停止转储: {} - {}",
+                writer.path().display(),
+                e
+            );
+            *dump = None;
+        }
+    }
+}
+
+/// 录制文件对应的原始数据包转储路径
+pub fn raw_dump_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("danmaku.bin")
+}
+
+/// 解出转储中的全部记录，末尾写到一半的记录被忽略
+pub fn read_records(mut data: &[u8]) -> Vec<(i64, &[u8])> {
+    let mut records = vec![];
+
+    while data.len() >= RECORD_HEADER_LEN {
+        let timestamp = i64::from_be_bytes(data[..8].try_into().unwrap_or_default());
+        let len = u32::from_be_bytes(data[8..12].try_into().unwrap_or_default()) as usize;
+        let Some(frame) = data.get(RECORD_HEADER_LEN..RECORD_HEADER_LEN + len) else {
+            break;
+        };
+
+        records.push((timestamp, frame));
+        data = &data[RECORD_HEADER_LEN + len..];
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_roundtrip() {
+        let video = std::env::temp_dir().join(format!("blive-raw-{}.flv", std::process::id()));
+        let sink = RawDumpSink::default();
+
+        // 未设置转储文件时直接丢弃
+        sink.write(1, b"ignored");
+
+        sink.replace(Some(RawDump::create(&video).unwrap()));
+        sink.write(1700000000000, b"\x00\x00\x00\x10first");
+        sink.write(1700000000500, b"second");
+        sink.replace(None);
+
+        let path = raw_dump_path(&video);
+        let mut data = std::fs::read(&path).unwrap();
+        assert_eq!(
+            read_records(&data),
+            vec![
+                (1700000000000, b"\x00\x00\x00\x10first".as_slice()),
+                (1700000000500, b"second".as_slice()),
+            ]
+        );
+
+        data.truncate(data.len() - 2);
+        assert_eq!(read_records(&data).len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,378 @@
+//! 录像旁的弹幕文件
+//!
+//! XML 与 B 站弹幕下载格式兼容，可直接导入弹幕播放器或压制工具；ASS 为滚动字幕，普通播放器挂载即可观看
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::core::{danmaku::protocol::LiveMessage, disk::extended_path};
+
+/// 每写入这么多条刷新一次，程序异常退出时最多丢失这部分弹幕
+const FLUSH_EVERY: usize = 64;
+
+/// ASS 画布尺寸
+const ASS_WIDTH: i64 = 1920;
+const ASS_FONT_SIZE: i64 = 48;
+/// 滚动弹幕占用的行数，只铺满画面上半部分
+const ASS_LANES: usize = 10;
+/// 滚动弹幕横穿屏幕的时长（毫秒）
+const ASS_SCROLL_MS: i64 = 8000;
+/// 醒目留言在顶部停留的时长（毫秒）
+const ASS_SUPER_CHAT_MS: i64 = 10000;
+
+const ASS_HEADER: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 1920
+PlayResY: 1080
+WrapStyle: 2
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Danmaku,Microsoft YaHei,48,&H00FFFFFF,&H00FFFFFF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,0,7,0,0,0,1
+Style: SuperChat,Microsoft YaHei,48,&H0000D7FF,&H0000D7FF,&H00000000,&H00000000,1,0,0,0,100,100,0,0,1,2,0,8,0,0,20,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SidecarFormat {
+    Xml,
+    Ass,
+}
+
+impl SidecarFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            SidecarFormat::Xml => "xml",
+            SidecarFormat::Ass => "ass",
+        }
+    }
+}
+
+/// 录制中的弹幕文件，弹幕时间按录制开始时间换算为视频内的位置
+pub struct DanmakuSidecar {
+    path: PathBuf,
+    format: SidecarFormat,
+    writer: Option<BufWriter<File>>,
+    started_at: i64,
+    /// ASS 每行最近一条弹幕完全进入画面的时间，新弹幕优先放在已空出的行
+    lanes: [i64; ASS_LANES],
+    pending: usize,
+}
+
+impl std::fmt::Debug for DanmakuSidecar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DanmakuSidecar")
+            .field("path", &self.path)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+impl DanmakuSidecar {
+    /// 在录制文件旁创建弹幕文件，`started_at` 为录制开始时间（毫秒时间戳）
+    pub fn create(video_path: &Path, format: SidecarFormat, started_at: i64) -> io::Result<Self> {
+        let path = video_path.with_extension(format.extension());
+        let mut writer = BufWriter::new(File::create(extended_path(&path.to_string_lossy()))?);
+
+        match format {
+            SidecarFormat::Xml => writer.write_all(
+                b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<i>\n<chatserver>chat.bilibili.com</chatserver>\n<chatid>0</chatid>\n",
+            )?,
+            SidecarFormat::Ass => writer.write_all(ASS_HEADER.as_bytes())?,
+        }
+
+        Ok(Self {
+            path,
+            format,
+            writer: Some(writer),
+            started_at,
+            lanes: [i64::MIN; ASS_LANES],
+            pending: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&mut self, message: &LiveMessage) -> io::Result<()> {
+        let line = match self.format {
+            SidecarFormat::Xml => self.xml_line(message),
+            SidecarFormat::Ass => self.ass_line(message),
+        };
+
+        let (Some(line), Some(writer)) = (line, self.writer.as_mut()) else {
+            return Ok(());
+        };
+
+        writer.write_all(line.as_bytes())?;
+
+        self.pending += 1;
+        if self.pending >= FLUSH_EVERY {
+            self.pending = 0;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// 写入结尾并关闭文件
+    pub fn finish(mut self) -> io::Result<()> {
+        self.close()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+
+        if self.format == SidecarFormat::Xml {
+            writer.write_all(b"</i>\n")?;
+        }
+
+        writer.flush()
+    }
+
+    /// 消息在视频中的位置（毫秒），早于录制开始的放在开头
+    fn offset(&self, timestamp: i64) -> i64 {
+        (timestamp - self.started_at).max(0)
+    }
+
+    fn xml_line(&self, message: &LiveMessage) -> Option<String> {
+        let line = match message {
+            LiveMessage::Danmaku(danmaku) => format!(
+                "<d p=\"{:.3},1,25,16777215,{},0,{},0\" user=\"{}\">{}</d>\n",
+                self.offset(danmaku.timestamp) as f64 / 1000.0,
+                danmaku.timestamp / 1000,
+                danmaku.uid,
+                escape_xml(&danmaku.uname),
+                escape_xml(&danmaku.content),
+            ),
+            LiveMessage::Gift(gift) => format!(
+                "<gift ts=\"{:.3}\" uid=\"{}\" user=\"{}\" giftname=\"{}\" giftcount=\"{}\"/>\n",
+                self.offset(gift.timestamp) as f64 / 1000.0,
+                gift.uid,
+                escape_xml(&gift.uname),
+                escape_xml(&gift.gift_name),
+                gift.count,
+            ),
+            LiveMessage::SuperChat(super_chat) => format!(
+                "<sc ts=\"{:.3}\" uid=\"{}\" user=\"{}\" price=\"{}\">{}</sc>\n",
+                self.offset(super_chat.timestamp) as f64 / 1000.0,
+                super_chat.uid,
+                escape_xml(&super_chat.uname),
+                super_chat.price,
+                escape_xml(&super_chat.content),
+            ),
+        };
+
+        Some(line)
+    }
+
+    /// 弹幕从右向左滚动，醒目留言固定在顶部；礼物不进入字幕，以免刷屏
+    fn ass_line(&mut self, message: &LiveMessage) -> Option<String> {
+        match message {
+            LiveMessage::Danmaku(danmaku) => {
+                let start = self.offset(danmaku.timestamp);
+                let width = danmaku.content.chars().count() as i64 * ASS_FONT_SIZE;
+                let lane = self.take_lane(start, width);
+                let y = lane as i64 * ASS_FONT_SIZE;
+
+                Some(format!(
+                    "Dialogue: 0,{},{},Danmaku,,0,0,0,,{{\\move({ASS_WIDTH},{y},{},{y})}}{}\n",
+                    ass_time(start),
+                    ass_time(start + ASS_SCROLL_MS),
+                    -width,
+                    escape_ass(&danmaku.content),
+                ))
+            }
+            LiveMessage::SuperChat(super_chat) => {
+                let start = self.offset(super_chat.timestamp);
+
+                Some(format!(
+                    "Dialogue: 1,{},{},SuperChat,,0,0,0,,￥{} {}: {}\n",
+                    ass_time(start),
+                    ass_time(start + ASS_SUPER_CHAT_MS),
+                    super_chat.price,
+                    escape_ass(&super_chat.uname),
+                    escape_ass(&super_chat.content),
+                ))
+            }
+            LiveMessage::Gift(_) => None,
+        }
+    }
+
+    /// 选出最早空出的行，并记录这条弹幕尾部进入画面的时间
+    fn take_lane(&mut self, start: i64, width: i64) -> usize {
+        let lane = self
+            .lanes
+            .iter()
+            .position(|free_at| *free_at <= start)
+            .unwrap_or_else(|| {
+                (0..ASS_LANES)
+                    .min_by_key(|lane| self.lanes[*lane])
+                    .unwrap_or_default()
+            });
+
+        self.lanes[lane] = start + ASS_SCROLL_MS * width / (ASS_WIDTH + width);
+        lane
+    }
+}
+
+impl Drop for DanmakuSidecar {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            tracing::warn!("弹幕文件写入失败: {} - {}", self.path.display(), e);
+        }
+    }
+}
+
+/// 转义 XML 特殊字符，并去掉 XML 1.0 不允许的控制字符
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// ASS 中 `{}` 为特效标签、`\` 为转义，替换为全角字符
+fn escape_ass(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '{' => '｛',
+            '}' => '｝',
+            '\\' => '＼',
+            '\n' | '\r' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// ASS 时间格式 `h:mm:ss.cc`
+fn ass_time(ms: i64) -> String {
+    let centis = ms / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360_000,
+        centis / 6000 % 60,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::danmaku::{
+        DanmakuMessage,
+        protocol::{GiftMessage, SuperChatMessage},
+    };
+
+    fn messages() -> Vec<LiveMessage> {
+        vec![
+            LiveMessage::Danmaku(DanmakuMessage {
+                timestamp: 1500,
+                uid: 1,
+                uname: "观众".to_string(),
+                content: "<晚上好> & {你好}".to_string(),
+            }),
+            LiveMessage::Gift(GiftMessage {
+                timestamp: 2000,
+                uid: 2,
+                uname: "老板".to_string(),
+                gift_name: "小心心".to_string(),
+                count: 3,
+            }),
+            LiveMessage::SuperChat(SuperChatMessage {
+                timestamp: 500,
+                uid: 3,
+                uname: "老板".to_string(),
+                content: "加油".to_string(),
+                price: 30,
+            }),
+        ]
+    }
+
+    fn write(format: SidecarFormat) -> String {
+        let video = std::env::temp_dir().join(format!(
+            "blive-sidecar-{}-{}.flv",
+            std::process::id(),
+            format.extension()
+        ));
+        let mut sidecar = DanmakuSidecar::create(&video, format, 1000).unwrap();
+        let path = sidecar.path().to_path_buf();
+        for message in messages() {
+            sidecar.append(&message).unwrap();
+        }
+        sidecar.finish().unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(path);
+        content
+    }
+
+    #[test]
+    fn test_xml_sidecar() {
+        let content = write(SidecarFormat::Xml);
+
+        assert!(content.starts_with("<?xml"));
+        assert!(content.ends_with("</i>\n"));
+        assert!(content.contains(
+            "<d p=\"0.500,1,25,16777215,1,0,1,0\" user=\"观众\">&lt;晚上好&gt; &amp; {你好}</d>"
+        ));
+        assert!(content.contains("giftname=\"小心心\" giftcount=\"3\""));
+        // 早于录制开始的消息放在开头
+        assert!(
+            content.contains("<sc ts=\"0.000\" uid=\"3\" user=\"老板\" price=\"30\">加油</sc>")
+        );
+    }
+
+    #[test]
+    fn test_ass_sidecar() {
+        let content = write(SidecarFormat::Ass);
+
+        assert!(content.starts_with("[Script Info]"));
+        assert!(content.contains(
+            "Dialogue: 0,0:00:00.50,0:00:08.50,Danmaku,,0,0,0,,{\\move(1920,0,-576,0)}<晚上好> & ｛你好｝"
+        ));
+        assert!(content.contains("SuperChat,,0,0,0,,￥30 老板: 加油"));
+        assert!(!content.contains("小心心"));
+    }
+
+    #[test]
+    fn test_ass_lanes() {
+        let video = std::env::temp_dir().join(format!("blive-lanes-{}.flv", std::process::id()));
+        let mut sidecar = DanmakuSidecar::create(&video, SidecarFormat::Ass, 0).unwrap();
+
+        assert_eq!(sidecar.take_lane(0, 480), 0);
+        // 前一条尚未完全进入画面时换行
+        assert_eq!(sidecar.take_lane(100, 480), 1);
+        assert_eq!(sidecar.take_lane(2000, 480), 0);
+
+        let path = sidecar.path().to_path_buf();
+        drop(sidecar);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_ass_time() {
+        assert_eq!(ass_time(0), "0:00:00.00");
+        assert_eq!(ass_time(3_723_450), "1:02:03.45");
+    }
+}
@@ -0,0 +1,113 @@
+use std::{fmt::Write as _, io, path::Path};
+
+use crate::core::danmaku::{DanmakuMessage, archive::read_archive_with_start};
+
+/// 每条字幕的最长显示时间
+const DISPLAY_MS: i64 = 4000;
+
+/// 相邻两条字幕的最小间隔，弹幕密度过高时按此抽稀
+const MIN_INTERVAL_MS: i64 = 1000;
+
+/// 把弹幕抽稀为互不重叠的 SRT 字幕，返回字幕内容与条数
+///
+/// `started_at` 为录制开始时间（毫秒时间戳），早于它的弹幕会被丢弃
+pub fn to_srt(messages: &[DanmakuMessage], started_at: i64) -> (String, usize) {
+    let mut sampled: Vec<&DanmakuMessage> = vec![];
+    for message in messages {
+        if message.timestamp < started_at {
+            continue;
+        }
+        if sampled
+            .last()
+            .is_some_and(|last| message.timestamp - last.timestamp < MIN_INTERVAL_MS)
+        {
+            continue;
+        }
+        sampled.push(message);
+    }
+
+    let mut output = String::new();
+    for (i, message) in sampled.iter().enumerate() {
+        let start = message.timestamp - started_at;
+        // 下一条出现时结束，避免字幕重叠
+        let end = sampled
+            .get(i + 1)
+            .map(|next| next.timestamp - started_at)
+            .unwrap_or(i64::MAX)
+            .min(start + DISPLAY_MS);
+
+        let _ = write!(
+            output,
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            srt_time(start),
+            srt_time(end),
+            message.content.replace('\n', " ")
+        );
+    }
+
+    (output, sampled.len())
+}
+
+/// SRT 时间格式 `HH:MM:SS,mmm`
+fn srt_time(ms: i64) -> String {
+    let ms = ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// 把弹幕归档导出为 SRT 字幕，返回字幕条数
+///
+/// 旧归档没有记录录制开始时间，以第一条弹幕的时间作为起点
+pub fn export_srt(source: &Path, target: &Path) -> io::Result<usize> {
+    let (started_at, messages) = read_archive_with_start(source)?;
+    let started_at = started_at
+        .or_else(|| messages.first().map(|message| message.timestamp))
+        .unwrap_or_default();
+
+    let (srt, count) = to_srt(&messages, started_at);
+    std::fs::write(target, srt)?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(timestamp: i64, content: &str) -> DanmakuMessage {
+        DanmakuMessage {
+            timestamp,
+            uid: 1,
+            uname: "test".to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_to_srt() {
+        let started_at = 1_000_000;
+        let messages = vec![
+            message(started_at - 500, "录制前"),
+            message(started_at + 1_500, "第一条"),
+            // 间隔过短的弹幕被抽掉
+            message(started_at + 1_800, "太密"),
+            message(started_at + 3_000, "第二条"),
+            message(started_at + 3_661_000, "换行\n弹幕"),
+        ];
+
+        let (srt, count) = to_srt(&messages, started_at);
+        assert_eq!(count, 3);
+        assert_eq!(
+            srt,
+            "1\n00:00:01,500 --> 00:00:03,000\n第一条\n\n\
+             2\n00:00:03,000 --> 00:00:07,000\n第二条\n\n\
+             3\n01:01:01,000 --> 01:01:05,000\n换行 弹幕\n\n"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::Path;
+
+use sysinfo::Disks;
+
+/// 磁盘空间信息（字节）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskSpace {
+    pub available: u64,
+    pub total: u64,
+}
+
+/// 查询路径所在磁盘的剩余空间，按最长挂载点匹配
+pub fn disk_space(path: &Path) -> Option<DiskSpace> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let disks = Disks::new_with_refreshed_list();
+
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| DiskSpace {
+            available: disk.available_space(),
+            total: disk.total_space(),
+        })
+}
+
+/// Windows 未启用长路径时的路径长度上限
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Windows 下转换为 `\\?\` 前缀的扩展路径以绕过 260 字符的限制，其他平台原样返回
+pub fn extended_path(path: &str) -> String {
+    if cfg!(windows) {
+        to_extended_path(path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// 扩展路径不会再经过系统解析，须是绝对路径、只用 `\` 分隔且不含 `.`、`..`，相对路径原样返回
+fn to_extended_path(path: &str) -> String {
+    if path.starts_with(r"\\?\") {
+        return path.to_string();
+    }
+
+    let normalized = path.replace('/', r"\");
+    let (prefix, rest) = if let Some(rest) = normalized.strip_prefix(r"\\") {
+        (r"\\?\UNC\", rest)
+    } else if normalized.as_bytes().get(1) == Some(&b':')
+        && normalized.as_bytes().get(2) == Some(&b'\\')
+        && normalized.as_bytes()[0].is_ascii_alphabetic()
+    {
+        (r"\\?\", normalized.as_str())
+    } else {
+        return path.to_string();
+    };
+
+    let mut segments: Vec<&str> = vec![];
+    for segment in rest.split('\\') {
+        match segment {
+            "" | "." => {}
+            // 不越过盘符或 UNC 的服务器名
+            ".." => {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    format!("{prefix}{}", segments.join(r"\"))
+}
+
+/// 剩余空间低于该值视为磁盘即将写满
+pub const LOW_SPACE_THRESHOLD: u64 = 1024 * 1024 * 1024;
+
+/// 目录能否用于录制：可以创建、可以写入且剩余空间充足，查询不到空间时不作限制
+pub fn is_dir_usable(dir: &Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+
+    // 只读挂载或权限不足时目录存在但无法写入，需要实际写一次
+    let probe = dir.join(".blive_write_probe");
+    if std::fs::write(&probe, b"").is_err() {
+        return false;
+    }
+    let _ = std::fs::remove_file(&probe);
+
+    disk_space(dir).is_none_or(|space| space.available >= LOW_SPACE_THRESHOLD)
+}
+
+/// 按优先级选出第一个可用的目录，全部不可用时返回 `None`
+pub fn select_usable_dir<'a>(
+    dirs: &'a [String],
+    usable: impl Fn(&Path) -> bool,
+) -> Option<&'a str> {
+    dirs.iter()
+        .map(String::as_str)
+        .find(|dir| usable(Path::new(dir)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_usable_dir() {
+        let dirs = vec!["/a".to_string(), "/b".to_string(), "/c".to_string()];
+
+        assert_eq!(select_usable_dir(&dirs, |_| true), Some("/a"));
+        assert_eq!(
+            select_usable_dir(&dirs, |dir| dir != Path::new("/a")),
+            Some("/b")
+        );
+        assert_eq!(select_usable_dir(&dirs, |_| false), None);
+    }
+
+    #[test]
+    fn test_to_extended_path() {
+        assert_eq!(
+            to_extended_path("C:/录制/主播//./分P/../a.flv"),
+            r"\\?\C:\录制\主播\a.flv"
+        );
+        assert_eq!(
+            to_extended_path(r"\\nas\share\a.flv"),
+            r"\\?\UNC\nas\share\a.flv"
+        );
+        assert_eq!(to_extended_path(r"\\?\C:\a.flv"), r"\\?\C:\a.flv");
+        assert_eq!(to_extended_path("record/a.flv"), "record/a.flv");
+    }
+}
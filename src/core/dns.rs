@@ -0,0 +1,493 @@
+//! DNS 解析缓存、自定义 DNS 服务器与域名直连覆盖表
+//!
+//! reqwest 通过 [`CachedResolver`] 使用这里的解析结果，ffmpeg 则通过
+//! [`ffmpeg_input`] 把 http 地址改写为 IP 直连并携带原始 Host 头。
+//! https 地址需要原始域名完成 SNI 与证书校验，ffmpeg 又无法单独指定解析结果，只能交给它自行解析。
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use futures::channel::oneshot;
+use parking_lot::{Mutex, RwLock};
+
+use crate::settings::NetworkSettings;
+
+/// 向自定义 DNS 服务器查询的超时时间
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+const DNS_PORT: u16 = 53;
+
+/// 执行阻塞查询的线程数
+const LOOKUP_WORKERS: usize = 4;
+
+static RESOLVER: LazyLock<DnsResolver> = LazyLock::new(DnsResolver::default);
+
+type LookupJob = (String, oneshot::Sender<io::Result<Vec<IpAddr>>>);
+
+/// 共享的查询线程池，避免每次查询都新建线程
+static LOOKUP_POOL: LazyLock<flume::Sender<LookupJob>> = LazyLock::new(|| {
+    let (tx, rx) = flume::unbounded::<LookupJob>();
+
+    for index in 0..LOOKUP_WORKERS {
+        let rx = rx.clone();
+        let spawned = std::thread::Builder::new()
+            .name(format!("blive-dns-{index}"))
+            .spawn(move || {
+                while let Ok((host, tx)) = rx.recv() {
+                    let _ = tx.send(resolver().lookup(&host));
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::warn!("启动 DNS 查询线程失败: {}", e);
+        }
+    }
+
+    tx
+});
+
+/// 全局解析器
+pub fn resolver() -> &'static DnsResolver {
+    &RESOLVER
+}
+
+#[derive(Debug, Default)]
+struct DnsConfig {
+    servers: Vec<SocketAddr>,
+    cache_ttl: Duration,
+    overrides: BTreeMap<String, IpAddr>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedAddrs {
+    addrs: Vec<IpAddr>,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct DnsResolver {
+    config: RwLock<Arc<DnsConfig>>,
+    cache: Mutex<HashMap<String, CachedAddrs>>,
+}
+
+impl DnsResolver {
+    /// 应用网络设置，同时清空已有缓存
+    pub fn configure(&self, network: &NetworkSettings) {
+        let servers = network
+            .dns_servers
+            .iter()
+            .filter_map(|server| {
+                let addr = parse_server(server);
+                if addr.is_none() {
+                    tracing::warn!("忽略无效的 DNS 服务器: {}", server);
+                }
+                addr
+            })
+            .collect();
+
+        *self.config.write() = Arc::new(DnsConfig {
+            servers,
+            cache_ttl: Duration::from_secs(network.dns_cache_seconds as u64),
+            overrides: network
+                .host_overrides
+                .iter()
+                .map(|(host, ip)| (host.to_lowercase(), *ip))
+                .collect(),
+        });
+        self.cache.lock().clear();
+    }
+
+    fn config(&self) -> Arc<DnsConfig> {
+        self.config.read().clone()
+    }
+
+    /// 覆盖表或未过期缓存中的结果，不会发起查询
+    pub fn lookup_cached(&self, host: &str) -> Option<Vec<IpAddr>> {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Some(vec![ip]);
+        }
+
+        let host = host.to_lowercase();
+        if let Some(ip) = self.config().overrides.get(&host) {
+            return Some(vec![*ip]);
+        }
+
+        self.cache
+            .lock()
+            .get(&host)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.addrs.clone())
+    }
+
+    /// 解析域名，可能阻塞，需在后台线程调用
+    ///
+    /// 查询失败时退回到已过期的缓存，避免 DNS 短暂不可用导致断流
+    pub fn lookup(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        if let Some(addrs) = self.lookup_cached(host) {
+            return Ok(addrs);
+        }
+
+        let host = host.to_lowercase();
+        let config = self.config();
+        let result = if config.servers.is_empty() {
+            lookup_system(&host)
+        } else {
+            lookup_servers(&config.servers, &host)
+        };
+
+        match result {
+            Ok(addrs) => {
+                if !config.cache_ttl.is_zero() {
+                    self.cache.lock().insert(
+                        host,
+                        CachedAddrs {
+                            addrs: addrs.clone(),
+                            expires_at: Instant::now() + config.cache_ttl,
+                        },
+                    );
+                }
+                Ok(addrs)
+            }
+            Err(e) => match self.cache.lock().get(&host) {
+                Some(entry) => {
+                    tracing::warn!("解析 {} 失败，使用过期的缓存结果: {}", host, e);
+                    Ok(entry.addrs.clone())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// 是否需要接管 ffmpeg 的域名解析
+    fn overrides_system(&self, host: &str) -> bool {
+        let config = self.config();
+        !config.servers.is_empty() || config.overrides.contains_key(&host.to_lowercase())
+    }
+
+    /// 见 [`ffmpeg_input`]
+    fn ffmpeg_input(&self, url: &str) -> (String, Option<String>) {
+        let Some((scheme, rest)) = url.split_once("://") else {
+            return (url.to_string(), None);
+        };
+        // 改写域名会破坏 TLS 的 SNI 与证书校验
+        if !scheme.eq_ignore_ascii_case("http") {
+            return (url.to_string(), None);
+        }
+        let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(authority_end);
+        let (host, port) = split_host_port(authority);
+
+        if host.parse::<IpAddr>().is_ok() || !self.overrides_system(host) {
+            return (url.to_string(), None);
+        }
+
+        match self.lookup(host) {
+            Ok(addrs) if !addrs.is_empty() => {
+                let ip = match addrs[0] {
+                    IpAddr::V4(ip) => ip.to_string(),
+                    IpAddr::V6(ip) => format!("[{ip}]"),
+                };
+                let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+                (
+                    format!("{scheme}://{ip}{port}{path}"),
+                    Some(authority.to_string()),
+                )
+            }
+            Ok(_) => (url.to_string(), None),
+            Err(e) => {
+                tracing::warn!("为 ffmpeg 解析 {} 失败，交由 ffmpeg 自行解析: {}", host, e);
+                (url.to_string(), None)
+            }
+        }
+    }
+}
+
+/// 供 reqwest 使用的解析器，查询交给全局的 [`DnsResolver`]
+#[derive(Debug, Default)]
+pub struct CachedResolver;
+
+impl reqwest::dns::Resolve for CachedResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+
+        Box::pin(async move {
+            let addrs = match resolver().lookup_cached(&host) {
+                Some(addrs) => addrs,
+                None => {
+                    let (tx, rx) = oneshot::channel();
+                    LOOKUP_POOL
+                        .send((host, tx))
+                        .map_err(|_| io::Error::other("DNS 查询线程不可用"))?;
+                    rx.await
+                        .map_err(|_| io::Error::other("DNS 解析线程意外退出"))??
+                }
+            };
+
+            Ok(Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0))) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// 为 ffmpeg 改写输入地址
+///
+/// 配置了自定义 DNS 服务器或命中覆盖表时，把 http 地址的域名替换为解析出的 IP，
+/// 并返回原始的 Host 头；https 等其他地址保持原样交给 ffmpeg 自行解析。
+pub fn ffmpeg_input(url: &str) -> (String, Option<String>) {
+    resolver().ffmpeg_input(url)
+}
+
+fn split_host_port(authority: &str) -> (&str, Option<&str>) {
+    // 去掉用户信息
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']').unwrap_or((rest, ""));
+        return (host, rest.strip_prefix(':'));
+    }
+
+    match authority.split_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (authority, None),
+    }
+}
+
+fn parse_server(server: &str) -> Option<SocketAddr> {
+    let server = server.trim();
+    server.parse::<SocketAddr>().ok().or_else(|| {
+        server
+            .parse::<IpAddr>()
+            .ok()
+            .map(|ip| SocketAddr::new(ip, DNS_PORT))
+    })
+}
+
+/// 解析 `域名=IP` 形式的覆盖表，多条用逗号分隔，无效条目会被忽略
+pub fn parse_host_overrides(value: &str) -> BTreeMap<String, IpAddr> {
+    value
+        .split([',', '，'])
+        .filter_map(|entry| {
+            let (host, ip) = entry.split_once('=')?;
+            let host = host.trim().to_lowercase();
+            let ip = ip.trim().parse().ok()?;
+            (!host.is_empty()).then_some((host, ip))
+        })
+        .collect()
+}
+
+/// 覆盖表的文本形式，与 [`parse_host_overrides`] 互逆
+pub fn format_host_overrides(overrides: &BTreeMap<String, IpAddr>) -> String {
+    overrides
+        .iter()
+        .map(|(host, ip)| format!("{host}={ip}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn lookup_system(host: &str) -> io::Result<Vec<IpAddr>> {
+    let addrs: Vec<IpAddr> = (host, 0).to_socket_addrs()?.map(|addr| addr.ip()).collect();
+    if addrs.is_empty() {
+        return Err(io::Error::other(format!("{host} 没有可用的地址")));
+    }
+
+    Ok(addrs)
+}
+
+/// 依次向配置的 DNS 服务器查询 A 记录
+fn lookup_servers(servers: &[SocketAddr], host: &str) -> io::Result<Vec<IpAddr>> {
+    let mut last_error = io::Error::other("没有可用的 DNS 服务器");
+
+    for server in servers {
+        match query_server(*server, host) {
+            Ok(addrs) => return Ok(addrs),
+            Err(e) => {
+                tracing::debug!("DNS 服务器 {} 查询 {} 失败: {}", server, host, e);
+                last_error = e;
+            }
+        }
+    }
+
+    Err(last_error)
+}
+
+fn query_server(server: SocketAddr, host: &str) -> io::Result<Vec<IpAddr>> {
+    let bind_addr: IpAddr = match server {
+        SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+        SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+    };
+    let socket = UdpSocket::bind((bind_addr, 0))?;
+    socket.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    socket.connect(server)?;
+
+    let id = rand::random::<u16>();
+    socket.send(&build_query(id, host))?;
+
+    let mut buf = [0u8; 512];
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    while Instant::now() < deadline {
+        let len = socket.recv(&mut buf)?;
+        // 忽略不属于本次查询的响应
+        if let Some(addrs) = parse_response(id, &buf[..len]) {
+            return if addrs.is_empty() {
+                Err(io::Error::other(format!("{host} 没有 A 记录")))
+            } else {
+                Ok(addrs)
+            };
+        }
+    }
+
+    Err(io::Error::new(io::ErrorKind::TimedOut, "DNS 查询超时"))
+}
+
+fn build_query(id: u16, host: &str) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(32 + host.len());
+    packet.extend_from_slice(&id.to_be_bytes());
+    // 期望递归查询，一个问题
+    packet.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+    for label in host.trim_end_matches('.').split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+
+    // QTYPE = A，QCLASS = IN
+    packet.extend_from_slice(&[0x00, 0x01, 0x00, 0x01]);
+    packet
+}
+
+/// 跳过报文中的域名，返回其后的位置
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)? as usize;
+        match len {
+            0 => return Some(pos + 1),
+            // 压缩指针占两个字节
+            len if len & 0xC0 == 0xC0 => return Some(pos + 2),
+            len => pos += 1 + len,
+        }
+    }
+}
+
+/// 解析 DNS 响应中的 A 记录，ID 不匹配或报文无效时返回 `None`
+fn parse_response(id: u16, packet: &[u8]) -> Option<Vec<IpAddr>> {
+    let read_u16 = |pos: usize| -> Option<u16> {
+        let bytes = packet.get(pos..pos + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+
+    if read_u16(0)? != id || packet.get(2)? & 0x80 == 0 {
+        return None;
+    }
+
+    let question_count = read_u16(4)?;
+    let answer_count = read_u16(6)?;
+
+    let mut pos = 12;
+    for _ in 0..question_count {
+        pos = skip_name(packet, pos)? + 4;
+    }
+
+    let mut addrs = vec![];
+    for _ in 0..answer_count {
+        pos = skip_name(packet, pos)?;
+        let record_type = read_u16(pos)?;
+        let record_class = read_u16(pos + 2)?;
+        let data_len = read_u16(pos + 8)? as usize;
+        pos += 10;
+
+        let data = packet.get(pos..pos + data_len)?;
+        pos += data_len;
+
+        if record_type == 1 && record_class == 1 && data_len == 4 {
+            addrs.push(IpAddr::V4(Ipv4Addr::new(
+                data[0], data[1], data[2], data[3],
+            )));
+        }
+    }
+
+    Some(addrs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let mut packet = build_query(0x1234, "cn-gotcha.bilivideo.com");
+        // 标记为响应，一条 CNAME 与一条 A 记录
+        packet[2] |= 0x80;
+        packet[7] = 2;
+        packet.extend_from_slice(&[0xC0, 0x0C, 0x00, 0x05, 0x00, 0x01, 0, 0, 0, 60, 0x00, 0x02]);
+        packet.extend_from_slice(&[0xC0, 0x0C]);
+        packet.extend_from_slice(&[0xC0, 0x0C, 0x00, 0x01, 0x00, 0x01, 0, 0, 0, 60, 0x00, 0x04]);
+        packet.extend_from_slice(&[1, 2, 3, 4]);
+
+        assert_eq!(
+            parse_response(0x1234, &packet),
+            Some(vec![IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4))])
+        );
+        // ID 不匹配
+        assert_eq!(parse_response(0x4321, &packet), None);
+        // 截断的报文
+        assert_eq!(parse_response(0x1234, &packet[..packet.len() - 2]), None);
+    }
+
+    #[test]
+    fn test_host_overrides() {
+        let overrides =
+            parse_host_overrides("CN.example.com = 1.2.3.4，bad=xx, =5.6.7.8,b.com=::1");
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(
+            overrides.get("cn.example.com"),
+            Some(&IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)))
+        );
+        assert_eq!(
+            parse_host_overrides(&format_host_overrides(&overrides)),
+            overrides
+        );
+    }
+
+    #[test]
+    fn test_ffmpeg_input_keeps_https_host() {
+        let resolver = DnsResolver::default();
+        resolver.configure(&NetworkSettings {
+            host_overrides: parse_host_overrides("cn-gotcha.bilivideo.com=1.2.3.4"),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            resolver.ffmpeg_input("http://cn-gotcha.bilivideo.com:8080/live.flv?expires=1"),
+            (
+                "http://1.2.3.4:8080/live.flv?expires=1".to_string(),
+                Some("cn-gotcha.bilivideo.com:8080".to_string())
+            )
+        );
+        // https 需要原始域名做 SNI 与证书校验
+        assert_eq!(
+            resolver.ffmpeg_input("https://cn-gotcha.bilivideo.com/live.flv"),
+            ("https://cn-gotcha.bilivideo.com/live.flv".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("example.com"), ("example.com", None));
+        assert_eq!(
+            split_host_port("user@example.com:8080"),
+            ("example.com", Some("8080"))
+        );
+        assert_eq!(split_host_port("[::1]:443"), ("::1", Some("443")));
+        assert_eq!(parse_server("223.5.5.5"), "223.5.5.5:53".parse().ok());
+        assert_eq!(
+            parse_server("223.5.5.5:5353"),
+            "223.5.5.5:5353".parse().ok()
+        );
+        assert_eq!(parse_server("dns.example"), None);
+    }
+}
@@ -1,35 +1,105 @@
+pub mod chapters;
+pub mod classify;
 pub mod context;
 pub mod error;
+pub mod ffmpeg_log;
 pub mod http_hls;
 pub mod http_stream;
+pub mod metadata;
+#[cfg(test)]
+mod mock_server;
+pub mod part_file;
 pub mod stats;
+pub mod storyboard;
 pub mod template;
+pub mod timeline;
+pub mod transcode;
 pub mod utils;
+pub mod writer;
 
+use crate::components::DownloaderStatus;
+use crate::core::disk::{extended_path, is_dir_usable, select_usable_dir};
+use crate::core::dns;
+use crate::core::downloader::context::DownloaderEvent;
 use crate::core::downloader::error::DownloaderError;
-use crate::core::downloader::template::DownloaderFilenameTemplate;
+use crate::core::downloader::metadata::RecordingMetadata;
+use crate::core::downloader::part_file::{final_path, part_path, path_taken};
+use crate::core::downloader::template::{
+    DownloaderFilenameTemplate, sanitize_filename, split_subdirectory,
+};
 use crate::core::downloader::{http_hls::HttpHlsDownloader, http_stream::HttpStreamDownloader};
+use crate::core::ffmpeg::ensure_ffmpeg;
 use crate::core::http_client::HttpClient;
 use crate::core::http_client::room::LiveRoomInfoData;
-use crate::core::http_client::stream::{LiveRoomStreamUrl, PlayStream};
+use crate::core::http_client::stream::{
+    LiveRoomStreamUrl, PlayStream, PlayStreamFormat, StreamCodecInfo,
+};
 use crate::core::http_client::user::LiveUserInfo;
+use crate::core::probe;
+use crate::core::record_lock;
 use crate::log_user_action;
 use crate::settings::{
-    DEFAULT_RECORD_NAME, LiveProtocol, Quality, Strategy, StreamCodec, VideoContainer,
+    DEFAULT_READ_BUFFER_KB, DEFAULT_RECORD_NAME, LiveProtocol, Quality, Strategy, StreamCodec,
+    TranscodeSettings, VideoContainer,
 };
+use crate::state::AppState;
 use anyhow::{Context, Result};
 use chrono::NaiveDateTime;
 use chrono_tz::Asia::Shanghai;
 use gpui::AsyncApp;
-use rand::Rng;
-use std::sync::Mutex;
+use parking_lot::Mutex;
+use rand::seq::IndexedRandom;
+use std::sync::Arc;
 
 pub use context::{DownloadConfig, DownloaderContext};
 pub use stats::DownloadStats;
 
+/// 同名录制的最大分 P 数，达到后续写到最后一个分 P
+pub const MAX_PARTS: u32 = 50;
+
+/// 流地址过期前提前轮换的秒数
+pub const ROTATION_LEAD_SECS: i64 = 60;
+
+/// 检查流地址是否即将过期的间隔
+pub const ROTATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub const REFERER: &str = "https://live.bilibili.com/";
 pub const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 
+/// ffmpeg 的输入地址与请求头
+///
+/// 按 DNS 设置改写为 IP 直连时附带原始 Host 头；ffmpeg 的 `-headers` 只能指定一次，多个请求头需合并
+pub fn ffmpeg_input(url: &str) -> (String, Vec<(&'static str, String)>) {
+    let (input, host) = dns::ffmpeg_input(url);
+
+    let mut headers = vec![
+        ("User-Agent", USER_AGENT.to_string()),
+        ("Referer", REFERER.to_string()),
+    ];
+    if let Some(host) = host {
+        headers.push(("Host", host));
+    }
+
+    (input, headers)
+}
+
+/// 流地址 `expires` 参数给出的过期时间（秒级时间戳），没有该参数时返回 `None`
+pub fn stream_expires_at(url: &str) -> Option<i64> {
+    let (_, query) = url.split_once('?')?;
+
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("expires=")?.parse().ok())
+}
+
+/// 合并为 ffmpeg `-headers` 参数的格式
+pub fn ffmpeg_headers(headers: &[(&str, String)]) -> String {
+    headers
+        .iter()
+        .map(|(key, value)| format!("{key}: {value}\r\n"))
+        .collect()
+}
+
 pub trait Downloader {
     /// 开始下载
     fn start(&mut self, cx: &mut AsyncApp) -> Result<()>;
@@ -52,27 +122,142 @@ pub enum DownloaderType {
 pub struct BLiveDownloader {
     pub context: DownloaderContext,
     downloader: Mutex<Option<DownloaderType>>,
+    /// 当前流地址的过期时间（秒级时间戳），用于计划内轮换
+    expires_at: Mutex<Option<i64>>,
+    /// 持有录制锁的录制目录
+    lock_dir: Mutex<Option<String>>,
 }
 
 impl BLiveDownloader {
-    async fn start_download(&self, cx: &mut AsyncApp, record_dir: &str) -> Result<()> {
+    /// 录制使用的画质与是否严格画质，计费网络下按设置限制画质
+    fn stream_preference(&self, cx: &mut AsyncApp) -> (Quality, bool) {
+        cx.read_global(|state: &AppState, _| {
+            (
+                state.recording_quality(self.context.quality),
+                state.settings.strict_quality,
+            )
+        })
+        .unwrap_or((self.context.quality, false))
+    }
+
+    /// 开始录制，`stream_info` 为已获取的流信息，为空时请求最新地址
+    async fn start_download(
+        &self,
+        cx: &mut AsyncApp,
+        record_dir: &str,
+        stream_info: Option<Arc<LiveRoomStreamUrl>>,
+    ) -> Result<()> {
+        // 先处理上一段录制遗留的完成事件，避免初始化时被清空而留下临时文件
+        self.context.flush_events(cx);
         self.context.init();
 
+        // 其他实例正在录制该房间时不重复录制，等下一次轮询重试
+        let room_id = self.context.room_id;
+        let locked_by = record_lock::acquire(record_dir, room_id).err();
+        self.context.update_global_state(cx, |state, _| {
+            state.locked_by = locked_by.map(|holder| holder.pid);
+        });
+        if let Some(holder) = locked_by {
+            anyhow::bail!("由其他实例录制中 - 房间: {}, PID: {}", room_id, holder.pid);
+        }
+        *self.lock_dir.lock() = Some(record_dir.to_string());
+
+        let (quality, strict) = self.stream_preference(cx);
+
         // 获取流信息
-        let stream_info = self.get_stream_info().await?;
+        let stream_info = match stream_info {
+            Some(stream_info) => stream_info,
+            None => self.get_stream_info(quality).await?,
+        };
+
+        // 解析下载URL和选择下载器类型，严格画质下失败时展示原因，等下一次轮询重试
+        let (url, downloader_type, format, codec, qn) =
+            match self.parse_stream_url(&stream_info, quality, strict) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    if strict {
+                        tracing::warn!("{} - 房间: {}", e, self.context.room_id);
+                        let cause = e.to_string();
+                        self.context.update_global_state(cx, |state, _| {
+                            state.downloader_status = Some(DownloaderStatus::Error { cause });
+                        });
+                    }
+                    return Err(e);
+                }
+            };
+        let stream_quality = Quality::from_qn(qn).unwrap_or(quality);
+        self.context.update_global_state(cx, |state, _| {
+            state.stream_quality = Some(stream_quality);
+        });
+        if stream_quality != quality {
+            tracing::info!(
+                "直播流画质与设置不一致 - 房间: {}, 设置: {}, 实际: {}",
+                self.context.room_id,
+                quality,
+                stream_quality
+            );
+        }
 
-        // 解析下载URL和选择下载器类型
-        let (url, downloader_type, format, codec) = self.parse_stream_url(&stream_info)?;
+        // 按标题分段时直播间信息已更新，使用最新的标题命名
+        let (room_info, record_name, alias, record_dirs) = cx
+            .read_global(|state: &AppState, _| {
+                let settings = state.get_room_settings(self.context.room_id);
+                (
+                    state
+                        .get_room_state(self.context.room_id)
+                        .and_then(|state| state.room_info.clone()),
+                    settings
+                        .map(|settings| settings.record_name.clone())
+                        .filter(|record_name| !record_name.is_empty()),
+                    settings
+                        .and_then(|settings| settings.alias())
+                        .map(str::to_string),
+                    state.settings.record_dir_candidates(record_dir),
+                )
+            })
+            .unwrap_or_default();
+        let room_info = room_info.unwrap_or_else(|| self.context.room_info.clone());
+        let record_name = record_name.unwrap_or_else(|| DEFAULT_RECORD_NAME.to_string());
+
+        // 录制目录空间不足或不可写时按优先级回退到备用目录，全部不可用时仍使用录制目录，由后续步骤报错
+        let primary_dir = record_dirs
+            .first()
+            .cloned()
+            .unwrap_or_else(|| record_dir.to_string());
+        let selected_dir = cx
+            .background_executor()
+            .spawn(
+                async move { select_usable_dir(&record_dirs, is_dir_usable).map(str::to_string) },
+            )
+            .await
+            .unwrap_or_else(|| primary_dir.clone());
+        if selected_dir != primary_dir {
+            tracing::warn!(
+                "录制目录不可用，切换到备用目录 - 房间: {}, {} -> {}",
+                self.context.room_id,
+                primary_dir,
+                selected_dir
+            );
+            self.context
+                .push_event_async(DownloaderEvent::RecordDirSwitched {
+                    from: primary_dir,
+                    to: selected_dir.clone(),
+                })
+                .await;
+        }
 
-        // 生成文件名
-        let filename = self.generate_filename()?;
+        // 生成文件名，模板中的子目录并入录制目录
+        let filename =
+            self.generate_filename(&room_info, &record_name, &alias.unwrap_or_default())?;
+        let (record_dir, filename) = split_subdirectory(&selected_dir, &filename);
+        let record_dir = record_dir.as_str();
 
         // 获取文件扩展名
         let ext = format.ext();
 
         // 确保录制目录存在
-        if !std::path::Path::new(record_dir).exists() {
-            if std::fs::create_dir_all(record_dir).is_ok() {
+        if !std::path::Path::new(&extended_path(record_dir)).exists() {
+            if std::fs::create_dir_all(extended_path(record_dir)).is_ok() {
                 log_user_action("录制目录创建成功", Some(&format!("路径: {record_dir}")));
             } else {
                 return Err(anyhow::anyhow!("无法创建录制目录: {}", record_dir));
@@ -82,17 +267,46 @@ impl BLiveDownloader {
         // 处理文件路径冲突
         let file_path = self.resolve_file_path(record_dir, &filename, ext)?;
 
+        let (read_buffer_size, transcode) = cx
+            .read_global(|state: &AppState, _| {
+                (
+                    state.settings.read_buffer_size(),
+                    state.settings.transcode.clone(),
+                )
+            })
+            .unwrap_or_else(|_| {
+                (
+                    DEFAULT_READ_BUFFER_KB as usize * 1024,
+                    TranscodeSettings::default(),
+                )
+            });
+
         let config = DownloadConfig {
-            output_path: file_path.clone(),
+            // 录制中写入临时文件，完成后再重命名为最终文件名
+            output_path: part_path(&file_path),
             overwrite: false,
             timeout: 30,
             retry_count: 3,
             codec,
             format,
-            quality: self.context.quality,
+            quality: stream_quality,
             strategy: self.context.strategy,
+            read_buffer_size,
+            transcode,
+            metadata: RecordingMetadata::new(&room_info, &self.context.user_info),
         };
 
+        // ffmpeg 只在实际用到时才检测，缺失时自动下载
+        let needs_ffmpeg = matches!(downloader_type, DownloaderType::HttpHls(_))
+            || self.context.strategy == Strategy::PriorityConfig;
+        if needs_ffmpeg {
+            cx.background_executor()
+                .spawn(async { ensure_ffmpeg() })
+                .await?;
+        }
+
+        let probe_url = url.clone();
+
         // 根据下载器类型创建具体的下载器
         let mut final_downloader = match downloader_type {
             DownloaderType::HttpStream(_) => {
@@ -137,25 +351,50 @@ impl BLiveDownloader {
             }
         }
 
-        self.downloader
-            .try_lock()
-            .unwrap()
-            .replace(final_downloader);
+        self.downloader.lock().replace(final_downloader);
+        *self.expires_at.lock() = stream_expires_at(&probe_url);
+
+        // 探测实际的流信息
+        self.context.probe_stream(cx, probe_url);
 
         Ok(())
     }
 
     pub async fn start(&self, cx: &mut AsyncApp, record_dir: &str) -> Result<()> {
-        match self.start_download(cx, record_dir).await {
+        match self.start_download(cx, record_dir, None).await {
             Ok(_) => Ok(()),
-            Err(e) => Err(e),
+            Err(e) => {
+                self.release_lock();
+                Err(e)
+            }
+        }
+    }
+
+    /// 刷新录制锁的心跳，只在录制中刷新
+    pub fn refresh_lock(&self) {
+        if !self.context.is_running() {
+            return;
+        }
+
+        if let Some(record_dir) = self.lock_dir.lock().as_deref()
+            && !record_lock::refresh(record_dir, self.context.room_id)
+        {
+            tracing::warn!("录制锁已被其他实例接管 - 房间: {}", self.context.room_id);
+        }
+    }
+
+    /// 释放录制锁，可重复调用
+    pub fn release_lock(&self) {
+        if let Some(record_dir) = self.lock_dir.lock().take() {
+            record_lock::release(&record_dir, self.context.room_id);
         }
     }
 
     pub async fn stop(&self) {
-        let mut downloader_guard = self.downloader.lock().unwrap();
-        if let Some(ref mut downloader) = downloader_guard.as_mut() {
-            match downloader {
+        // 先取出下载器再等待停止，避免跨 await 持有锁
+        let downloader = self.downloader.lock().take();
+        if let Some(mut downloader) = downloader {
+            match &mut downloader {
                 DownloaderType::HttpStream(downloader) => {
                     if let Some(downloader) = downloader {
                         let _ = downloader.stop().await;
@@ -168,6 +407,12 @@ impl BLiveDownloader {
                 }
             }
         }
+
+        // 轮换时马上录制新分 P，继续持有录制锁与弹幕连接
+        if !self.context.is_rotating() {
+            self.release_lock();
+            self.context.disconnect_danmaku();
+        }
     }
 
     pub async fn restart(&self, cx: &mut AsyncApp, record_dir: &str) -> Result<()> {
@@ -175,8 +420,72 @@ impl BLiveDownloader {
         self.start(cx, record_dir).await
     }
 
+    /// 轮换期间短暂停止下载，仍视为运行中，避免轮询重复启动
     pub fn is_running(&self) -> bool {
-        self.context.is_running()
+        self.context.is_running() || self.context.is_rotating()
+    }
+
+    /// 流地址即将过期时取出过期时间，取出后不会重复触发轮换
+    pub fn take_rotation_due(&self, now: i64) -> Option<i64> {
+        let mut expires_at = self.expires_at.lock();
+
+        if self.context.is_running() && expires_at.is_some_and(|at| now + ROTATION_LEAD_SECS >= at)
+        {
+            expires_at.take()
+        } else {
+            None
+        }
+    }
+
+    /// 计划内轮换：先获取新地址，成功后结束当前分 P 并立即用新地址录制新分 P，不经过错误重连流程
+    pub async fn rotate(&self, cx: &mut AsyncApp, record_dir: &str, expires_at: i64) -> Result<()> {
+        let (quality, strict) = self.stream_preference(cx);
+
+        // 拿不到可用的新地址时继续录制当前分 P，等下次检查再试
+        let stream_info = match self.get_stream_info(quality).await.and_then(|stream_info| {
+            self.parse_stream_url(&stream_info, quality, strict)?;
+            Ok(stream_info)
+        }) {
+            Ok(stream_info) => stream_info,
+            Err(e) => {
+                self.expires_at.lock().get_or_insert(expires_at);
+                return Err(e);
+            }
+        };
+
+        tracing::info!(
+            "计划内轮换 - 房间: {}, 流地址将于 {} 过期，切换到新分 P",
+            self.context.room_id,
+            chrono::DateTime::from_timestamp(expires_at, 0)
+                .map(|time| time
+                    .with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string())
+                .unwrap_or_default()
+        );
+
+        self.context.begin_rotation();
+        self.stop().await;
+        let result = self.start_download(cx, record_dir, Some(stream_info)).await;
+        self.context.end_rotation();
+        if result.is_err() {
+            self.release_lock();
+        }
+
+        result
+    }
+
+    /// 供外部播放器使用的直播流地址，与画质探测共用缓存
+    pub async fn play_url(&self) -> Result<String> {
+        let stream_info = probe::stream_info(
+            &self.context.client,
+            self.context.room_info.room_id,
+            self.context.quality.to_quality(),
+        )
+        .await?;
+        let (url, ..) = self.parse_stream_url(&stream_info, self.context.quality, false)?;
+
+        Ok(url)
     }
 }
 
@@ -199,6 +508,8 @@ impl BLiveDownloader {
         Self {
             context,
             downloader: Mutex::new(None),
+            expires_at: Mutex::new(None),
+            lock_dir: Mutex::new(None),
         }
     }
 
@@ -207,171 +518,116 @@ impl BLiveDownloader {
         Some(self.context.get_stats())
     }
 
-    /// 获取直播流信息
-    async fn get_stream_info(&self) -> Result<LiveRoomStreamUrl> {
-        match self
-            .context
-            .client
-            .get_live_room_stream_url(
-                self.context.room_info.room_id,
-                self.context.quality.to_quality(),
-            )
-            .await
-        {
-            Ok(stream_info) => Ok(stream_info),
-            Err(e) => Err(e),
-        }
+    /// 获取直播流信息，与画质探测共用并发额度，开始录制时总是请求最新地址
+    async fn get_stream_info(&self, quality: Quality) -> Result<Arc<LiveRoomStreamUrl>> {
+        probe::refresh_stream_info(
+            &self.context.client,
+            self.context.room_info.room_id,
+            quality.to_quality(),
+        )
+        .await
     }
 
+    /// 解析直播流地址，返回（地址, 下载器类型, 容器, 编码, 实际画质 qn）
+    ///
+    /// 严格画质模式下容器、编码与画质都须与设置一致，否则依次尝试其他协议，全部不满足时报错
     fn parse_stream_url(
         &self,
         stream_info: &LiveRoomStreamUrl,
-    ) -> Result<(String, DownloaderType, VideoContainer, StreamCodec)> {
+        quality: Quality,
+        strict: bool,
+    ) -> Result<(String, DownloaderType, VideoContainer, StreamCodec, u32)> {
         let playurl_info = stream_info
             .playurl_info
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("未找到播放信息"))?;
 
-        match self.context.strategy {
-            Strategy::LowCost => {
-                // 优先尝试http_stream协议
-                if let Some(stream) = playurl_info
-                    .playurl
-                    .stream
-                    .iter()
-                    .find(|stream| stream.protocol_name == LiveProtocol::HttpStream)
-                {
-                    return self.parse_http_stream(stream);
-                }
+        // 按策略排列协议的尝试顺序，LowCost 策略总是直接拉流
+        let candidates = match self.context.strategy {
+            Strategy::LowCost => [
+                (LiveProtocol::HttpStream, DownloaderType::HttpStream(None)),
+                (LiveProtocol::default(), DownloaderType::HttpStream(None)),
+            ],
+            Strategy::PriorityConfig => [
+                (LiveProtocol::default(), DownloaderType::HttpHls(None)),
+                (LiveProtocol::HttpStream, DownloaderType::HttpStream(None)),
+            ],
+        };
 
-                // 如果没有http_stream，尝试http_hls协议
-                if let Some(stream) = playurl_info
-                    .playurl
-                    .stream
-                    .iter()
-                    .find(|stream| stream.protocol_name == LiveProtocol::default())
-                {
-                    return self.parse_http_stream(stream);
-                }
-            }
-            Strategy::PriorityConfig => {
-                // 优先尝试http_hls协议
-                if let Some(stream) = playurl_info
-                    .playurl
-                    .stream
-                    .iter()
-                    .find(|stream| stream.protocol_name == LiveProtocol::default())
-                {
-                    return self.parse_hls_stream(stream);
-                }
+        let mut last_error = None;
+        for (protocol, downloader_type) in candidates {
+            let Some(stream) = playurl_info
+                .playurl
+                .stream
+                .iter()
+                .find(|stream| stream.protocol_name == protocol)
+            else {
+                continue;
+            };
 
-                // 如果没有http_hls，尝试http_stream协议
-                if let Some(stream) = playurl_info
-                    .playurl
-                    .stream
-                    .iter()
-                    .find(|stream| stream.protocol_name == LiveProtocol::HttpStream)
-                {
-                    return self.parse_http_stream(stream);
-                }
+            match self.parse_stream(stream, downloader_type, quality, strict) {
+                Err(e) if strict => last_error = Some(e),
+                result => return result,
             }
         }
 
-        anyhow::bail!("未找到合适的直播流协议");
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("未找到合适的直播流协议")))
     }
 
-    fn parse_http_stream(
+    fn parse_stream(
         &self,
         stream: &PlayStream,
-    ) -> Result<(String, DownloaderType, VideoContainer, StreamCodec)> {
-        if stream.format.is_empty() {
-            anyhow::bail!("未找到合适的直播流");
-        }
-
-        // 优先选择配置中的格式
-        let format_stream = stream
-            .format
-            .iter()
-            .find(|format| format.format_name == self.context.format)
-            .or_else(|| stream.format.first())
-            .ok_or_else(|| anyhow::anyhow!("未找到合适的视频格式"))?;
-
-        if format_stream.codec.is_empty() {
-            anyhow::bail!("未找到合适的视频编码");
-        }
-
-        // 优先按照设置选择编码格式
-        let codec = format_stream
-            .codec
-            .iter()
-            .find(|codec| codec.codec_name == self.context.codec)
-            .unwrap_or_else(|| format_stream.codec.first().unwrap());
+        downloader_type: DownloaderType,
+        quality: Quality,
+        strict: bool,
+    ) -> Result<(String, DownloaderType, VideoContainer, StreamCodec, u32)> {
+        let (format_stream, codec) = select_codec(
+            stream,
+            self.context.format,
+            self.context.codec,
+            quality,
+            strict,
+        )?;
 
         // 随机选择URL
-        let url_info = &codec.url_info[rand::rng().random_range(0..codec.url_info.len())];
+        let url_info = codec
+            .url_info
+            .choose(&mut rand::rng())
+            .ok_or_else(|| anyhow::anyhow!("未找到可用的直播流地址"))?;
         let url = format!("{}{}{}", url_info.host, codec.base_url, url_info.extra);
 
         Ok((
             url,
-            DownloaderType::HttpStream(None),
+            downloader_type,
             format_stream.format_name,
             codec.codec_name,
+            codec.current_qn,
         ))
     }
 
-    fn parse_hls_stream(
+    fn generate_filename(
         &self,
-        stream: &PlayStream,
-    ) -> Result<(String, DownloaderType, VideoContainer, StreamCodec)> {
-        if stream.format.is_empty() {
-            anyhow::bail!("未找到合适的HLS流");
-        }
-
-        // 优先选择配置中的格式
-        let format_stream = stream
-            .format
-            .iter()
-            .find(|format| format.format_name == self.context.format)
-            .or_else(|| stream.format.first())
-            .ok_or_else(|| anyhow::anyhow!("未找到合适的视频格式"))?;
-
-        if format_stream.codec.is_empty() {
-            anyhow::bail!("未找到合适的视频编码");
-        }
-
-        // 优先按照设置选择编码格式
-        let codec = format_stream
-            .codec
-            .iter()
-            .find(|codec| codec.codec_name == self.context.codec)
-            .unwrap_or_else(|| format_stream.codec.first().unwrap());
-
-        // 随机选择URL
-        let url_info = &codec.url_info[rand::rng().random_range(0..codec.url_info.len())];
-        let url = format!("{}{}{}", url_info.host, codec.base_url, url_info.extra);
-
-        Ok((
-            url,
-            DownloaderType::HttpHls(None),
-            format_stream.format_name,
-            codec.codec_name,
-        ))
-    }
-
-    fn generate_filename(&self) -> Result<String> {
-        let room_info = &self.context.room_info;
+        room_info: &LiveRoomInfoData,
+        record_name: &str,
+        alias: &str,
+    ) -> Result<String> {
         let user_info = &self.context.user_info;
         let quality = self.context.quality;
 
-        let template = leon::Template::parse(DEFAULT_RECORD_NAME)
-            .unwrap_or_else(|_| leon::Template::parse("{up_name}_{datetime}").unwrap());
+        let template = leon::Template::parse(record_name)
+            .or_else(|_| leon::Template::parse("{up_name}_{datetime}"))
+            .context("无效的文件名模板")?;
 
         let live_time = NaiveDateTime::parse_from_str(&room_info.live_time, "%Y-%m-%d %H:%M:%S")
             .unwrap_or_default();
-        let live_time = live_time.and_local_timezone(Shanghai).unwrap();
+        let live_time = live_time
+            .and_local_timezone(Shanghai)
+            .earliest()
+            .context("无效的开播时间")?;
 
         let values = DownloaderFilenameTemplate {
             up_name: user_info.uname.clone(),
+            alias: alias.to_string(),
             quality,
             room_id: room_info.room_id,
             datetime: live_time.format("%Y-%m-%d %H点%M分").to_string(),
@@ -379,15 +635,14 @@ impl BLiveDownloader {
             room_description: room_info.description.clone(),
             room_area_name: room_info.area_name.clone(),
             date: live_time.format("%Y-%m-%d").to_string(),
+            year: live_time.format("%Y").to_string(),
         };
 
         let filename = template.render(&values).unwrap_or_default();
-        Ok(filename)
+        Ok(sanitize_filename(&filename))
     }
 
     fn resolve_file_path(&self, base_path: &str, filename: &str, ext: &str) -> Result<String> {
-        const MAX_PARTS: u32 = 50; // 最大分片数量限制
-
         let initial_file_path = format!("{base_path}/{filename}.{ext}");
         let file_stem = std::path::Path::new(filename)
             .file_stem()
@@ -397,8 +652,8 @@ impl BLiveDownloader {
         let folder_path = format!("{base_path}/{file_stem}");
 
         // 检查是否已经存在分P文件夹
-        let folder_exists = std::path::Path::new(&folder_path).exists();
-        let initial_file_exists = std::path::Path::new(&initial_file_path).exists();
+        let folder_exists = std::path::Path::new(&extended_path(&folder_path)).exists();
+        let initial_file_exists = path_taken(&initial_file_path);
 
         // 如果文件夹和原文件都不存在，返回原始路径
         if !folder_exists && !initial_file_exists {
@@ -408,15 +663,17 @@ impl BLiveDownloader {
         // 如果存在分P文件夹或原文件存在，需要使用分P系统
         if folder_exists || initial_file_exists {
             // 创建文件夹（如果不存在）
-            std::fs::create_dir_all(&folder_path).context("无法创建文件夹")?;
+            std::fs::create_dir_all(extended_path(&folder_path)).context("无法创建文件夹")?;
 
             // 扫描文件夹中现有的分P文件，找到所有现有的编号
             let mut existing_parts = Vec::new();
 
-            if let Ok(folder) = std::fs::read_dir(&folder_path) {
+            if let Ok(folder) = std::fs::read_dir(extended_path(&folder_path)) {
                 for entry in folder.flatten() {
                     let file_name_os = entry.file_name();
                     let file_name = file_name_os.to_string_lossy();
+                    // 录制中或未收尾的临时文件同样占用编号
+                    let file_name = final_path(&file_name);
 
                     // 检查是否是我们的分P文件格式: {file_stem}_P{number}.{ext}
                     if let Some(name_without_ext) = file_name.strip_suffix(&format!(".{ext}")) {
@@ -454,10 +711,22 @@ impl BLiveDownloader {
             #[allow(unused)]
             let mut new_file_path = format!("{folder_path}/{new_file_name}");
 
-            if initial_file_exists && !std::path::Path::new(&first_part_path).exists() {
-                std::fs::rename(&initial_file_path, &first_part_path).context(format!(
-                    "重命名原文件失败: {initial_file_path} -> {first_part_path}"
-                ))?;
+            // 原文件可能是未收尾的临时文件，随之移动为 P1 的临时文件
+            let initial_part_path = part_path(&initial_file_path);
+            let first_part_move =
+                if std::path::Path::new(&extended_path(&initial_file_path)).exists() {
+                    Some((initial_file_path.clone(), first_part_path.clone()))
+                } else if std::path::Path::new(&extended_path(&initial_part_path)).exists() {
+                    Some((initial_part_path, part_path(&first_part_path)))
+                } else {
+                    None
+                };
+
+            if let Some((from, to)) = first_part_move
+                && !path_taken(&first_part_path)
+            {
+                std::fs::rename(extended_path(&from), extended_path(&to))
+                    .context(format!("重命名原文件失败: {from} -> {to}"))?;
 
                 // 返回分P文件路径 P2
                 next_part_number = 2;
@@ -482,3 +751,129 @@ impl BLiveDownloader {
         }
     }
 }
+
+/// 按设置选择容器与编码，非严格模式下找不到时回退到第一个可用的，严格模式下还要求画质一致
+fn select_codec(
+    stream: &PlayStream,
+    format: VideoContainer,
+    codec: StreamCodec,
+    quality: Quality,
+    strict: bool,
+) -> Result<(&PlayStreamFormat, &StreamCodecInfo)> {
+    let format_stream = match stream
+        .format
+        .iter()
+        .find(|stream| stream.format_name == format)
+    {
+        Some(format_stream) => format_stream,
+        None if strict => anyhow::bail!("严格画质：直播流没有 {format} 格式"),
+        None => stream
+            .format
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("未找到合适的视频格式"))?,
+    };
+
+    let codec_info = match format_stream
+        .codec
+        .iter()
+        .find(|info| info.codec_name == codec)
+    {
+        Some(codec_info) => codec_info,
+        None if strict => anyhow::bail!("严格画质：{format} 格式没有 {codec} 编码"),
+        None => format_stream
+            .codec
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("未找到合适的视频编码"))?,
+    };
+
+    if strict && codec_info.current_qn != quality.to_quality() {
+        anyhow::bail!(
+            "严格画质：需要{quality}，当前只能获取到{}",
+            Quality::from_qn(codec_info.current_qn)
+                .map(|quality| quality.to_string())
+                .unwrap_or_else(|| format!("画质 {}", codec_info.current_qn))
+        );
+    }
+
+    Ok((format_stream, codec_info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_expires_at() {
+        assert_eq!(
+            stream_expires_at(
+                "https://cn-gddg.bilivideo.com/live/index.m3u8?expires=1700000000&sign=abc&trid=x"
+            ),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            stream_expires_at("https://example.com/live.flv?trid=x"),
+            None
+        );
+        assert_eq!(stream_expires_at("https://example.com/live.flv"), None);
+    }
+
+    #[test]
+    fn test_select_codec() {
+        let stream = serde_json::from_value::<PlayStream>(serde_json::json!({
+            "protocol_name": "http_stream",
+            "format": [{
+                "format_name": "flv",
+                "codec": [{
+                    "codec_name": "avc",
+                    "current_qn": 400,
+                    "accept_qn": [10000, 400],
+                    "base_url": "/live.flv",
+                    "url_info": [],
+                }],
+            }],
+        }))
+        .unwrap();
+
+        // 非严格模式回退到可用的编码
+        let (_, codec) = select_codec(
+            &stream,
+            VideoContainer::FLV,
+            StreamCodec::HEVC,
+            Quality::Original,
+            false,
+        )
+        .unwrap();
+        assert_eq!(codec.codec_name, StreamCodec::AVC);
+
+        let error = select_codec(
+            &stream,
+            VideoContainer::FLV,
+            StreamCodec::AVC,
+            Quality::Original,
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "严格画质：需要原画，当前只能获取到蓝光");
+
+        assert!(
+            select_codec(
+                &stream,
+                VideoContainer::FLV,
+                StreamCodec::AVC,
+                Quality::BlueRay,
+                true,
+            )
+            .is_ok()
+        );
+        assert!(
+            select_codec(
+                &stream,
+                VideoContainer::FLV,
+                StreamCodec::HEVC,
+                Quality::BlueRay,
+                true,
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::core::disk::extended_path;
+
+/// 一个章节，从直播标题或分区变化的时刻开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    /// 相对录制开始的偏移（毫秒）
+    pub start_ms: u64,
+    pub title: String,
+    pub area: String,
+}
+
+/// 录制期间按标题/分区变化生成章节，写入录制文件旁的 FFMETADATA
+///
+/// 只有一个章节时不写文件；合并时可用
+/// `ffmpeg -i 录制.flv -i 录制.chapters.txt -map_metadata 1 -map_chapters 1 -c copy 录制.mkv`
+#[derive(Debug, Clone)]
+pub struct ChapterMarks {
+    path: PathBuf,
+    started_at: Instant,
+    chapters: Vec<Chapter>,
+}
+
+impl ChapterMarks {
+    pub fn start(video_path: &Path, title: &str, area: &str, now: Instant) -> Self {
+        Self {
+            path: chapters_path(video_path),
+            started_at: now,
+            chapters: vec![Chapter {
+                start_ms: 0,
+                title: title.to_string(),
+                area: area.to_string(),
+            }],
+        }
+    }
+
+    pub fn chapters(&self) -> &[Chapter] {
+        &self.chapters
+    }
+
+    /// 标题或分区变化时开始新章节，返回是否新增了章节
+    pub fn observe(&mut self, title: &str, area: &str, now: Instant) -> bool {
+        if self
+            .chapters
+            .last()
+            .is_some_and(|last| last.title == title && last.area == area)
+        {
+            return false;
+        }
+
+        let start_ms = now.duration_since(self.started_at).as_millis() as u64;
+        self.chapters.push(Chapter {
+            start_ms,
+            title: title.to_string(),
+            area: area.to_string(),
+        });
+
+        // 录制中途异常退出时也能保留已有章节
+        if let Err(e) = self.write(start_ms) {
+            tracing::warn!("章节写入失败: {} - {}", self.path.display(), e);
+        }
+
+        true
+    }
+
+    /// 录制结束时写入最后一个章节的结尾
+    pub fn finish(self, now: Instant) {
+        let end_ms = now.duration_since(self.started_at).as_millis() as u64;
+        if let Err(e) = self.write(end_ms) {
+            tracing::warn!("章节写入失败: {} - {}", self.path.display(), e);
+        }
+    }
+
+    fn write(&self, end_ms: u64) -> io::Result<()> {
+        if self.chapters.len() < 2 {
+            return Ok(());
+        }
+
+        std::fs::write(
+            extended_path(&self.path.to_string_lossy()),
+            render_ffmetadata(&self.chapters, end_ms),
+        )
+    }
+}
+
+/// 录制文件对应的章节文件路径
+pub fn chapters_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("chapters.txt")
+}
+
+/// FFMETADATA 中 `=`、`;`、`#`、`\` 与换行需要转义
+fn escape_metadata(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 生成 FFMETADATA，每个章节在下一个章节开始时结束
+fn render_ffmetadata(chapters: &[Chapter], end_ms: u64) -> String {
+    let mut output = String::from(";FFMETADATA1\n");
+
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapters
+            .get(i + 1)
+            .map(|next| next.start_ms)
+            .unwrap_or(end_ms)
+            .max(chapter.start_ms);
+        let title = if chapter.area.is_empty() {
+            chapter.title.clone()
+        } else {
+            format!("[{}] {}", chapter.area, chapter.title)
+        };
+
+        output.push_str(&format!(
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.start_ms,
+            end,
+            escape_metadata(&title)
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_chapter_marks() {
+        let video = std::env::temp_dir().join(format!("blive-chapters-{}.flv", std::process::id()));
+        let path = chapters_path(&video);
+        let start = Instant::now();
+
+        let mut marks = ChapterMarks::start(&video, "杂谈", "聊天", start);
+        assert!(!marks.observe("杂谈", "聊天", start + Duration::from_secs(10)));
+        // 只有一个章节时不写文件
+        assert!(!path.exists());
+
+        assert!(marks.observe("打游戏=开心", "单机", start + Duration::from_secs(60)));
+        assert!(marks.observe("打游戏=开心", "网游", start + Duration::from_secs(90)));
+        assert_eq!(marks.chapters().len(), 3);
+        marks.finish(start + Duration::from_secs(120));
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(";FFMETADATA1\n"));
+        assert_eq!(content.matches("[CHAPTER]").count(), 3);
+        assert!(content.contains("START=0\nEND=60000\ntitle=[聊天] 杂谈\n"));
+        assert!(content.contains("START=60000\nEND=90000\ntitle=[单机] 打游戏\\=开心\n"));
+        assert!(content.contains("START=90000\nEND=120000\n"));
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::core::downloader::error::DownloaderError;
+
+/// ffmpeg 错误日志的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfmpegErrorKind {
+    /// 网络连接异常或服务端返回错误状态码
+    Network,
+    /// 流数据无法识别
+    InvalidData,
+    /// 本地磁盘无法写入
+    Storage,
+}
+
+/// 一条分类规则
+struct ErrorRule {
+    pattern: &'static str,
+    kind: FfmpegErrorKind,
+    recoverable: bool,
+}
+
+/// 分类规则表，按顺序匹配，命中第一条即返回
+const ERROR_RULES: &[ErrorRule] = &[
+    ErrorRule {
+        pattern: r"(?i)no space left on device|permission denied|read-only file system",
+        kind: FfmpegErrorKind::Storage,
+        recoverable: false,
+    },
+    ErrorRule {
+        pattern: r"(?i)server returned (4\d\d|5\d\d|5xx)",
+        kind: FfmpegErrorKind::Network,
+        recoverable: true,
+    },
+    ErrorRule {
+        pattern: r"(?i)connection (reset|refused|timed out)|timeout|no route to host|network is unreachable|broken pipe",
+        kind: FfmpegErrorKind::Network,
+        recoverable: true,
+    },
+    ErrorRule {
+        pattern: r"(?i)protocol not found|invalid data found|decoder failed",
+        kind: FfmpegErrorKind::InvalidData,
+        recoverable: true,
+    },
+];
+
+static COMPILED_RULES: LazyLock<Vec<(Regex, &'static ErrorRule)>> = LazyLock::new(|| {
+    ERROR_RULES
+        .iter()
+        .map(|rule| (Regex::new(rule.pattern).expect("无效的错误分类正则"), rule))
+        .collect()
+});
+
+/// ffmpeg 错误日志的分类结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FfmpegErrorClass {
+    pub kind: FfmpegErrorKind,
+    /// 是否可以通过重连恢复
+    pub recoverable: bool,
+}
+
+impl FfmpegErrorClass {
+    /// 转换为下载器错误，`output_path` 用于磁盘类错误
+    pub fn into_error(self, message: String, output_path: &str) -> DownloaderError {
+        match self.kind {
+            FfmpegErrorKind::Network => DownloaderError::NetworkConnectionFailed { message },
+            FfmpegErrorKind::InvalidData => DownloaderError::NoSuitableStreamProtocol,
+            FfmpegErrorKind::Storage => DownloaderError::FileWriteFailed {
+                path: output_path.to_string(),
+                reason: message,
+            },
+        }
+    }
+}
+
+/// 对 ffmpeg 的 error 级别日志分类，未命中任何规则时返回 `None`
+pub fn classify_ffmpeg_error(message: &str) -> Option<FfmpegErrorClass> {
+    COMPILED_RULES
+        .iter()
+        .find(|(regex, _)| regex.is_match(message))
+        .map(|(_, rule)| FfmpegErrorClass {
+            kind: rule.kind,
+            recoverable: rule.recoverable,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(message: &str) -> Option<FfmpegErrorKind> {
+        classify_ffmpeg_error(message).map(|class| class.kind)
+    }
+
+    #[test]
+    fn test_classify_ffmpeg_error() {
+        assert_eq!(
+            kind("https://cn-gotcha.example/live.flv: Connection reset by peer"),
+            Some(FfmpegErrorKind::Network)
+        );
+        assert_eq!(
+            kind("Connection to tcp://1.2.3.4:443 failed: Connection timed out"),
+            Some(FfmpegErrorKind::Network)
+        );
+        assert_eq!(
+            kind("HTTP error 403 Forbidden: Server returned 403 Forbidden (access denied)"),
+            Some(FfmpegErrorKind::Network)
+        );
+        assert_eq!(
+            kind("live.m3u8: Invalid data found when processing input"),
+            Some(FfmpegErrorKind::InvalidData)
+        );
+        assert_eq!(kind("Non-monotonous DTS in output stream 0:1"), None);
+
+        let storage =
+            classify_ffmpeg_error("av_interleaved_write_frame(): No space left on device").unwrap();
+        assert_eq!(storage.kind, FfmpegErrorKind::Storage);
+        assert!(!storage.recoverable);
+        assert!(
+            !storage
+                .into_error("No space left on device".to_string(), "a.flv")
+                .is_recoverable()
+        );
+    }
+}
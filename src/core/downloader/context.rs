@@ -1,28 +1,70 @@
 use std::{
-    collections::VecDeque,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    pin::pin,
     sync::{Arc, atomic},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use futures::future::{Either, select};
 use gpui::{App, AsyncApp};
-use try_lock::TryLock;
+use parking_lot::Mutex;
 
 use crate::{
     components::{DownloaderStatus, RoomCardStatus},
     core::{
-        HttpClient,
+        HttpClient, audio, auth,
+        blank::{self, BlankSegment},
+        danmaku::{
+            archive::DanmakuArchive,
+            client::DanmakuConnection,
+            highlights::{Highlight, HighlightTracker, write_highlights},
+            raw::{RawDump, RawDumpSink},
+            sidecar::{DanmakuSidecar, SidecarFormat},
+        },
         downloader::{
-            DownloadStats,
+            BLiveDownloader, DownloadStats,
+            chapters::ChapterMarks,
             error::DownloaderError,
+            ffmpeg_input,
+            metadata::RecordingMetadata,
+            part_file::{self, final_path},
+            storyboard::{Storyboard, capture_snapshot, snapshots},
+            timeline::{SessionEventKind, SessionTimeline, live_start_millis},
             utils::{pretty_bytes, pretty_duration},
         },
-        http_client::{room::LiveRoomInfoData, user::LiveUserInfo},
+        ffmpeg::ensure_ffmpeg,
+        ffprobe,
+        history::{self, HistoryEvent, HistoryRecord},
+        http_client::{
+            room::{LiveRoomInfoData, LiveStatus},
+            user::LiveUserInfo,
+        },
+        loudnorm, mqtt, relay, repair,
+        webhook::{self, WebhookPayload},
+    },
+    crash, log_recording_error, log_recording_start, log_recording_stop,
+    logger::redact_sensitive,
+    profile_scope,
+    settings::{
+        DEFAULT_READ_BUFFER_KB, Quality, Strategy, StreamCodec, TranscodeSettings, VideoContainer,
+        WebhookEvent,
     },
-    log_recording_error, log_recording_start, log_recording_stop,
-    settings::{Quality, Strategy, StreamCodec, VideoContainer},
     state::{AppState, RoomCardState},
 };
 
+/// 事件 channel 的容量，消费者跟不上时生产者会等待
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 没有事件时事件处理任务的唤醒间隔，用于刷新心跳与检查是否退出
+const EVENT_IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 看门狗检查间隔
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 事件处理任务超过该时长没有心跳即视为卡死
+const EVENT_PROCESSOR_TIMEOUT: Duration = Duration::from_secs(15);
+
 #[derive(Debug, Clone)]
 pub enum DownloaderEvent {
     Started {
@@ -42,6 +84,70 @@ pub enum DownloaderEvent {
     Error {
         error: DownloaderError,
     },
+    /// 完成后的 ffprobe 校验未通过
+    OutputCorrupted {
+        file_path: String,
+        reason: String,
+    },
+    /// 录制目录不可用，本次分段改用备用目录
+    RecordDirSwitched {
+        from: String,
+        to: String,
+    },
+}
+
+/// 下载线程与事件处理任务之间的有界事件 channel
+#[derive(Debug, Clone)]
+pub struct EventChannel {
+    tx: flume::Sender<DownloaderEvent>,
+    rx: flume::Receiver<DownloaderEvent>,
+}
+
+impl Default for EventChannel {
+    fn default() -> Self {
+        let (tx, rx) = flume::bounded(EVENT_CHANNEL_CAPACITY);
+        Self { tx, rx }
+    }
+}
+
+impl EventChannel {
+    /// 发送事件，channel 已满时阻塞等待消费者
+    pub fn send(&self, event: DownloaderEvent) {
+        // 两端都由自身持有，不会断开
+        let _ = self.tx.send(event);
+    }
+
+    /// 异步发送事件，channel 已满时等待而不阻塞线程
+    pub async fn send_async(&self, event: DownloaderEvent) {
+        let _ = self.tx.send_async(event).await;
+    }
+
+    /// 不阻塞地发送事件，channel 已满时返回 `false`
+    pub fn try_send(&self, event: DownloaderEvent) -> bool {
+        self.tx.try_send(event).is_ok()
+    }
+
+    /// 等待下一个事件
+    pub async fn recv(&self) -> Option<DownloaderEvent> {
+        self.rx.recv_async().await.ok()
+    }
+
+    /// 取出全部待处理事件
+    pub fn drain(&self) -> Vec<DownloaderEvent> {
+        self.rx.try_iter().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.rx.drain();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +168,12 @@ pub struct DownloadConfig {
     pub quality: Quality,
     /// 下载策略
     pub strategy: Strategy,
+    /// 直播流读缓冲区大小（字节）
+    pub read_buffer_size: usize,
+    /// 转码设置，仅 PriorityConfig 策略使用
+    pub transcode: TranscodeSettings,
+    /// 写入容器的元数据，仅 PriorityConfig 策略使用
+    pub metadata: RecordingMetadata,
 }
 
 impl Default for DownloadConfig {
@@ -75,6 +187,9 @@ impl Default for DownloadConfig {
             codec: StreamCodec::default(),
             format: VideoContainer::default(),
             quality: Quality::default(),
+            read_buffer_size: DEFAULT_READ_BUFFER_KB as usize * 1024,
+            transcode: TranscodeSettings::default(),
+            metadata: RecordingMetadata::default(),
         }
     }
 }
@@ -89,9 +204,24 @@ pub struct DownloaderContext {
     pub format: VideoContainer,
     pub codec: StreamCodec,
     pub strategy: Strategy,
-    stats: Arc<TryLock<DownloadStats>>,
+    stats: Arc<Mutex<DownloadStats>>,
     is_running: Arc<atomic::AtomicBool>,
-    event_queue: Arc<TryLock<VecDeque<DownloaderEvent>>>,
+    event_channel: EventChannel,
+    ffmpeg_log: Arc<Mutex<Option<String>>>,
+    ffmpeg_pid: Arc<atomic::AtomicU32>,
+    timeline: Arc<Mutex<SessionTimeline>>,
+    /// 已完成录像的故事板截图，在时间线记录完成时读取一次，避免界面每帧扫描目录
+    storyboards: Arc<Mutex<HashMap<String, Vec<(Duration, PathBuf)>>>>,
+    /// 事件处理任务最近一次心跳（毫秒时间戳）
+    processor_heartbeat: Arc<atomic::AtomicI64>,
+    /// 事件处理任务的代数，重新启动后旧任务与旧看门狗据此退出
+    processor_generation: Arc<atomic::AtomicU64>,
+    /// 正在进行计划内轮换，旧分 P 的完成不视为录制结束
+    rotating: Arc<atomic::AtomicBool>,
+    /// 弹幕服务器连接，跨分 P 保持，下载器停止时断开
+    danmaku: Arc<Mutex<Option<DanmakuConnection>>>,
+    /// 弹幕原始数据包转储，开启时每个分 P 一个文件
+    raw_dump: RawDumpSink,
 }
 
 impl DownloaderContext {
@@ -115,17 +245,140 @@ impl DownloaderContext {
             quality,
             format,
             codec,
-            stats: Arc::new(TryLock::new(DownloadStats::default())),
+            stats: Arc::new(Mutex::new(DownloadStats::default())),
             is_running: Arc::new(atomic::AtomicBool::new(false)),
-            event_queue: Arc::new(TryLock::new(VecDeque::new())),
+            event_channel: EventChannel::default(),
+            ffmpeg_log: Arc::new(Mutex::new(None)),
+            ffmpeg_pid: Arc::new(atomic::AtomicU32::new(0)),
+            timeline: Arc::new(Mutex::new(SessionTimeline::default())),
+            storyboards: Arc::new(Mutex::new(HashMap::new())),
+            processor_heartbeat: Arc::new(atomic::AtomicI64::new(0)),
+            processor_generation: Arc::new(atomic::AtomicU64::new(0)),
+            rotating: Arc::new(atomic::AtomicBool::new(false)),
+            danmaku: Arc::new(Mutex::new(None)),
+            raw_dump: RawDumpSink::default(),
         }
     }
 
     pub fn init(&self) {
-        self.stats.try_lock().unwrap().reset();
+        self.stats.lock().reset();
         self.is_running
             .store(false, std::sync::atomic::Ordering::Relaxed);
-        self.event_queue.try_lock().unwrap().clear();
+        self.event_channel.clear();
+        self.set_ffmpeg_log(None);
+        self.set_ffmpeg_pid(None);
+    }
+
+    /// 记录当前会话的 ffmpeg 日志路径，错误信息会引用该文件
+    pub fn set_ffmpeg_log(&self, path: Option<String>) {
+        *self.ffmpeg_log.lock() = path;
+    }
+
+    pub fn ffmpeg_log(&self) -> Option<String> {
+        self.ffmpeg_log.lock().clone()
+    }
+
+    /// 记录当前 ffmpeg 子进程的 pid，用于统计资源占用
+    pub fn set_ffmpeg_pid(&self, pid: Option<u32>) {
+        self.ffmpeg_pid.store(
+            pid.unwrap_or_default(),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    pub fn ffmpeg_pid(&self) -> Option<u32> {
+        match self.ffmpeg_pid.load(std::sync::atomic::Ordering::Relaxed) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// 直播流结束后确认房间是否仍在直播，查询失败时按断流处理
+    pub async fn is_still_live(&self) -> bool {
+        match self.client.get_live_room_info(self.room_id).await {
+            Ok(room_info) => room_info.live_status == LiveStatus::Live,
+            Err(e) => {
+                tracing::warn!(
+                    "确认直播状态失败，按断流处理 - 房间: {}, 错误: {}",
+                    self.room_id,
+                    e
+                );
+                true
+            }
+        }
+    }
+
+    /// 当前录制会话的事件时间线
+    pub fn timeline(&self) -> SessionTimeline {
+        self.timeline.lock().clone()
+    }
+
+    /// 已完成录像的故事板截图
+    pub fn storyboards(&self) -> HashMap<String, Vec<(Duration, PathBuf)>> {
+        self.storyboards.lock().clone()
+    }
+
+    /// 记录会话事件，会话结束时把完整时间线写入历史记录
+    fn record_timeline(&self, kind: SessionEventKind) {
+        if let SessionEventKind::Completed { file_path, .. } = &kind {
+            self.storyboards
+                .lock()
+                .insert(file_path.clone(), snapshots(Path::new(file_path)));
+        }
+
+        let mut timeline = self.timeline.lock();
+
+        if timeline.record(chrono::Local::now().timestamp_millis(), kind) {
+            let live_start = live_start_millis(&self.room_info.live_time);
+
+            if let Some(coverage) = timeline.coverage(live_start) {
+                tracing::info!(
+                    "录制覆盖率 - 房间: {}, {}, 直播时长: {}, 录制时长: {}",
+                    self.room_id,
+                    coverage.describe(),
+                    pretty_duration(coverage.live_ms as u64 / 1000),
+                    pretty_duration(coverage.recorded_ms as u64 / 1000)
+                );
+                for gap in coverage.describe_gaps() {
+                    tracing::info!("录制缺口 - 房间: {}, {}", self.room_id, gap);
+                }
+            }
+
+            history::append_record(&HistoryRecord::now(
+                self.room_id,
+                HistoryEvent::Session {
+                    title: self.room_info.title.clone(),
+                    up_name: self.user_info.uname.clone(),
+                    events: timeline.events().to_vec(),
+                    live_start,
+                },
+            ));
+        }
+    }
+
+    fn webhook_payload(&self, event: WebhookEvent) -> WebhookPayload {
+        WebhookPayload::new(
+            event,
+            self.room_id,
+            &self.user_info.uname,
+            &self.room_info.title,
+        )
+    }
+
+    /// 推送到全局与房间配置的 Webhook，并发布到 MQTT
+    fn dispatch_event(&self, cx: &mut AsyncApp, payload: WebhookPayload) {
+        let _ = cx.read_global(|state: &AppState, cx| {
+            mqtt::dispatch(state, &payload);
+            webhook::dispatch(state, cx, payload);
+        });
+    }
+
+    /// 错误描述，ffmpeg 会话会附带日志文件路径
+    fn describe_error(&self, error: &DownloaderError) -> String {
+        match self.ffmpeg_log() {
+            Some(path) => format!("{error}（ffmpeg 日志: {path}）"),
+            None => error.to_string(),
+        }
     }
 
     pub fn emit_downloader_event(&self, cx: &mut AsyncApp, event: DownloaderEvent) {
@@ -138,25 +391,58 @@ impl DownloaderContext {
         });
     }
 
-    /// 推送事件到队列
-    pub fn push_event(&self, event: DownloaderEvent) {
-        if let Some(mut queue) = self.event_queue.try_lock() {
-            queue.push_back(event);
+    /// 开始计划内轮换，当前分 P 的完成只结束该分 P，不结束录制会话
+    pub fn begin_rotation(&self) {
+        self.rotating
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        self.record_timeline(SessionEventKind::Rotating);
+    }
+
+    pub fn end_rotation(&self) {
+        self.rotating
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_rotating(&self) -> bool {
+        self.rotating.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// 立即处理尚未处理的事件，确保上一分 P 的完成事件不会被新分 P 的初始化清空
+    pub fn flush_events(&self, cx: &mut AsyncApp) {
+        for event in self.event_channel.drain() {
+            self.process_event(cx, event);
         }
     }
 
-    /// 处理队列中的所有事件，返回处理的事件数量
-    pub fn process_events(&self, cx: &mut AsyncApp) -> usize {
-        let mut processed = 0;
+    /// 推送事件，事件处理任务跟不上时会阻塞，只应在下载线程中调用
+    pub fn push_event(&self, event: DownloaderEvent) {
+        self.event_channel.send(event);
+    }
+
+    /// 在异步任务中推送事件，事件处理任务跟不上时让出执行而不是阻塞线程
+    pub async fn push_event_async(&self, event: DownloaderEvent) {
+        self.event_channel.send_async(event).await;
+    }
 
-        if let Some(mut queue) = self.event_queue.try_lock() {
-            while let Some(event) = queue.pop_front() {
-                self.handle_event(cx, event);
-                processed += 1;
-            }
+    /// 不阻塞地推送事件，channel 已满时丢弃并记录日志
+    ///
+    /// 前台代码、事件处理任务自身与看门狗都不能阻塞等待事件处理
+    pub fn try_push_event(&self, event: DownloaderEvent) {
+        if !self.event_channel.try_send(event) {
+            tracing::warn!("事件 channel 已满，丢弃事件 - 房间: {}", self.room_id);
         }
+    }
 
-        processed
+    /// 取出尚未处理的事件，供测试检查下载器的行为
+    #[cfg(test)]
+    pub(crate) fn take_events(&self) -> Vec<DownloaderEvent> {
+        self.event_channel.drain()
+    }
+
+    fn process_event(&self, cx: &mut AsyncApp, event: DownloaderEvent) {
+        profile_scope!("downloader.process_event");
+
+        self.handle_event(cx, event);
     }
 
     /// 处理单个事件
@@ -168,8 +454,20 @@ impl DownloaderContext {
         // 事件现在通过全局状态管理，这里只处理内部状态
         match &event {
             DownloaderEvent::Started { file_path } => {
+                // 上一个文件因出错没有完成事件时，先收尾它的附属文件
+                self.close_open_sidecars(cx);
+
                 // 确保运行状态为true
                 self.set_running(true);
+                repair::mark_unfinished(self.room_id, file_path);
+                relay::register(self.room_id, Path::new(file_path));
+
+                // 录制中写入的是临时文件，其余流程都按最终文件名处理
+                let file_path = final_path(file_path);
+
+                self.record_timeline(SessionEventKind::Started {
+                    file_path: file_path.to_owned(),
+                });
 
                 self.emit_downloader_event(
                     cx,
@@ -178,8 +476,42 @@ impl DownloaderContext {
                     },
                 );
 
+                self.dispatch_event(
+                    cx,
+                    self.webhook_payload(WebhookEvent::RecordStart)
+                        .file_path(file_path),
+                );
+
+                let archive = self.create_danmaku_archive(cx, file_path);
+                let sidecar = self.create_danmaku_sidecar(cx, file_path);
+                let storyboard_interval = cx
+                    .read_global(|state: &AppState, _| state.settings.storyboard_interval_minutes)
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .map(|minutes| Duration::from_secs(minutes as u64 * 60));
+
+                self.raw_dump.replace(self.create_raw_dump(cx, file_path));
+                self.connect_danmaku(cx);
+
                 // 更新全局状态
                 self.update_global_state(cx, |state, _| {
+                    let room_info = state.room_info.as_ref().unwrap_or(&self.room_info);
+                    state.chapters = Some(ChapterMarks::start(
+                        Path::new(file_path),
+                        &room_info.title,
+                        &room_info.area_name,
+                        Instant::now(),
+                    ));
+                    state.highlights = Some(HighlightTracker::new(
+                        chrono::Local::now().timestamp_millis(),
+                    ));
+                    state.storyboard = storyboard_interval.map(|interval| {
+                        Storyboard::start(Path::new(file_path), interval, Instant::now())
+                    });
+                    state.danmaku.clear();
+                    state.danmaku_archive = archive;
+                    state.danmaku_sidecar = sidecar;
+                    state.media_info = None;
                     state.status = RoomCardStatus::LiveRecording;
                     state.downloader_status = Some(DownloaderStatus::Started {
                         file_path: file_path.to_owned(),
@@ -196,6 +528,35 @@ impl DownloaderContext {
                     stats.download_speed_kbps = *download_speed_kbps;
                 });
 
+                // 稳定录制一段时间后清零重连计数，避免长时间挂机后偶发抖动直接耗尽重试次数
+                let reset_after = cx
+                    .read_global(|state: &AppState, _| state.settings.reconnect_reset_minutes)
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .map(|minutes| Duration::from_secs(minutes as u64 * 60));
+                let room_id = self.room_id;
+                let mut snapshot = None;
+                self.update_global_state(cx, |state, _| {
+                    if state.reconnect_manager.record_stable(reset_after) {
+                        tracing::info!("录制已稳定，重置重连计数 - 房间: {}", room_id);
+                    }
+
+                    if let Some(path) = state
+                        .storyboard
+                        .as_mut()
+                        .and_then(|storyboard| storyboard.due(Instant::now()))
+                    {
+                        snapshot = state
+                            .downloader
+                            .clone()
+                            .map(|downloader| (downloader, path));
+                    }
+                });
+
+                if let Some((downloader, path)) = snapshot {
+                    self.capture_snapshot(cx, downloader, path);
+                }
+
                 self.emit_downloader_event(
                     cx,
                     DownloaderEvent::Progress {
@@ -206,22 +567,48 @@ impl DownloaderContext {
                 );
             }
             DownloaderEvent::Error { error } => {
-                if error.is_recoverable() {
-                    self.push_event(DownloaderEvent::Reconnecting);
+                let cause = self.describe_error(error);
+                let reason = error.is_recoverable().then(|| error.reconnect_reason());
+
+                // 出错后文件不再增长，结束本地转发的连接
+                relay::unregister_room(self.room_id);
+
+                self.record_timeline(SessionEventKind::Error {
+                    message: cause.clone(),
+                    reason,
+                });
+
+                if let Some(reason) = reason {
+                    self.update_stats(|stats| stats.record_reconnect(reason));
+                    history::append_record(&HistoryRecord::now(
+                        self.room_id,
+                        HistoryEvent::Reconnect { reason },
+                    ));
+
+                    self.try_push_event(DownloaderEvent::Reconnecting);
+                } else {
+                    self.dispatch_event(
+                        cx,
+                        self.webhook_payload(WebhookEvent::RecordError)
+                            .message(&cause),
+                    );
+
+                    // 不再重连，本次录制到此结束
+                    self.close_open_sidecars(cx);
                 }
 
                 // 更新全局状态
                 self.update_global_state(cx, |state, _| {
-                    state.downloader_status = Some(DownloaderStatus::Error {
-                        cause: error.to_string(),
-                    });
+                    state.downloader_status = Some(DownloaderStatus::Error { cause });
                 });
             }
             DownloaderEvent::Reconnecting => {
+                self.record_timeline(SessionEventKind::Reconnecting);
                 self.emit_downloader_event(cx, DownloaderEvent::Reconnecting);
 
                 self.update_global_state(cx, |state, _| {
                     state.reconnecting = true;
+                    state.reconnect_manager.mark_unstable();
                 });
             }
             DownloaderEvent::Completed {
@@ -229,33 +616,384 @@ impl DownloaderContext {
                 file_path,
                 duration,
             } => {
+                repair::mark_finished(file_path);
+                relay::unregister(self.room_id, Path::new(file_path));
+
+                // 正常收尾后把临时文件原子重命名为最终文件，失败时保留临时文件
+                let file_path = &match part_file::finalize(file_path) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        tracing::warn!(
+                            "重命名录制文件失败 - 房间: {}, 文件: {}, 错误: {}",
+                            self.room_id,
+                            file_path,
+                            e
+                        );
+                        file_path.to_owned()
+                    }
+                };
+
                 // 更新完成统计
                 self.update_stats(|stats| {
                     stats.bytes_downloaded = *file_size;
                 });
 
+                // 高光时刻需在完成事件之前记录，才能随会话写入历史
+                let highlights = self.close_sidecars(cx, file_path);
+
+                self.record_timeline(SessionEventKind::Completed {
+                    file_path: file_path.to_owned(),
+                    file_size: *file_size,
+                    duration: *duration,
+                });
+
+                // 计划内轮换与断流重连只是切换分 P，卡片保持录制状态
+                let rotating = self.is_rotating() || self.timeline.lock().is_reconnect_pending();
+
+                // 下载完成，停止运行状态；需早于通知卡片，卡片据此释放录制锁
+                if !rotating {
+                    self.set_running(false);
+                }
+
+                if !rotating {
+                    self.emit_downloader_event(
+                        cx,
+                        DownloaderEvent::Completed {
+                            file_path: file_path.to_owned(),
+                            file_size: *file_size,
+                            duration: *duration,
+                        },
+                    );
+                }
+
+                if !rotating {
+                    self.update_global_state(cx, |state, _| {
+                        state.status = RoomCardStatus::WaitLiveStreaming;
+                        state.downloader_status = Some(DownloaderStatus::Completed {
+                            file_path: file_path.to_owned(),
+                            file_size: *file_size,
+                            duration: *duration,
+                        });
+                    });
+                }
+
+                self.verify_output(cx, file_path.to_owned(), highlights);
+            }
+            DownloaderEvent::OutputCorrupted { file_path, reason } => {
                 self.emit_downloader_event(
                     cx,
-                    DownloaderEvent::Completed {
+                    DownloaderEvent::OutputCorrupted {
                         file_path: file_path.to_owned(),
-                        file_size: *file_size,
-                        duration: *duration,
+                        reason: reason.to_owned(),
                     },
                 );
 
-                // 更新全局状态
                 self.update_global_state(cx, |state, _| {
-                    state.status = RoomCardStatus::WaitLiveStreaming;
-                    state.downloader_status = Some(DownloaderStatus::Completed {
+                    state.downloader_status = Some(DownloaderStatus::Corrupted {
                         file_path: file_path.to_owned(),
-                        file_size: *file_size,
-                        duration: *duration,
+                        reason: reason.to_owned(),
                     });
                 });
+            }
+            DownloaderEvent::RecordDirSwitched { from, to } => {
+                self.emit_downloader_event(
+                    cx,
+                    DownloaderEvent::RecordDirSwitched {
+                        from: from.to_owned(),
+                        to: to.to_owned(),
+                    },
+                );
+            }
+        }
+    }
 
-                // 下载完成，停止运行状态
-                self.set_running(false);
+    /// 收尾录制文件的附属文件：写出高光时刻与章节，结束截图，丢弃弹幕归档与字幕时写入结尾
+    ///
+    /// 可重复调用，已收尾的部分不会重复写入；返回高光时刻
+    fn close_sidecars(&self, cx: &mut AsyncApp, file_path: &str) -> Vec<Highlight> {
+        let highlights = self.finish_highlights(cx, file_path);
+
+        self.update_global_state(cx, |state, _| {
+            if let Some(chapters) = state.chapters.take() {
+                chapters.finish(Instant::now());
             }
+            state.storyboard = None;
+            state.danmaku_archive = None;
+            state.danmaku_sidecar = None;
+        });
+
+        highlights
+    }
+
+    /// 出错或停止后没有完成事件时，收尾仍在写入的文件的附属文件
+    fn close_open_sidecars(&self, cx: &mut AsyncApp) {
+        let file_path = self.timeline.lock().open_file().map(str::to_string);
+
+        if let Some(file_path) = file_path {
+            self.close_sidecars(cx, &file_path);
+        }
+    }
+
+    /// 根据录制期间的弹幕密度生成高光时刻，写入录制文件旁并记录到会话时间线
+    fn finish_highlights(&self, cx: &mut AsyncApp, file_path: &str) -> Vec<Highlight> {
+        let mut tracker = None;
+        self.update_global_state(cx, |state, _| {
+            tracker = state.highlights.take();
+        });
+
+        let highlights = tracker.map(HighlightTracker::finish).unwrap_or_default();
+        if highlights.is_empty() {
+            return highlights;
+        }
+
+        if let Err(e) = write_highlights(Path::new(file_path), &highlights) {
+            tracing::warn!("高光时刻写入失败: {} - {}", file_path, e);
+        }
+
+        self.record_timeline(SessionEventKind::Highlights {
+            file_path: file_path.to_owned(),
+            highlights: highlights.clone(),
+        });
+
+        highlights
+    }
+
+    /// 在后台从直播流截取一帧，失败只记录日志，下次到时间再试
+    fn capture_snapshot(&self, cx: &mut AsyncApp, downloader: Arc<BLiveDownloader>, path: PathBuf) {
+        let room_id = self.room_id;
+
+        cx.spawn(async move |cx| {
+            let result = match downloader.play_url().await {
+                Ok(url) => {
+                    cx.background_executor()
+                        .spawn({
+                            let path = path.clone();
+                            async move { capture_snapshot(&url, &path) }
+                        })
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                tracing::warn!(
+                    "录制截图失败 - 房间: {}, 文件: {}, 错误: {}",
+                    room_id,
+                    path.display(),
+                    e
+                );
+            }
+        })
+        .detach();
+    }
+
+    /// 录制完成后用 ffprobe 检查产物的时长与音视频轨，并发出录制完成的 Webhook
+    ///
+    /// LowCost 策略直接写出原始流，开启元数据写入时先重新封装一次；响度标准化同样在校验前完成，以免同时读写。
+    /// 仅保留音频的房间在提取完成后才发出 Webhook，携带的是音频文件路径
+    fn verify_output(&self, cx: &mut AsyncApp, file_path: String, highlights: Vec<Highlight>) {
+        let context = self.clone();
+        let room_id = self.room_id;
+        let (embed_metadata, loudnorm, blank_detect_minutes, audio_format) = cx
+            .read_global(|state: &AppState, _| {
+                (
+                    state.settings.embed_metadata,
+                    state.settings.loudnorm.clone(),
+                    state.settings.blank_detect_minutes,
+                    state
+                        .get_room_settings(room_id)
+                        .filter(|settings| settings.audio_only)
+                        .map(|settings| settings.audio_format.unwrap_or_default()),
+                )
+            })
+            .unwrap_or_default();
+        #[cfg(feature = "whisper")]
+        let whisper = cx
+            .read_global(|state: &AppState, _| state.settings.whisper.clone())
+            .unwrap_or_default();
+        let embed_metadata = embed_metadata && self.strategy == Strategy::LowCost;
+        if audio_format.is_none() {
+            self.dispatch_event(
+                cx,
+                self.webhook_payload(WebhookEvent::RecordComplete)
+                    .file_path(&file_path),
+            );
+        }
+        let mut metadata = RecordingMetadata::new(&self.room_info, &self.user_info);
+        metadata.description = highlights
+            .iter()
+            .map(Highlight::describe)
+            .collect::<Vec<_>>()
+            .join("；");
+
+        cx.spawn(async move |cx| {
+            let video_path = file_path.clone();
+            let mut path = file_path.clone();
+            let (file_path, audio_extracted, result) = cx
+                .background_executor()
+                .spawn(async move {
+                    if embed_metadata
+                        && let Err(e) = ensure_ffmpeg().and_then(|_| {
+                            repair::remux_file(Path::new(&path), &metadata.ffmpeg_args())
+                        })
+                    {
+                        tracing::warn!("写入元数据失败 - 文件: {}, 错误: {}", path, e);
+                    }
+
+                    if loudnorm.enabled
+                        && let Err(e) = ensure_ffmpeg()
+                            .and_then(|_| loudnorm::normalize_file(Path::new(&path), &loudnorm))
+                    {
+                        tracing::warn!("响度标准化失败 - 文件: {}, 错误: {}", path, e);
+                    }
+
+                    let mut audio_extracted = false;
+                    if let Some(format) = audio_format {
+                        match ensure_ffmpeg()
+                            .and_then(|_| audio::extract_file(Path::new(&path), format))
+                        {
+                            Ok(audio_path) => {
+                                path = audio_path.to_string_lossy().to_string();
+                                audio_extracted = true;
+                            }
+                            Err(e) => tracing::warn!("提取音频失败 - 文件: {}, 错误: {}", path, e),
+                        }
+                    }
+
+                    let result = ffprobe::probe(&path, &[]);
+                    (path, audio_extracted, result)
+                })
+                .await;
+
+            // 提取音频后原录制文件已删除，完成状态与历史记录改为指向音频文件
+            if audio_extracted {
+                let audio_path = file_path.clone();
+                context.update_global_state(cx, |state, _| {
+                    if let Some(DownloaderStatus::Completed { file_path, .. }) =
+                        &mut state.downloader_status
+                    {
+                        *file_path = audio_path;
+                    }
+                });
+                context.record_audio_extracted(video_path, file_path.clone());
+            }
+            if audio_format.is_some() {
+                context.dispatch_event(
+                    cx,
+                    context
+                        .webhook_payload(WebhookEvent::RecordComplete)
+                        .file_path(&file_path),
+                );
+            }
+
+            let reason = match result {
+                Ok(media_info) if audio_extracted => media_info.audio_recording_issues().join("、"),
+                Ok(media_info) => media_info.recording_issues().join("、"),
+                Err(e) => e.to_string(),
+            };
+            if reason.is_empty() {
+                #[cfg(feature = "whisper")]
+                crate::core::whisper::enqueue(cx, file_path.clone().into(), whisper);
+
+                // 黑屏检测依赖视频轨
+                if blank_detect_minutes > 0 && !audio_extracted {
+                    context
+                        .detect_blank(cx, file_path, blank_detect_minutes)
+                        .await;
+                }
+                return;
+            }
+
+            tracing::warn!(
+                "录制产物可能损坏 - 房间: {}, 文件: {}, 原因: {}",
+                context.room_id,
+                file_path,
+                reason
+            );
+            context.handle_event(cx, DownloaderEvent::OutputCorrupted { file_path, reason });
+        })
+        .detach();
+    }
+
+    /// 检测录制文件中的黑屏/静音段，结果写入录制文件旁并在时间线中提示
+    async fn detect_blank(&self, cx: &mut AsyncApp, file_path: String, minutes: u32) {
+        let path = file_path.clone();
+        let result = cx
+            .background_executor()
+            .spawn(async move {
+                ensure_ffmpeg()?;
+                blank::detect_blank(Path::new(&path), minutes as u64 * 60)
+            })
+            .await;
+
+        let segments = match result {
+            Ok(segments) if segments.is_empty() => return,
+            Ok(segments) => segments,
+            Err(e) => {
+                tracing::warn!("黑屏/静音检测失败 - 文件: {}, 错误: {}", file_path, e);
+                return;
+            }
+        };
+
+        tracing::warn!(
+            "录制文件疑似包含黑屏/静音段 - 房间: {}, 文件: {}, 段数: {}",
+            self.room_id,
+            file_path,
+            segments.len()
+        );
+
+        if let Err(e) = blank::write_blank_segments(Path::new(&file_path), &segments) {
+            tracing::warn!("黑屏/静音检测结果写入失败: {} - {}", file_path, e);
+        }
+
+        self.record_blank_segments(file_path, segments);
+    }
+
+    /// 检测在会话结束后才完成时，时间线已写入历史，需单独追加一条记录
+    fn record_blank_segments(&self, file_path: String, segments: Vec<BlankSegment>) {
+        let mut timeline = self.timeline.lock();
+        let finished = timeline.is_finished();
+
+        timeline.record(
+            chrono::Local::now().timestamp_millis(),
+            SessionEventKind::BlankSegments {
+                file_path: file_path.clone(),
+                segments: segments.clone(),
+            },
+        );
+
+        if finished {
+            history::append_record(&HistoryRecord::now(
+                self.room_id,
+                HistoryEvent::BlankSegments {
+                    file_path,
+                    segments,
+                },
+            ));
+        }
+    }
+
+    /// 提取音频通常在会话结束后才完成，此时时间线已写入历史，需单独追加一条记录
+    fn record_audio_extracted(&self, video_path: String, file_path: String) {
+        let mut timeline = self.timeline.lock();
+        let finished = timeline.is_finished();
+
+        timeline.record(
+            chrono::Local::now().timestamp_millis(),
+            SessionEventKind::AudioExtracted {
+                video_path: video_path.clone(),
+                file_path: file_path.clone(),
+            },
+        );
+
+        if finished {
+            history::append_record(&HistoryRecord::now(
+                self.room_id,
+                HistoryEvent::AudioExtracted {
+                    video_path,
+                    file_path,
+                },
+            ));
         }
     }
 
@@ -284,13 +1022,17 @@ impl DownloaderContext {
                 );
             }
             DownloaderEvent::Error { error } => {
+                let description = self.describe_error(error);
                 if error.is_recoverable() {
                     log_recording_error(
                         self.room_info.room_id,
-                        &format!("网络异常，正在重连: {error}"),
+                        &format!("网络异常，正在重连: {description}"),
                     );
                 } else {
-                    log_recording_error(self.room_info.room_id, &format!("录制失败: {error}"));
+                    log_recording_error(
+                        self.room_info.room_id,
+                        &format!("录制失败: {description}"),
+                    );
                 }
             }
             DownloaderEvent::Reconnecting => {
@@ -311,36 +1053,185 @@ impl DownloaderContext {
                     pretty_duration(*duration)
                 );
             }
+            DownloaderEvent::OutputCorrupted { .. } | DownloaderEvent::RecordDirSwitched { .. } => {
+                // 校验或切换目录时已记录
+            }
         }
     }
 
-    /// 启动事件处理任务
+    /// 启动事件处理任务，并由看门狗检测其是否卡死
     pub fn start_event_processor(&self, cx: &mut AsyncApp) {
         let context = self.clone();
+        let generation = self
+            .processor_generation
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+            + 1;
+        self.heartbeat();
 
         cx.spawn(async move |cx| {
             loop {
-                // 每 1s 处理一次事件队列
-                cx.background_executor().timer(Duration::from_secs(1)).await;
+                // 事件到达即处理，空闲时定期醒来刷新心跳
+                let next = match select(
+                    pin!(context.event_channel.recv()),
+                    pin!(cx.background_executor().timer(EVENT_IDLE_INTERVAL)),
+                )
+                .await
+                {
+                    Either::Left((event, _)) => Some(event),
+                    Either::Right(_) => None,
+                };
 
-                let processed = context.process_events(cx);
+                context.heartbeat();
+                match next {
+                    Some(Some(event)) => context.process_event(cx, event),
+                    Some(None) => break,
+                    // 没有待处理事件且不在运行状态，退出循环
+                    None => {
+                        if !context.is_running() && context.event_channel.is_empty() {
+                            break;
+                        }
+                    }
+                }
 
-                // 如果没有事件处理且不在运行状态，退出循环
-                if processed == 0 && !context.is_running() {
-                    break;
+                // 已启动新的事件处理任务
+                if !context.is_current_processor(generation) {
+                    return;
                 }
             }
 
             // 最后处理剩余的事件
-            context.process_events(cx);
+            for event in context.event_channel.drain() {
+                context.process_event(cx, event);
+            }
+
+            // 停止时下载任务已因出错退出，不会再有完成事件
+            if context.is_current_processor(generation) {
+                relay::unregister_room(context.room_id);
+                context.close_open_sidecars(cx);
+            }
+        })
+        .detach();
+
+        self.start_watchdog(generation);
+    }
+
+    fn heartbeat(&self) {
+        self.processor_heartbeat.store(
+            chrono::Local::now().timestamp_millis(),
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    fn is_current_processor(&self, generation: u64) -> bool {
+        self.processor_generation
+            .load(std::sync::atomic::Ordering::Relaxed)
+            == generation
+    }
+
+    /// 看门狗：在独立线程中检查事件处理任务的心跳
+    ///
+    /// 事件处理任务运行在前台线程，卡住时无法在前台自救，看门狗只负责及时记录并上报一条内部错误，
+    /// 事件处理恢复后错误随之显示
+    fn start_watchdog(&self, generation: u64) {
+        let context = self.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name(format!("blive-watchdog-{}", self.room_id))
+            .spawn(move || {
+                let mut detector = StallDetector::default();
+
+                loop {
+                    std::thread::sleep(WATCHDOG_INTERVAL);
+
+                    if !context.is_current_processor(generation) || !context.is_running() {
+                        break;
+                    }
+
+                    let heartbeat = context
+                        .processor_heartbeat
+                        .load(std::sync::atomic::Ordering::Relaxed);
+                    let now = chrono::Local::now().timestamp_millis();
+                    let Some(delay) = detector.check(heartbeat, now) else {
+                        continue;
+                    };
+
+                    tracing::error!(
+                        "事件处理任务无响应 - 房间: {}, 延迟: {}ms, 待处理事件: {}",
+                        context.room_id,
+                        delay.as_millis(),
+                        context.event_channel.len()
+                    );
+
+                    context.try_push_event(DownloaderEvent::Error {
+                        error: DownloaderError::InternalError {
+                            message: format!("事件处理任务 {}ms 无响应", delay.as_millis()),
+                        },
+                    });
+                }
+            });
+
+        if let Err(e) = spawned {
+            tracing::warn!("启动看门狗线程失败 - 房间: {}, 错误: {}", self.room_id, e);
+        }
+    }
+
+    /// 在后台用 ffprobe 探测直播流的实际分辨率、帧率、码率等信息
+    pub fn probe_stream(&self, cx: &mut AsyncApp, url: String) {
+        let context = self.clone();
+
+        cx.spawn(async move |cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let (input, headers) = ffmpeg_input(&url);
+                    let headers = headers
+                        .iter()
+                        .map(|(key, value)| (*key, value.as_str()))
+                        .collect::<Vec<_>>();
+                    ffprobe::probe(&input, &headers)
+                })
+                .await;
+
+            match result {
+                Ok(media_info) => {
+                    tracing::info!(
+                        "流信息 - 房间: {}, 分辨率: {}, 帧率: {:?}, 视频编码: {:?}, 音频编码: {:?}",
+                        context.room_id,
+                        media_info.resolution().unwrap_or_default(),
+                        media_info.frame_rate,
+                        media_info.video_codec,
+                        media_info.audio_codec,
+                    );
+
+                    context.update_global_state(cx, |state, cx| {
+                        state.media_info = Some(media_info);
+
+                        if let Some(entity) = state.entity.clone() {
+                            cx.notify(entity.entity_id());
+                        }
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "流信息探测失败 - 房间: {}, 错误: {}",
+                        context.room_id,
+                        redact_sensitive(&e.to_string())
+                    );
+                }
+            }
         })
         .detach();
     }
 
     /// 设置运行状态
     pub fn set_running(&self, running: bool) {
-        self.is_running
-            .store(running, std::sync::atomic::Ordering::Relaxed);
+        let was_running = self
+            .is_running
+            .swap(running, std::sync::atomic::Ordering::Relaxed);
+
+        if was_running != running {
+            crash::set_recording(running);
+        }
     }
 
     /// 检查是否正在运行
@@ -353,20 +1244,158 @@ impl DownloaderContext {
     where
         F: FnOnce(&mut DownloadStats),
     {
-        if let Some(mut stats) = self.stats.try_lock() {
-            updater(&mut stats);
-        }
+        updater(&mut self.stats.lock());
     }
 
     /// 获取统计信息
     pub fn get_stats(&self) -> DownloadStats {
-        self.stats
-            .try_lock()
-            .map(|guard| guard.clone())
-            .unwrap_or_else(|| {
-                eprintln!("无法获取统计信息锁");
-                DownloadStats::default()
+        self.stats.lock().clone()
+    }
+
+    /// 按弹幕设置在录制文件旁创建归档
+    fn create_danmaku_archive(
+        &self,
+        cx: &mut AsyncApp,
+        file_path: &str,
+    ) -> Option<Arc<Mutex<DanmakuArchive>>> {
+        let (archive, compress) = cx
+            .read_global(|state: &AppState, _| {
+                (
+                    state.settings.danmaku.archive,
+                    state.settings.danmaku.compress_archive,
+                )
             })
+            .ok()?;
+
+        if !archive {
+            return None;
+        }
+
+        let started_at = chrono::Local::now().timestamp_millis();
+        match DanmakuArchive::create(Path::new(file_path), compress, started_at) {
+            Ok(archive) => Some(Arc::new(Mutex::new(archive))),
+            Err(e) => {
+                tracing::warn!("无法创建弹幕归档 - 房间: {}, 错误: {}", self.room_id, e);
+                None
+            }
+        }
+    }
+
+    /// 房间开启录制弹幕时在录制文件旁创建弹幕文件
+    fn create_danmaku_sidecar(
+        &self,
+        cx: &mut AsyncApp,
+        file_path: &str,
+    ) -> Option<Arc<Mutex<DanmakuSidecar>>> {
+        let format = cx
+            .read_global(|state: &AppState, _| {
+                state
+                    .get_room_settings(self.room_id)
+                    .filter(|settings| settings.record_danmaku)
+                    .map(|_| match state.settings.danmaku.ass_sidecar {
+                        true => SidecarFormat::Ass,
+                        false => SidecarFormat::Xml,
+                    })
+            })
+            .ok()??;
+
+        let started_at = chrono::Local::now().timestamp_millis();
+        match DanmakuSidecar::create(Path::new(file_path), format, started_at) {
+            Ok(sidecar) => Some(Arc::new(Mutex::new(sidecar))),
+            Err(e) => {
+                tracing::warn!("无法创建弹幕文件 - 房间: {}, 错误: {}", self.room_id, e);
+                None
+            }
+        }
+    }
+
+    /// 开启原始数据包转储时在录制文件旁创建转储文件
+    fn create_raw_dump(&self, cx: &mut AsyncApp, file_path: &str) -> Option<RawDump> {
+        let enabled = cx
+            .read_global(|state: &AppState, _| state.settings.danmaku.raw_dump)
+            .ok()?;
+
+        if !enabled {
+            return None;
+        }
+
+        match RawDump::create(Path::new(file_path)) {
+            Ok(dump) => Some(dump),
+            Err(e) => {
+                tracing::warn!(
+                    "无法创建弹幕原始数据包转储 - 房间: {}, 错误: {}",
+                    self.room_id,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// 连接弹幕服务器，已有连接时沿用，分 P 切换与轮换不会重连
+    fn connect_danmaku(&self, cx: &mut AsyncApp) {
+        let connection = {
+            let mut danmaku = self.danmaku.lock();
+            if danmaku.is_some() {
+                return;
+            }
+
+            let connection = DanmakuConnection::default();
+            *danmaku = Some(connection.clone());
+            connection
+        };
+
+        let room_id = self.room_id;
+        let raw_dump = self.raw_dump.clone();
+        let client = self.client.clone();
+
+        cx.spawn(async move |cx| {
+            let info = match client.get_danmu_info(room_id).await {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    tracing::warn!(
+                        "获取弹幕服务器失败，匿名连接默认服务器 - 房间: {}, 错误: {}",
+                        room_id,
+                        e
+                    );
+                    None
+                }
+            };
+
+            // 获取服务器期间下载器可能已经停止
+            if connection.is_stopped() {
+                return;
+            }
+
+            let messages = match connection.run(room_id, auth::uid(), info, raw_dump) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    tracing::warn!("无法启动弹幕连接 - 房间: {}, 错误: {}", room_id, e);
+                    return;
+                }
+            };
+
+            while let Ok(message) = messages.recv_async().await {
+                let _ = cx.update_global(|state: &mut AppState, _| {
+                    if let Some(room_state) = state
+                        .room_states
+                        .iter_mut()
+                        .find(|room_state| room_state.room_id == room_id)
+                    {
+                        room_state.record_live_message(message, &state.danmaku_filter);
+                    }
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// 断开弹幕服务器连接
+    pub fn disconnect_danmaku(&self) {
+        if let Some(connection) = self.danmaku.lock().take() {
+            connection.stop();
+        }
+        self.raw_dump.replace(None);
     }
 
     /// 更新全局状态
@@ -381,3 +1410,97 @@ impl DownloaderContext {
         });
     }
 }
+
+/// 事件处理任务的卡死检测，同一次卡死只上报一次
+#[derive(Debug, Default)]
+struct StallDetector {
+    reported: bool,
+}
+
+impl StallDetector {
+    /// 根据最近一次心跳（毫秒时间戳）判断，新出现卡死时返回已卡住的时长
+    fn check(&mut self, heartbeat: i64, now: i64) -> Option<Duration> {
+        let delay = Duration::from_millis(now.saturating_sub(heartbeat).max(0) as u64);
+
+        if delay < EVENT_PROCESSOR_TIMEOUT {
+            self.reported = false;
+            return None;
+        }
+        if self.reported {
+            return None;
+        }
+
+        self.reported = true;
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_detector_reports_once_per_stall() {
+        let mut detector = StallDetector::default();
+        let timeout = EVENT_PROCESSOR_TIMEOUT.as_millis() as i64;
+
+        assert_eq!(detector.check(0, 1_000), None);
+        assert_eq!(
+            detector.check(0, timeout),
+            Some(Duration::from_millis(timeout as u64))
+        );
+        // 仍在卡住，不重复上报
+        assert_eq!(detector.check(0, timeout * 2), None);
+
+        // 恢复心跳后再次卡住会重新上报
+        assert_eq!(detector.check(timeout * 2, timeout * 2 + 1_000), None);
+        assert!(detector.check(timeout * 2, timeout * 4).is_some());
+    }
+
+    #[test]
+    fn test_event_channel_concurrent_push_and_drain() {
+        const PRODUCERS: usize = 8;
+        const EVENTS_PER_PRODUCER: usize = 10_000;
+
+        let queue = EventChannel::default();
+        let producers = (0..PRODUCERS)
+            .map(|_| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..EVENTS_PER_PRODUCER {
+                        queue.send(DownloaderEvent::Reconnecting);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // 生产者写入的同时持续取出，channel 满时生产者等待，任何一方都不应 panic 或丢事件
+        let mut drained = 0;
+        while producers.iter().any(|producer| !producer.is_finished()) {
+            drained += queue.drain().len();
+        }
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        drained += queue.drain().len();
+
+        assert_eq!(drained, PRODUCERS * EVENTS_PER_PRODUCER);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_event_channel_bounded() {
+        let channel = EventChannel::default();
+
+        for _ in 0..EVENT_CHANNEL_CAPACITY {
+            assert!(channel.try_send(DownloaderEvent::Reconnecting));
+        }
+        assert!(!channel.try_send(DownloaderEvent::Reconnecting));
+
+        assert!(matches!(
+            futures::executor::block_on(channel.recv()),
+            Some(DownloaderEvent::Reconnecting)
+        ));
+        assert_eq!(channel.len(), EVENT_CHANNEL_CAPACITY - 1);
+    }
+}
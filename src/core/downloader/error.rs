@@ -1,3 +1,35 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// 触发重连的原因分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconnectReason {
+    /// 流地址过期或鉴权失效（403/404 等）
+    StreamExpired,
+    /// CDN 节点故障（5xx、连接被重置、数据异常）
+    CdnFailure,
+    /// 本地网络问题（超时、DNS、网络不可达）
+    LocalNetwork,
+    /// ffmpeg 崩溃或启动失败
+    FfmpegCrash,
+    /// 其他原因
+    Other,
+}
+
+impl fmt::Display for ReconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReconnectReason::StreamExpired => write!(f, "流地址过期"),
+            ReconnectReason::CdnFailure => write!(f, "CDN 故障"),
+            ReconnectReason::LocalNetwork => write!(f, "本地网络"),
+            ReconnectReason::FfmpegCrash => write!(f, "ffmpeg 崩溃"),
+            ReconnectReason::Other => write!(f, "其他"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
 pub enum DownloaderError {
     // 没有找到合适的直播流协议
@@ -31,6 +63,10 @@ pub enum DownloaderError {
     #[error("文件写入失败: {path} - {reason}")]
     FileWriteFailed { path: String, reason: String },
 
+    // 应用内部错误，如事件处理任务卡死
+    #[error("内部错误: {message}")]
+    InternalError { message: String },
+
     // 配置相关错误
     #[error("无效的录制配置: {field} = {value} ({reason})")]
     InvalidRecordingConfig {
@@ -48,6 +84,12 @@ impl DownloaderError {
             | DownloaderError::NoSuitableVideoFormat
             | DownloaderError::NoSuitableVideoCodec => true,
             DownloaderError::StartupFailed { .. } => true,
+            // 磁盘写满或无权限时重连也无法恢复
+            DownloaderError::FileCreationFailed { .. }
+            | DownloaderError::FileWriteFailed { .. }
+            | DownloaderError::InvalidRecordingConfig { .. } => false,
+            // 内部错误仅用于上报，不触发重连
+            DownloaderError::InternalError { .. } => false,
             _ => true,
         }
     }
@@ -59,4 +101,88 @@ impl DownloaderError {
             _ => true,
         }
     }
+
+    /// 将错误归类为重连原因
+    pub fn reconnect_reason(&self) -> ReconnectReason {
+        match self {
+            DownloaderError::NetworkConnectionFailed { message } => {
+                classify_network_message(message)
+            }
+            DownloaderError::FfmpegFatalError { .. } | DownloaderError::StartupFailed { .. } => {
+                ReconnectReason::FfmpegCrash
+            }
+            // ffmpeg 读到无法识别的数据，通常是节点返回了异常内容
+            DownloaderError::NoSuitableStreamProtocol => ReconnectReason::CdnFailure,
+            _ => ReconnectReason::Other,
+        }
+    }
+}
+
+fn classify_network_message(message: &str) -> ReconnectReason {
+    let message = message.to_lowercase();
+    let contains_any = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+
+    if contains_any(&["403", "404", "410", "forbidden", "not found", "expired"]) {
+        ReconnectReason::StreamExpired
+    } else if contains_any(&[
+        "500",
+        "502",
+        "503",
+        "504",
+        "5xx",
+        "bad gateway",
+        "service unavailable",
+        "connection reset",
+        "invalid data",
+    ]) {
+        ReconnectReason::CdnFailure
+    } else {
+        ReconnectReason::LocalNetwork
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(message: &str) -> DownloaderError {
+        DownloaderError::NetworkConnectionFailed {
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reconnect_reason() {
+        assert_eq!(
+            network("HTTP请求失败: 403 Forbidden").reconnect_reason(),
+            ReconnectReason::StreamExpired
+        );
+        assert_eq!(
+            network("Server returned 5XX Server Error reply").reconnect_reason(),
+            ReconnectReason::CdnFailure
+        );
+        assert_eq!(
+            network("Connection reset by peer").reconnect_reason(),
+            ReconnectReason::CdnFailure
+        );
+        assert_eq!(
+            network("Connection timed out").reconnect_reason(),
+            ReconnectReason::LocalNetwork
+        );
+        assert_eq!(
+            DownloaderError::FfmpegFatalError {
+                message: "Conversion failed!".to_string()
+            }
+            .reconnect_reason(),
+            ReconnectReason::FfmpegCrash
+        );
+        assert_eq!(
+            DownloaderError::FileWriteFailed {
+                path: "a.flv".to_string(),
+                reason: "disk full".to_string()
+            }
+            .reconnect_reason(),
+            ReconnectReason::Other
+        );
+    }
 }
@@ -0,0 +1,57 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{LineWriter, Write},
+};
+
+use chrono::Local;
+
+use crate::{core::downloader::part_file::final_path, logger::redact_sensitive};
+
+/// 录制会话对应的 ffmpeg 日志路径，录制中的临时文件按最终文件名命名
+pub fn ffmpeg_log_path(output_path: &str) -> String {
+    format!("{}.ffmpeg.log", final_path(output_path))
+}
+
+/// 把 ffmpeg 的完整输出按行写入 `{output}.ffmpeg.log`
+pub struct FfmpegSessionLog {
+    path: String,
+    writer: Option<LineWriter<File>>,
+}
+
+impl FfmpegSessionLog {
+    pub fn create(output_path: &str) -> Self {
+        let path = ffmpeg_log_path(output_path);
+        let writer = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => Some(LineWriter::new(file)),
+            Err(e) => {
+                tracing::warn!("无法创建 ffmpeg 日志文件: {}, 错误: {}", path, e);
+                None
+            }
+        };
+
+        Self { path, writer }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 写入一行日志，写入失败后不再重试
+    pub fn write_line(&mut self, level: &str, message: &str) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+
+        let line = format!(
+            "[{}] [{}] {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            level,
+            redact_sensitive(message.trim_end())
+        );
+
+        if let Err(e) = writeln!(writer, "{line}") {
+            tracing::warn!("写入 ffmpeg 日志失败: {}, 错误: {}", self.path, e);
+            self.writer = None;
+        }
+    }
+}
@@ -1,9 +1,11 @@
+use crate::core::disk::extended_path;
 use crate::core::downloader::{
-    DownloadConfig, Downloader, DownloaderContext, DownloaderError, REFERER, USER_AGENT,
-    context::DownloaderEvent,
+    DownloadConfig, Downloader, DownloaderContext, DownloaderError,
+    classify::classify_ffmpeg_error, context::DownloaderEvent, ffmpeg_headers, ffmpeg_input,
+    ffmpeg_log::FfmpegSessionLog, part_file::ffmpeg_format, transcode::ffmpeg_codec_args,
 };
-use crate::settings::StreamCodec;
-use anyhow::Result;
+use crate::core::scheduler::{TaskPriority, scheduler};
+use anyhow::{Context, Result};
 use futures::channel::oneshot;
 use gpui::AsyncApp;
 use std::{
@@ -44,21 +46,18 @@ impl HttpHlsDownloader {
             cmd.no_overwrite();
         }
 
-        cmd.args(["-headers", format!("User-Agent: {USER_AGENT}").as_str()])
-            .args(["-headers", format!("Referer: {REFERER}").as_str()])
+        let (input, headers) = ffmpeg_input(url);
+        cmd.args(["-headers", ffmpeg_headers(&headers).as_str()])
             .arg("-i")
-            .arg(url)
-            .args(["-vf", "scale=1920:1080"])
-            .args(["-c:a", "aac"])
-            .args(["-bsf:a", "aac_adtstoasc"])
-            .arg("-c:v")
-            .arg(match config.codec {
-                StreamCodec::AVC => "libx264",
-                StreamCodec::HEVC => "hevc",
-            })
-            .arg(config.output_path.clone());
+            .arg(input)
+            .args(ffmpeg_codec_args(config.codec, &config.transcode))
+            .args(config.metadata.ffmpeg_args());
+        if let Some(format) = ffmpeg_format(&config.output_path) {
+            cmd.args(["-f", format]);
+        }
+        cmd.arg(extended_path(&config.output_path));
 
-        let process = cmd.spawn().unwrap();
+        let process = cmd.spawn().context("无法启动FFmpeg进程")?;
 
         Ok(process)
     }
@@ -83,7 +82,7 @@ impl Downloader for HttpHlsDownloader {
         let output_path = config.output_path.clone();
 
         // 发送开始事件
-        self.context.push_event(DownloaderEvent::Started {
+        self.context.try_push_event(DownloaderEvent::Started {
             file_path: output_path.clone(),
         });
 
@@ -98,9 +97,16 @@ impl Downloader for HttpHlsDownloader {
         #[cfg(feature = "ffmpeg")]
         cx.background_executor()
             .spawn(async move {
+                let _permit = scheduler().acquire(TaskPriority::RecordingIo).await;
+
+                let mut session_log = FfmpegSessionLog::create(&output_path);
+                context.set_ffmpeg_log(Some(session_log.path().to_string()));
+                session_log.write_line("info", &format!("输入: {url}"));
+
                 let mut process = match Self::download_stream(&url, &config) {
                     Ok(p) => p,
                     Err(e) => {
+                        session_log.write_line("error", &format!("ffmpeg 启动失败: {e}"));
                         context.push_event(DownloaderEvent::Error {
                             error: DownloaderError::StartupFailed {
                                 command: format!("ffmpeg -i {url}"),
@@ -110,18 +116,24 @@ impl Downloader for HttpHlsDownloader {
                         return;
                     }
                 };
+                context.set_ffmpeg_pid(Some(process.as_inner().id()));
 
                 match process.iter() {
                     Ok(iter) => {
                         for event in iter {
                             // 检查是否收到停止信号
                             if !is_running.load(std::sync::atomic::Ordering::Relaxed) {
-                                process.quit().unwrap();
+                                // ffmpeg 可能已自行退出，quit 失败时直接结束进程
+                                if let Err(e) = process.quit() {
+                                    tracing::warn!("无法通知 ffmpeg 退出: {}", e);
+                                    let _ = process.kill();
+                                }
                                 if let Err(e) = process.wait() {
-                                    eprintln!("FFmpeg进程wait失败: {e}");
+                                    tracing::warn!("等待 ffmpeg 进程退出失败: {}", e);
                                 } else {
-                                    println!("FFmpeg进程已成功清理");
+                                    tracing::debug!("ffmpeg 进程已成功清理");
                                 }
+                                context.set_ffmpeg_pid(None);
                                 context.push_event(DownloaderEvent::Completed {
                                     file_path: output_path.clone(),
                                     file_size: bytes_downloaded,
@@ -154,7 +166,13 @@ impl Downloader for HttpHlsDownloader {
                                         duration: start_time.elapsed().as_secs_f64() as u64,
                                     });
                                 }
+                                ffmpeg_sidecar::event::FfmpegEvent::Error(message) => {
+                                    session_log.write_line("error", &message);
+                                }
                                 ffmpeg_sidecar::event::FfmpegEvent::Log(level, message) => {
+                                    session_log
+                                        .write_line(&format!("{level:?}").to_lowercase(), &message);
+
                                     match level {
                                         ffmpeg_sidecar::event::LogLevel::Fatal => {
                                             context.push_event(DownloaderEvent::Error {
@@ -164,25 +182,9 @@ impl Downloader for HttpHlsDownloader {
                                             });
                                         }
                                         ffmpeg_sidecar::event::LogLevel::Error => {
-                                            // 根据错误消息智能分类
-                                            if message.contains("Connection reset")
-                                                || message.contains("timeout")
-                                                || message.contains("No route to host")
-                                                || message.contains("Connection refused")
-                                            {
+                                            if let Some(class) = classify_ffmpeg_error(&message) {
                                                 context.push_event(DownloaderEvent::Error {
-                                                    error:
-                                                        DownloaderError::NetworkConnectionFailed {
-                                                            message,
-                                                        },
-                                                });
-                                            } else if message.contains("Protocol not found")
-                                                || message.contains("Invalid data found")
-                                                || message.contains("Decoder failed")
-                                            {
-                                                context.push_event(DownloaderEvent::Error {
-                                                    error:
-                                                        DownloaderError::NoSuitableStreamProtocol,
+                                                    error: class.into_error(message, &output_path),
                                                 });
                                             }
                                         }
@@ -204,6 +206,8 @@ impl Downloader for HttpHlsDownloader {
                         });
                     }
                 }
+
+                context.set_ffmpeg_pid(None);
             })
             .detach();
 
@@ -211,7 +215,7 @@ impl Downloader for HttpHlsDownloader {
     }
 
     async fn stop(&mut self) -> Result<()> {
-        self.set_running(true);
+        self.set_running(false);
 
         if let Some(stop_rx) = self.stop_rx.take() {
             match stop_rx.await {
@@ -229,3 +233,175 @@ impl Downloader for HttpHlsDownloader {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "ffmpeg"))]
+mod tests {
+    use std::process::Command;
+
+    use gpui::TestAppContext;
+
+    use super::*;
+    use crate::{
+        core::{
+            downloader::mock_server::{
+                MockResponse, MockServer, output_path, run_until, test_context, wait_for_event,
+            },
+            repair::ffmpeg_path,
+        },
+        settings::Strategy,
+    };
+
+    /// 测试依赖系统中的 ffmpeg，缺失时跳过
+    fn ffmpeg_installed() -> bool {
+        let installed = Command::new(ffmpeg_path())
+            .arg("-version")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !installed {
+            eprintln!("未找到 ffmpeg，跳过 HLS 测试");
+        }
+
+        installed
+    }
+
+    /// 用 ffmpeg 生成一段 2 秒的 TS 分片
+    fn test_segment() -> Vec<u8> {
+        let output = Command::new(ffmpeg_path())
+            .args(["-v", "error", "-f", "lavfi", "-i"])
+            .arg("testsrc=duration=2:size=320x240:rate=25")
+            .args(["-f", "lavfi", "-i", "sine=duration=2"])
+            .args(["-c:v", "libx264", "-c:a", "aac", "-f", "mpegts", "pipe:1"])
+            .output()
+            .expect("无法启动 ffmpeg");
+        assert!(output.status.success(), "生成测试分片失败");
+
+        output.stdout
+    }
+
+    fn playlist(segments: usize, ended: bool) -> MockResponse {
+        let mut body = String::from(
+            "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:2\n#EXT-X-MEDIA-SEQUENCE:0\n",
+        );
+        for index in 0..segments {
+            body.push_str(&format!("#EXTINF:2.0,\n/seg{index}.ts\n"));
+        }
+        if ended {
+            body.push_str("#EXT-X-ENDLIST\n");
+        }
+
+        MockResponse::Body {
+            content_type: "application/vnd.apple.mpegurl",
+            body: body.into_bytes(),
+        }
+    }
+
+    fn serve_segments(server: &MockServer, segments: usize) {
+        let segment = test_segment();
+        for index in 0..segments {
+            server.route(
+                &format!("/seg{index}.ts"),
+                MockResponse::Body {
+                    content_type: "video/mp2t",
+                    body: segment.clone(),
+                },
+            );
+        }
+    }
+
+    fn hls_downloader(url: String, name: &str) -> (HttpHlsDownloader, DownloaderContext) {
+        let context = test_context(Strategy::PriorityConfig);
+        let config = DownloadConfig {
+            output_path: output_path(name).to_string_lossy().to_string(),
+            strategy: Strategy::PriorityConfig,
+            ..Default::default()
+        };
+
+        (
+            HttpHlsDownloader::new(url, config, context.clone()),
+            context,
+        )
+    }
+
+    #[gpui::test]
+    fn test_hls_completed(cx: &mut TestAppContext) {
+        if !ffmpeg_installed() {
+            return;
+        }
+        cx.executor().allow_parking();
+        let server = MockServer::start();
+        server.route("/live.m3u8", playlist(2, true));
+        serve_segments(&server, 2);
+
+        let (mut downloader, context) = hls_downloader(server.url("/live.m3u8"), "hls.mp4");
+        downloader.start(&mut cx.to_async()).unwrap();
+
+        let events = wait_for_event(cx, &context, |event| {
+            matches!(event, DownloaderEvent::Completed { .. })
+        });
+
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, DownloaderEvent::Error { .. }))
+        );
+        assert_eq!(server.hits("/seg1.ts"), 1);
+
+        let Some(DownloaderEvent::Completed { file_path, .. }) = events.last() else {
+            unreachable!();
+        };
+        assert!(std::fs::metadata(file_path).unwrap().len() > 0);
+        let _ = std::fs::remove_file(file_path);
+    }
+
+    #[gpui::test]
+    fn test_hls_forbidden(cx: &mut TestAppContext) {
+        if !ffmpeg_installed() {
+            return;
+        }
+        cx.executor().allow_parking();
+        let server = MockServer::start();
+        server.route("/live.m3u8", MockResponse::Status(403));
+
+        let (mut downloader, context) = hls_downloader(server.url("/live.m3u8"), "forbidden.mp4");
+        downloader.start(&mut cx.to_async()).unwrap();
+
+        let events = wait_for_event(cx, &context, |event| {
+            matches!(event, DownloaderEvent::Error { .. })
+        });
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DownloaderEvent::Error {
+                error: DownloaderError::NetworkConnectionFailed { .. }
+            }
+        )));
+    }
+
+    #[gpui::test]
+    fn test_hls_stop(cx: &mut TestAppContext) {
+        if !ffmpeg_installed() {
+            return;
+        }
+        cx.executor().allow_parking();
+        let server = MockServer::start();
+        // 没有 ENDLIST 的直播列表，ffmpeg 会一直刷新
+        server.route("/live.m3u8", playlist(2, false));
+        serve_segments(&server, 2);
+
+        let (mut downloader, context) = hls_downloader(server.url("/live.m3u8"), "stop.mp4");
+        downloader.start(&mut cx.to_async()).unwrap();
+
+        run_until(cx, downloader.stop()).unwrap();
+        assert!(!downloader.is_running());
+
+        let events = context.take_events();
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, DownloaderEvent::Completed { .. })),
+            "停止后应当发送完成事件，实际: {events:?}"
+        );
+
+        let _ = std::fs::remove_file(output_path("stop.mp4"));
+    }
+}
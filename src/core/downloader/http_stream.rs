@@ -1,8 +1,18 @@
+use crate::core::disk::extended_path;
 use crate::core::downloader::{
     DownloadConfig, Downloader, DownloaderContext, DownloaderError, REFERER, USER_AGENT,
+    classify::classify_ffmpeg_error,
     context::DownloaderEvent,
+    ffmpeg_headers, ffmpeg_input,
+    ffmpeg_log::FfmpegSessionLog,
+    part_file::ffmpeg_format,
+    stats::{BufferStats, PROGRESS_REPORT_INTERVAL, ProgressAggregator},
+    transcode::ffmpeg_codec_args,
+    writer::FileWriter,
 };
-use crate::settings::{Strategy, StreamCodec};
+use crate::core::scheduler::{TaskPriority, scheduler};
+use crate::profile_span;
+use crate::settings::Strategy;
 use anyhow::{Context, Result};
 use futures::{AsyncReadExt, channel::oneshot};
 use gpui::{
@@ -10,10 +20,22 @@ use gpui::{
     http_client::{AsyncBody, Method, Request},
 };
 use std::{
-    io::Write,
     sync::{Arc, atomic::AtomicBool},
     time::Instant,
 };
+use tracing::Instrument;
+
+/// 直播流读取结束的原因
+enum ReadOutcome {
+    /// 服务端关闭了连接
+    Eof,
+    /// 收到停止信号
+    Stopped,
+    /// 读取出错
+    Failed(String),
+    /// 写盘线程已出错退出
+    WriteFailed,
+}
 
 #[derive(Debug)]
 pub struct HttpStreamDownloader {
@@ -48,19 +70,16 @@ impl HttpStreamDownloader {
             cmd.no_overwrite();
         }
 
-        cmd.args(["-headers", format!("User-Agent: {USER_AGENT}").as_str()])
-            .args(["-headers", format!("Referer: {REFERER}").as_str()])
+        let (input, headers) = ffmpeg_input(url);
+        cmd.args(["-headers", ffmpeg_headers(&headers).as_str()])
             .arg("-i")
-            .arg(url)
-            .args(["-vf", "scale=1920:1080"])
-            .args(["-c:a", "aac"])
-            .args(["-bsf:a", "aac_adtstoasc"])
-            .arg("-c:v")
-            .arg(match config.codec {
-                StreamCodec::AVC => "libx264",
-                StreamCodec::HEVC => "hevc",
-            })
-            .arg(config.output_path.clone());
+            .arg(input)
+            .args(ffmpeg_codec_args(config.codec, &config.transcode))
+            .args(config.metadata.ffmpeg_args());
+        if let Some(format) = ffmpeg_format(&config.output_path) {
+            cmd.args(["-f", format]);
+        }
+        cmd.arg(extended_path(&config.output_path));
 
         let process = cmd.spawn().context("无法启动FFmpeg进程")?;
 
@@ -89,7 +108,7 @@ impl Downloader for HttpStreamDownloader {
         let output_path = config.output_path.clone();
 
         // 发送开始事件
-        self.context.push_event(DownloaderEvent::Started {
+        self.context.try_push_event(DownloaderEvent::Started {
             file_path: output_path.clone(),
         });
 
@@ -104,13 +123,25 @@ impl Downloader for HttpStreamDownloader {
             Strategy::LowCost => {
                 cx.background_executor()
                     .spawn(async move {
-                        let request = Request::builder()
+                        // 录制 IO 优先级最高，只登记不排队
+                        let _permit = scheduler().acquire(TaskPriority::RecordingIo).await;
+
+                        let request = match Request::builder()
                             .uri(url)
                             .header("User-Agent", USER_AGENT)
                             .header("Referer", REFERER)
                             .method(Method::GET)
                             .body(AsyncBody::empty())
-                            .unwrap();
+                        {
+                            Ok(request) => request,
+                            Err(e) => {
+                                return context.push_event(DownloaderEvent::Error {
+                                    error: DownloaderError::NetworkConnectionFailed {
+                                        message: format!("无效的直播流请求: {e}"),
+                                    },
+                                });
+                            }
+                        };
 
                         match context.client.send(request).await {
                             Ok(mut response) => {
@@ -123,91 +154,113 @@ impl Downloader for HttpStreamDownloader {
                                 }
 
                                 let body = response.body_mut();
-                                let mut buffer = [0; 8192];
-                                let mut bytes_downloaded = 0u64;
-                                let mut download_speed_kbps = 0f32;
-                                let mut last_report_time = Instant::now();
-                                let mut last_report_bytes = 0u64;
-
-                                match std::fs::File::create(&config.output_path) {
-                                    Ok(mut file) => {
-                                        while let Ok(bytes_read) = body.read(&mut buffer).await {
-                                            if bytes_read == 0 {
-                                                context.push_event(DownloaderEvent::Completed {
-                                                    file_path: output_path.clone(),
-                                                    file_size: bytes_downloaded,
-                                                    duration: start_time.elapsed().as_secs_f64()
-                                                        as u64,
-                                                });
-                                                let _ = stop_tx.send(());
-                                                break; // EOF
-                                            }
+                                let mut buffer = vec![0; config.read_buffer_size];
+                                let mut buffer_stats = BufferStats::new(buffer.len());
+                                let mut progress = ProgressAggregator::new(
+                                    PROGRESS_REPORT_INTERVAL,
+                                    Instant::now(),
+                                );
 
-                                            match file.write_all(&buffer[..bytes_read]) {
-                                                Ok(_) => {
-                                                    bytes_downloaded += bytes_read as u64;
-                                                    let duration_ms =
-                                                        start_time.elapsed().as_millis() as u64;
-
-                                                    // 计算下载速度（KBps）
-                                                    let now = Instant::now();
-                                                    let elapsed = now
-                                                        .duration_since(last_report_time)
-                                                        .as_secs_f64();
-                                                    if elapsed > 1.0 {
-                                                        let bytes_delta =
-                                                            bytes_downloaded - last_report_bytes;
-                                                        download_speed_kbps = ((bytes_delta as f64)
-                                                            / 1024.0
-                                                            / elapsed)
-                                                            as f32;
-                                                        last_report_time = now;
-                                                        last_report_bytes = bytes_downloaded;
-                                                    }
-
-                                                    if elapsed > 1.0 {
-                                                        context.push_event(
-                                                            DownloaderEvent::Progress {
-                                                                bytes_downloaded,
-                                                                download_speed_kbps,
-                                                                duration_ms,
-                                                            },
-                                                        );
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    context.push_event(DownloaderEvent::Error {
-                                                        error: DownloaderError::FileWriteFailed {
-                                                            path: config.output_path.clone(),
-                                                            reason: e.to_string(),
-                                                        },
-                                                    });
-                                                }
-                                            }
+                                let writer =
+                                    match std::fs::File::create(extended_path(&config.output_path))
+                                        .and_then(FileWriter::spawn)
+                                    {
+                                        Ok(writer) => writer,
+                                        Err(e) => {
+                                            return context.push_event(DownloaderEvent::Error {
+                                                error: DownloaderError::FileCreationFailed {
+                                                    path: config.output_path,
+                                                    reason: e.to_string(),
+                                                },
+                                            });
+                                        }
+                                    };
 
-                                            if !is_running
-                                                .load(std::sync::atomic::Ordering::Relaxed)
-                                            {
-                                                context.push_event(DownloaderEvent::Completed {
-                                                    file_path: output_path.clone(),
-                                                    file_size: bytes_downloaded,
-                                                    duration: start_time.elapsed().as_secs_f64()
-                                                        as u64,
-                                                });
-                                                let _ = stop_tx.send(());
-                                                break;
-                                            }
+                                let outcome = loop {
+                                    let bytes_read = match body
+                                        .read(&mut buffer)
+                                        .instrument(profile_span!("downloader.read"))
+                                        .await
+                                    {
+                                        Ok(bytes_read) => bytes_read,
+                                        Err(e) => {
+                                            break ReadOutcome::Failed(format!(
+                                                "读取直播流失败: {e}"
+                                            ));
                                         }
+                                    };
+
+                                    if bytes_read == 0 {
+                                        break ReadOutcome::Eof;
                                     }
+                                    buffer_stats.record(bytes_read);
+
+                                    // 写盘在专用线程上进行，队列满时在此等待
+                                    if !writer
+                                        .write(buffer[..bytes_read].to_vec())
+                                        .instrument(profile_span!("downloader.write"))
+                                        .await
+                                    {
+                                        break ReadOutcome::WriteFailed;
+                                    }
+
+                                    // 按时间聚合进度，避免每次读取都产生事件
+                                    if let Some((bytes_downloaded, download_speed_kbps)) =
+                                        progress.record(bytes_read as u64, Instant::now())
+                                    {
+                                        context.update_stats(|stats| stats.buffer = buffer_stats);
+                                        context.push_event(DownloaderEvent::Progress {
+                                            bytes_downloaded,
+                                            download_speed_kbps,
+                                            duration_ms: start_time.elapsed().as_millis() as u64,
+                                        });
+                                    }
+
+                                    if !is_running.load(std::sync::atomic::Ordering::Relaxed) {
+                                        break ReadOutcome::Stopped;
+                                    }
+                                };
+
+                                // 等待队列中的数据落盘后再发送结束事件
+                                let file_size = match writer.finish().await {
+                                    Ok(file_size) => file_size,
                                     Err(e) => {
                                         context.push_event(DownloaderEvent::Error {
-                                            error: DownloaderError::FileCreationFailed {
-                                                path: config.output_path,
+                                            error: DownloaderError::FileWriteFailed {
+                                                path: config.output_path.clone(),
                                                 reason: e.to_string(),
                                             },
                                         });
+                                        let _ = stop_tx.send(());
+                                        return;
                                     }
+                                };
+
+                                let completed = DownloaderEvent::Completed {
+                                    file_path: output_path.clone(),
+                                    file_size,
+                                    duration: start_time.elapsed().as_secs_f64() as u64,
+                                };
+
+                                let failure = match outcome {
+                                    ReadOutcome::Failed(message) => Some(message),
+                                    // 网络断开也可能表现为 EOF，需确认是否真的下播
+                                    ReadOutcome::Eof if context.is_still_live().await => {
+                                        Some("直播流意外结束，直播仍在进行".to_string())
+                                    }
+                                    ReadOutcome::Eof
+                                    | ReadOutcome::Stopped
+                                    | ReadOutcome::WriteFailed => None,
+                                };
+
+                                // 断流先报错以便重连，已写完的分 P 仍需完成事件收尾临时文件
+                                if let Some(message) = failure {
+                                    context.push_event(DownloaderEvent::Error {
+                                        error: DownloaderError::NetworkConnectionFailed { message },
+                                    });
                                 }
+                                context.push_event(completed);
+                                let _ = stop_tx.send(());
                             }
                             Err(e) => {
                                 context.push_event(DownloaderEvent::Error {
@@ -226,9 +279,16 @@ impl Downloader for HttpStreamDownloader {
                     .spawn(async move {
                         use ffmpeg_sidecar::event::FfmpegEvent;
 
+                        let _permit = scheduler().acquire(TaskPriority::RecordingIo).await;
+
+                        let mut session_log = FfmpegSessionLog::create(&output_path);
+                        context.set_ffmpeg_log(Some(session_log.path().to_string()));
+                        session_log.write_line("info", &format!("输入: {url}"));
+
                         let mut process = match Self::download_stream(&url, &config) {
                             Ok(p) => p,
                             Err(e) => {
+                                session_log.write_line("error", &format!("ffmpeg 启动失败: {e}"));
                                 context.push_event(DownloaderEvent::Error {
                                     error: DownloaderError::StartupFailed {
                                         command: format!("ffmpeg -i {url}"),
@@ -238,89 +298,100 @@ impl Downloader for HttpStreamDownloader {
                                 return;
                             }
                         };
+                        context.set_ffmpeg_pid(Some(process.as_inner().id()));
 
-                        if let Ok(iter) = process.iter() {
-                            for event in iter {
-                                // 检查是否收到停止信号
-                                if !is_running.load(std::sync::atomic::Ordering::Relaxed) {
-                                    process.quit().unwrap();
-                                    if let Err(e) = process.wait() {
-                                        eprintln!("FFmpeg进程wait失败: {e}");
-                                    } else {
-                                        println!("FFmpeg进程已成功清理");
-                                    }
+                        let iter = match process.iter() {
+                            Ok(iter) => iter,
+                            Err(e) => {
+                                context.set_ffmpeg_pid(None);
+                                context.push_event(DownloaderEvent::Error {
+                                    error: DownloaderError::StartupFailed {
+                                        command: format!("ffmpeg -i {url}"),
+                                        stderr: e.to_string(),
+                                    },
+                                });
+                                return;
+                            }
+                        };
+
+                        for event in iter {
+                            // 检查是否收到停止信号
+                            if !is_running.load(std::sync::atomic::Ordering::Relaxed) {
+                                // ffmpeg 可能已自行退出，quit 失败时直接结束进程
+                                if let Err(e) = process.quit() {
+                                    tracing::warn!("无法通知 ffmpeg 退出: {}", e);
+                                    let _ = process.kill();
+                                }
+                                if let Err(e) = process.wait() {
+                                    tracing::warn!("等待 ffmpeg 进程退出失败: {}", e);
+                                } else {
+                                    tracing::debug!("ffmpeg 进程已成功清理");
+                                }
+                                context.set_ffmpeg_pid(None);
+                                context.push_event(DownloaderEvent::Completed {
+                                    file_path: output_path.clone(),
+                                    file_size: bytes_downloaded,
+                                    duration: start_time.elapsed().as_secs_f64() as u64,
+                                });
+                                let _ = stop_tx.send(());
+                                return;
+                            }
+
+                            match event {
+                                FfmpegEvent::Progress(progress) => {
+                                    bytes_downloaded = progress.size_kb as u64 * 1024; // 转换为字节
+                                    let duration_ms = start_time.elapsed().as_millis() as u64;
+
+                                    context.push_event(DownloaderEvent::Progress {
+                                        bytes_downloaded,
+                                        download_speed_kbps: progress.bitrate_kbps,
+                                        duration_ms,
+                                    });
+                                }
+                                FfmpegEvent::Done => {
                                     context.push_event(DownloaderEvent::Completed {
                                         file_path: output_path.clone(),
                                         file_size: bytes_downloaded,
                                         duration: start_time.elapsed().as_secs_f64() as u64,
                                     });
-                                    let _ = stop_tx.send(());
-                                    return;
                                 }
+                                FfmpegEvent::LogEOF => {
+                                    context.push_event(DownloaderEvent::Completed {
+                                        file_path: output_path.clone(),
+                                        file_size: bytes_downloaded,
+                                        duration: start_time.elapsed().as_secs_f64() as u64,
+                                    });
+                                }
+                                FfmpegEvent::Error(msg) => {
+                                    session_log.write_line("error", &msg);
+                                }
+                                FfmpegEvent::Log(level, msg) => {
+                                    session_log
+                                        .write_line(&format!("{level:?}").to_lowercase(), &msg);
 
-                                match event {
-                                    FfmpegEvent::Progress(progress) => {
-                                        bytes_downloaded = progress.size_kb as u64 * 1024; // 转换为字节
-                                        let duration_ms = start_time.elapsed().as_millis() as u64;
-
-                                        context.push_event(DownloaderEvent::Progress {
-                                            bytes_downloaded,
-                                            download_speed_kbps: progress.bitrate_kbps,
-                                            duration_ms,
-                                        });
-                                    }
-                                    FfmpegEvent::Done => {
-                                        context.push_event(DownloaderEvent::Completed {
-                                            file_path: output_path.clone(),
-                                            file_size: bytes_downloaded,
-                                            duration: start_time.elapsed().as_secs_f64() as u64,
-                                        });
-                                    }
-                                    FfmpegEvent::LogEOF => {
-                                        context.push_event(DownloaderEvent::Completed {
-                                            file_path: output_path.clone(),
-                                            file_size: bytes_downloaded,
-                                            duration: start_time.elapsed().as_secs_f64() as u64,
-                                        });
-                                    }
-                                    FfmpegEvent::Log(level, msg) => {
-                                        match level {
-                                            ffmpeg_sidecar::event::LogLevel::Fatal => {
+                                    match level {
+                                        ffmpeg_sidecar::event::LogLevel::Fatal => {
+                                            context.push_event(DownloaderEvent::Error {
+                                                error: DownloaderError::FfmpegFatalError {
+                                                    message: msg,
+                                                },
+                                            });
+                                        }
+                                        ffmpeg_sidecar::event::LogLevel::Error => {
+                                            if let Some(class) = classify_ffmpeg_error(&msg) {
                                                 context.push_event(DownloaderEvent::Error {
-                                                    error: DownloaderError::FfmpegFatalError {
-                                                        message: msg,
-                                                    },
+                                                    error: class.into_error(msg, &output_path),
                                                 });
                                             }
-                                            ffmpeg_sidecar::event::LogLevel::Error => {
-                                                // 根据错误消息智能分类
-                                                if msg.contains("Connection reset")
-                                                    || msg.contains("timeout")
-                                                    || msg.contains("No route to host")
-                                                    || msg.contains("Connection refused")
-                                                {
-                                                    context.push_event(DownloaderEvent::Error {
-                                                        error: DownloaderError::NetworkConnectionFailed {
-                                                            message: msg,
-                                                        },
-                                                    });
-                                                } else if msg.contains("Protocol not found")
-                                                    || msg.contains("Invalid data found")
-                                                    || msg.contains("Decoder failed")
-                                                {
-                                                    context.push_event(DownloaderEvent::Error {
-                                                        error:
-                                                            DownloaderError::NoSuitableStreamProtocol,
-                                                    });
-                                                }
-                                            }
-                                            _ => {}
                                         }
+                                        _ => {}
                                     }
-                                    _ => {}
                                 }
+                                _ => {}
                             }
                         }
+
+                        context.set_ffmpeg_pid(None);
                     })
                     .detach();
             }
@@ -348,3 +419,125 @@ impl Downloader for HttpStreamDownloader {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use gpui::TestAppContext;
+
+    use super::*;
+    use crate::core::downloader::mock_server::{
+        MockResponse, MockServer, output_path, run_until, test_context, wait_for_event,
+    };
+
+    fn low_cost_downloader(url: String, name: &str) -> (HttpStreamDownloader, DownloaderContext) {
+        let context = test_context(Strategy::LowCost);
+        let config = DownloadConfig {
+            output_path: output_path(name).to_string_lossy().to_string(),
+            strategy: Strategy::LowCost,
+            ..Default::default()
+        };
+
+        (
+            HttpStreamDownloader::new(url, config, context.clone()),
+            context,
+        )
+    }
+
+    #[gpui::test]
+    fn test_stream_forbidden(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start();
+        server.route("/live.flv", MockResponse::Status(403));
+
+        let (mut downloader, context) =
+            low_cost_downloader(server.url("/live.flv"), "forbidden.flv");
+        downloader.start(&mut cx.to_async()).unwrap();
+
+        let events = wait_for_event(cx, &context, |event| {
+            matches!(event, DownloaderEvent::Error { .. })
+        });
+        let error = events.iter().find_map(|event| match event {
+            DownloaderEvent::Error { error } => Some(error),
+            _ => None,
+        });
+
+        assert!(matches!(
+            error,
+            Some(DownloaderError::NetworkConnectionFailed { message }) if message.contains("403")
+        ));
+        assert_eq!(server.hits("/live.flv"), 1);
+    }
+
+    #[gpui::test]
+    fn test_stream_dropped(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start();
+        server.route("/live.flv", MockResponse::dropped_stream(5));
+
+        let (mut downloader, context) = low_cost_downloader(server.url("/live.flv"), "dropped.flv");
+        downloader.start(&mut cx.to_async()).unwrap();
+
+        let events = wait_for_event(cx, &context, |event| {
+            matches!(event, DownloaderEvent::Completed { .. })
+        });
+
+        // 断流应当先报错以便重连，再发送完成事件收尾已写完的分 P
+        let error = events.iter().position(|event| {
+            matches!(
+                event,
+                DownloaderEvent::Error {
+                    error: DownloaderError::NetworkConnectionFailed { .. }
+                }
+            )
+        });
+        let completed = events
+            .iter()
+            .position(|event| matches!(event, DownloaderEvent::Completed { .. }));
+        assert!(error.is_some_and(|error| completed.is_some_and(|completed| error < completed)));
+
+        if let Some(DownloaderEvent::Completed { file_path, .. }) = completed.map(|i| &events[i]) {
+            let _ = std::fs::remove_file(file_path);
+        }
+    }
+
+    #[gpui::test]
+    fn test_stream_stop(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let server = MockServer::start();
+        server.route(
+            "/live.flv",
+            MockResponse::slow_stream(Duration::from_millis(50)),
+        );
+
+        let (mut downloader, context) = low_cost_downloader(server.url("/live.flv"), "stop.flv");
+        downloader.start(&mut cx.to_async()).unwrap();
+
+        wait_for_event(cx, &context, |event| {
+            matches!(event, DownloaderEvent::Progress { .. })
+        });
+
+        run_until(cx, downloader.stop()).unwrap();
+        assert!(!downloader.is_running());
+        assert!(!context.is_running());
+
+        let events = context.take_events();
+        let Some(DownloaderEvent::Completed {
+            file_path,
+            file_size,
+            ..
+        }) = events.last()
+        else {
+            panic!("停止后应当发送完成事件，实际: {events:?}");
+        };
+
+        assert!(*file_size > 0);
+        assert_eq!(
+            std::fs::metadata(file_path).unwrap().len(),
+            *file_size,
+            "写入文件的大小应与统计一致"
+        );
+        let _ = std::fs::remove_file(file_path);
+    }
+}
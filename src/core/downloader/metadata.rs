@@ -0,0 +1,72 @@
+use crate::core::http_client::{room::LiveRoomInfoData, user::LiveUserInfo};
+
+/// 写入输出容器的元数据，媒体库据此识别录制内容
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordingMetadata {
+    pub title: String,
+    pub artist: String,
+    /// 录制日期，`YYYY-MM-DD`
+    pub date: String,
+    pub room_id: u64,
+    /// 录制结束后才能确定的描述，如高光时刻
+    pub description: String,
+}
+
+impl RecordingMetadata {
+    pub fn new(room_info: &LiveRoomInfoData, user_info: &LiveUserInfo) -> Self {
+        Self {
+            title: room_info.title.clone(),
+            artist: user_info.uname.clone(),
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            room_id: room_info.room_id,
+            description: String::new(),
+        }
+    }
+
+    /// ffmpeg 的 `-metadata` 参数
+    ///
+    /// MP4 中对应 ©nam/©ART/©day/©cmt/desc，MKV 中写入同名 Tags，FLV 写入 onMetaData
+    pub fn ffmpeg_args(&self) -> Vec<String> {
+        let comment = format!("https://live.bilibili.com/{}", self.room_id);
+        let tags = [
+            ("title", self.title.as_str()),
+            ("artist", self.artist.as_str()),
+            ("date", self.date.as_str()),
+            ("comment", comment.as_str()),
+            ("description", self.description.as_str()),
+        ];
+
+        tags.into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .flat_map(|(key, value)| ["-metadata".to_string(), format!("{key}={value}")])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_args() {
+        let metadata = RecordingMetadata {
+            title: "标题=带等号".to_string(),
+            artist: String::new(),
+            date: "2025-01-01".to_string(),
+            room_id: 123,
+            description: String::new(),
+        };
+
+        assert_eq!(
+            metadata.ffmpeg_args(),
+            [
+                "-metadata",
+                "title=标题=带等号",
+                "-metadata",
+                "date=2025-01-01",
+                "-metadata",
+                "comment=https://live.bilibili.com/123",
+            ]
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! 下载器集成测试使用的本地 HTTP/HLS 模拟服务器
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    pin::pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use futures::FutureExt;
+use gpui::TestAppContext;
+use parking_lot::Mutex;
+use reqwest_client::ReqwestClient;
+
+use crate::{
+    core::{
+        HttpClient,
+        downloader::{DownloaderContext, context::DownloaderEvent},
+        http_client::{USER_AGENT, room::LiveRoomInfoData, user::LiveUserInfo},
+    },
+    settings::{Quality, Strategy, StreamCodec, VideoContainer},
+};
+
+/// 等待下载器事件的超时时间
+pub const EVENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 推流结束的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// 发送完全部数据后正常关闭连接
+    Eof,
+    /// 声明的长度尚未发送完就断开连接
+    Drop,
+}
+
+/// 一个路径的预设响应
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// 只返回状态码
+    Status(u16),
+    /// 一次性返回完整内容
+    Body {
+        content_type: &'static str,
+        body: Vec<u8>,
+    },
+    /// 按固定间隔分块推流，`chunks` 为 `None` 时持续推流直到客户端断开
+    Stream {
+        chunk: Vec<u8>,
+        chunks: Option<usize>,
+        interval: Duration,
+        end: StreamEnd,
+    },
+}
+
+impl MockResponse {
+    /// 持续推流的慢速直播流
+    pub fn slow_stream(interval: Duration) -> Self {
+        Self::Stream {
+            chunk: vec![0x42; 4096],
+            chunks: None,
+            interval,
+            end: StreamEnd::Eof,
+        }
+    }
+
+    /// 推送若干块后断流
+    pub fn dropped_stream(chunks: usize) -> Self {
+        Self::Stream {
+            chunk: vec![0x42; 4096],
+            chunks: Some(chunks),
+            interval: Duration::from_millis(10),
+            end: StreamEnd::Drop,
+        }
+    }
+}
+
+/// 本地模拟服务器，可在运行中修改路由以模拟断流、403、慢速等场景
+pub struct MockServer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<HashMap<String, MockResponse>>>,
+    hits: Arc<Mutex<HashMap<String, usize>>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("无法启动模拟服务器");
+        let addr = listener.local_addr().expect("无法获取模拟服务器地址");
+
+        let server = Self {
+            addr,
+            routes: Default::default(),
+            hits: Default::default(),
+            shutdown: Default::default(),
+        };
+
+        let routes = server.routes.clone();
+        let hits = server.hits.clone();
+        let shutdown = server.shutdown.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if shutdown.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let routes = routes.clone();
+                let hits = hits.clone();
+                let shutdown = shutdown.clone();
+                std::thread::spawn(move || {
+                    let _ = handle_connection(stream, &routes, &hits, &shutdown);
+                });
+            }
+        });
+
+        server
+    }
+
+    /// 设置路径的响应，已存在时覆盖
+    pub fn route(&self, path: &str, response: MockResponse) -> &Self {
+        self.routes.lock().insert(path.to_string(), response);
+        self
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// 路径被请求的次数
+    pub fn hits(&self, path: &str) -> usize {
+        self.hits.lock().get(path).copied().unwrap_or_default()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        // 唤醒阻塞在 accept 上的线程
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    routes: &Mutex<HashMap<String, MockResponse>>,
+    hits: &Mutex<HashMap<String, usize>>,
+    shutdown: &AtomicBool,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    *hits.lock().entry(path.clone()).or_default() += 1;
+
+    let response = routes.lock().get(&path).cloned();
+    match response {
+        None => write_status(&mut stream, 404),
+        Some(MockResponse::Status(status)) => write_status(&mut stream, status),
+        Some(MockResponse::Body { content_type, body }) => {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )?;
+            stream.write_all(&body)
+        }
+        Some(MockResponse::Stream {
+            chunk,
+            chunks,
+            interval,
+            end,
+        }) => {
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: video/x-flv\r\n")?;
+            // 声明比实际更长的内容，提前关闭即为断流
+            if let (StreamEnd::Drop, Some(chunks)) = (end, chunks) {
+                write!(stream, "Content-Length: {}\r\n", chunk.len() * (chunks + 1))?;
+            }
+            write!(stream, "Connection: close\r\n\r\n")?;
+
+            let mut sent = 0;
+            while chunks.is_none_or(|chunks| sent < chunks) && !shutdown.load(Ordering::Relaxed) {
+                stream.write_all(&chunk)?;
+                stream.flush()?;
+                sent += 1;
+                std::thread::sleep(interval);
+            }
+
+            stream.shutdown(std::net::Shutdown::Both)
+        }
+    }
+}
+
+fn write_status(stream: &mut TcpStream, status: u16) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status} Mock\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    )
+}
+
+/// 构造不依赖真实房间的下载器上下文
+pub fn test_context(strategy: Strategy) -> DownloaderContext {
+    let client = ReqwestClient::user_agent(USER_AGENT).expect("无法创建 HTTP 客户端");
+
+    DownloaderContext::new(
+        0,
+        HttpClient::new(Arc::new(client)),
+        LiveRoomInfoData::default(),
+        LiveUserInfo::default(),
+        strategy,
+        Quality::default(),
+        VideoContainer::default(),
+        StreamCodec::default(),
+    )
+}
+
+/// 测试输出文件路径，已存在时先删除
+pub fn output_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("blive-test-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// 驱动执行器直到出现满足条件的事件，返回期间收到的全部事件
+pub fn wait_for_event(
+    cx: &mut TestAppContext,
+    context: &DownloaderContext,
+    predicate: impl Fn(&DownloaderEvent) -> bool,
+) -> Vec<DownloaderEvent> {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    let mut events = vec![];
+
+    loop {
+        cx.run_until_parked();
+        let drained = context.take_events();
+        let found = drained.iter().any(&predicate);
+        events.extend(drained);
+
+        if found {
+            return events;
+        }
+
+        assert!(
+            Instant::now() < deadline,
+            "等待下载器事件超时，已收到: {events:?}"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// 驱动执行器直到 future 完成
+pub fn run_until<F: Future>(cx: &mut TestAppContext, future: F) -> F::Output {
+    let deadline = Instant::now() + EVENT_TIMEOUT;
+    let mut future = pin!(future);
+
+    loop {
+        if let Some(output) = future.as_mut().now_or_never() {
+            return output;
+        }
+
+        cx.run_until_parked();
+        assert!(Instant::now() < deadline, "等待 future 完成超时");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,101 @@
+//! 录制中的临时文件
+//!
+//! 录制时写入 `录像.flv.part`，正常收尾后原子重命名为 `录像.flv`，同步工具与录像统计只会看到完整的文件
+
+use std::{io, path::Path};
+
+use crate::core::disk::extended_path;
+
+/// 录制中文件的后缀
+pub const PART_SUFFIX: &str = ".part";
+
+/// 最终文件对应的临时文件路径
+pub fn part_path(final_path: &str) -> String {
+    format!("{final_path}{PART_SUFFIX}")
+}
+
+/// 临时文件对应的最终文件路径，不是临时文件时原样返回
+pub fn final_path(path: &str) -> &str {
+    path.strip_suffix(PART_SUFFIX).unwrap_or(path)
+}
+
+/// 最终文件或其临时文件已存在
+pub fn path_taken(final_path: &str) -> bool {
+    Path::new(&extended_path(final_path)).exists()
+        || Path::new(&extended_path(&part_path(final_path))).exists()
+}
+
+/// 把临时文件重命名为最终文件，返回最终文件路径；不是临时文件时直接返回
+///
+/// 同一目录内的重命名是原子的，最终文件已存在时不会覆盖
+pub fn finalize(path: &str) -> io::Result<String> {
+    let target = final_path(path);
+    if target == path {
+        return Ok(path.to_string());
+    }
+
+    if Path::new(&extended_path(target)).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("目标文件已存在: {target}"),
+        ));
+    }
+
+    std::fs::rename(extended_path(path), extended_path(target))?;
+
+    Ok(target.to_string())
+}
+
+/// 临时文件的扩展名无法推断封装格式，需要显式告诉 ffmpeg
+pub fn ffmpeg_format(output_path: &str) -> Option<&'static str> {
+    match Path::new(final_path(output_path))
+        .extension()?
+        .to_str()?
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "flv" => Some("flv"),
+        "mkv" => Some("matroska"),
+        "mp4" => Some("mp4"),
+        "ts" => Some("mpegts"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_path() {
+        assert_eq!(part_path("/rec/主播.flv"), "/rec/主播.flv.part");
+        assert_eq!(final_path("/rec/主播.flv.part"), "/rec/主播.flv");
+        assert_eq!(final_path("/rec/主播.flv"), "/rec/主播.flv");
+        assert_eq!(ffmpeg_format("/rec/主播.mkv.part"), Some("matroska"));
+        assert_eq!(ffmpeg_format("/rec/主播.MP4"), Some("mp4"));
+        assert_eq!(ffmpeg_format("/rec/主播"), None);
+    }
+
+    #[test]
+    fn test_finalize() {
+        let dir = std::env::temp_dir().join("blive_part_file_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let target = dir.join("录像.flv").to_string_lossy().to_string();
+        let part = part_path(&target);
+        std::fs::write(&part, b"FLV").unwrap();
+        assert!(path_taken(&target));
+
+        assert_eq!(finalize(&part).unwrap(), target);
+        assert!(!Path::new(&part).exists());
+        assert_eq!(std::fs::read(&target).unwrap(), b"FLV");
+
+        // 最终文件已存在时不覆盖
+        std::fs::write(&part, b"new").unwrap();
+        assert!(finalize(&part).is_err());
+        assert_eq!(std::fs::read(&target).unwrap(), b"FLV");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
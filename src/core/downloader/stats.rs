@@ -1,9 +1,23 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use crate::core::downloader::error::ReconnectReason;
+
+/// 下载循环内进度的上报间隔
+pub const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(500);
+
 // 下载统计信息
 #[derive(Debug, Clone, Default)]
 pub struct DownloadStats {
     pub bytes_downloaded: u64,
     pub download_speed_kbps: f32,
     pub duration_ms: u64,
+    /// 各原因的重连次数，重启下载时保留
+    pub reconnect_reasons: BTreeMap<ReconnectReason, u32>,
+    /// 读缓冲的使用情况
+    pub buffer: BufferStats,
 }
 
 impl DownloadStats {
@@ -11,5 +25,146 @@ impl DownloadStats {
         self.bytes_downloaded = 0;
         self.download_speed_kbps = 0.0;
         self.duration_ms = 0;
+        self.buffer = BufferStats::default();
+    }
+
+    pub fn record_reconnect(&mut self, reason: ReconnectReason) {
+        *self.reconnect_reasons.entry(reason).or_default() += 1;
+    }
+
+    pub fn reconnect_count(&self) -> u32 {
+        self.reconnect_reasons.values().sum()
+    }
+}
+
+/// 读缓冲的使用情况
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BufferStats {
+    /// 缓冲区大小（字节）
+    pub size: usize,
+    /// 读取次数
+    pub reads: u64,
+    /// 读满缓冲区的次数，占比持续偏高说明缓冲区偏小
+    pub full_reads: u64,
+    /// 累计读取的字节数
+    pub bytes: u64,
+}
+
+impl BufferStats {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, bytes_read: usize) {
+        self.reads += 1;
+        self.bytes += bytes_read as u64;
+        if bytes_read >= self.size {
+            self.full_reads += 1;
+        }
+    }
+
+    /// 读满缓冲区的比例
+    pub fn full_ratio(&self) -> f32 {
+        if self.reads == 0 {
+            return 0.0;
+        }
+
+        self.full_reads as f32 / self.reads as f32
+    }
+
+    /// 平均每次读取的字节数
+    pub fn average_read(&self) -> u64 {
+        self.bytes.checked_div(self.reads).unwrap_or_default()
+    }
+}
+
+/// 在下载循环内按时间聚合进度，每个上报间隔最多产生一次进度事件
+#[derive(Debug)]
+pub struct ProgressAggregator {
+    interval: Duration,
+    bytes: u64,
+    last_report_time: Instant,
+    last_report_bytes: u64,
+}
+
+impl ProgressAggregator {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            bytes: 0,
+            last_report_time: now,
+            last_report_bytes: 0,
+        }
+    }
+
+    /// 累计读取的字节数，到达上报间隔时返回总字节数与期间的平均速度（KB/s）
+    pub fn record(&mut self, bytes: u64, now: Instant) -> Option<(u64, f32)> {
+        self.bytes += bytes;
+
+        let elapsed = now.duration_since(self.last_report_time);
+        if elapsed < self.interval {
+            return None;
+        }
+
+        let speed_kbps =
+            ((self.bytes - self.last_report_bytes) as f64 / 1024.0 / elapsed.as_secs_f64()) as f32;
+        self.last_report_time = now;
+        self.last_report_bytes = self.bytes;
+
+        Some((self.bytes, speed_kbps))
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_aggregator() {
+        let start = Instant::now();
+        let mut progress = ProgressAggregator::new(PROGRESS_REPORT_INTERVAL, start);
+
+        // 间隔内的读取只累计，不上报
+        for i in 1..=10 {
+            assert_eq!(
+                progress.record(8192, start + Duration::from_millis(i * 10)),
+                None
+            );
+        }
+        assert_eq!(progress.bytes(), 81920);
+
+        let (bytes, speed) = progress
+            .record(20480, start + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(bytes, 102400);
+        assert_eq!(speed, 100.0);
+
+        assert_eq!(
+            progress.record(8192, start + Duration::from_millis(1100)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_buffer_stats() {
+        let mut buffer = BufferStats::new(1024);
+        assert_eq!(buffer.full_ratio(), 0.0);
+
+        buffer.record(1024);
+        buffer.record(512);
+        buffer.record(1024);
+        buffer.record(256);
+
+        assert_eq!(buffer.reads, 4);
+        assert_eq!(buffer.full_reads, 2);
+        assert_eq!(buffer.full_ratio(), 0.5);
+        assert_eq!(buffer.average_read(), 704);
     }
 }
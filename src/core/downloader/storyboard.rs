@@ -0,0 +1,170 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+use crate::core::{
+    disk::extended_path,
+    downloader::{ffmpeg_headers, ffmpeg_input},
+    repair::ffmpeg_path,
+};
+
+/// 开始录制后第一张截图的延迟，避开开播时的黑屏与卡顿
+const FIRST_SNAPSHOT_DELAY: Duration = Duration::from_secs(30);
+
+/// 截图宽度，高度按比例缩放
+const SNAPSHOT_WIDTH: u32 = 320;
+
+/// 录制期间按固定间隔从直播流截图，生成录像的故事板
+///
+/// 截图保存在录制文件旁的 `录像.storyboard/` 目录，以相对录制开始的 `HHMMSS.jpg` 命名
+#[derive(Debug, Clone)]
+pub struct Storyboard {
+    video_path: PathBuf,
+    started_at: Instant,
+    interval: Duration,
+    next: Duration,
+}
+
+impl Storyboard {
+    pub fn start(video_path: &Path, interval: Duration, now: Instant) -> Self {
+        Self {
+            video_path: video_path.to_path_buf(),
+            started_at: now,
+            interval,
+            next: FIRST_SNAPSHOT_DELAY.min(interval),
+        }
+    }
+
+    /// 到了截图时间时返回截图路径，错过的多次截图只补一张
+    pub fn due(&mut self, now: Instant) -> Option<PathBuf> {
+        let elapsed = now.saturating_duration_since(self.started_at);
+        if elapsed < self.next || self.interval.is_zero() {
+            return None;
+        }
+
+        while self.next <= elapsed {
+            self.next += self.interval;
+        }
+
+        Some(snapshot_path(&self.video_path, elapsed))
+    }
+}
+
+/// 录像的故事板目录
+pub fn storyboard_dir(video_path: &Path) -> PathBuf {
+    video_path.with_extension("storyboard")
+}
+
+fn snapshot_path(video_path: &Path, offset: Duration) -> PathBuf {
+    let seconds = offset.as_secs();
+    storyboard_dir(video_path).join(format!(
+        "{:02}{:02}{:02}.jpg",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    ))
+}
+
+fn parse_offset(stem: &str) -> Option<Duration> {
+    if stem.len() != 6 || !stem.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours = stem[0..2].parse::<u64>().ok()?;
+    let minutes = stem[2..4].parse::<u64>().ok()?;
+    let seconds = stem[4..6].parse::<u64>().ok()?;
+
+    Some(Duration::from_secs(hours * 3600 + minutes * 60 + seconds))
+}
+
+/// 录像已有的截图，按时间排序
+pub fn snapshots(video_path: &Path) -> Vec<(Duration, PathBuf)> {
+    let Ok(entries) =
+        std::fs::read_dir(extended_path(&storyboard_dir(video_path).to_string_lossy()))
+    else {
+        return vec![];
+    };
+
+    let mut snapshots = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jpg"))
+        .filter_map(|path| {
+            let offset = parse_offset(&path.file_stem()?.to_string_lossy())?;
+            Some((offset, path))
+        })
+        .collect::<Vec<_>>();
+    snapshots.sort();
+
+    snapshots
+}
+
+/// 从直播流截取一帧
+pub fn capture_snapshot(url: &str, output: &Path) -> Result<()> {
+    if let Some(dir) = output.parent() {
+        std::fs::create_dir_all(extended_path(&dir.to_string_lossy()))
+            .context("无法创建故事板目录")?;
+    }
+
+    let (input, headers) = ffmpeg_input(url);
+    let output_result = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-y", "-headers"])
+        .arg(ffmpeg_headers(&headers))
+        // 网络流最多等待 10 秒，避免卡住的截图一直占用进程
+        .args(["-rw_timeout", "10000000"])
+        .arg("-i")
+        .arg(input)
+        .args(["-frames:v", "1", "-q:v", "4", "-vf"])
+        .arg(format!("scale={SNAPSHOT_WIDTH}:-2"))
+        .arg(extended_path(&output.to_string_lossy()))
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output_result.status.success() {
+        let _ = std::fs::remove_file(extended_path(&output.to_string_lossy()));
+        anyhow::bail!(
+            "ffmpeg 截图失败: {}",
+            String::from_utf8_lossy(&output_result.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storyboard_schedule() {
+        let now = Instant::now();
+        let video = Path::new("/录制/主播_标题.flv");
+        let mut storyboard = Storyboard::start(video, Duration::from_secs(300), now);
+
+        assert_eq!(storyboard.due(now + Duration::from_secs(10)), None);
+        assert_eq!(
+            storyboard.due(now + Duration::from_secs(31)),
+            Some(PathBuf::from("/录制/主播_标题.storyboard/000031.jpg"))
+        );
+        assert_eq!(storyboard.due(now + Duration::from_secs(200)), None);
+
+        // 长时间没有进度时只补一张
+        assert_eq!(
+            storyboard.due(now + Duration::from_secs(3700)),
+            Some(PathBuf::from("/录制/主播_标题.storyboard/010140.jpg"))
+        );
+        assert_eq!(storyboard.due(now + Duration::from_secs(3800)), None);
+        assert!(storyboard.due(now + Duration::from_secs(3930)).is_some());
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("010203"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_offset("1234"), None);
+        assert_eq!(parse_offset("01020a"), None);
+    }
+}
@@ -1,9 +1,11 @@
 use std::borrow::Cow;
 
-use crate::settings::Quality;
+use crate::{core::downloader::MAX_PARTS, settings::Quality};
 
 pub struct DownloaderFilenameTemplate {
     pub up_name: String,
+    /// 房间别名，为空时 `{alias}` 取主播名
+    pub alias: String,
     pub quality: Quality,
     pub room_id: u64,
     pub room_title: String,
@@ -11,24 +13,204 @@ pub struct DownloaderFilenameTemplate {
     pub room_area_name: String,
     pub date: String,
     pub datetime: String,
+    pub year: String,
 }
 
 impl leon::Values for DownloaderFilenameTemplate {
     fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
         match key {
-            "up_name" => Some(Cow::Borrowed(&self.up_name)),
+            "up_name" => Some(Cow::Owned(sanitize(&self.up_name))),
+            "alias" if self.alias.is_empty() => Some(Cow::Owned(sanitize(&self.up_name))),
+            "alias" => Some(Cow::Owned(sanitize(&self.alias))),
             "quality" => Some(Cow::Owned(self.quality.to_string())),
             "room_id" => Some(Cow::Owned(self.room_id.to_string())),
             "datetime" => Some(Cow::Borrowed(&self.datetime)),
-            "room_title" => Some(Cow::Owned(
-                self.room_title.to_owned().chars().take(10).collect(),
-            )),
-            "room_description" => Some(Cow::Owned(
-                self.room_description.to_owned().chars().take(20).collect(),
-            )),
-            "room_area_name" => Some(Cow::Borrowed(&self.room_area_name)),
+            "room_title" => Some(Cow::Owned(sanitize(
+                &self.room_title.chars().take(10).collect::<String>(),
+            ))),
+            "room_description" => Some(Cow::Owned(sanitize(
+                &self.room_description.chars().take(20).collect::<String>(),
+            ))),
+            "room_area_name" => Some(Cow::Owned(sanitize(&self.room_area_name))),
             "date" => Some(Cow::Borrowed(&self.date)),
+            "year" => Some(Cow::Borrowed(&self.year)),
             _ => None,
         }
     }
 }
+
+/// 替换文件名中不允许的字符，模板里的 `/` 才表示子目录，标题等取值中的 `/` 不能产生目录
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// 单个路径段的最大字节数，多数文件系统限制为 255，预留扩展名、分 P 序号与附属文件后缀
+const MAX_SEGMENT_BYTES: usize = 200;
+
+/// Windows 保留的设备名，带扩展名时同样无法创建
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 处理渲染后的文件名，使其在各平台都能创建
+///
+/// 模板中的 `\` 同样视为子目录分隔符；逐段替换非法字符、去掉 Windows 不允许的结尾空格与 `.`、
+/// 避开保留设备名并截断过长的段
+pub fn sanitize_filename(filename: &str) -> String {
+    filename
+        .replace('\\', "/")
+        .split('/')
+        .map(sanitize_segment)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sanitize_segment(segment: &str) -> String {
+    let segment = sanitize(segment);
+    let mut segment = truncate_bytes(segment.trim(), MAX_SEGMENT_BYTES)
+        .trim_end_matches(['.', ' '])
+        .to_string();
+
+    let stem = segment.split('.').next().unwrap_or_default().trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(stem))
+    {
+        segment.insert(stem.len(), '_');
+    }
+
+    segment
+}
+
+/// 按字节截断，不拆开多字节字符
+fn truncate_bytes(value: &str, max_bytes: usize) -> &str {
+    if value.len() <= max_bytes {
+        return value;
+    }
+
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|index| value.is_char_boundary(*index))
+        .unwrap_or_default();
+    &value[..end]
+}
+
+/// 用当前时间与示例标题渲染模板，供设置中预览文件名
+pub fn preview_filename(record_name: &str, up_name: &str, alias: &str, room_id: u64) -> String {
+    let now = chrono::Local::now();
+    let values = DownloaderFilenameTemplate {
+        up_name: up_name.to_string(),
+        alias: alias.to_string(),
+        quality: Quality::default(),
+        room_id,
+        room_title: "直播标题示例".to_string(),
+        room_description: String::new(),
+        room_area_name: "分区".to_string(),
+        date: now.format("%Y-%m-%d").to_string(),
+        datetime: now.format("%Y-%m-%d %H点%M分").to_string(),
+        year: now.format("%Y").to_string(),
+    };
+
+    let filename = leon::Template::parse(record_name)
+        .ok()
+        .and_then(|template| template.render(&values).ok())
+        .unwrap_or_default();
+    sanitize_filename(&filename)
+}
+
+/// 预计最长的录制文件路径，即分 P 到上限时的 `{目录}/{文件名}/{文件名}_P50.{扩展名}`
+pub fn longest_record_path(record_dir: &str, filename: &str, ext: &str) -> String {
+    let (dir, filename) = split_subdirectory(record_dir, filename);
+    format!("{dir}/{filename}/{filename}_P{MAX_PARTS}.{ext}")
+}
+
+/// 把渲染结果中的子目录并入录制目录，返回（目录, 文件名）
+///
+/// 忽略空的路径段与 `.`、`..`，避免取值为空时产生多余层级或跳出录制目录
+pub fn split_subdirectory(record_dir: &str, filename: &str) -> (String, String) {
+    let mut parts = filename
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !matches!(*part, "" | "." | ".."))
+        .collect::<Vec<_>>();
+    let filename = parts.pop().unwrap_or_default().to_string();
+
+    let dir = std::iter::once(record_dir)
+        .chain(parts)
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (dir, filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::RecordNamePreset;
+
+    #[test]
+    fn test_media_library_template() {
+        let values = DownloaderFilenameTemplate {
+            up_name: "主播/A".to_string(),
+            alias: String::new(),
+            quality: Quality::Original,
+            room_id: 1,
+            room_title: "标题: 杂谈".to_string(),
+            room_description: String::new(),
+            room_area_name: String::new(),
+            date: "2025-01-02".to_string(),
+            datetime: "2025-01-02 20点00分".to_string(),
+            year: "2025".to_string(),
+        };
+
+        let template = leon::Template::parse(RecordNamePreset::MediaLibrary.template()).unwrap();
+        let filename = template.render(&values).unwrap();
+        assert_eq!(
+            filename,
+            "主播_A/Season 2025/主播_A - 2025-01-02 - 标题_ 杂谈"
+        );
+
+        assert_eq!(
+            split_subdirectory("/record", &filename),
+            (
+                "/record/主播_A/Season 2025".to_string(),
+                "主播_A - 2025-01-02 - 标题_ 杂谈".to_string()
+            )
+        );
+        let template = leon::Template::parse("{alias}_{room_id}").unwrap();
+        assert_eq!(template.render(&values).unwrap(), "主播_A_1");
+
+        assert_eq!(
+            split_subdirectory("/record", "/../a//b"),
+            ("/record/a".to_string(), "b".to_string())
+        );
+        assert_eq!(
+            longest_record_path("/record", "主播/标题", "flv"),
+            "/record/主播/标题/标题_P50.flv"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(
+            sanitize_filename("主播\\第1场: 杂谈?. "),
+            "主播/第1场_ 杂谈_"
+        );
+        assert_eq!(sanitize_filename("con"), "con_");
+        assert_eq!(sanitize_filename("Season 1/LPT1.flv"), "Season 1/LPT1_.flv");
+        assert_eq!(sanitize_filename("CONSOLE"), "CONSOLE");
+
+        let long = "直播".repeat(100);
+        let sanitized = sanitize_filename(&long);
+        assert!(sanitized.len() <= MAX_SEGMENT_BYTES);
+        assert!(long.starts_with(&sanitized));
+    }
+}
@@ -0,0 +1,487 @@
+use chrono::{DateTime, Local, NaiveDateTime};
+use chrono_tz::Asia::Shanghai;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    blank::BlankSegment,
+    danmaku::highlights::Highlight,
+    downloader::{error::ReconnectReason, utils::pretty_duration},
+};
+
+/// 录制会话中的一条事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    /// 发生时间（毫秒时间戳）
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEventKind {
+    /// 开始录制
+    Started { file_path: String },
+    /// 重连后切换到新的分 P 文件
+    PartSwitched { file_path: String },
+    /// 录制出错
+    Error {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        reason: Option<ReconnectReason>,
+    },
+    /// 开始重连
+    Reconnecting,
+    /// 流地址即将过期，计划内切换到新地址与新分 P
+    Rotating,
+    /// 录制文件的弹幕高光时刻
+    Highlights {
+        file_path: String,
+        highlights: Vec<Highlight>,
+    },
+    /// 文件写入完成
+    Completed {
+        file_path: String,
+        file_size: u64,
+        duration: u64,
+    },
+    /// 完成后检测到的可疑黑屏/静音段
+    BlankSegments {
+        file_path: String,
+        segments: Vec<BlankSegment>,
+    },
+    /// 完成后提取音频并删除了原录制文件
+    AudioExtracted {
+        video_path: String,
+        file_path: String,
+    },
+}
+
+impl SessionEventKind {
+    /// 时间线中展示的描述
+    pub fn describe(&self) -> String {
+        match self {
+            SessionEventKind::Started { file_path } => format!("开始录制: {file_path}"),
+            SessionEventKind::PartSwitched { file_path } => format!("切换分P: {file_path}"),
+            SessionEventKind::Error {
+                message,
+                reason: Some(reason),
+            } => format!("错误（{reason}）: {message}"),
+            SessionEventKind::Error {
+                message,
+                reason: None,
+            } => format!("错误: {message}"),
+            SessionEventKind::Reconnecting => "开始重连".to_string(),
+            SessionEventKind::Rotating => "计划内轮换".to_string(),
+            SessionEventKind::Highlights { highlights, .. } => format!(
+                "高光时刻: {}",
+                highlights
+                    .iter()
+                    .map(Highlight::describe)
+                    .collect::<Vec<_>>()
+                    .join("；")
+            ),
+            SessionEventKind::Completed { file_path, .. } => format!("录制完成: {file_path}"),
+            SessionEventKind::BlankSegments { segments, .. } => format!(
+                "疑似黑屏/静音: {}",
+                segments
+                    .iter()
+                    .map(BlankSegment::describe)
+                    .collect::<Vec<_>>()
+                    .join("；")
+            ),
+            SessionEventKind::AudioExtracted { file_path, .. } => {
+                format!("提取音频: {file_path}")
+            }
+        }
+    }
+}
+
+/// 录制文件最终保留的路径，提取音频后原录制文件已删除，改为音频文件
+pub fn output_path<'a>(events: &'a [SessionEvent], file_path: &'a str) -> &'a str {
+    events
+        .iter()
+        .find_map(|event| match &event.kind {
+            SessionEventKind::AudioExtracted {
+                video_path,
+                file_path: audio_path,
+            } if video_path == file_path => Some(audio_path.as_str()),
+            _ => None,
+        })
+        .unwrap_or(file_path)
+}
+
+/// 一次录制会话的事件时间线，会话跨越重连与分 P，直到非重连导致的完成为止
+#[derive(Debug, Clone, Default)]
+pub struct SessionTimeline {
+    events: Vec<SessionEvent>,
+    reconnect_pending: bool,
+    finished: bool,
+}
+
+impl SessionTimeline {
+    /// 记录事件，返回会话是否就此结束
+    pub fn record(&mut self, timestamp: i64, kind: SessionEventKind) -> bool {
+        let kind = match kind {
+            SessionEventKind::Started { file_path } => {
+                self.reconnect_pending = false;
+
+                if self.finished || self.events.is_empty() {
+                    self.events.clear();
+                    self.finished = false;
+                    SessionEventKind::Started { file_path }
+                } else {
+                    SessionEventKind::PartSwitched { file_path }
+                }
+            }
+            // 可恢复的错误之后会重连，断流分 P 的完成事件紧随错误到达，早于重连事件
+            kind @ (SessionEventKind::Reconnecting
+            | SessionEventKind::Rotating
+            | SessionEventKind::Error {
+                reason: Some(_), ..
+            }) => {
+                self.reconnect_pending = true;
+                kind
+            }
+            kind => kind,
+        };
+
+        let completed = matches!(kind, SessionEventKind::Completed { .. });
+        self.events.push(SessionEvent { timestamp, kind });
+
+        // 重连过程中的完成只是当前分 P 结束
+        if completed && !self.reconnect_pending && !self.finished {
+            self.finished = true;
+            return true;
+        }
+
+        false
+    }
+
+    /// 正在重连或轮换，此时的完成只结束当前分 P
+    pub fn is_reconnect_pending(&self) -> bool {
+        self.reconnect_pending
+    }
+
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// 已开始但尚未完成的录制文件
+    pub fn open_file(&self) -> Option<&str> {
+        // 以最后一次开始或完成事件为准
+        self.events
+            .iter()
+            .rev()
+            .find_map(|event| match &event.kind {
+                SessionEventKind::Started { file_path }
+                | SessionEventKind::PartSwitched { file_path } => Some(Some(file_path.as_str())),
+                SessionEventKind::Completed { .. } => Some(None),
+                _ => None,
+            })?
+    }
+
+    /// 会话结束后的覆盖率，会话未结束时返回 `None`
+    pub fn coverage(&self, live_start: Option<i64>) -> Option<Coverage> {
+        if !self.finished {
+            return None;
+        }
+
+        Coverage::from_events(&self.events, live_start)
+    }
+}
+
+/// 小于该时长的空档视为计时误差，不算缺口
+const GAP_THRESHOLD_MS: i64 = 3_000;
+
+/// 录制覆盖率：直播实际时长（开播到下播）与录制文件总时长的对比
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coverage {
+    /// 直播时长（毫秒）
+    pub live_ms: i64,
+    /// 录制文件总时长（毫秒）
+    pub recorded_ms: i64,
+    /// 未录到的时间段（毫秒时间戳）
+    pub gaps: Vec<(i64, i64)>,
+}
+
+impl Coverage {
+    /// 由会话时间线计算覆盖率，`live_start` 为开播时间，缺失时从开始录制算起
+    ///
+    /// 每个分 P 从开始/切换事件算起，按完成时的文件时长推算结束时间，分 P 之间的空档即为缺口；
+    /// 最后一个分 P 完成的时间视为下播时间
+    pub fn from_events(events: &[SessionEvent], live_start: Option<i64>) -> Option<Self> {
+        let mut parts = vec![];
+        let mut part_start = None;
+        let mut live_end = None;
+
+        for event in events {
+            match &event.kind {
+                SessionEventKind::Started { .. } | SessionEventKind::PartSwitched { .. } => {
+                    part_start = Some(event.timestamp);
+                }
+                SessionEventKind::Completed { duration, .. } => {
+                    if let Some(start) = part_start.take() {
+                        parts.push((start, start + *duration as i64 * 1000));
+                    }
+                    live_end = Some(event.timestamp);
+                }
+                _ => {}
+            }
+        }
+
+        let first_start = parts.first()?.0;
+        let live_start = live_start.map_or(first_start, |start| start.min(first_start));
+        let live_end = live_end?.max(parts.last()?.1);
+
+        let mut gaps = vec![];
+        let mut covered_until = live_start;
+        for &(start, end) in &parts {
+            if start - covered_until >= GAP_THRESHOLD_MS {
+                gaps.push((covered_until, start));
+            }
+            covered_until = covered_until.max(end);
+        }
+
+        Some(Self {
+            live_ms: live_end - live_start,
+            recorded_ms: parts.iter().map(|(start, end)| end - start).sum(),
+            gaps,
+        })
+    }
+
+    /// 覆盖率百分比，最高 100
+    pub fn percent(&self) -> u32 {
+        if self.live_ms <= 0 {
+            return 100;
+        }
+
+        (self.recorded_ms * 100 / self.live_ms).clamp(0, 100) as u32
+    }
+
+    /// 形如“覆盖率 98%，缺 3 段”的结论
+    pub fn describe(&self) -> String {
+        if self.gaps.is_empty() {
+            format!("覆盖率 {}%", self.percent())
+        } else {
+            format!("覆盖率 {}%，缺 {} 段", self.percent(), self.gaps.len())
+        }
+    }
+
+    /// 形如“98%/缺 3 段”的简短结论，用于导出
+    pub fn summary(&self) -> String {
+        if self.gaps.is_empty() {
+            format!("{}%", self.percent())
+        } else {
+            format!("{}%/缺 {} 段", self.percent(), self.gaps.len())
+        }
+    }
+
+    /// 每个缺口的起止时间与时长
+    pub fn describe_gaps(&self) -> Vec<String> {
+        let format_time = |timestamp| {
+            DateTime::from_timestamp_millis(timestamp)
+                .map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string())
+                .unwrap_or_default()
+        };
+
+        self.gaps
+            .iter()
+            .map(|&(start, end)| {
+                format!(
+                    "{} ~ {}（{}）",
+                    format_time(start),
+                    format_time(end),
+                    pretty_duration(((end - start) / 1000) as u64)
+                )
+            })
+            .collect()
+    }
+}
+
+/// 解析接口返回的开播时间（北京时间）为毫秒时间戳，未开播或格式不对时返回 `None`
+pub fn live_start_millis(live_time: &str) -> Option<i64> {
+    NaiveDateTime::parse_from_str(live_time, "%Y-%m-%d %H:%M:%S")
+        .ok()?
+        .and_local_timezone(Shanghai)
+        .earliest()
+        .map(|time| time.timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(file_path: &str) -> SessionEventKind {
+        SessionEventKind::Started {
+            file_path: file_path.to_string(),
+        }
+    }
+
+    fn completed(file_path: &str) -> SessionEventKind {
+        SessionEventKind::Completed {
+            file_path: file_path.to_string(),
+            file_size: 0,
+            duration: 0,
+        }
+    }
+
+    #[test]
+    fn test_timeline_spans_reconnects() {
+        let mut timeline = SessionTimeline::default();
+
+        assert!(!timeline.record(0, started("P1.flv")));
+        assert!(!timeline.record(
+            1,
+            SessionEventKind::Error {
+                message: "Connection reset".to_string(),
+                reason: Some(ReconnectReason::CdnFailure),
+            }
+        ));
+        assert!(!timeline.record(2, SessionEventKind::Reconnecting));
+        assert!(!timeline.record(3, completed("P1.flv")));
+        assert!(!timeline.record(4, started("P2.flv")));
+        assert!(timeline.record(5, completed("P2.flv")));
+
+        assert_eq!(timeline.events().len(), 6);
+        assert_eq!(
+            timeline.events()[4].kind,
+            SessionEventKind::PartSwitched {
+                file_path: "P2.flv".to_string()
+            }
+        );
+
+        // 新会话从头开始
+        assert!(!timeline.record(6, started("next.flv")));
+        assert_eq!(timeline.events().len(), 1);
+    }
+
+    #[test]
+    fn test_timeline_completed_before_reconnecting() {
+        let mut timeline = SessionTimeline::default();
+
+        // 断流的分 P 完成事件紧随错误，早于重连事件到达
+        assert!(!timeline.record(0, started("P1.flv")));
+        assert!(!timeline.record(
+            1,
+            SessionEventKind::Error {
+                message: "Connection reset".to_string(),
+                reason: Some(ReconnectReason::CdnFailure),
+            }
+        ));
+        assert!(!timeline.record(2, completed("P1.flv")));
+        assert!(!timeline.record(3, SessionEventKind::Reconnecting));
+        assert!(!timeline.record(4, started("P2.flv")));
+        assert!(timeline.record(5, completed("P2.flv")));
+
+        // 不可恢复的错误之后录制结束
+        assert!(!timeline.record(6, started("next.flv")));
+        assert!(!timeline.record(
+            7,
+            SessionEventKind::Error {
+                message: "磁盘已满".to_string(),
+                reason: None,
+            }
+        ));
+        assert!(timeline.record(8, completed("next.flv")));
+    }
+
+    #[test]
+    fn test_timeline_open_file() {
+        let mut timeline = SessionTimeline::default();
+        assert_eq!(timeline.open_file(), None);
+
+        timeline.record(0, started("P1.flv"));
+        timeline.record(
+            1,
+            SessionEventKind::Error {
+                message: "Connection reset".to_string(),
+                reason: Some(ReconnectReason::CdnFailure),
+            },
+        );
+        assert_eq!(timeline.open_file(), Some("P1.flv"));
+
+        timeline.record(2, SessionEventKind::Reconnecting);
+        timeline.record(3, started("P2.flv"));
+        assert_eq!(timeline.open_file(), Some("P2.flv"));
+
+        timeline.record(4, completed("P2.flv"));
+        assert_eq!(timeline.open_file(), None);
+    }
+
+    #[test]
+    fn test_output_path() {
+        let mut timeline = SessionTimeline::default();
+        timeline.record(0, started("a.flv"));
+        timeline.record(1, completed("a.flv"));
+        assert_eq!(output_path(timeline.events(), "a.flv"), "a.flv");
+
+        timeline.record(
+            2,
+            SessionEventKind::AudioExtracted {
+                video_path: "a.flv".to_string(),
+                file_path: "a.m4a".to_string(),
+            },
+        );
+        assert_eq!(output_path(timeline.events(), "a.flv"), "a.m4a");
+        assert_eq!(output_path(timeline.events(), "b.flv"), "b.flv");
+    }
+
+    #[test]
+    fn test_timeline_spans_rotation() {
+        let mut timeline = SessionTimeline::default();
+
+        timeline.record(0, started("P1.flv"));
+        assert!(!timeline.record(1, SessionEventKind::Rotating));
+        assert!(!timeline.record(2, completed("P1.flv")));
+        assert!(!timeline.record(3, started("P2.flv")));
+        assert!(timeline.record(4, completed("P2.flv")));
+        assert_eq!(timeline.events()[1].kind.describe(), "计划内轮换");
+    }
+
+    #[test]
+    fn test_coverage() {
+        let completed = |duration| SessionEventKind::Completed {
+            file_path: String::new(),
+            file_size: 0,
+            duration,
+        };
+        let mut timeline = SessionTimeline::default();
+
+        timeline.record(10_000, started("P1.flv"));
+        assert_eq!(timeline.coverage(None), None);
+        timeline.record(60_000, SessionEventKind::Reconnecting);
+        timeline.record(61_000, completed(50));
+        timeline.record(65_000, started("P2.flv"));
+        timeline.record(100_000, completed(35));
+
+        // 开播后 10 秒才开始录制，重连缺了 5 秒
+        let coverage = timeline.coverage(Some(0)).unwrap();
+        assert_eq!(coverage.live_ms, 100_000);
+        assert_eq!(coverage.recorded_ms, 85_000);
+        assert_eq!(coverage.gaps, vec![(0, 10_000), (60_000, 65_000)]);
+        assert_eq!(coverage.describe(), "覆盖率 85%，缺 2 段");
+        assert_eq!(coverage.summary(), "85%/缺 2 段");
+
+        let coverage = timeline.coverage(None).unwrap();
+        assert_eq!(coverage.gaps, vec![(60_000, 65_000)]);
+        assert_eq!(coverage.percent(), 94);
+    }
+
+    #[test]
+    fn test_live_start_millis() {
+        assert_eq!(
+            live_start_millis("2024-01-01 08:00:00"),
+            Some(1_704_067_200_000)
+        );
+        assert_eq!(live_start_millis("0000-00-00 00:00:00"), None);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::settings::{StreamCodec, TranscodeSettings};
+
+/// PriorityConfig 策略下 ffmpeg 的编码参数
+///
+/// 默认流复制，只转封装；开启重编码时才按目标分辨率缩放并重新编码
+pub fn ffmpeg_codec_args(codec: StreamCodec, transcode: &TranscodeSettings) -> Vec<String> {
+    if !transcode.reencode {
+        // HLS 分片中的 AAC 带 ADTS 头，封装到 FLV/MP4 前需转换
+        return ["-c", "copy", "-bsf:a", "aac_adtstoasc"]
+            .map(String::from)
+            .to_vec();
+    }
+
+    let mut args = vec![];
+    if let Some(resolution) = transcode.resolution {
+        args.push("-vf".to_string());
+        args.push(format!("scale={}:{}", resolution.width, resolution.height));
+    }
+
+    let encoder = match codec {
+        StreamCodec::AVC => "libx264",
+        StreamCodec::HEVC => "libx265",
+    };
+    args.extend(["-c:v", encoder, "-c:a", "aac"].map(String::from));
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Resolution;
+
+    #[test]
+    fn test_ffmpeg_codec_args() {
+        // 默认不重编码
+        assert_eq!(
+            ffmpeg_codec_args(StreamCodec::AVC, &TranscodeSettings::default()),
+            ["-c", "copy", "-bsf:a", "aac_adtstoasc"]
+        );
+
+        let mut transcode = TranscodeSettings {
+            reencode: true,
+            resolution: None,
+        };
+        assert_eq!(
+            ffmpeg_codec_args(StreamCodec::HEVC, &transcode),
+            ["-c:v", "libx265", "-c:a", "aac"]
+        );
+
+        transcode.resolution = Some(Resolution {
+            width: 1280,
+            height: 720,
+        });
+        assert_eq!(
+            ffmpeg_codec_args(StreamCodec::AVC, &transcode),
+            ["-vf", "scale=1280:720", "-c:v", "libx264", "-c:a", "aac"]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use futures::channel::oneshot;
+
+/// 写盘队列容量（块数），写盘跟不上时网络读取会等待
+pub const WRITE_QUEUE_CAPACITY: usize = 256;
+
+/// 写盘后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteBackend {
+    /// 标准库同步写入
+    Std,
+    /// io_uring 异步写入，需启用 `io-uring` feature，仅 Linux 可用
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    IoUring,
+}
+
+impl WriteBackend {
+    /// 编译进来的全部后端
+    pub const ALL: &[WriteBackend] = &[
+        WriteBackend::Std,
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        WriteBackend::IoUring,
+    ];
+
+    /// 默认使用的后端，启用 `io-uring` feature 时优先使用 io_uring
+    pub fn preferred() -> Self {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        {
+            WriteBackend::IoUring
+        }
+
+        #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+        {
+            WriteBackend::Std
+        }
+    }
+}
+
+impl fmt::Display for WriteBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteBackend::Std => write!(f, "std"),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            WriteBackend::IoUring => write!(f, "io_uring"),
+        }
+    }
+}
+
+/// 在专用线程上写盘，避免同步 IO 阻塞异步执行器
+///
+/// 网络读取与写盘之间通过有界 channel 连接，写盘变慢时发送端会等待
+pub struct FileWriter {
+    tx: flume::Sender<Vec<u8>>,
+    done: oneshot::Receiver<io::Result<u64>>,
+}
+
+impl FileWriter {
+    pub fn spawn(file: File) -> io::Result<Self> {
+        Self::spawn_with(file, WriteBackend::preferred())
+    }
+
+    pub fn spawn_with(file: File, backend: WriteBackend) -> io::Result<Self> {
+        let (tx, rx) = flume::bounded::<Vec<u8>>(WRITE_QUEUE_CAPACITY);
+        let (done_tx, done) = oneshot::channel();
+
+        std::thread::Builder::new()
+            .name("blive-writer".to_string())
+            .spawn(move || {
+                // 出错时直接返回，接收端随之关闭，发送端据此得知写入失败
+                let result = match backend {
+                    WriteBackend::Std => write_std(file, rx),
+                    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                    WriteBackend::IoUring => uring::write(file, rx),
+                };
+
+                let _ = done_tx.send(result);
+            })?;
+
+        Ok(Self { tx, done })
+    }
+
+    /// 发送一块数据，写盘线程已出错退出时返回 `false`
+    pub async fn write(&self, chunk: Vec<u8>) -> bool {
+        self.tx.send_async(chunk).await.is_ok()
+    }
+
+    /// 等待队列中的数据全部落盘，返回写入的总字节数
+    pub async fn finish(self) -> io::Result<u64> {
+        drop(self.tx);
+
+        self.done
+            .await
+            .unwrap_or_else(|_| Err(io::Error::other("写盘线程意外退出")))
+    }
+}
+
+fn write_std(file: File, rx: flume::Receiver<Vec<u8>>) -> io::Result<u64> {
+    let mut writer = BufWriter::new(file);
+    let mut written = 0u64;
+
+    for chunk in rx.iter() {
+        writer.write_all(&chunk)?;
+        written += chunk.len() as u64;
+    }
+    writer.flush()?;
+
+    Ok(written)
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring {
+    use std::{fs::File, io};
+
+    /// 在写盘线程上运行单线程 io_uring 运行时，按偏移顺序提交写入
+    pub(super) fn write(file: File, rx: flume::Receiver<Vec<u8>>) -> io::Result<u64> {
+        // 内核不支持或被 seccomp 禁用时退回标准库写入
+        let runtime = match tokio_uring::Runtime::new(&tokio_uring::builder()) {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                tracing::warn!("io_uring 不可用，改用标准写盘: {}", e);
+                return super::write_std(file, rx);
+            }
+        };
+
+        runtime.block_on(async move {
+            let file = tokio_uring::fs::File::from_std(file);
+            let mut written = 0u64;
+
+            while let Ok(chunk) = rx.recv_async().await {
+                let len = chunk.len() as u64;
+                let (result, _) = file.write_all_at(chunk, written).await;
+                result?;
+                written += len;
+            }
+            file.close().await?;
+
+            Ok(written)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_chunks(backend: WriteBackend, name: &str, chunks: usize, chunk_size: usize) -> u64 {
+        let path = std::env::temp_dir().join(format!(
+            "blive-writer-{name}-{backend}-{}.flv",
+            std::process::id()
+        ));
+        let writer = FileWriter::spawn_with(File::create(&path).unwrap(), backend).unwrap();
+
+        let written = futures::executor::block_on(async {
+            for _ in 0..chunks {
+                assert!(writer.write(vec![0x42; chunk_size]).await);
+            }
+            writer.finish().await.unwrap()
+        });
+
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), written);
+        let _ = std::fs::remove_file(path);
+        written
+    }
+
+    #[test]
+    fn test_file_writer() {
+        for backend in WriteBackend::ALL {
+            let written = write_chunks(*backend, "test", WRITE_QUEUE_CAPACITY * 2, 1024);
+            assert_eq!(written, WRITE_QUEUE_CAPACITY as u64 * 2 * 1024);
+        }
+    }
+}
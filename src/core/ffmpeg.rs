@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+
+/// ffmpeg 的检测状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FfmpegStatus {
+    /// 尚未用到 ffmpeg，未做检测
+    Unchecked,
+    /// 已确认可用
+    Ready,
+    /// 检测失败且无法自动下载
+    Missing,
+}
+
+static STATUS: AtomicU8 = AtomicU8::new(FfmpegStatus::Unchecked as u8);
+
+/// 避免多个房间同时触发下载
+static ENSURE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 当前检测状态，不会触发检测
+pub fn ffmpeg_status() -> FfmpegStatus {
+    match STATUS.load(Ordering::Relaxed) {
+        1 => FfmpegStatus::Ready,
+        2 => FfmpegStatus::Missing,
+        _ => FfmpegStatus::Unchecked,
+    }
+}
+
+fn set_status(status: FfmpegStatus) {
+    STATUS.store(status as u8, Ordering::Relaxed);
+}
+
+/// 确保 ffmpeg 可用，首次需要时才检测，缺失时自动下载
+///
+/// 可能阻塞较长时间，需在后台线程调用
+pub fn ensure_ffmpeg() -> Result<()> {
+    if ffmpeg_status() == FfmpegStatus::Ready {
+        return Ok(());
+    }
+
+    let _guard = ENSURE_LOCK.lock();
+    if ffmpeg_status() == FfmpegStatus::Ready {
+        return Ok(());
+    }
+
+    let result = detect_or_download();
+    set_status(match result {
+        Ok(()) => FfmpegStatus::Ready,
+        Err(_) => FfmpegStatus::Missing,
+    });
+
+    result
+}
+
+#[cfg(feature = "ffmpeg")]
+fn detect_or_download() -> Result<()> {
+    use anyhow::Context;
+
+    if ffmpeg_sidecar::command::ffmpeg_is_installed() {
+        return Ok(());
+    }
+
+    tracing::info!("未检测到 ffmpeg，开始自动下载");
+    ffmpeg_sidecar::download::auto_download().context("无法自动下载 ffmpeg")?;
+    tracing::info!("ffmpeg 下载完成");
+
+    Ok(())
+}
+
+#[cfg(not(feature = "ffmpeg"))]
+fn detect_or_download() -> Result<()> {
+    match crate::diagnostics::ffmpeg_version() {
+        Some(_) => Ok(()),
+        None => anyhow::bail!("未找到可用的 ffmpeg"),
+    }
+}
@@ -0,0 +1,256 @@
+use std::{path::PathBuf, process::Command};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::core::disk::extended_path;
+
+/// ffprobe 探测得到的媒体信息
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaInfo {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f32>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub audio_sample_rate: Option<u32>,
+    pub bitrate_kbps: Option<u32>,
+    pub duration_secs: Option<f64>,
+}
+
+impl MediaInfo {
+    pub fn has_video(&self) -> bool {
+        self.video_codec.is_some()
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.audio_codec.is_some()
+    }
+
+    /// 录制产物的异常项，为空表示检查通过
+    pub fn recording_issues(&self) -> Vec<&'static str> {
+        let mut issues = vec![];
+
+        if !self.duration_secs.is_some_and(|duration| duration > 0.0) {
+            issues.push("时长为 0");
+        }
+        if !self.has_video() {
+            issues.push("缺少视频轨");
+        }
+        if !self.has_audio() {
+            issues.push("缺少音频轨");
+        }
+
+        issues
+    }
+
+    /// 纯音频产物的异常项，不要求视频轨
+    pub fn audio_recording_issues(&self) -> Vec<&'static str> {
+        let mut issues = self.recording_issues();
+        issues.retain(|issue| *issue != "缺少视频轨");
+        issues
+    }
+
+    /// 分辨率描述，如 1920x1080
+    pub fn resolution(&self) -> Option<String> {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => Some(format!("{width}x{height}")),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    avg_frame_rate: Option<String>,
+    r_frame_rate: Option<String>,
+    sample_rate: Option<String>,
+    bit_rate: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    bit_rate: Option<String>,
+}
+
+/// ffprobe 可执行文件路径
+pub fn ffprobe_path() -> PathBuf {
+    #[cfg(feature = "ffmpeg")]
+    {
+        ffmpeg_sidecar::ffprobe::ffprobe_path()
+    }
+    #[cfg(not(feature = "ffmpeg"))]
+    {
+        PathBuf::from("ffprobe")
+    }
+}
+
+/// 探测输入（文件路径或流地址）的媒体信息
+pub fn probe(input: &str, headers: &[(&str, &str)]) -> Result<MediaInfo> {
+    let mut cmd = Command::new(ffprobe_path());
+
+    cmd.args(["-v", "error", "-print_format", "json"])
+        .args(["-show_streams", "-show_format"]);
+
+    if !headers.is_empty() {
+        let headers = headers
+            .iter()
+            .map(|(key, value)| format!("{key}: {value}\r\n"))
+            .collect::<String>();
+        cmd.args(["-headers", headers.as_str()]);
+        // 网络流最多等待 10 秒
+        cmd.args(["-rw_timeout", "10000000"]);
+    }
+
+    // 本地文件使用扩展路径，流地址原样传入
+    let output = cmd
+        .arg(extended_path(input))
+        .output()
+        .context("无法启动ffprobe进程")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "ffprobe 执行失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    parse_probe_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_probe_output(json: &str) -> Result<MediaInfo> {
+    let output: ProbeOutput = serde_json::from_str(json).context("无法解析ffprobe输出")?;
+    let mut info = MediaInfo::default();
+
+    for stream in output.streams {
+        match stream.codec_type.as_deref() {
+            Some("video") if info.video_codec.is_none() => {
+                info.video_codec = stream.codec_name;
+                info.width = stream.width;
+                info.height = stream.height;
+                info.frame_rate = stream
+                    .avg_frame_rate
+                    .as_deref()
+                    .and_then(parse_frame_rate)
+                    .or_else(|| stream.r_frame_rate.as_deref().and_then(parse_frame_rate));
+                if let Some(bitrate) = stream.bit_rate.as_deref().and_then(parse_kbps) {
+                    info.bitrate_kbps = Some(bitrate);
+                }
+            }
+            Some("audio") if info.audio_codec.is_none() => {
+                info.audio_codec = stream.codec_name;
+                info.audio_sample_rate = stream.sample_rate.and_then(|rate| rate.parse().ok());
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(format) = output.format {
+        info.duration_secs = format.duration.and_then(|duration| duration.parse().ok());
+        if info.bitrate_kbps.is_none() {
+            info.bitrate_kbps = format.bit_rate.as_deref().and_then(parse_kbps);
+        }
+    }
+
+    Ok(info)
+}
+
+/// 解析 ffprobe 的帧率表示，如 `60/1`、`30000/1001`
+fn parse_frame_rate(value: &str) -> Option<f32> {
+    let (num, den) = value.split_once('/')?;
+    let num = num.parse::<f32>().ok()?;
+    let den = den.parse::<f32>().ok()?;
+
+    if num <= 0.0 || den <= 0.0 {
+        return None;
+    }
+
+    Some(num / den)
+}
+
+fn parse_kbps(value: &str) -> Option<u32> {
+    value.parse::<u64>().ok().map(|bps| (bps / 1000) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_probe_output() {
+        let json = r#"{
+            "streams": [
+                {
+                    "codec_type": "video",
+                    "codec_name": "hevc",
+                    "width": 1920,
+                    "height": 1080,
+                    "avg_frame_rate": "0/0",
+                    "r_frame_rate": "60/1"
+                },
+                {
+                    "codec_type": "audio",
+                    "codec_name": "aac",
+                    "sample_rate": "48000"
+                }
+            ],
+            "format": {
+                "duration": "12.500000",
+                "bit_rate": "8000000"
+            }
+        }"#;
+
+        let info = parse_probe_output(json).unwrap();
+
+        assert_eq!(info.resolution().as_deref(), Some("1920x1080"));
+        assert_eq!(info.frame_rate, Some(60.0));
+        assert_eq!(info.video_codec.as_deref(), Some("hevc"));
+        assert_eq!(info.audio_codec.as_deref(), Some("aac"));
+        assert_eq!(info.audio_sample_rate, Some(48000));
+        assert_eq!(info.bitrate_kbps, Some(8000));
+        assert_eq!(info.duration_secs, Some(12.5));
+    }
+
+    #[test]
+    fn test_parse_audio_only() {
+        let json = r#"{"streams": [{"codec_type": "audio", "codec_name": "aac"}]}"#;
+        let info = parse_probe_output(json).unwrap();
+
+        assert!(!info.has_video());
+        assert!(info.has_audio());
+    }
+
+    #[test]
+    fn test_recording_issues() {
+        let info = MediaInfo {
+            video_codec: Some("h264".to_string()),
+            audio_codec: Some("aac".to_string()),
+            duration_secs: Some(3600.0),
+            ..Default::default()
+        };
+        assert!(info.recording_issues().is_empty());
+
+        let audio_only = MediaInfo {
+            video_codec: None,
+            duration_secs: Some(0.0),
+            ..info
+        };
+        assert_eq!(
+            audio_only.recording_issues(),
+            vec!["时长为 0", "缺少视频轨"]
+        );
+        assert_eq!(audio_only.audio_recording_issues(), vec!["时长为 0"]);
+    }
+}
@@ -0,0 +1,695 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    fs::OpenOptions,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        blank::BlankSegment,
+        downloader::{
+            error::ReconnectReason,
+            timeline::{Coverage, SessionEvent, SessionEventKind},
+            utils::{pretty_bytes, pretty_duration},
+        },
+    },
+    settings::config_dir,
+};
+
+/// 历史记录文件，每行一条 JSON 记录
+pub fn history_file() -> PathBuf {
+    config_dir().join("history.jsonl")
+}
+
+/// 一条历史记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// 记录时间（毫秒时间戳）
+    pub timestamp: i64,
+    /// 房间号
+    pub room_id: u64,
+    #[serde(flatten)]
+    pub event: HistoryEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    /// 录制中断并重连
+    Reconnect { reason: ReconnectReason },
+    /// 一次完整的录制会话
+    Session {
+        title: String,
+        up_name: String,
+        events: Vec<SessionEvent>,
+        /// 开播时间（毫秒时间戳），用于计算覆盖率
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        live_start: Option<i64>,
+    },
+    /// 异常中断的录制文件已自动修复
+    Repaired { file_path: String },
+    /// 静默时段内开播，未启动录制
+    QuietSkipped { title: String, up_name: String },
+    /// 会话结束后才完成的黑屏/静音检测
+    BlankSegments {
+        file_path: String,
+        segments: Vec<BlankSegment>,
+    },
+    /// 会话结束后才完成的音频提取，原录制文件已删除
+    AudioExtracted {
+        video_path: String,
+        file_path: String,
+    },
+}
+
+impl HistoryRecord {
+    pub fn now(room_id: u64, event: HistoryEvent) -> Self {
+        Self {
+            timestamp: chrono::Local::now().timestamp_millis(),
+            room_id,
+            event,
+        }
+    }
+}
+
+/// 追加一条历史记录
+pub fn append_record(record: &HistoryRecord) {
+    let result = serde_json::to_string(record)
+        .map_err(std::io::Error::other)
+        .and_then(|line| {
+            let path = history_file();
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{line}")
+        });
+
+    if let Err(e) = result {
+        tracing::warn!("写入历史记录失败: {}", e);
+    }
+}
+
+/// 读取全部历史记录，无法解析的行会被跳过
+pub fn load_records() -> Vec<HistoryRecord> {
+    let Ok(file) = std::fs::File::open(history_file()) else {
+        return vec![];
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// 房间每场录制完成的文件，按记录顺序从旧到新排列，没有完成文件的会话不计入；
+/// 提取过音频的录制以音频文件为准
+pub fn session_files(records: &[HistoryRecord], room_id: u64) -> Vec<Vec<PathBuf>> {
+    let audio = extracted_audio(records);
+
+    records
+        .iter()
+        .filter(|record| record.room_id == room_id)
+        .filter_map(|record| match &record.event {
+            HistoryEvent::Session { events, .. } => Some(
+                events
+                    .iter()
+                    .filter_map(|event| match &event.kind {
+                        SessionEventKind::Completed { file_path, .. } => Some(PathBuf::from(
+                            audio.get(file_path.as_str()).copied().unwrap_or(file_path),
+                        )),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        })
+        .filter(|files| !files.is_empty())
+        .collect()
+}
+
+/// 提取音频后原录制文件已删除，返回录制文件到音频文件的映射
+fn extracted_audio(records: &[HistoryRecord]) -> HashMap<&str, &str> {
+    let mut audio = HashMap::new();
+
+    for record in records {
+        match &record.event {
+            HistoryEvent::Session { events, .. } => {
+                for event in events {
+                    if let SessionEventKind::AudioExtracted {
+                        video_path,
+                        file_path,
+                    } = &event.kind
+                    {
+                        audio.insert(video_path.as_str(), file_path.as_str());
+                    }
+                }
+            }
+            HistoryEvent::AudioExtracted {
+                video_path,
+                file_path,
+            } => {
+                audio.insert(video_path.as_str(), file_path.as_str());
+            }
+            _ => {}
+        }
+    }
+
+    audio
+}
+
+/// 全部房间录制完成的文件，存储清理只处理这些文件；提取过音频的录制以音频文件为准
+pub fn completed_files(records: &[HistoryRecord]) -> Vec<PathBuf> {
+    let audio = extracted_audio(records);
+
+    records
+        .iter()
+        .filter_map(|record| match &record.event {
+            HistoryEvent::Session { events, .. } => Some(events),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|event| match &event.kind {
+            SessionEventKind::Completed { file_path, .. } => Some(PathBuf::from(
+                audio.get(file_path.as_str()).copied().unwrap_or(file_path),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 按原因聚合重连次数，`room_id` 为 `None` 时统计全部房间
+pub fn reconnect_reason_counts(
+    records: &[HistoryRecord],
+    room_id: Option<u64>,
+) -> BTreeMap<ReconnectReason, u32> {
+    let mut counts = BTreeMap::new();
+
+    for record in records {
+        if room_id.is_some_and(|room_id| room_id != record.room_id) {
+            continue;
+        }
+
+        if let HistoryEvent::Reconnect { reason } = record.event {
+            *counts.entry(reason).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+/// CSV 表头，时长与大小保留原始数值便于在 Excel 中统计
+const CSV_HEADER: &str = "开始时间,主播,标题,时长(秒),大小(字节),路径,结果,覆盖率";
+
+/// 含逗号、引号或换行的字段需要加引号，引号本身写两次
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// 把历史记录整理为每个录制文件一行的 CSV，返回内容与行数
+///
+/// 会话按分 P 拆成多行，结果取该分 P 期间最后一次错误，覆盖率为整场会话的结论；
+/// 自动修复的文件单独成行，提取过音频的分 P 路径为音频文件
+pub fn recordings_csv(records: &[HistoryRecord]) -> (String, usize) {
+    let audio = extracted_audio(records);
+    // 带 BOM，Excel 才能正确识别 UTF-8 中文
+    let mut output = format!("\u{feff}{CSV_HEADER}\r\n");
+    let mut rows = 0;
+
+    let mut push_row = |fields: [&str; 8]| {
+        let line = fields.map(csv_field).join(",");
+        let _ = write!(output, "{line}\r\n");
+        rows += 1;
+    };
+
+    for record in records {
+        match &record.event {
+            HistoryEvent::Session {
+                title,
+                up_name,
+                events,
+                live_start,
+            } => {
+                let coverage = Coverage::from_events(events, *live_start)
+                    .map(|coverage| coverage.summary())
+                    .unwrap_or_default();
+                let mut part_start = record.timestamp;
+                let mut last_error = None;
+
+                for event in events {
+                    match &event.kind {
+                        SessionEventKind::Started { .. }
+                        | SessionEventKind::PartSwitched { .. } => {
+                            part_start = event.timestamp;
+                            last_error = None;
+                        }
+                        SessionEventKind::Error { .. } => last_error = Some(event.kind.describe()),
+                        SessionEventKind::Completed {
+                            file_path,
+                            file_size,
+                            duration,
+                        } => push_row([
+                            &format_timestamp(part_start),
+                            up_name,
+                            title,
+                            &duration.to_string(),
+                            &file_size.to_string(),
+                            audio.get(file_path.as_str()).copied().unwrap_or(file_path),
+                            last_error.as_deref().unwrap_or("成功"),
+                            &coverage,
+                        ]),
+                        _ => {}
+                    }
+                }
+            }
+            HistoryEvent::Repaired { file_path } => push_row([
+                &format_timestamp(record.timestamp),
+                "",
+                "",
+                "",
+                "",
+                file_path,
+                "异常中断，已自动修复",
+                "",
+            ]),
+            HistoryEvent::Reconnect { .. }
+            | HistoryEvent::QuietSkipped { .. }
+            | HistoryEvent::BlankSegments { .. }
+            | HistoryEvent::AudioExtracted { .. } => {}
+        }
+    }
+
+    (output, rows)
+}
+
+/// 导出全部录制记录为 CSV，返回导出的行数
+pub fn export_csv(target: &Path) -> io::Result<usize> {
+    let (csv, rows) = recordings_csv(&load_records());
+    std::fs::write(target, csv)?;
+
+    Ok(rows)
+}
+
+/// 一个主播一个月的录制统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonthlyStats {
+    /// 月份，如 `2025-08`
+    pub month: String,
+    pub room_id: u64,
+    /// 该月最近一场的主播名
+    pub up_name: String,
+    /// 录制场次
+    pub sessions: u32,
+    /// 总时长（秒）
+    pub duration: u64,
+    /// 总大小（字节）
+    pub size: u64,
+}
+
+impl MonthlyStats {
+    /// 平均码率（kbps），没有时长时为 0
+    pub fn bitrate_kbps(&self) -> u64 {
+        (self.size * 8 / 1000)
+            .checked_div(self.duration)
+            .unwrap_or_default()
+    }
+}
+
+/// 按月份与房间聚合录制会话，月份取会话开始的时间，没有完成文件的会话不计入
+///
+/// 结果按月份从新到旧、同月内按房间号排列
+pub fn monthly_stats(records: &[HistoryRecord]) -> Vec<MonthlyStats> {
+    let mut stats = BTreeMap::<(String, u64), MonthlyStats>::new();
+
+    for record in records {
+        let HistoryEvent::Session {
+            up_name, events, ..
+        } = &record.event
+        else {
+            continue;
+        };
+
+        let completed = events
+            .iter()
+            .filter_map(|event| match event.kind {
+                SessionEventKind::Completed {
+                    file_size,
+                    duration,
+                    ..
+                } => Some((file_size, duration)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if completed.is_empty() {
+            continue;
+        }
+
+        let started_at = events
+            .first()
+            .map_or(record.timestamp, |event| event.timestamp);
+        let month = chrono::DateTime::from_timestamp_millis(started_at)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m")
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        let entry = stats
+            .entry((month.clone(), record.room_id))
+            .or_insert_with(|| MonthlyStats {
+                month,
+                room_id: record.room_id,
+                ..Default::default()
+            });
+        entry.up_name = up_name.clone();
+        entry.sessions += 1;
+        for (size, duration) in completed {
+            entry.size += size;
+            entry.duration += duration;
+        }
+    }
+
+    let mut stats = stats.into_values().collect::<Vec<_>>();
+    stats.sort_by(|a, b| b.month.cmp(&a.month).then(a.room_id.cmp(&b.room_id)));
+    stats
+}
+
+/// 月度统计 CSV 表头，时长与大小保留原始数值便于在 Excel 中统计
+const MONTHLY_CSV_HEADER: &str = "月份,主播,房间号,场次,总时长(秒),总大小(字节),平均码率(kbps)";
+
+/// 月度统计整理为 CSV，每个主播每月一行
+pub fn monthly_csv(stats: &[MonthlyStats]) -> String {
+    let mut output = format!("\u{feff}{MONTHLY_CSV_HEADER}\r\n");
+
+    for item in stats {
+        let line = [
+            item.month.clone(),
+            item.up_name.clone(),
+            item.room_id.to_string(),
+            item.sessions.to_string(),
+            item.duration.to_string(),
+            item.size.to_string(),
+            item.bitrate_kbps().to_string(),
+        ]
+        .map(|field| csv_field(&field))
+        .join(",");
+        let _ = write!(output, "{line}\r\n");
+    }
+
+    output
+}
+
+/// 月度统计整理为 Markdown，每月一张表并附合计，便于规划存储
+pub fn monthly_markdown(stats: &[MonthlyStats]) -> String {
+    let mut output = String::from("# 月度录制统计\n");
+
+    for month in stats.chunk_by(|a, b| a.month == b.month) {
+        let _ = write!(
+            output,
+            "\n## {}\n\n| 主播 | 房间号 | 场次 | 总时长 | 总大小 | 平均码率 |\n| --- | ---: | ---: | ---: | ---: | ---: |\n",
+            month[0].month
+        );
+
+        let mut total = MonthlyStats::default();
+        for item in month {
+            let _ = writeln!(
+                output,
+                "| {} | {} | {} | {} | {} | {} kbps |",
+                item.up_name.replace('|', "\\|"),
+                item.room_id,
+                item.sessions,
+                pretty_duration(item.duration),
+                pretty_bytes(item.size),
+                item.bitrate_kbps()
+            );
+
+            total.sessions += item.sessions;
+            total.duration += item.duration;
+            total.size += item.size;
+        }
+
+        let _ = writeln!(
+            output,
+            "| **合计** | | {} | {} | {} | {} kbps |",
+            total.sessions,
+            pretty_duration(total.duration),
+            pretty_bytes(total.size),
+            total.bitrate_kbps()
+        );
+    }
+
+    output
+}
+
+/// 导出月度统计，保存为 `.md` 时导出 Markdown，否则导出 CSV，返回统计的行数
+pub fn export_monthly_report(target: &Path) -> io::Result<usize> {
+    let stats = monthly_stats(&load_records());
+    let content = if target.extension().is_some_and(|ext| ext == "md") {
+        monthly_markdown(&stats)
+    } else {
+        monthly_csv(&stats)
+    };
+    std::fs::write(target, content)?;
+
+    Ok(stats.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = HistoryRecord {
+            timestamp: 1,
+            room_id: 2,
+            event: HistoryEvent::Reconnect {
+                reason: ReconnectReason::CdnFailure,
+            },
+        };
+
+        let line = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            line,
+            r#"{"timestamp":1,"room_id":2,"type":"reconnect","reason":"cdn_failure"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<HistoryRecord>(&line).unwrap(),
+            record
+        );
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        use crate::core::downloader::timeline::SessionEventKind;
+
+        let record = HistoryRecord {
+            timestamp: 1,
+            room_id: 2,
+            event: HistoryEvent::Session {
+                title: "标题".to_string(),
+                up_name: "主播".to_string(),
+                events: vec![SessionEvent {
+                    timestamp: 1,
+                    kind: SessionEventKind::Reconnecting,
+                }],
+                live_start: Some(0),
+            },
+        };
+
+        let line = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HistoryRecord>(&line).unwrap(),
+            record
+        );
+    }
+
+    #[test]
+    fn test_reconnect_reason_counts() {
+        let records = [
+            (1, ReconnectReason::CdnFailure),
+            (1, ReconnectReason::CdnFailure),
+            (2, ReconnectReason::LocalNetwork),
+        ]
+        .map(|(room_id, reason)| HistoryRecord {
+            timestamp: 0,
+            room_id,
+            event: HistoryEvent::Reconnect { reason },
+        });
+
+        let all = reconnect_reason_counts(&records, None);
+        assert_eq!(all[&ReconnectReason::CdnFailure], 2);
+        assert_eq!(all[&ReconnectReason::LocalNetwork], 1);
+
+        let room = reconnect_reason_counts(&records, Some(2));
+        assert_eq!(room.len(), 1);
+    }
+
+    #[test]
+    fn test_recordings_csv() {
+        let event = |timestamp, kind| SessionEvent { timestamp, kind };
+        let records = [
+            HistoryRecord {
+                timestamp: 0,
+                room_id: 1,
+                event: HistoryEvent::Session {
+                    title: "杂谈, 聊天".to_string(),
+                    up_name: "主播".to_string(),
+                    events: vec![
+                        event(
+                            0,
+                            SessionEventKind::Started {
+                                file_path: "P1.flv".to_string(),
+                            },
+                        ),
+                        event(
+                            1,
+                            SessionEventKind::Error {
+                                message: "Connection reset".to_string(),
+                                reason: None,
+                            },
+                        ),
+                        event(2, SessionEventKind::Reconnecting),
+                        event(
+                            3,
+                            SessionEventKind::Completed {
+                                file_path: "P1.flv".to_string(),
+                                file_size: 1024,
+                                duration: 60,
+                            },
+                        ),
+                        event(
+                            4,
+                            SessionEventKind::Started {
+                                file_path: "P2.flv".to_string(),
+                            },
+                        ),
+                        event(
+                            5,
+                            SessionEventKind::Completed {
+                                file_path: "P2.flv".to_string(),
+                                file_size: 2048,
+                                duration: 120,
+                            },
+                        ),
+                    ],
+                    live_start: None,
+                },
+            },
+            HistoryRecord {
+                timestamp: 6,
+                room_id: 1,
+                event: HistoryEvent::Reconnect {
+                    reason: ReconnectReason::CdnFailure,
+                },
+            },
+            HistoryRecord {
+                timestamp: 7,
+                room_id: 1,
+                event: HistoryEvent::Repaired {
+                    file_path: "P3.flv".to_string(),
+                },
+            },
+            HistoryRecord {
+                timestamp: 8,
+                room_id: 1,
+                event: HistoryEvent::AudioExtracted {
+                    video_path: "P2.flv".to_string(),
+                    file_path: "P2.m4a".to_string(),
+                },
+            },
+        ];
+
+        let (csv, rows) = recordings_csv(&records);
+        assert_eq!(rows, 3);
+
+        let lines = csv.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines[0], format!("\u{feff}{CSV_HEADER}"));
+        assert!(
+            lines[1].ends_with(",主播,\"杂谈, 聊天\",60,1024,P1.flv,错误: Connection reset,100%")
+        );
+        assert!(lines[2].ends_with(",120,2048,P2.m4a,成功,100%"));
+        assert!(lines[3].ends_with(",,,,,P3.flv,异常中断，已自动修复,"));
+
+        assert_eq!(
+            completed_files(&records),
+            [PathBuf::from("P1.flv"), PathBuf::from("P2.m4a")]
+        );
+        assert_eq!(
+            session_files(&records, 1),
+            [vec![PathBuf::from("P1.flv"), PathBuf::from("P2.m4a")]]
+        );
+    }
+
+    #[test]
+    fn test_monthly_stats() {
+        let session = |room_id, up_name: &str, timestamp, parts: &[(u64, u64)]| HistoryRecord {
+            timestamp,
+            room_id,
+            event: HistoryEvent::Session {
+                title: String::new(),
+                up_name: up_name.to_string(),
+                events: parts
+                    .iter()
+                    .map(|&(file_size, duration)| SessionEvent {
+                        timestamp,
+                        kind: SessionEventKind::Completed {
+                            file_path: String::new(),
+                            file_size,
+                            duration,
+                        },
+                    })
+                    .collect(),
+                live_start: None,
+            },
+        };
+        // 2025-07-15 与 2025-08-15（UTC 中午，任何时区都在同一月）
+        let july = 1_752_580_800_000;
+        let august = 1_755_259_200_000;
+        let records = [
+            session(1, "主播", july, &[(1_000_000, 10)]),
+            session(1, "主播", august, &[(1_000_000, 10), (1_000_000, 10)]),
+            session(2, "a|b", august, &[(3_000_000, 30)]),
+            session(1, "主播", august, &[]),
+        ];
+
+        let stats = monthly_stats(&records);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats[0].month, "2025-08");
+        assert_eq!(stats[0].room_id, 1);
+        assert_eq!(stats[0].sessions, 1);
+        assert_eq!(stats[0].size, 2_000_000);
+        assert_eq!(stats[0].bitrate_kbps(), 800);
+        assert_eq!(stats[2].month, "2025-07");
+
+        let csv = monthly_csv(&stats);
+        let lines = csv.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines[0], format!("\u{feff}{MONTHLY_CSV_HEADER}"));
+        assert_eq!(lines[1], "2025-08,主播,1,1,20,2000000,800");
+
+        let markdown = monthly_markdown(&stats);
+        assert!(markdown.contains("| a\\|b | 2 | 1 | 00:00:30 | 2.86 MB | 800 kbps |"));
+        assert!(markdown.contains("| **合计** | | 2 | 00:00:50 | 4.77 MB | 800 kbps |"));
+    }
+}
@@ -1,15 +1,55 @@
+use crate::core::auth;
+use crate::core::dns::CachedResolver;
 use crate::logger::{log_network_request, log_network_response};
+use crate::settings::NetworkSettings;
 use anyhow::{Context, Result};
 use futures::AsyncReadExt;
 use gpui::http_client::{AsyncBody, HttpClient as GPUIHttpClient, Method, Request, Response};
+use reqwest_client::ReqwestClient;
 use std::fmt::Debug;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
+pub mod passport;
+pub mod playback;
 pub mod room;
 pub mod stream;
 pub mod user;
 
+pub const USER_AGENT: &str = "blive/0.1.0";
+
+/// 建立连接的超时时间
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 只向 B 站的接口携带登录 Cookie，Webhook 等第三方地址不会收到
+fn is_bilibili_host(host: &str) -> bool {
+    host == "bilibili.com" || host.ends_with(".bilibili.com")
+}
+
+/// 按网络设置创建底层客户端，所有请求共用同一个连接池
+pub fn build_reqwest_client(network: &NetworkSettings) -> Result<ReqwestClient> {
+    let keep_alive = Duration::from_secs(network.keep_alive_seconds as u64);
+    // 不保持空闲连接时也就无从复用
+    let pool_size = if keep_alive.is_zero() {
+        0
+    } else {
+        network.pool_max_idle_per_host as usize
+    };
+
+    let client = reqwest::Client::builder()
+        .use_rustls_tls()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_max_idle_per_host(pool_size)
+        .pool_idle_timeout(keep_alive)
+        .tcp_keepalive((!keep_alive.is_zero()).then_some(keep_alive))
+        .dns_resolver(Arc::new(CachedResolver))
+        .build()
+        .context("Failed to build http client")?;
+
+    Ok(ReqwestClient::from(client))
+}
+
 #[derive(Debug, serde::Deserialize)]
 pub struct BasicResponse<Data: Sized> {
     pub code: i32,
@@ -25,11 +65,19 @@ impl HttpClient {
         Self { inner: client }
     }
 
-    pub async fn send(&self, request: Request<AsyncBody>) -> Result<Response<AsyncBody>> {
+    pub async fn send(&self, mut request: Request<AsyncBody>) -> Result<Response<AsyncBody>> {
         let method = request.method().to_string();
         let url = request.uri().to_string();
         let start_time = Instant::now();
 
+        if request.uri().host().is_some_and(is_bilibili_host)
+            && !request.headers().contains_key("Cookie")
+            && let Some(cookie) = auth::cookie_header()
+            && let Ok(value) = cookie.parse()
+        {
+            request.headers_mut().insert("Cookie", value);
+        }
+
         log_network_request(&url, &method);
 
         let result = self
@@ -52,6 +100,65 @@ impl HttpClient {
         result
     }
 
+    /// 检测直播接口的连通性，返回请求耗时
+    pub async fn check_connectivity(&self) -> Result<Duration> {
+        let request = Request::builder()
+            .uri("https://api.live.bilibili.com/")
+            .method(Method::GET)
+            .body(AsyncBody::empty())
+            .context("Failed to build request")?;
+
+        let start_time = Instant::now();
+        self.send(request).await?;
+
+        Ok(start_time.elapsed())
+    }
+
+    /// 下载完整的响应内容，用于封面等小文件
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        let request = Request::builder()
+            .uri(url)
+            .method(Method::GET)
+            .body(AsyncBody::empty())
+            .context("Failed to build request")?;
+
+        let mut response = self.send(request).await.context("Failed to send request")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to download {url}: {}",
+                response.status()
+            ));
+        }
+
+        let mut body = Vec::new();
+        response.body_mut().read_to_end(&mut body).await?;
+
+        Ok(body)
+    }
+
+    /// 以 JSON 请求体 POST，用于 Webhook 推送
+    pub async fn post_json<T: serde::Serialize>(&self, url: &str, body: &T) -> Result<()> {
+        let body = serde_json::to_vec(body).context("Failed to serialize request body")?;
+        let request = Request::builder()
+            .uri(url)
+            .method(Method::POST)
+            .header("Content-Type", "application/json")
+            .body(AsyncBody::from(body))
+            .context("Failed to build request")?;
+
+        let response = self.send(request).await.context("Failed to send request")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to post {url}: {}",
+                response.status()
+            ));
+        }
+
+        Ok(())
+    }
+
     pub async fn get_live_room_info(&self, room_id: u64) -> Result<room::LiveRoomInfoData> {
         let url = format!("https://api.live.bilibili.com/room/v1/Room/get_info?room_id={room_id}");
 
@@ -127,6 +234,150 @@ impl HttpClient {
 
         Ok(data.data)
     }
+
+    /// 获取弹幕服务器地址与鉴权 token
+    pub async fn get_danmu_info(&self, room_id: u64) -> Result<room::DanmuInfo> {
+        let url = format!(
+            "https://api.live.bilibili.com/xlive/web-room/v1/index/getDanmuInfo?id={room_id}&type=0"
+        );
+
+        let request = Request::builder()
+            .uri(&url)
+            .method(Method::GET)
+            .body(AsyncBody::empty())
+            .context("Failed to build request")?;
+
+        let mut response = self.send(request).await.context("Failed to send request")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get danmu info"));
+        }
+
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        let data: BasicResponse<Option<room::DanmuInfo>> = serde_json::from_str(&body)?;
+
+        data.data
+            .filter(|_| data.code == 0)
+            .ok_or_else(|| anyhow::anyhow!("Failed to get danmu info: code {}", data.code))
+    }
+
+    /// 获取主播最近的直播回放，房间未开启回放时为空
+    pub async fn get_replay_list(&self, uid: u64) -> Result<Vec<playback::Replay>> {
+        let url = format!(
+            "https://api.live.bilibili.com/xlive/web-room/v1/videoService/GetOtherSliceList?live_uid={uid}&time_range=3&page=1&page_size=30"
+        );
+
+        let request = Request::builder()
+            .uri(&url)
+            .method(Method::GET)
+            .body(AsyncBody::empty())
+            .context("Failed to build request")?;
+
+        let mut response = self.send(request).await.context("Failed to send request")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get replay list"));
+        }
+
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        let data: BasicResponse<Option<playback::ReplayList>> = serde_json::from_str(&body)?;
+        if data.code != 0 {
+            return Err(anyhow::anyhow!("获取回放列表失败，错误码: {}", data.code));
+        }
+
+        Ok(data.data.unwrap_or_default().replay_info)
+    }
+
+    /// 获取回放的分段流地址
+    pub async fn get_replay_streams(
+        &self,
+        replay: &playback::Replay,
+    ) -> Result<Vec<playback::ReplayStream>> {
+        let url = format!(
+            "https://api.live.bilibili.com/xlive/web-room/v1/videoService/GetUserSliceStream?live_key={}&start_time={}&end_time={}",
+            replay.live_key, replay.start_time, replay.end_time
+        );
+
+        let request = Request::builder()
+            .uri(&url)
+            .method(Method::GET)
+            .body(AsyncBody::empty())
+            .context("Failed to build request")?;
+
+        let mut response = self.send(request).await.context("Failed to send request")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Failed to get replay streams"));
+        }
+
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        let data: BasicResponse<Option<playback::ReplayStreamList>> = serde_json::from_str(&body)?;
+        if data.code != 0 {
+            return Err(anyhow::anyhow!("获取回放地址失败，错误码: {}", data.code));
+        }
+
+        Ok(data.data.unwrap_or_default().list)
+    }
+
+    async fn get_data<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<BasicResponse<T>> {
+        let request = Request::builder()
+            .uri(url)
+            .method(Method::GET)
+            .body(AsyncBody::empty())
+            .context("Failed to build request")?;
+
+        let mut response = self.send(request).await.context("Failed to send request")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to get {url}: {}",
+                response.status()
+            ));
+        }
+
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// 当前 Cookie 对应的账号，未登录或 Cookie 失效时 `is_login` 为 `false`
+    pub async fn get_nav(&self) -> Result<passport::NavData> {
+        let data: BasicResponse<passport::NavData> = self
+            .get_data("https://api.bilibili.com/x/web-interface/nav")
+            .await?;
+
+        Ok(data.data)
+    }
+
+    /// 申请扫码登录的二维码
+    pub async fn generate_login_qrcode(&self) -> Result<passport::LoginQrCode> {
+        let data: BasicResponse<passport::LoginQrCode> = self
+            .get_data("https://passport.bilibili.com/x/passport-login/web/qrcode/generate")
+            .await?;
+
+        Ok(data.data)
+    }
+
+    /// 查询扫码登录的状态
+    pub async fn poll_login_qrcode(&self, qrcode_key: &str) -> Result<passport::LoginQrCodePoll> {
+        let data: BasicResponse<passport::LoginQrCodePoll> = self
+            .get_data(&format!(
+                "https://passport.bilibili.com/x/passport-login/web/qrcode/poll?qrcode_key={qrcode_key}"
+            ))
+            .await?;
+
+        Ok(data.data)
+    }
 }
 
 impl Clone for HttpClient {
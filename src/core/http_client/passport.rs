@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// 二维码登录状态：登录成功
+pub const QRCODE_CONFIRMED: i32 = 0;
+/// 二维码登录状态：二维码已失效
+pub const QRCODE_EXPIRED: i32 = 86038;
+/// 二维码登录状态：已扫码，等待在手机上确认
+pub const QRCODE_SCANNED: i32 = 86090;
+/// 二维码登录状态：尚未扫码
+pub const QRCODE_WAITING: i32 = 86101;
+
+/// 申请到的登录二维码
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoginQrCode {
+    /// 二维码内容
+    pub url: String,
+    /// 轮询登录状态使用的 key
+    pub qrcode_key: String,
+}
+
+/// 二维码登录的轮询结果
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoginQrCodePoll {
+    /// 登录成功时的跳转地址，查询参数中带有 Cookie
+    pub url: String,
+    pub code: i32,
+    pub message: String,
+}
+
+/// 当前登录账号
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct NavData {
+    #[serde(rename = "isLogin")]
+    pub is_login: bool,
+    #[serde(default)]
+    pub mid: u64,
+    #[serde(default)]
+    pub uname: String,
+    /// 大会员状态，1 为有效
+    #[serde(rename = "vipStatus", default)]
+    pub vip_status: u8,
+}
+
+impl NavData {
+    pub fn is_vip(&self) -> bool {
+        self.vip_status == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nav_data() {
+        let nav: NavData = serde_json::from_str(r#"{"isLogin":false}"#).unwrap();
+        assert!(!nav.is_login);
+
+        let nav: NavData = serde_json::from_str(
+            r#"{"isLogin":true,"mid":42,"uname":"测试","vipStatus":1,"money":0}"#,
+        )
+        .unwrap();
+        assert!(nav.is_login && nav.is_vip());
+        assert_eq!(nav.mid, 42);
+    }
+}
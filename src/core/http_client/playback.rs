@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// 主播的直播回放列表
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReplayList {
+    #[serde(default)]
+    pub replay_info: Vec<Replay>,
+}
+
+/// 一场直播的回放
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Replay {
+    pub live_key: String,
+    /// 开播时间（秒级时间戳）
+    pub start_time: i64,
+    /// 下播时间（秒级时间戳）
+    pub end_time: i64,
+    #[serde(default)]
+    pub live_info: ReplayLiveInfo,
+    #[serde(default)]
+    pub video_info: ReplayVideoInfo,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReplayLiveInfo {
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReplayVideoInfo {
+    /// 回放生成状态，2 表示已生成
+    #[serde(default)]
+    pub replay_status: i32,
+    /// 回放时长（秒）
+    #[serde(default)]
+    pub duration: u64,
+}
+
+impl Replay {
+    /// 回放已生成，可以下载
+    pub fn is_ready(&self) -> bool {
+        self.video_info.replay_status == 2
+    }
+}
+
+/// 回放的分段流地址
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ReplayStreamList {
+    #[serde(default)]
+    pub list: Vec<ReplayStream>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReplayStream {
+    /// m3u8 地址
+    pub stream: String,
+    #[serde(default)]
+    pub start_time: i64,
+    #[serde(default)]
+    pub end_time: i64,
+}
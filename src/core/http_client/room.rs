@@ -27,7 +27,55 @@ pub struct LiveRoomInfoData {
     pub background: String,
     pub title: String,
     pub user_cover: String,
+    /// 直播中的关键帧截图
+    #[serde(default)]
+    pub keyframe: String,
     pub live_time: String,
     pub tags: String,
     pub area_name: String,
 }
+
+impl LiveRoomInfoData {
+    /// 父分区与子分区，如 `虚拟主播 · 虚拟日常`，缺少父分区时只有子分区
+    pub fn area_path(&self) -> String {
+        [self.parent_area_name.trim(), self.area_name.trim()]
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// 弹幕服务器信息
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct DanmuInfo {
+    /// 连接鉴权使用的 token
+    pub token: String,
+    pub host_list: Vec<DanmuHost>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DanmuHost {
+    pub host: String,
+    pub port: u16,
+    pub wss_port: u16,
+    pub ws_port: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area_path() {
+        let mut room_info = LiveRoomInfoData {
+            parent_area_name: "虚拟主播".to_string(),
+            area_name: "虚拟日常".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(room_info.area_path(), "虚拟主播 · 虚拟日常");
+
+        room_info.parent_area_name.clear();
+        assert_eq!(room_info.area_path(), "虚拟日常");
+    }
+}
@@ -0,0 +1,168 @@
+//! 预览解码的硬件加速
+//!
+//! 按平台优先选择 D3D11VA/DXVA2、VideoToolbox 或 VAAPI，ffmpeg 不支持时改用软解并回退到低清流，
+//! 避免软解 1080p60 占满 CPU。
+
+use std::{fmt, process::Command, sync::OnceLock};
+
+use crate::{core::repair::ffmpeg_path, settings::Quality};
+
+/// 软解时预览使用的画质
+pub const SOFTWARE_PREVIEW_QUALITY: Quality = Quality::HD;
+
+static AVAILABLE: OnceLock<Vec<HwAccel>> = OnceLock::new();
+
+/// ffmpeg 的硬件解码方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HwAccel {
+    D3d11va,
+    Dxva2,
+    VideoToolbox,
+    Vaapi,
+}
+
+impl HwAccel {
+    /// 当前平台按优先级排列的候选
+    pub fn platform_candidates() -> &'static [HwAccel] {
+        if cfg!(target_os = "windows") {
+            &[HwAccel::D3d11va, HwAccel::Dxva2]
+        } else if cfg!(target_os = "macos") {
+            &[HwAccel::VideoToolbox]
+        } else if cfg!(target_os = "linux") {
+            &[HwAccel::Vaapi]
+        } else {
+            &[]
+        }
+    }
+
+    /// ffmpeg `-hwaccel` 参数使用的名称
+    pub fn ffmpeg_name(self) -> &'static str {
+        match self {
+            HwAccel::D3d11va => "d3d11va",
+            HwAccel::Dxva2 => "dxva2",
+            HwAccel::VideoToolbox => "videotoolbox",
+            HwAccel::Vaapi => "vaapi",
+        }
+    }
+
+    fn from_ffmpeg_name(name: &str) -> Option<Self> {
+        match name {
+            "d3d11va" => Some(HwAccel::D3d11va),
+            "dxva2" => Some(HwAccel::Dxva2),
+            "videotoolbox" => Some(HwAccel::VideoToolbox),
+            "vaapi" => Some(HwAccel::Vaapi),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HwAccel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HwAccel::D3d11va => write!(f, "D3D11VA"),
+            HwAccel::Dxva2 => write!(f, "DXVA2"),
+            HwAccel::VideoToolbox => write!(f, "VideoToolbox"),
+            HwAccel::Vaapi => write!(f, "VAAPI"),
+        }
+    }
+}
+
+/// 解析 `ffmpeg -hwaccels` 的输出
+fn parse_hwaccels(output: &str) -> Vec<HwAccel> {
+    output
+        .lines()
+        .filter_map(|line| HwAccel::from_ffmpeg_name(line.trim()))
+        .collect()
+}
+
+/// ffmpeg 支持的硬件解码方式，首次调用时检测，需在后台线程调用
+pub fn available_hwaccels() -> &'static [HwAccel] {
+    AVAILABLE.get_or_init(|| {
+        let output = Command::new(ffmpeg_path())
+            .args(["-hide_banner", "-hwaccels"])
+            .output();
+
+        match output {
+            Ok(output) if output.status.success() => {
+                parse_hwaccels(&String::from_utf8_lossy(&output.stdout))
+            }
+            _ => {
+                tracing::warn!("无法获取 ffmpeg 支持的硬件解码方式");
+                vec![]
+            }
+        }
+    })
+}
+
+/// 预览的解码方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewDecode {
+    /// 为空时软解
+    pub hwaccel: Option<HwAccel>,
+    /// 拉取的画质
+    pub quality: Quality,
+}
+
+impl PreviewDecode {
+    /// 在可用的硬件解码中按平台优先级选择，没有时软解并把画质降到 [`SOFTWARE_PREVIEW_QUALITY`]
+    pub fn select(available: &[HwAccel], quality: Quality) -> Self {
+        let hwaccel = HwAccel::platform_candidates()
+            .iter()
+            .find(|candidate| available.contains(candidate))
+            .copied();
+
+        let quality = match hwaccel {
+            Some(_) => quality,
+            None if quality.to_quality() > SOFTWARE_PREVIEW_QUALITY.to_quality() => {
+                SOFTWARE_PREVIEW_QUALITY
+            }
+            None => quality,
+        };
+
+        Self { hwaccel, quality }
+    }
+
+    /// 放在 `-i` 之前的 ffmpeg 解码参数
+    pub fn ffmpeg_args(&self) -> Vec<&'static str> {
+        match self.hwaccel {
+            Some(hwaccel) => vec!["-hwaccel", hwaccel.ffmpeg_name()],
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hwaccels() {
+        let output =
+            "Hardware acceleration methods:\nvdpau\ncuda\nvaapi\nqsv\ndrm\nopencl\nvulkan\n";
+        assert_eq!(parse_hwaccels(output), vec![HwAccel::Vaapi]);
+        assert!(parse_hwaccels("Hardware acceleration methods:\n").is_empty());
+    }
+
+    #[test]
+    fn test_preview_decode_fallback() {
+        // 没有硬件解码时软解并降低画质
+        let decode = PreviewDecode::select(&[], Quality::Original);
+        assert_eq!(decode.hwaccel, None);
+        assert_eq!(decode.quality, SOFTWARE_PREVIEW_QUALITY);
+        assert!(decode.ffmpeg_args().is_empty());
+
+        // 本身画质已足够低时保持不变
+        assert_eq!(
+            PreviewDecode::select(&[], Quality::Smooth).quality,
+            Quality::Smooth
+        );
+
+        let available = HwAccel::platform_candidates();
+        let decode = PreviewDecode::select(available, Quality::Original);
+        if let Some(hwaccel) = available.first() {
+            assert_eq!(decode.hwaccel, Some(*hwaccel));
+            assert_eq!(decode.quality, Quality::Original);
+            assert_eq!(decode.ffmpeg_args(), ["-hwaccel", hwaccel.ffmpeg_name()]);
+        }
+    }
+}
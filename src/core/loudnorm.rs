@@ -0,0 +1,110 @@
+//! 响度标准化：用 ffmpeg loudnorm 滤镜（EBU R128）重编码音轨，视频流复制
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    core::{disk::extended_path, repair::ffmpeg_path},
+    settings::LoudnormSettings,
+};
+
+/// 标准化后的音频码率
+const AUDIO_BITRATE: &str = "192k";
+
+/// loudnorm 内部会升采样，输出时恢复为常用的采样率
+const AUDIO_SAMPLE_RATE: &str = "48000";
+
+/// 处理时使用的临时文件，如 `a.flv` -> `a.loudnorm.flv`
+fn loudnorm_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{stem}.loudnorm.{}", ext.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}.loudnorm")),
+    }
+}
+
+/// 输出文件之前的 ffmpeg 参数，超出范围的设置会被限制到 loudnorm 接受的区间
+pub fn loudnorm_args(settings: &LoudnormSettings) -> Vec<String> {
+    let filter = format!(
+        "loudnorm=I={}:TP={}:LRA={}",
+        settings.integrated.clamp(-70.0, -5.0),
+        settings.true_peak.clamp(-9.0, 0.0),
+        settings.lra.clamp(1.0, 50.0)
+    );
+
+    [
+        "-map",
+        "0",
+        "-c",
+        "copy",
+        "-af",
+        &filter,
+        "-c:a",
+        "aac",
+        "-b:a",
+        AUDIO_BITRATE,
+        "-ar",
+        AUDIO_SAMPLE_RATE,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+/// 标准化音频响度并替换原文件
+pub fn normalize_file(path: &Path, settings: &LoudnormSettings) -> Result<()> {
+    let path = PathBuf::from(extended_path(&path.to_string_lossy()));
+    let temp = loudnorm_path(&path);
+
+    let output = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-y", "-i"])
+        .arg(&path)
+        .args(loudnorm_args(settings))
+        .arg(&temp)
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp);
+        anyhow::bail!(
+            "ffmpeg 响度标准化失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    std::fs::rename(&temp, &path).context("无法替换响度标准化后的文件")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loudnorm_args() {
+        let args = loudnorm_args(&LoudnormSettings::default());
+        assert!(args.contains(&"loudnorm=I=-16:TP=-1.5:LRA=11".to_string()));
+
+        let settings = LoudnormSettings {
+            enabled: true,
+            integrated: -100.0,
+            true_peak: 3.0,
+            lra: 0.0,
+        };
+        assert!(loudnorm_args(&settings).contains(&"loudnorm=I=-70:TP=0:LRA=1".to_string()));
+    }
+
+    #[test]
+    fn test_loudnorm_path() {
+        assert_eq!(
+            loudnorm_path(Path::new("/录制/a.flv")),
+            PathBuf::from("/录制/a.loudnorm.flv")
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! 计费网络检测：Windows 读取当前联网配置的费用类型，Linux 读取 NetworkManager 的 `Metered` 属性（需启用 `dbus` 特性）
+
+/// 检测当前是否处于计费网络，无法判断时返回 `None`
+#[cfg(target_os = "windows")]
+pub fn is_metered() -> Option<bool> {
+    use windows::Networking::Connectivity::{NetworkCostType, NetworkInformation};
+
+    let profile = NetworkInformation::GetInternetConnectionProfile().ok()?;
+    let cost = profile.GetConnectionCost().ok()?;
+    let cost_type = cost.NetworkCostType().ok()?;
+
+    Some(
+        matches!(
+            cost_type,
+            NetworkCostType::Fixed | NetworkCostType::Variable
+        ) || cost.Roaming().unwrap_or(false)
+            || cost.OverDataLimit().unwrap_or(false),
+    )
+}
+
+/// 系统总线连接，定时检测时复用，连接断开后下次检测重新建立
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+static SYSTEM_BUS: parking_lot::Mutex<Option<zbus::blocking::Connection>> =
+    parking_lot::Mutex::new(None);
+
+/// 检测当前是否处于计费网络，无法判断时返回 `None`
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+pub fn is_metered() -> Option<bool> {
+    let mut bus = SYSTEM_BUS.lock();
+    if bus.is_none() {
+        *bus = zbus::blocking::Connection::system().ok();
+    }
+    let connection = bus.as_ref()?;
+
+    let metered = zbus::blocking::Proxy::new(
+        connection,
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+    )
+    .ok()
+    .and_then(|proxy| proxy.get_property::<u32>("Metered").ok());
+
+    // 查询失败可能只是 NetworkManager 未运行，总线本身也不可用时才丢弃连接
+    if metered.is_none()
+        && zbus::blocking::fdo::DBusProxy::new(connection)
+            .and_then(|proxy| proxy.get_id().map_err(zbus::Error::from))
+            .is_err()
+    {
+        *bus = None;
+    }
+
+    nm_metered(metered?)
+}
+
+/// 检测当前是否处于计费网络，无法判断时返回 `None`
+///
+/// macOS 只能通过 Network.framework 的路径监听得到 `isExpensive`，暂不支持
+#[cfg(not(any(target_os = "windows", all(target_os = "linux", feature = "dbus"))))]
+pub fn is_metered() -> Option<bool> {
+    None
+}
+
+/// NetworkManager 的 `NMMetered`：0 未知、1 是、2 否、3 推测是、4 推测否
+#[cfg_attr(not(all(target_os = "linux", feature = "dbus")), allow(dead_code))]
+fn nm_metered(value: u32) -> Option<bool> {
+    match value {
+        1 | 3 => Some(true),
+        2 | 4 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nm_metered() {
+        assert_eq!(nm_metered(0), None);
+        assert_eq!(nm_metered(3), Some(true));
+        assert_eq!(nm_metered(4), Some(false));
+    }
+}
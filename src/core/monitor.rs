@@ -0,0 +1,129 @@
+use std::{collections::VecDeque, time::Duration};
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, get_current_pid};
+
+/// 内存采样间隔
+pub const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// ffmpeg 子进程资源采样间隔
+pub const PROCESS_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 最多保留的采样点数（12 小时）
+const MAX_MEMORY_SAMPLES: usize = 720;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemorySample {
+    /// 采样时间（毫秒时间戳）
+    pub timestamp: i64,
+    /// 常驻内存（字节）
+    pub rss: u64,
+}
+
+/// 应用自身的资源占用监控
+#[derive(Debug, Clone, Default)]
+pub struct ResourceMonitor {
+    samples: VecDeque<MemorySample>,
+    alerting: bool,
+}
+
+impl ResourceMonitor {
+    /// 记录一次采样，首次超过阈值时返回 `true`，回落到阈值以下后可再次告警
+    pub fn record(&mut self, sample: MemorySample, threshold: Option<u64>) -> bool {
+        if self.samples.len() >= MAX_MEMORY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+
+        let exceeded = threshold.is_some_and(|threshold| sample.rss > threshold);
+        let alert = exceeded && !self.alerting;
+        self.alerting = exceeded;
+
+        alert
+    }
+
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &MemorySample> {
+        self.samples.iter()
+    }
+
+    pub fn latest(&self) -> Option<MemorySample> {
+        self.samples.back().copied()
+    }
+
+    pub fn peak(&self) -> Option<u64> {
+        self.samples.iter().map(|sample| sample.rss).max()
+    }
+}
+
+/// 当前进程的常驻内存（字节）
+pub fn process_memory() -> Option<u64> {
+    let pid = get_current_pid().ok()?;
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        ProcessRefreshKind::new().with_memory(),
+    );
+
+    system.process(pid).map(|process| process.memory())
+}
+
+/// 子进程的资源占用
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessUsage {
+    /// CPU 占用，以单核为 100%
+    pub cpu_usage: f32,
+    /// 常驻内存（字节）
+    pub memory: u64,
+}
+
+/// 子进程资源采样器，CPU 占用需要两次刷新之间的差值，因此需复用同一实例
+#[derive(Default)]
+pub struct ProcessSampler {
+    system: System,
+}
+
+impl ProcessSampler {
+    /// 采样指定进程，已退出的进程返回 `None`
+    pub fn sample(&mut self, pids: &[u32]) -> Vec<Option<ProcessUsage>> {
+        let pids = pids
+            .iter()
+            .map(|pid| Pid::from_u32(*pid))
+            .collect::<Vec<_>>();
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&pids),
+            ProcessRefreshKind::new().with_cpu().with_memory(),
+        );
+
+        pids.iter()
+            .map(|pid| {
+                self.system.process(*pid).map(|process| ProcessUsage {
+                    cpu_usage: process.cpu_usage(),
+                    memory: process.memory(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(rss: u64) -> MemorySample {
+        MemorySample { timestamp: 0, rss }
+    }
+
+    #[test]
+    fn test_memory_alert_once_until_recovered() {
+        let mut monitor = ResourceMonitor::default();
+        let threshold = Some(100);
+
+        assert!(!monitor.record(sample(50), threshold));
+        assert!(monitor.record(sample(150), threshold));
+        assert!(!monitor.record(sample(160), threshold));
+        assert!(!monitor.record(sample(80), threshold));
+        assert!(monitor.record(sample(120), threshold));
+        assert!(!monitor.record(sample(1000), None));
+
+        assert_eq!(monitor.peak(), Some(1000));
+    }
+}
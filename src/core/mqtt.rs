@@ -0,0 +1,320 @@
+//! MQTT 事件发布：开播与录制事件以 JSON 发布到 broker，便于 Home Assistant 等联动
+//!
+//! 事件稀疏，每次发布单独建立连接，只实现 MQTT 3.1.1 的 QoS 0 发布。
+//! 连接使用阻塞 IO，发布统一交给专用线程依次执行，不占用后台执行器
+
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{core::webhook::WebhookPayload, settings::MqttSettings, state::AppState};
+
+const DEFAULT_PORT: u16 = 1883;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 连接保持时间（秒），每次发布后立即断开，只需满足协议要求
+const KEEP_ALIVE: u16 = 60;
+
+/// 等待发布的消息上限，broker 长时间不可达时丢弃新消息
+const QUEUE_CAPACITY: usize = 256;
+
+struct PublishJob {
+    settings: MqttSettings,
+    topic: String,
+    body: Vec<u8>,
+    room_id: u64,
+}
+
+static QUEUE: LazyLock<Option<flume::Sender<PublishJob>>> = LazyLock::new(|| {
+    let (tx, rx) = flume::bounded::<PublishJob>(QUEUE_CAPACITY);
+
+    let spawned = std::thread::Builder::new()
+        .name("blive-mqtt".to_string())
+        .spawn(move || {
+            while let Ok(job) = rx.recv() {
+                if let Err(e) = publish(&job.settings, &job.topic, &job.body) {
+                    tracing::warn!(
+                        "MQTT 发布失败 - 房间: {}, 主题: {}, 错误: {}",
+                        job.room_id,
+                        job.topic,
+                        e
+                    );
+                }
+            }
+        });
+
+    match spawned {
+        Ok(_) => Some(tx),
+        Err(e) => {
+            tracing::warn!("启动 MQTT 发布线程失败: {}", e);
+            None
+        }
+    }
+});
+
+/// 剩余长度的变长编码
+fn encode_remaining_length(mut length: usize, buf: &mut Vec<u8>) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        buf.push(byte);
+
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+fn encode_string(value: &str, buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut buf = vec![header];
+    encode_remaining_length(body.len(), &mut buf);
+    buf.extend(body);
+    buf
+}
+
+fn connect_packet(client_id: &str, username: &str, password: &str) -> Vec<u8> {
+    let mut flags = 0x02; // clean session
+    if !username.is_empty() {
+        flags |= 0x80;
+        if !password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+
+    let mut body = Vec::new();
+    encode_string("MQTT", &mut body);
+    body.push(4); // 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE.to_be_bytes());
+    encode_string(client_id, &mut body);
+    if flags & 0x80 != 0 {
+        encode_string(username, &mut body);
+    }
+    if flags & 0x40 != 0 {
+        encode_string(password, &mut body);
+    }
+
+    packet(0x10, body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(topic, &mut body);
+    body.extend_from_slice(payload);
+
+    packet(0x30 | retain as u8, body)
+}
+
+/// 解析 `host`、`host:port` 或 `mqtt://host:port`
+fn parse_broker(broker: &str) -> Option<(String, u16)> {
+    let broker = broker.trim();
+    let broker = broker.strip_prefix("mqtt://").unwrap_or(broker);
+    let broker = broker.trim_end_matches('/');
+    if broker.is_empty() {
+        return None;
+    }
+
+    match broker.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() => Some((host.to_string(), port.parse().ok()?)),
+        Some(_) => None,
+        None => Some((broker.to_string(), DEFAULT_PORT)),
+    }
+}
+
+/// 主题层级分隔符与通配符不能出现在变量里，替换为 `_`
+fn escape_topic_level(value: &str) -> String {
+    value.replace(['/', '+', '#'], "_")
+}
+
+/// 渲染主题模板，支持 `{room_id}`、`{event}`、`{up_name}`
+pub fn render_topic(template: &str, payload: &WebhookPayload) -> String {
+    template
+        .replace("{room_id}", &payload.room_id.to_string())
+        .replace("{event}", payload.event.key())
+        .replace("{up_name}", &escape_topic_level(&payload.up_name))
+}
+
+/// 在配置的前缀后追加随机后缀，同一 ID 的连接会被 broker 踢下线
+fn unique_client_id(prefix: &str) -> String {
+    format!("{}-{:08x}", prefix.trim(), rand::random::<u32>())
+}
+
+/// 连接 broker 发布一条消息后断开
+pub fn publish(settings: &MqttSettings, topic: &str, payload: &[u8]) -> Result<()> {
+    let (host, port) = parse_broker(&settings.broker).context("无效的 MQTT broker 地址")?;
+    let addr = (host.as_str(), port)
+        .to_socket_addrs()
+        .context("无法解析 MQTT broker 地址")?
+        .next()
+        .context("无法解析 MQTT broker 地址")?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT).context("无法连接 MQTT broker")?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    stream.write_all(&connect_packet(
+        &unique_client_id(&settings.client_id),
+        &settings.username,
+        &settings.password,
+    ))?;
+
+    let mut connack = [0u8; 4];
+    stream
+        .read_exact(&mut connack)
+        .context("MQTT broker 未响应连接请求")?;
+    if connack[0] != 0x20 {
+        anyhow::bail!("MQTT broker 返回了非预期的响应");
+    }
+    if connack[3] != 0 {
+        anyhow::bail!("MQTT broker 拒绝连接，返回码: {}", connack[3]);
+    }
+
+    stream.write_all(&publish_packet(topic, payload, settings.retain))?;
+    stream.write_all(&[0xE0, 0x00])?;
+
+    Ok(())
+}
+
+/// 按设置发布事件，未配置 broker 时忽略，失败只记录日志
+pub fn dispatch(state: &AppState, payload: &WebhookPayload) {
+    let settings = state.settings.mqtt.clone();
+    if settings.broker.trim().is_empty() {
+        return;
+    }
+
+    let topic = render_topic(&settings.topic, payload);
+    let Ok(body) = serde_json::to_vec(payload) else {
+        return;
+    };
+    let room_id = payload.room_id;
+
+    let Some(queue) = QUEUE.as_ref() else {
+        return;
+    };
+    if queue
+        .try_send(PublishJob {
+            settings,
+            topic,
+            body,
+            room_id,
+        })
+        .is_err()
+    {
+        tracing::warn!("MQTT 发布队列已满，丢弃事件 - 房间: {}", room_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::settings::WebhookEvent;
+
+    #[test]
+    fn test_remaining_length() {
+        for (length, expected) in [
+            (0, vec![0x00]),
+            (127, vec![0x7F]),
+            (128, vec![0x80, 0x01]),
+            (16_383, vec![0xFF, 0x7F]),
+            (2_097_152, vec![0x80, 0x80, 0x80, 0x01]),
+        ] {
+            let mut buf = Vec::new();
+            encode_remaining_length(length, &mut buf);
+            assert_eq!(buf, expected, "length {length}");
+        }
+    }
+
+    #[test]
+    fn test_parse_broker() {
+        assert_eq!(
+            parse_broker("mqtt://192.168.1.2:1884/"),
+            Some(("192.168.1.2".to_string(), 1884))
+        );
+        assert_eq!(
+            parse_broker(" homeassistant.local "),
+            Some(("homeassistant.local".to_string(), DEFAULT_PORT))
+        );
+        assert_eq!(parse_broker(""), None);
+        assert_eq!(parse_broker("host:port"), None);
+    }
+
+    #[test]
+    fn test_render_topic() {
+        let payload = WebhookPayload::new(WebhookEvent::LiveStart, 732, "主播", "标题");
+        assert_eq!(
+            render_topic("blive/{room_id}/{event}", &payload),
+            "blive/732/live_start"
+        );
+
+        let payload = WebhookPayload::new(WebhookEvent::LiveStart, 732, "A/B+#", "标题");
+        assert_eq!(
+            render_topic("blive/{up_name}/{event}", &payload),
+            "blive/A_B__/live_start"
+        );
+    }
+
+    #[test]
+    fn test_unique_client_id() {
+        let first = unique_client_id("blive");
+        let second = unique_client_id("blive");
+        assert!(first.starts_with("blive-"));
+        assert_eq!(first.len(), "blive-".len() + 8);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_publish() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let broker = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut connect = [0u8; 2];
+            stream.read_exact(&mut connect).unwrap();
+            let mut body = vec![0u8; connect[1] as usize];
+            stream.read_exact(&mut body).unwrap();
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).unwrap();
+            (connect[0], body, rest)
+        });
+
+        let settings = MqttSettings {
+            broker: format!("127.0.0.1:{port}"),
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            retain: true,
+            ..Default::default()
+        };
+        publish(&settings, "blive/732/live_start", b"{}").unwrap();
+
+        let (header, connect, rest) = broker.join().unwrap();
+        assert_eq!(header, 0x10);
+        assert_eq!(connect[7], 0xC2);
+        assert_eq!(
+            rest,
+            [
+                publish_packet("blive/732/live_start", b"{}", true),
+                vec![0xE0, 0x00]
+            ]
+            .concat()
+        );
+    }
+}
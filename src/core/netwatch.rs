@@ -0,0 +1,68 @@
+//! 网络变化检测：定期比较本机网卡地址，切换 Wi-Fi、有线或开关 VPN 时地址会随之变化
+
+use std::{net::IpAddr, time::Duration};
+
+/// 网卡地址的检查间隔
+pub const NETWORK_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 一次网卡快照：非回环网卡的（名称, 地址），已排序
+pub type InterfaceSnapshot = Vec<(String, IpAddr)>;
+
+/// 读取当前网卡地址，链路本地地址在未联网时也会存在，不计入
+pub fn interface_snapshot() -> InterfaceSnapshot {
+    let mut snapshot = if_addrs::get_if_addrs()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|interface| !interface.is_loopback() && !is_link_local(&interface.ip()))
+        .map(|interface| (interface.name.clone(), interface.ip()))
+        .collect::<Vec<_>>();
+    snapshot.sort();
+
+    snapshot
+}
+
+fn is_link_local(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_link_local(),
+        IpAddr::V6(ip) => ip.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+/// 比较前后两次快照，判断网络是否发生切换
+#[derive(Debug, Default)]
+pub struct NetworkWatcher {
+    last: Option<InterfaceSnapshot>,
+}
+
+impl NetworkWatcher {
+    /// 记录一次快照，地址变化且仍有可用网卡时返回 `true`，首次记录不算变化
+    pub fn observe(&mut self, snapshot: InterfaceSnapshot) -> bool {
+        let changed = self.last.as_ref().is_some_and(|last| *last != snapshot);
+        let online = !snapshot.is_empty();
+        self.last = Some(snapshot);
+
+        changed && online
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_watcher() {
+        let wifi = vec![("wlan0".to_string(), "192.168.1.5".parse().unwrap())];
+        let wired = vec![("eth0".to_string(), "10.0.0.8".parse().unwrap())];
+        let mut watcher = NetworkWatcher::default();
+
+        assert!(!watcher.observe(wifi.clone()));
+        assert!(!watcher.observe(wifi));
+        // 断网时不触发，接上有线后触发
+        assert!(!watcher.observe(vec![]));
+        assert!(watcher.observe(wired));
+
+        assert!(is_link_local(&"fe80::1".parse().unwrap()));
+        assert!(is_link_local(&"169.254.0.1".parse().unwrap()));
+        assert!(!is_link_local(&"2001:db8::1".parse().unwrap()));
+    }
+}
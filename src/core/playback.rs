@@ -0,0 +1,118 @@
+//! 直播回放补录：房间开启官方回放时，下载回放的 m3u8 补齐漏录的场次
+
+use std::{path::Path, process::Command};
+
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use chrono_tz::Asia::Shanghai;
+
+use crate::{
+    core::{
+        downloader::{
+            ffmpeg_headers, ffmpeg_input,
+            metadata::RecordingMetadata,
+            template::{DownloaderFilenameTemplate, sanitize_filename},
+            transcode::ffmpeg_codec_args,
+        },
+        http_client::playback::Replay,
+        repair::ffmpeg_path,
+    },
+    settings::{Quality, StreamCodec, TranscodeSettings},
+};
+
+/// 回放文件名：沿用房间的命名模板，时间取回放的开播时间，末尾标注回放与分段
+pub fn replay_filename(
+    record_name: &str,
+    up_name: &str,
+    alias: &str,
+    room_id: u64,
+    replay: &Replay,
+    part: usize,
+    parts: usize,
+) -> String {
+    let start_time = DateTime::from_timestamp(replay.start_time, 0)
+        .unwrap_or_default()
+        .with_timezone(&Shanghai);
+
+    let values = DownloaderFilenameTemplate {
+        up_name: up_name.to_string(),
+        alias: alias.to_string(),
+        quality: Quality::default(),
+        room_id,
+        room_title: replay.live_info.title.clone(),
+        room_description: String::new(),
+        room_area_name: String::new(),
+        date: start_time.format("%Y-%m-%d").to_string(),
+        datetime: start_time.format("%Y-%m-%d %H点%M分").to_string(),
+        year: start_time.format("%Y").to_string(),
+    };
+
+    let filename = leon::Template::parse(record_name)
+        .or_else(|_| leon::Template::parse("{up_name}_{datetime}"))
+        .ok()
+        .and_then(|template| template.render(&values).ok())
+        .unwrap_or_else(|| format!("{room_id}_{}", values.datetime));
+    let filename = sanitize_filename(&filename);
+
+    if parts > 1 {
+        format!("{filename}_回放_P{part}")
+    } else {
+        format!("{filename}_回放")
+    }
+}
+
+/// 用 ffmpeg 流复制下载回放，参数与 HLS 录制一致
+pub fn download_replay(url: &str, output_path: &Path, metadata: &RecordingMetadata) -> Result<()> {
+    let (input, headers) = ffmpeg_input(url);
+
+    let output = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-n", "-headers"])
+        .arg(ffmpeg_headers(&headers))
+        .arg("-i")
+        .arg(input)
+        .args(ffmpeg_codec_args(
+            StreamCodec::default(),
+            &TranscodeSettings::default(),
+        ))
+        .args(metadata.ffmpeg_args())
+        .arg(output_path)
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "ffmpeg 下载回放失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::http_client::playback::ReplayLiveInfo, settings::DEFAULT_RECORD_NAME};
+
+    #[test]
+    fn test_replay_filename() {
+        let replay = Replay {
+            live_key: "key".to_string(),
+            start_time: 1_700_000_000,
+            end_time: 1_700_003_600,
+            live_info: ReplayLiveInfo {
+                title: "补档/测试".to_string(),
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            replay_filename(DEFAULT_RECORD_NAME, "主播", "", 732, &replay, 1, 1),
+            "主播_补档_测试_2023-11-15 06点13分_回放"
+        );
+        assert_eq!(
+            replay_filename(DEFAULT_RECORD_NAME, "主播", "", 732, &replay, 2, 3),
+            "主播_补档_测试_2023-11-15 06点13分_回放_P2"
+        );
+    }
+}
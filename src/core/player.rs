@@ -0,0 +1,116 @@
+//! 用外部播放器打开直播流或录制文件
+
+use std::{io, process::Command};
+
+use crate::core::downloader::{REFERER, USER_AGENT};
+
+/// 按空白拆分命令，双引号内的空白保留
+fn split_command(command: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_arg = false;
+
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+
+    args
+}
+
+/// 展开命令模板中的 `{url}`、`{user_agent}`、`{referer}`，模板中没有 `{url}` 时追加到末尾
+///
+/// 先拆分再替换，地址与 UA 中的空格不会破坏参数
+pub fn player_command(template: &str, url: &str) -> Option<(String, Vec<String>)> {
+    let mut args = split_command(template);
+    if args.is_empty() {
+        return None;
+    }
+
+    if !args.iter().any(|arg| arg.contains("{url}")) {
+        args.push("{url}".to_string());
+    }
+
+    let mut args = args.into_iter().map(|arg| {
+        arg.replace("{user_agent}", USER_AGENT)
+            .replace("{referer}", REFERER)
+            .replace("{url}", url)
+    });
+    let program = args.next()?;
+
+    Some((program, args.collect()))
+}
+
+/// 启动播放器，不等待其退出
+pub fn open_in_player(template: &str, url: &str) -> io::Result<()> {
+    let (program, args) = player_command(template, url)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "播放器命令为空"))?;
+
+    let mut child = Command::new(&program).args(&args).spawn()?;
+    tracing::info!("已启动外部播放器: {}", program);
+
+    // 回收子进程，避免播放器退出后留下僵尸进程
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::DEFAULT_PLAYER_COMMAND;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command(r#"  "C:\Program Files\PotPlayer\PotPlayerMini64.exe"  /new "" "#),
+            [
+                r"C:\Program Files\PotPlayer\PotPlayerMini64.exe",
+                "/new",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_player_command() {
+        let (program, args) =
+            player_command(DEFAULT_PLAYER_COMMAND, "https://example.com/live.flv?a=1").unwrap();
+        assert_eq!(program, "mpv");
+        assert_eq!(
+            args,
+            [
+                format!("--user-agent={USER_AGENT}"),
+                format!("--referrer={REFERER}"),
+                "https://example.com/live.flv?a=1".to_string(),
+            ]
+        );
+
+        // 没有 {url} 时追加到末尾，文件路径中的空格保留在同一个参数里
+        let (program, args) = player_command("vlc --play-and-exit", "/录制/a b.flv").unwrap();
+        assert_eq!(program, "vlc");
+        assert_eq!(args, ["--play-and-exit", "/录制/a b.flv"]);
+
+        assert!(player_command("   ", "a.flv").is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! 直播流画质/协议探测
+//!
+//! 探测请求（getRoomPlayInfo）统一经过调度器的 [`TaskPriority::Probe`] 额度，
+//! 结果在 [`PROBE_CACHE_TTL`] 内共享，同一房间同一画质不会重复请求。
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use parking_lot::Mutex;
+
+use crate::core::{
+    HttpClient,
+    http_client::stream::LiveRoomStreamUrl,
+    scheduler::{TaskPriority, scheduler},
+};
+
+/// 探测结果的有效期，流地址带有时效签名，不宜缓存过久
+pub const PROBE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+static PROBE_CACHE: LazyLock<ProbeCache> = LazyLock::new(|| ProbeCache::new(PROBE_CACHE_TTL));
+
+/// 全局探测结果缓存
+pub fn probe_cache() -> &'static ProbeCache {
+    &PROBE_CACHE
+}
+
+/// 按房间号与画质缓存的流信息
+#[derive(Debug)]
+pub struct ProbeCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(u64, u32), (Instant, Arc<LiveRoomStreamUrl>)>>,
+}
+
+impl ProbeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get_at(&self, room_id: u64, quality: u32, now: Instant) -> Option<Arc<LiveRoomStreamUrl>> {
+        let mut entries = self.entries.lock();
+        // 顺便清理过期的结果
+        entries.retain(|_, (fetched_at, _)| now.duration_since(*fetched_at) < self.ttl);
+
+        entries
+            .get(&(room_id, quality))
+            .map(|(_, info)| info.clone())
+    }
+
+    pub fn get(&self, room_id: u64, quality: u32) -> Option<Arc<LiveRoomStreamUrl>> {
+        self.get_at(room_id, quality, Instant::now())
+    }
+
+    fn insert_at(&self, room_id: u64, quality: u32, info: Arc<LiveRoomStreamUrl>, now: Instant) {
+        self.entries.lock().insert((room_id, quality), (now, info));
+    }
+
+    pub fn insert(&self, room_id: u64, quality: u32, info: Arc<LiveRoomStreamUrl>) {
+        self.insert_at(room_id, quality, info, Instant::now());
+    }
+
+    /// 清除房间的全部结果，如流地址已失效
+    pub fn invalidate(&self, room_id: u64) {
+        self.entries.lock().retain(|(id, _), _| *id != room_id);
+    }
+}
+
+/// 获取流信息，优先使用缓存
+pub async fn stream_info(
+    client: &HttpClient,
+    room_id: u64,
+    quality: u32,
+) -> Result<Arc<LiveRoomStreamUrl>> {
+    if let Some(info) = probe_cache().get(room_id, quality) {
+        return Ok(info);
+    }
+
+    let _permit = scheduler().acquire(TaskPriority::Probe).await;
+
+    // 排队期间可能已有其他任务探测过
+    if let Some(info) = probe_cache().get(room_id, quality) {
+        return Ok(info);
+    }
+
+    fetch(client, room_id, quality).await
+}
+
+/// 重新请求流信息并更新缓存，用于开始录制等需要最新地址的场景
+pub async fn refresh_stream_info(
+    client: &HttpClient,
+    room_id: u64,
+    quality: u32,
+) -> Result<Arc<LiveRoomStreamUrl>> {
+    let _permit = scheduler().acquire(TaskPriority::Probe).await;
+
+    fetch(client, room_id, quality).await
+}
+
+async fn fetch(client: &HttpClient, room_id: u64, quality: u32) -> Result<Arc<LiveRoomStreamUrl>> {
+    let info = Arc::new(client.get_live_room_stream_url(room_id, quality).await?);
+    probe_cache().insert(room_id, quality, info.clone());
+
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream_url(room_id: u64) -> Arc<LiveRoomStreamUrl> {
+        Arc::new(
+            serde_json::from_value(serde_json::json!({
+                "room_id": room_id,
+                "short_id": 0,
+                "uid": 1,
+                "is_hidden": false,
+                "is_locked": false,
+                "is_portrait": false,
+                "live_status": 1,
+                "hidden_till": 0,
+                "lock_till": 0,
+                "encrypted": false,
+                "pwd_verified": false,
+                "live_time": 0,
+                "room_shield": 0,
+                "all_special_types": [],
+                "playurl_info": null,
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_probe_cache() {
+        let cache = ProbeCache::new(Duration::from_secs(30));
+        let start = Instant::now();
+
+        cache.insert_at(1, 10000, stream_url(1), start);
+        cache.insert_at(2, 10000, stream_url(2), start);
+
+        // 画质不同视为不同结果
+        assert!(cache.get_at(1, 400, start).is_none());
+        assert_eq!(
+            cache
+                .get_at(1, 10000, start + Duration::from_secs(10))
+                .unwrap()
+                .room_id,
+            1
+        );
+
+        // 过期后重新探测
+        assert!(
+            cache
+                .get_at(1, 10000, start + Duration::from_secs(30))
+                .is_none()
+        );
+
+        cache.insert_at(1, 10000, stream_url(1), start);
+        cache.invalidate(1);
+        assert!(cache.get_at(1, 10000, start).is_none());
+    }
+}
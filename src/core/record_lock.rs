@@ -0,0 +1,166 @@
+//! 房间级录制锁
+//!
+//! 录制目录下的 `.blive-{room_id}.lock` 记录持锁进程的 PID 与最近心跳，多个实例（如 GUI 与 CLI）
+//! 共用录制目录时，同一房间只由持锁的实例录制。持锁进程退出后心跳不再更新，过期的锁可被接管
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::disk::extended_path;
+
+/// 录制中刷新心跳的间隔
+pub const LOCK_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 心跳超过该时长（毫秒）未更新的锁视为已失效
+const LOCK_STALE_AFTER_MS: i64 = 60_000;
+
+/// 锁文件内容
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LockInfo {
+    /// 持锁进程
+    pub pid: u32,
+    /// 最近一次心跳（毫秒时间戳）
+    pub heartbeat: i64,
+}
+
+impl LockInfo {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            heartbeat: chrono::Local::now().timestamp_millis(),
+        }
+    }
+
+    /// 由其他进程持有且心跳未过期
+    fn held_by_other(&self, pid: u32, now: i64) -> bool {
+        self.pid != pid && now - self.heartbeat < LOCK_STALE_AFTER_MS
+    }
+}
+
+/// 房间的锁文件路径
+pub fn lock_path(record_dir: &str, room_id: u64) -> PathBuf {
+    Path::new(record_dir).join(format!(".blive-{room_id}.lock"))
+}
+
+fn read_lock(path: &Path) -> Option<LockInfo> {
+    let content = std::fs::read_to_string(extended_path(&path.to_string_lossy())).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_lock(path: &Path, create_new: bool) -> io::Result<()> {
+    let content = serde_json::to_string(&LockInfo::current()).map_err(io::Error::other)?;
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(!create_new)
+        .create_new(create_new)
+        .open(extended_path(&path.to_string_lossy()))?;
+
+    file.write_all(content.as_bytes())
+}
+
+/// 获取房间的录制锁，其他实例正在录制时返回其锁信息
+///
+/// 锁文件不存在时独占创建；已存在但属于本进程或已过期时直接覆盖。锁文件无法读写时不阻止录制
+pub fn acquire(record_dir: &str, room_id: u64) -> Result<(), LockInfo> {
+    let path = lock_path(record_dir, room_id);
+    if let Err(e) = std::fs::create_dir_all(extended_path(record_dir)) {
+        tracing::warn!("无法创建录制目录，跳过录制锁: {}, 错误: {}", record_dir, e);
+        return Ok(());
+    }
+
+    let result = match write_lock(&path, true) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            if let Some(holder) = read_lock(&path)
+                && holder.held_by_other(std::process::id(), chrono::Local::now().timestamp_millis())
+            {
+                return Err(holder);
+            }
+
+            write_lock(&path, false)
+        }
+        result => result,
+    };
+
+    if let Err(e) = result {
+        tracing::warn!("写入录制锁失败: {}, 错误: {}", path.display(), e);
+    }
+
+    Ok(())
+}
+
+/// 刷新本进程持有的锁的心跳，锁已被其他实例接管时返回 `false`
+pub fn refresh(record_dir: &str, room_id: u64) -> bool {
+    let path = lock_path(record_dir, room_id);
+    if read_lock(&path).is_some_and(|holder| holder.pid != std::process::id()) {
+        return false;
+    }
+
+    if let Err(e) = write_lock(&path, false) {
+        tracing::warn!("刷新录制锁失败: {}, 错误: {}", path.display(), e);
+    }
+
+    true
+}
+
+/// 释放本进程持有的锁
+pub fn release(record_dir: &str, room_id: u64) {
+    let path = lock_path(record_dir, room_id);
+    if read_lock(&path).is_some_and(|holder| holder.pid == std::process::id()) {
+        let _ = std::fs::remove_file(extended_path(&path.to_string_lossy()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_held_by_other() {
+        let lock = LockInfo {
+            pid: 1,
+            heartbeat: 100_000,
+        };
+
+        assert!(lock.held_by_other(2, 100_000 + LOCK_STALE_AFTER_MS - 1));
+        // 本进程持有或心跳过期时可以接管
+        assert!(!lock.held_by_other(1, 100_000));
+        assert!(!lock.held_by_other(2, 100_000 + LOCK_STALE_AFTER_MS));
+    }
+
+    #[test]
+    fn test_acquire_release() {
+        let dir = std::env::temp_dir().join(format!("blive-lock-{}", std::process::id()));
+        let record_dir = dir.to_string_lossy().to_string();
+        let path = lock_path(&record_dir, 1);
+
+        assert!(acquire(&record_dir, 1).is_ok());
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+        // 本进程重复获取不受影响
+        assert!(acquire(&record_dir, 1).is_ok());
+
+        // 其他进程的新鲜心跳会阻止录制
+        let other = LockInfo {
+            pid: std::process::id() + 1,
+            heartbeat: chrono::Local::now().timestamp_millis(),
+        };
+        std::fs::write(&path, serde_json::to_string(&other).unwrap()).unwrap();
+        assert_eq!(acquire(&record_dir, 1), Err(other));
+        assert!(!refresh(&record_dir, 1));
+        release(&record_dir, 1);
+        assert!(path.exists());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(acquire(&record_dir, 1).is_ok());
+        release(&record_dir, 1);
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,341 @@
+//! 本地转发：录制时在 127.0.0.1 上提供正在写入的录制文件，播放器可边录边看而不额外拉取直播流
+//!
+//! 地址为 `http://127.0.0.1:{port}/live/{room_id}`。FLV 从最近的关键帧开始发送以便直接看直播，
+//! 其他格式从文件开头发送，可在播放器中跳到末尾。
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+
+use crate::core::downloader::part_file::final_path;
+
+/// 追上文件末尾后等待新数据的间隔
+const TAIL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 正在录制的文件，按房间号索引
+static ACTIVE: LazyLock<Mutex<HashMap<u64, PathBuf>>> = LazyLock::new(Default::default);
+
+/// 开始录制时登记文件
+pub fn register(room_id: u64, path: &Path) {
+    ACTIVE.lock().insert(room_id, path.to_path_buf());
+}
+
+/// 录制结束时注销，已切换到新文件时保持不变
+pub fn unregister(room_id: u64, path: &Path) {
+    let mut active = ACTIVE.lock();
+    if active.get(&room_id).is_some_and(|active| active == path) {
+        active.remove(&room_id);
+    }
+}
+
+/// 录制出错或停止时注销房间，不论登记的是哪个文件
+pub fn unregister_room(room_id: u64) {
+    ACTIVE.lock().remove(&room_id);
+}
+
+fn current(room_id: u64) -> Option<PathBuf> {
+    ACTIVE.lock().get(&room_id).cloned()
+}
+
+/// 房间的本地播放地址
+pub fn relay_url(port: u16, room_id: u64) -> String {
+    format!("http://127.0.0.1:{port}/live/{room_id}")
+}
+
+/// 在本机端口上启动转发服务
+pub fn serve(port: u16) -> io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    tracing::info!("本地转发已启动: http://127.0.0.1:{}/live/{{房间号}}", port);
+
+    std::thread::Builder::new()
+        .name("relay".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // 每个播放器连接会持续到录制结束，单独占用一个线程
+                let spawned = std::thread::Builder::new()
+                    .name("relay-conn".to_string())
+                    .spawn(move || {
+                        if let Err(e) = handle_connection(stream) {
+                            tracing::debug!("本地转发连接结束: {}", e);
+                        }
+                    });
+
+                if let Err(e) = spawned {
+                    tracing::warn!("本地转发无法处理新连接: {}", e);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let Some((room_id, path)) = parse_request(&request_line)
+        .and_then(|room_id| current(room_id).map(|path| (room_id, path)))
+    else {
+        return write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+    };
+
+    let mut file = File::open(&path)?;
+    let content_type = content_type(&path);
+    let is_flv = content_type == "video/x-flv";
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+    )?;
+
+    if is_flv {
+        let len = file.metadata()?.len();
+        let start = flv_live_start(&mut BufReader::new(&file), len)?;
+        stream.write_all(&start.prefix)?;
+        file.seek(SeekFrom::Start(start.offset))?;
+    }
+
+    tracing::info!("本地转发开始 - 房间: {}, 文件: {}", room_id, path.display());
+
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read > 0 {
+            stream.write_all(&buffer[..bytes_read])?;
+            continue;
+        }
+
+        // 录制结束或已切换到新文件，发送剩余数据后关闭连接
+        if current(room_id).as_deref() != Some(path.as_path()) {
+            io::copy(&mut file, &mut stream)?;
+            return Ok(());
+        }
+
+        std::thread::sleep(TAIL_INTERVAL);
+    }
+}
+
+/// 按录制文件（去掉临时文件后缀）的扩展名返回 Content-Type
+fn content_type(path: &Path) -> &'static str {
+    let extension = Path::new(final_path(&path.to_string_lossy()))
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("flv") => "video/x-flv",
+        Some("mkv") => "video/x-matroska",
+        Some("mp4") => "video/mp4",
+        Some("ts") => "video/mp2t",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 解析 `GET /live/{room_id}`，允许带扩展名或查询参数
+fn parse_request(request_line: &str) -> Option<u64> {
+    let mut parts = request_line.split_whitespace();
+    if parts.next() != Some("GET") {
+        return None;
+    }
+
+    let room_id = parts.next()?.strip_prefix("/live/")?;
+    let end = room_id.find(['.', '?']).unwrap_or(room_id.len());
+    room_id[..end].parse().ok()
+}
+
+/// 从直播位置开始播放 FLV 所需的数据
+#[derive(Debug, PartialEq, Eq)]
+struct FlvStart {
+    /// 文件头、onMetaData 与音视频序列头
+    prefix: Vec<u8>,
+    /// 之后从该偏移继续发送
+    offset: u64,
+}
+
+/// 扫描 FLV 的 tag 头，找到最后一个完整的视频关键帧
+///
+/// 播放器需要先收到文件头与序列头才能解码，因此把它们放在关键帧之前发送；
+/// 找不到关键帧时从头发送。只读取 tag 头并相对跳过数据，避免大文件整体读入
+fn flv_live_start<R: Read + Seek>(reader: &mut BufReader<R>, len: u64) -> io::Result<FlvStart> {
+    let from_start = FlvStart {
+        prefix: vec![],
+        offset: 0,
+    };
+
+    let mut prefix = vec![0; 9];
+    if len < 13 || reader.read_exact(&mut prefix).is_err() || &prefix[..3] != b"FLV" {
+        return Ok(from_start);
+    }
+
+    // 文件头之后是 4 字节的 PreviousTagSize0
+    let data_offset = u32::from_be_bytes([prefix[5], prefix[6], prefix[7], prefix[8]]) as usize;
+    if data_offset < 9 || data_offset as u64 + 4 > len {
+        return Ok(from_start);
+    }
+    prefix.resize(data_offset + 4, 0);
+    reader.read_exact(&mut prefix[9..])?;
+
+    let mut script = None;
+    let mut video_header = None;
+    let mut audio_header = None;
+    let mut keyframe = None;
+
+    let mut pos = prefix.len() as u64;
+    while pos + 11 <= len {
+        let mut tag = [0; 13];
+        reader.read_exact(&mut tag[..11])?;
+        let mut consumed = 11;
+
+        let data_size = u32::from_be_bytes([0, tag[1], tag[2], tag[3]]) as u64;
+        let tag_size = 11 + data_size + 4;
+        // 最后一个 tag 可能还没写完
+        if pos + tag_size > len {
+            break;
+        }
+        if data_size >= 2 {
+            reader.read_exact(&mut tag[11..])?;
+            consumed = 13;
+        }
+
+        let slot = match tag[0] & 0x1f {
+            // 脚本数据，只取第一个 onMetaData
+            18 if script.is_none() => Some(&mut script),
+            9 if data_size >= 2 => {
+                // Enhanced FLV 的 HEVC 用高位标记扩展头，包类型在低四位
+                let extended = tag[11] & 0x80 != 0;
+                let sequence_header = if extended {
+                    tag[11] & 0x0f == 0
+                } else {
+                    tag[12] == 0
+                };
+
+                if !sequence_header && (tag[11] >> 4) & 0x07 == 1 {
+                    keyframe = Some(pos);
+                }
+                sequence_header.then_some(&mut video_header)
+            }
+            // AAC 序列头
+            8 if data_size >= 2 && tag[11] >> 4 == 10 && tag[12] == 0 => Some(&mut audio_header),
+            _ => None,
+        };
+
+        match slot {
+            Some(slot) => {
+                let mut bytes = vec![0; tag_size as usize];
+                reader.seek_relative(-consumed)?;
+                reader.read_exact(&mut bytes)?;
+                *slot = Some(bytes);
+            }
+            None => reader.seek_relative(tag_size as i64 - consumed)?,
+        }
+
+        pos += tag_size;
+    }
+
+    let Some(offset) = keyframe else {
+        return Ok(from_start);
+    };
+
+    for tag in [script, video_header, audio_header].into_iter().flatten() {
+        prefix.extend(tag);
+    }
+
+    Ok(FlvStart { prefix, offset })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn flv_tag(tag_type: u8, data: &[u8]) -> Vec<u8> {
+        let size = data.len() as u32;
+        let mut tag = vec![tag_type];
+        tag.extend(&size.to_be_bytes()[1..]);
+        tag.extend([0; 7]);
+        tag.extend(data);
+        tag.extend((11 + size).to_be_bytes());
+        tag
+    }
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(parse_request("GET /live/123 HTTP/1.1\r\n"), Some(123));
+        assert_eq!(
+            parse_request("GET /live/123.flv?t=1 HTTP/1.1\r\n"),
+            Some(123)
+        );
+        assert_eq!(parse_request("GET /healthz HTTP/1.1\r\n"), None);
+        assert_eq!(parse_request("POST /live/123 HTTP/1.1\r\n"), None);
+        assert_eq!(parse_request(""), None);
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type(Path::new("a/录像.flv.part")), "video/x-flv");
+        assert_eq!(content_type(Path::new("a/录像.MKV")), "video/x-matroska");
+        assert_eq!(content_type(Path::new("a/录像.mp4.part")), "video/mp4");
+        assert_eq!(content_type(Path::new("a/录像.ts")), "video/mp2t");
+        assert_eq!(
+            content_type(Path::new("a/录像")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_flv_live_start() {
+        let header = b"FLV\x01\x05\x00\x00\x00\x09\x00\x00\x00\x00".to_vec();
+        let script = flv_tag(18, b"onMetaData");
+        let video_header = flv_tag(9, &[0x17, 0x00, 1, 2]);
+        let audio_header = flv_tag(8, &[0xaf, 0x00, 3]);
+        let keyframe = flv_tag(9, &[0x17, 0x01, 4]);
+        let inter_frame = flv_tag(9, &[0x27, 0x01, 5]);
+        let audio = flv_tag(8, &[0xaf, 0x01, 6]);
+
+        let mut file = [
+            header.as_slice(),
+            &script,
+            &video_header,
+            &audio_header,
+            &keyframe,
+            &inter_frame,
+        ]
+        .concat();
+        let last_keyframe = file.len() as u64;
+        file.extend([keyframe.as_slice(), &audio, &inter_frame].concat());
+        // 未写完的 tag 不参与扫描
+        file.extend(&flv_tag(9, &[0x17, 0x01, 7])[..8]);
+
+        let len = file.len() as u64;
+        let start = flv_live_start(&mut BufReader::new(Cursor::new(file)), len).unwrap();
+        assert_eq!(start.offset, last_keyframe);
+        assert_eq!(
+            start.prefix,
+            [header.clone(), script, video_header, audio_header].concat()
+        );
+
+        // 没有关键帧时从头发送
+        let file = [header, audio].concat();
+        let len = file.len() as u64;
+        assert_eq!(
+            flv_live_start(&mut BufReader::new(Cursor::new(file)), len).unwrap(),
+            FlvStart {
+                prefix: vec![],
+                offset: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        disk::extended_path,
+        downloader::part_file,
+        ffmpeg::ensure_ffmpeg,
+        history::{self, HistoryEvent, HistoryRecord},
+    },
+    settings::config_dir,
+};
+
+/// 保护未收尾文件列表的读写
+static UNFINISHED_LOCK: Mutex<()> = Mutex::new(());
+
+/// 正在写入、尚未正常收尾的录制文件列表
+fn unfinished_file() -> PathBuf {
+    config_dir().join("unfinished.json")
+}
+
+/// ffmpeg 可执行文件路径
+pub fn ffmpeg_path() -> PathBuf {
+    #[cfg(feature = "ffmpeg")]
+    {
+        ffmpeg_sidecar::paths::ffmpeg_path()
+    }
+    #[cfg(not(feature = "ffmpeg"))]
+    {
+        PathBuf::from("ffmpeg")
+    }
+}
+
+/// 一个尚未正常收尾的录制文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UnfinishedFile {
+    room_id: u64,
+    file_path: String,
+}
+
+fn load_unfinished() -> Vec<UnfinishedFile> {
+    std::fs::read_to_string(unfinished_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_unfinished(files: &[UnfinishedFile]) {
+    let result = serde_json::to_string_pretty(files)
+        .map_err(std::io::Error::other)
+        .and_then(|content| {
+            let path = unfinished_file();
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            std::fs::write(path, content)
+        });
+
+    if let Err(e) = result {
+        tracing::warn!("写入未收尾文件列表失败: {}", e);
+    }
+}
+
+/// 开始写入录制文件时登记，正常完成前异常退出的文件会在下次启动时修复
+pub fn mark_unfinished(room_id: u64, file_path: &str) {
+    let _guard = UNFINISHED_LOCK.lock();
+    let mut files = load_unfinished();
+
+    if !files.iter().any(|file| file.file_path == file_path) {
+        files.push(UnfinishedFile {
+            room_id,
+            file_path: file_path.to_string(),
+        });
+        save_unfinished(&files);
+    }
+}
+
+/// 录制文件正常收尾后移除登记
+pub fn mark_finished(file_path: &str) {
+    let _guard = UNFINISHED_LOCK.lock();
+    let mut files = load_unfinished();
+    let len = files.len();
+
+    files.retain(|file| file.file_path != file_path);
+    if files.len() != len {
+        save_unfinished(&files);
+    }
+}
+
+/// 修复时使用的临时文件，如 `a.flv` -> `a.repairing.flv`
+fn repairing_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{stem}.repairing.{}", ext.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}.repairing")),
+    }
+}
+
+/// 用 ffmpeg 重新封装文件，补全时长与索引
+pub fn repair_file(path: &Path) -> Result<()> {
+    remux_file(path, &[])
+}
+
+/// 用 ffmpeg 流复制重新封装并替换原文件，`extra_args` 加在输出文件之前，如 `-metadata`
+pub fn remux_file(path: &Path, extra_args: &[String]) -> Result<()> {
+    // 扩展路径派生出的临时文件路径同样是扩展路径
+    let path = PathBuf::from(extended_path(&path.to_string_lossy()));
+    let temp = repairing_path(&path);
+
+    let output = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-y", "-i"])
+        .arg(&path)
+        .args(["-map", "0", "-c", "copy"])
+        .args(extra_args)
+        .arg(&temp)
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp);
+        anyhow::bail!(
+            "ffmpeg 重新封装失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    std::fs::rename(&temp, &path).context("无法替换修复后的文件")?;
+
+    Ok(())
+}
+
+/// 修复上次异常中断时遗留的录制文件，返回修复成功的文件
+pub fn repair_unfinished() -> Vec<PathBuf> {
+    let files = {
+        let _guard = UNFINISHED_LOCK.lock();
+        let files = load_unfinished();
+        if files.is_empty() {
+            return vec![];
+        }
+
+        // 确实有文件需要修复时才检测 ffmpeg，不可用时保留列表留待下次
+        if let Err(e) = ensure_ffmpeg() {
+            tracing::warn!("ffmpeg 不可用，暂不修复异常中断的录制文件: {}", e);
+            return vec![];
+        }
+
+        save_unfinished(&[]);
+        files
+    };
+
+    let mut repaired = vec![];
+    for UnfinishedFile { room_id, file_path } in files {
+        let path = PathBuf::from(&file_path);
+        if !path.metadata().is_ok_and(|meta| meta.len() > 0) {
+            continue;
+        }
+
+        // 录制中的临时文件先恢复为最终文件名，再按扩展名修复
+        let file_path = match part_file::finalize(&file_path) {
+            Ok(file_path) => file_path,
+            Err(e) => {
+                tracing::warn!("恢复录制文件名失败: {}, 错误: {}", file_path, e);
+                continue;
+            }
+        };
+        let path = PathBuf::from(&file_path);
+
+        match repair_file(&path) {
+            Ok(()) => {
+                tracing::info!("已修复异常中断的录制文件: {}", file_path);
+                history::append_record(&HistoryRecord::now(
+                    room_id,
+                    HistoryEvent::Repaired { file_path },
+                ));
+                repaired.push(path);
+            }
+            Err(e) => tracing::warn!("修复录制文件失败: {}, 错误: {}", file_path, e),
+        }
+    }
+
+    repaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repairing_path() {
+        assert_eq!(
+            repairing_path(Path::new("/rec/主播_2025-08-10.flv")),
+            PathBuf::from("/rec/主播_2025-08-10.repairing.flv")
+        );
+        assert_eq!(
+            repairing_path(Path::new("/rec/video")),
+            PathBuf::from("/rec/video.repairing")
+        );
+    }
+}
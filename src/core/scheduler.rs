@@ -0,0 +1,379 @@
+//! 后台任务调度，按优先级分配并发额度
+//!
+//! 录制 IO 不受限制，只做统计；其余任务共享 [`DEFAULT_POOL_SIZE`] 个并发额度，
+//! 额度释放时优先交给优先级更高的等待者。部分任务另有自身的并发上限，
+//! 如画质探测最多 [`PROBE_CONCURRENCY`] 个，避免集中请求触发风控。
+
+use std::{collections::VecDeque, fmt, sync::Arc, sync::LazyLock};
+
+use futures::channel::oneshot;
+use parking_lot::Mutex;
+
+/// 轮询、上传、清理等任务共享的并发额度
+pub const DEFAULT_POOL_SIZE: usize = 8;
+
+/// 画质/协议探测的并发上限
+pub const PROBE_CONCURRENCY: usize = 3;
+
+const PRIORITY_COUNT: usize = 5;
+
+static SCHEDULER: LazyLock<TaskScheduler> = LazyLock::new(|| TaskScheduler::new(DEFAULT_POOL_SIZE));
+
+/// 全局调度器
+pub fn scheduler() -> &'static TaskScheduler {
+    &SCHEDULER
+}
+
+/// 任务优先级，从高到低排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TaskPriority {
+    /// 录制读写
+    RecordingIo,
+    /// 房间状态轮询
+    Polling,
+    /// 画质/协议探测
+    Probe,
+    /// 上传
+    Upload,
+    /// 日志、缓存清理
+    Cleanup,
+}
+
+impl TaskPriority {
+    pub const ALL: [TaskPriority; PRIORITY_COUNT] = [
+        TaskPriority::RecordingIo,
+        TaskPriority::Polling,
+        TaskPriority::Probe,
+        TaskPriority::Upload,
+        TaskPriority::Cleanup,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 是否占用共享额度
+    fn pooled(self) -> bool {
+        self != TaskPriority::RecordingIo
+    }
+
+    /// 该优先级自身的并发上限
+    fn limit(self) -> Option<usize> {
+        match self {
+            TaskPriority::Probe => Some(PROBE_CONCURRENCY),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskPriority::RecordingIo => write!(f, "录制 IO"),
+            TaskPriority::Polling => write!(f, "轮询"),
+            TaskPriority::Probe => write!(f, "探测"),
+            TaskPriority::Upload => write!(f, "上传"),
+            TaskPriority::Cleanup => write!(f, "清理"),
+        }
+    }
+}
+
+/// 某一优先级的任务统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskStats {
+    pub running: usize,
+    pub waiting: usize,
+    pub completed: u64,
+}
+
+/// 调度器状态快照，供诊断页展示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerSnapshot {
+    pub pool_size: usize,
+    pub tasks: Vec<(TaskPriority, TaskStats)>,
+}
+
+#[derive(Default)]
+struct Inner {
+    pool_size: usize,
+    running: [usize; PRIORITY_COUNT],
+    completed: [u64; PRIORITY_COUNT],
+    waiting: [VecDeque<oneshot::Sender<()>>; PRIORITY_COUNT],
+}
+
+impl Inner {
+    fn pooled_running(&self) -> usize {
+        TaskPriority::ALL
+            .iter()
+            .filter(|priority| priority.pooled())
+            .map(|priority| self.running[priority.index()])
+            .sum()
+    }
+
+    /// 共享额度与该优先级自身的上限是否都还有空余
+    fn has_capacity(&self, priority: TaskPriority) -> bool {
+        self.pooled_running() < self.pool_size
+            && priority
+                .limit()
+                .is_none_or(|limit| self.running[priority.index()] < limit)
+    }
+
+    /// 把空出的额度按优先级交给等待者
+    fn dispatch(&mut self) {
+        for priority in TaskPriority::ALL
+            .iter()
+            .filter(|priority| priority.pooled())
+        {
+            let index = priority.index();
+            while self.has_capacity(*priority) {
+                let Some(waiter) = self.waiting[index].pop_front() else {
+                    break;
+                };
+
+                // 等待者已取消时跳过
+                if waiter.send(()).is_ok() {
+                    self.running[index] += 1;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TaskScheduler {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl fmt::Debug for TaskScheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskScheduler")
+            .field("snapshot", &self.snapshot())
+            .finish()
+    }
+}
+
+impl TaskScheduler {
+    pub fn new(pool_size: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                pool_size: pool_size.max(1),
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// 等待执行额度，额度在返回值被丢弃时归还
+    pub async fn acquire(&self, priority: TaskPriority) -> TaskPermit {
+        let rx = {
+            let mut inner = self.inner.lock();
+            // 同优先级已有人排队时不插队
+            let queued = !inner.waiting[priority.index()].is_empty();
+            if !priority.pooled() || (!queued && inner.has_capacity(priority)) {
+                inner.running[priority.index()] += 1;
+                return self.permit(priority);
+            }
+
+            let (tx, rx) = oneshot::channel();
+            inner.waiting[priority.index()].push_back(tx);
+            // 队列中可能只剩已取消的等待者，此时额度立即可用
+            inner.dispatch();
+            rx
+        };
+
+        let mut pending = PendingPermit {
+            scheduler: self,
+            priority,
+            rx: Some(rx),
+        };
+
+        if let Some(rx) = pending.rx.as_mut() {
+            // 发送端由调度器持有，只会在分配额度后发送
+            let _ = rx.await;
+        }
+        pending.rx = None;
+
+        self.permit(priority)
+    }
+
+    fn permit(&self, priority: TaskPriority) -> TaskPermit {
+        TaskPermit {
+            scheduler: self.clone(),
+            priority,
+        }
+    }
+
+    fn release(&self, priority: TaskPriority, completed: bool) {
+        let mut inner = self.inner.lock();
+        inner.running[priority.index()] -= 1;
+        if completed {
+            inner.completed[priority.index()] += 1;
+        }
+        inner.dispatch();
+    }
+
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        let mut inner = self.inner.lock();
+        // 顺便清理已取消的等待者
+        for waiting in inner.waiting.iter_mut() {
+            waiting.retain(|waiter| !waiter.is_canceled());
+        }
+
+        SchedulerSnapshot {
+            pool_size: inner.pool_size,
+            tasks: TaskPriority::ALL
+                .iter()
+                .map(|priority| {
+                    let index = priority.index();
+                    (
+                        *priority,
+                        TaskStats {
+                            running: inner.running[index],
+                            waiting: inner.waiting[index].len(),
+                            completed: inner.completed[index],
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 排队中的请求，未拿到额度就被取消时负责归还已分配的额度
+struct PendingPermit<'a> {
+    scheduler: &'a TaskScheduler,
+    priority: TaskPriority,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for PendingPermit<'_> {
+    fn drop(&mut self) {
+        if let Some(mut rx) = self.rx.take()
+            && let Ok(Some(())) = rx.try_recv()
+        {
+            self.scheduler.release(self.priority, false);
+        }
+    }
+}
+
+/// 执行额度
+pub struct TaskPermit {
+    scheduler: TaskScheduler,
+    priority: TaskPriority,
+}
+
+impl fmt::Debug for TaskPermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskPermit")
+            .field("priority", &self.priority)
+            .finish()
+    }
+}
+
+impl Drop for TaskPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(self.priority, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    fn stats(scheduler: &TaskScheduler, priority: TaskPriority) -> TaskStats {
+        scheduler
+            .snapshot()
+            .tasks
+            .into_iter()
+            .find(|(p, _)| *p == priority)
+            .map(|(_, stats)| stats)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scheduler_priority() {
+        let scheduler = TaskScheduler::new(1);
+
+        // 录制 IO 不占用共享额度
+        let recording = scheduler
+            .acquire(TaskPriority::RecordingIo)
+            .now_or_never()
+            .unwrap();
+        let polling = scheduler
+            .acquire(TaskPriority::Polling)
+            .now_or_never()
+            .unwrap();
+
+        let mut cleanup = Box::pin(scheduler.acquire(TaskPriority::Cleanup));
+        let mut upload = Box::pin(scheduler.acquire(TaskPriority::Upload));
+        assert!((&mut cleanup).now_or_never().is_none());
+        assert!((&mut upload).now_or_never().is_none());
+        assert_eq!(stats(&scheduler, TaskPriority::Cleanup).waiting, 1);
+
+        // 额度释放后先交给优先级更高的上传
+        drop(polling);
+        assert!((&mut cleanup).now_or_never().is_none());
+        let upload = upload.now_or_never().unwrap();
+
+        drop(upload);
+        let cleanup = cleanup.now_or_never().unwrap();
+        drop(cleanup);
+        drop(recording);
+
+        let snapshot = scheduler.snapshot();
+        assert!(snapshot.tasks.iter().all(|(_, stats)| stats.running == 0));
+        assert_eq!(stats(&scheduler, TaskPriority::Polling).completed, 1);
+    }
+
+    #[test]
+    fn test_scheduler_priority_limit() {
+        let scheduler = TaskScheduler::new(DEFAULT_POOL_SIZE);
+
+        let probes = (0..PROBE_CONCURRENCY)
+            .map(|_| {
+                scheduler
+                    .acquire(TaskPriority::Probe)
+                    .now_or_never()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // 探测达到上限后排队，但不影响其他任务使用剩余的共享额度
+        let mut probe = Box::pin(scheduler.acquire(TaskPriority::Probe));
+        assert!((&mut probe).now_or_never().is_none());
+        let cleanup = scheduler
+            .acquire(TaskPriority::Cleanup)
+            .now_or_never()
+            .unwrap();
+
+        drop(probes);
+        let probe = probe.now_or_never().unwrap();
+        assert_eq!(stats(&scheduler, TaskPriority::Probe).running, 1);
+
+        drop(probe);
+        drop(cleanup);
+    }
+
+    #[test]
+    fn test_scheduler_cancelled_waiter() {
+        let scheduler = TaskScheduler::new(1);
+        let first = scheduler
+            .acquire(TaskPriority::Polling)
+            .now_or_never()
+            .unwrap();
+
+        // 排队中被取消的请求不会占用额度
+        let mut cancelled = Box::pin(scheduler.acquire(TaskPriority::Polling));
+        assert!((&mut cancelled).now_or_never().is_none());
+        drop(cancelled);
+
+        drop(first);
+        assert!(
+            scheduler
+                .acquire(TaskPriority::Cleanup)
+                .now_or_never()
+                .is_some()
+        );
+        assert_eq!(stats(&scheduler, TaskPriority::Polling).running, 0);
+    }
+}
@@ -0,0 +1,330 @@
+//! 录像占用统计与按上限清理
+//!
+//! 只统计历史记录中录制完成的音视频文件，录制目录下的其他文件不计入也不会被删除；
+//! 清理时连同 BLive 写入的弹幕、章节、截图等附属文件一起删除
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::core::disk::extended_path;
+
+/// 计入占用的录制文件扩展名
+pub const RECORDING_EXTENSIONS: [&str; 7] = ["flv", "mkv", "mp4", "ts", "m4a", "aac", "flac"];
+
+/// BLive 在录制文件旁写入的附属文件，按 [`Path::with_extension`] 拼接
+const SIDECAR_EXTENSIONS: [&str; 10] = [
+    "blank.json",
+    "highlights.json",
+    "chapters.txt",
+    "storyboard",
+    "jsonl",
+    "jsonl.zst",
+    "xml",
+    "ass",
+    "danmaku.bin",
+    "srt",
+];
+
+/// 录制目录下的一个录制文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// 录像占用与上限（字节），上限为 0 表示不限制
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub used: u64,
+    pub limit: u64,
+}
+
+impl StorageUsage {
+    pub fn exceeded(&self) -> bool {
+        self.limit > 0 && self.used >= self.limit
+    }
+
+    /// 占用比例，未设置上限时为 `None`
+    pub fn ratio(&self) -> Option<f64> {
+        (self.limit > 0).then(|| self.used as f64 / self.limit as f64)
+    }
+}
+
+pub fn is_recording_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RECORDING_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// 出现第二个分 P 时，已完成的 `标题.flv` 会被移动为 `标题/标题_P1.flv`，命名与下载器的分 P 一致
+pub fn first_part_path(path: &Path) -> Option<PathBuf> {
+    let stem = Path::new(path.file_stem()?).file_stem()?.to_string_lossy();
+    let ext = path.extension()?.to_string_lossy();
+
+    Some(path.parent()?.join(&*stem).join(format!("{stem}_P1.{ext}")))
+}
+
+/// 历史记录中的录像当前所在的位置，首个分 P 被移动后返回移动后的路径，文件已不存在时返回 `None`
+pub fn locate_recording(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    first_part_path(path).filter(|moved| moved.is_file())
+}
+
+/// 录制完成记录对应的文件集合，按完整路径匹配，首个分 P 移动后的位置同样计入
+///
+/// 提取过音频的录制需传入音频文件的路径，见 [`history::completed_files`](crate::core::history::completed_files)
+pub fn recorded_set(files: impl IntoIterator<Item = PathBuf>) -> HashSet<PathBuf> {
+    files
+        .into_iter()
+        .flat_map(|path| {
+            let moved = first_part_path(&path);
+            std::iter::once(path).chain(moved)
+        })
+        .collect()
+}
+
+/// 文件是否为 BLive 录制完成的录像
+pub fn is_recorded(path: &Path, recorded: &HashSet<PathBuf>) -> bool {
+    recorded.contains(path)
+}
+
+/// 递归收集目录下由 BLive 录制的文件，命名模板中的 `/` 会产生子目录
+pub fn scan_recordings(dirs: &[PathBuf], recorded: &HashSet<PathBuf>) -> Vec<RecordingFile> {
+    let mut files = vec![];
+    let mut pending = dirs.to_vec();
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            if metadata.is_dir() {
+                pending.push(path);
+            } else if is_recording_file(&path) && is_recorded(&path, recorded) {
+                files.push(RecordingFile {
+                    path,
+                    size: metadata.len(),
+                    modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+    }
+
+    files
+}
+
+/// 从最旧的录像开始选出需要删除的文件，使总占用回到上限以内，跳过正在录制的文件
+pub fn select_for_cleanup<'a>(
+    files: &'a [RecordingFile],
+    limit: u64,
+    protected: &[PathBuf],
+) -> Vec<&'a RecordingFile> {
+    let mut used = files.iter().map(|file| file.size).sum::<u64>();
+    let mut candidates = files
+        .iter()
+        .filter(|file| !protected.contains(&file.path))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|file| file.modified);
+
+    candidates
+        .into_iter()
+        .take_while(|file| {
+            let selected = used > limit;
+            used = used.saturating_sub(file.size);
+            selected
+        })
+        .collect()
+}
+
+/// 超出房间配额时需要删除的最旧场次数，`sizes` 为各场录像大小（从旧到新），上限为 0 表示不限制
+pub fn sessions_over_quota(sizes: &[u64], max_bytes: u64, max_sessions: usize) -> usize {
+    let mut total = sizes.iter().sum::<u64>();
+    let mut remaining = sizes.len();
+
+    sizes
+        .iter()
+        .take_while(|size| {
+            let over = (max_bytes > 0 && total > max_bytes)
+                || (max_sessions > 0 && remaining > max_sessions);
+            total -= **size;
+            remaining -= 1;
+            over
+        })
+        .count()
+}
+
+/// 删除录制文件及 BLive 为其写入的附属文件（如 `a.blank.json`、`a.storyboard/`），返回释放的字节数
+///
+/// 只按确切的附属文件名删除，标题中带 `.` 的其他录像（如 `a.花絮.flv`）及其附属文件不受影响
+pub fn remove_recording(path: &Path) -> std::io::Result<u64> {
+    // 附属文件由扩展路径派生，同样不受路径长度限制
+    let path = PathBuf::from(extended_path(&path.to_string_lossy()));
+    let size = std::fs::metadata(&path)?.len();
+    std::fs::remove_file(&path)?;
+
+    let mut sidecars = SIDECAR_EXTENSIONS
+        .iter()
+        .map(|ext| path.with_extension(ext))
+        .collect::<Vec<_>>();
+    let mut ffmpeg_log = path.as_os_str().to_owned();
+    ffmpeg_log.push(".ffmpeg.log");
+    sidecars.push(PathBuf::from(ffmpeg_log));
+
+    for sidecar in sidecars {
+        let result = if sidecar.is_dir() {
+            std::fs::remove_dir_all(&sidecar)
+        } else if sidecar.is_file() {
+            std::fs::remove_file(&sidecar)
+        } else {
+            continue;
+        };
+        if let Err(e) = result {
+            tracing::warn!("删除附属文件失败: {} - {}", sidecar.display(), e);
+        }
+    }
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_select_for_cleanup() {
+        let file = |name: &str, size: u64, age: u64| RecordingFile {
+            path: PathBuf::from(name),
+            size,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - age),
+        };
+        let files = vec![
+            file("new.flv", 40, 1),
+            file("old.flv", 30, 100),
+            file("recording.flv", 20, 200),
+            file("middle.mkv", 10, 50),
+        ];
+
+        let selected = select_for_cleanup(&files, 60, &[PathBuf::from("recording.flv")])
+            .into_iter()
+            .map(|file| file.path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(selected, ["old.flv", "middle.mkv"]);
+
+        assert!(select_for_cleanup(&files, 100, &[]).is_empty());
+        assert_eq!(select_for_cleanup(&files, 0, &[]).len(), 4);
+
+        assert!(is_recording_file(Path::new("a.FLV")));
+        assert!(!is_recording_file(Path::new("a.blank.json")));
+    }
+
+    #[test]
+    fn test_sessions_over_quota() {
+        assert_eq!(sessions_over_quota(&[10, 20, 30], 0, 0), 0);
+        assert_eq!(sessions_over_quota(&[10, 20, 30], 0, 2), 1);
+        assert_eq!(sessions_over_quota(&[10, 20, 30], 35, 0), 2);
+        assert_eq!(sessions_over_quota(&[10, 20, 30], 10, 5), 3);
+    }
+
+    #[test]
+    fn test_cleanup_only_touches_recorded_files() {
+        let dir = std::env::temp_dir().join(format!("blive-storage-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("主播_标题.storyboard")).unwrap();
+        std::fs::create_dir_all(dir.join("分P")).unwrap();
+
+        let files = [
+            "主播_标题.flv",
+            "主播_标题.xml",
+            "主播_标题.danmaku.bin",
+            "主播_标题.flv.ffmpeg.log",
+            "主播_标题.storyboard/000031.jpg",
+            "分P/分P_P1.flv",
+            "分P/分P_P2.flv",
+            // 标题带 `.` 的相邻录像与用户自己的文件
+            "主播_标题.花絮.mp4",
+            "主播_标题.花絮.xml",
+            "家庭录像.mp4",
+            // 与录像同名但扩展名不同的文件，如用户自行转码的副本
+            "主播_标题.mp4",
+        ];
+        for name in files {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        let recorded = recorded_set([
+            dir.join("主播_标题.flv"),
+            dir.join("分P.flv"),
+            dir.join("分P/分P_P2.flv"),
+        ]);
+
+        let mut scanned = scan_recordings(std::slice::from_ref(&dir), &recorded)
+            .into_iter()
+            .map(|file| file.path)
+            .collect::<Vec<_>>();
+        scanned.sort();
+        assert_eq!(
+            scanned,
+            [
+                dir.join("主播_标题.flv"),
+                dir.join("分P/分P_P1.flv"),
+                dir.join("分P/分P_P2.flv"),
+            ]
+        );
+
+        for path in &scanned {
+            remove_recording(path).unwrap();
+        }
+
+        let mut remaining = files
+            .into_iter()
+            .filter(|name| dir.join(name).exists())
+            .collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            [
+                "主播_标题.mp4",
+                "主播_标题.花絮.mp4",
+                "主播_标题.花絮.xml",
+                "家庭录像.mp4"
+            ]
+        );
+        assert!(!dir.join("主播_标题.storyboard").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_locate_recording_after_first_part_moved() {
+        let dir = std::env::temp_dir().join(format!("blive-locate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("标题")).unwrap();
+
+        let recorded = dir.join("标题.flv");
+        assert_eq!(locate_recording(&recorded), None);
+
+        std::fs::write(&recorded, "P1").unwrap();
+        assert_eq!(locate_recording(&recorded), Some(recorded.clone()));
+
+        // 第二个分 P 开始时首个分 P 被移动到同名文件夹
+        let moved = dir.join("标题/标题_P1.flv");
+        std::fs::rename(&recorded, &moved).unwrap();
+        assert_eq!(locate_recording(&recorded), Some(moved));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
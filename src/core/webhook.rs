@@ -0,0 +1,186 @@
+//! Webhook 推送：开播、下播与录制事件以 JSON POST 到全局与房间配置的地址
+
+use gpui::App;
+use serde::Serialize;
+
+use crate::{
+    core::http_client::room::LiveStatus,
+    settings::{WebhookEvent, WebhookSettings},
+    state::AppState,
+};
+
+/// 推送的请求体
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub room_id: u64,
+    pub up_name: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 毫秒时间戳
+    pub timestamp: i64,
+    /// 可直接转发到群机器人的文本
+    pub text: String,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEvent, room_id: u64, up_name: &str, title: &str) -> Self {
+        Self {
+            event,
+            room_id,
+            up_name: up_name.to_string(),
+            title: title.to_string(),
+            file_path: None,
+            message: None,
+            timestamp: chrono::Local::now().timestamp_millis(),
+            text: String::new(),
+        }
+        .with_text()
+    }
+
+    pub fn file_path(mut self, file_path: &str) -> Self {
+        self.file_path = Some(file_path.to_string());
+        self.with_text()
+    }
+
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = Some(message.to_string());
+        self.with_text()
+    }
+
+    fn with_text(mut self) -> Self {
+        let mut text = format!(
+            "【{}】{}（{}）：{}",
+            self.up_name, self.event, self.room_id, self.title
+        );
+        if let Some(file_path) = &self.file_path {
+            text.push_str(&format!("\n文件: {file_path}"));
+        }
+        if let Some(message) = &self.message {
+            text.push_str(&format!("\n{message}"));
+        }
+
+        self.text = text;
+        self
+    }
+}
+
+/// 直播状态的变化，首次获取到状态时不视为变化，避免启动时重复推送
+pub fn live_event(previous: Option<LiveStatus>, current: LiveStatus) -> Option<WebhookEvent> {
+    match (previous?, current) {
+        (LiveStatus::Live, LiveStatus::Live) => None,
+        (_, LiveStatus::Live) => Some(WebhookEvent::LiveStart),
+        (LiveStatus::Live, _) => Some(WebhookEvent::LiveEnd),
+        _ => None,
+    }
+}
+
+/// 合并全局与房间的推送目标，按地址去重
+pub fn targets(
+    global: &WebhookSettings,
+    room: Option<&WebhookSettings>,
+    event: WebhookEvent,
+) -> Vec<String> {
+    let mut targets = Vec::new();
+
+    for settings in std::iter::once(global).chain(room) {
+        if !settings.accepts(event) {
+            continue;
+        }
+
+        for url in &settings.urls {
+            let url = url.trim();
+            if !url.is_empty() && !targets.iter().any(|target| target == url) {
+                targets.push(url.to_string());
+            }
+        }
+    }
+
+    targets
+}
+
+/// 按房间配置推送事件，失败只记录日志
+pub fn dispatch(state: &AppState, cx: &App, payload: WebhookPayload) {
+    let room = state
+        .get_room_settings(payload.room_id)
+        .and_then(|settings| settings.webhook.as_ref());
+    let targets = targets(&state.settings.webhook, room, payload.event);
+    if targets.is_empty() {
+        return;
+    }
+
+    let client = state.client.clone();
+    cx.background_executor()
+        .spawn(async move {
+            for url in targets {
+                if let Err(e) = client.post_json(&url, &payload).await {
+                    tracing::warn!(
+                        "Webhook 推送失败 - 房间: {}, 事件: {}, 地址: {}, 错误: {}",
+                        payload.room_id,
+                        payload.event,
+                        url,
+                        e
+                    );
+                }
+            }
+        })
+        .detach();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targets() {
+        let global = WebhookSettings {
+            urls: vec!["https://a".to_string(), "https://b".to_string()],
+            events: vec![WebhookEvent::LiveStart],
+        };
+        let room = WebhookSettings {
+            urls: vec!["https://b".to_string(), " https://c ".to_string()],
+            events: vec![],
+        };
+
+        assert_eq!(
+            targets(&global, Some(&room), WebhookEvent::LiveStart),
+            ["https://a", "https://b", "https://c"]
+        );
+        assert_eq!(
+            targets(&global, Some(&room), WebhookEvent::RecordError),
+            ["https://b", "https://c"]
+        );
+        assert!(targets(&global, None, WebhookEvent::LiveEnd).is_empty());
+    }
+
+    #[test]
+    fn test_live_event() {
+        assert_eq!(live_event(None, LiveStatus::Live), None);
+        assert_eq!(
+            live_event(Some(LiveStatus::Offline), LiveStatus::Live),
+            Some(WebhookEvent::LiveStart)
+        );
+        assert_eq!(
+            live_event(Some(LiveStatus::Live), LiveStatus::Carousel),
+            Some(WebhookEvent::LiveEnd)
+        );
+        assert_eq!(live_event(Some(LiveStatus::Live), LiveStatus::Live), None);
+        assert_eq!(
+            live_event(Some(LiveStatus::Offline), LiveStatus::Carousel),
+            None
+        );
+    }
+
+    #[test]
+    fn test_payload_text() {
+        let payload = WebhookPayload::new(WebhookEvent::RecordComplete, 732, "主播", "标题")
+            .file_path("/录制/a.flv");
+        assert_eq!(
+            payload.text,
+            "【主播】录制完成（732）：标题\n文件: /录制/a.flv"
+        );
+    }
+}
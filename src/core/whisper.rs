@@ -0,0 +1,159 @@
+//! 语音转写：调用 whisper.cpp 为完成的录像生成粗略字幕（SRT）
+//!
+//! 转写很耗 CPU，任务以清理优先级交给调度器并依次执行，只占用一半核心，不影响正在进行的录制
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::LazyLock,
+};
+
+use anyhow::{Context, Result};
+use futures::lock::Mutex;
+use gpui::AsyncApp;
+
+use crate::{
+    core::{
+        repair::ffmpeg_path,
+        scheduler::{TaskPriority, scheduler},
+    },
+    settings::WhisperSettings,
+};
+
+/// 同一时间只转写一个录像，其余任务在此排队
+static TRANSCRIBE_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// 把录像加入转写队列，未配置模型时忽略
+pub fn enqueue(cx: &AsyncApp, video_path: PathBuf, settings: WhisperSettings) {
+    if settings.model_path.trim().is_empty() {
+        return;
+    }
+
+    tracing::info!("加入语音转写队列: {}", video_path.display());
+    cx.background_executor()
+        .spawn(async move {
+            let _guard = TRANSCRIBE_LOCK.lock().await;
+            let _permit = scheduler().acquire(TaskPriority::Cleanup).await;
+
+            match transcribe(&video_path, &settings) {
+                Ok(srt) => tracing::info!("语音转写完成: {}", srt.display()),
+                Err(e) => {
+                    tracing::warn!("语音转写失败 - 文件: {}, 错误: {}", video_path.display(), e)
+                }
+            }
+        })
+        .detach();
+}
+
+/// 字幕文件与录像同名，如 `a.flv` -> `a.srt`
+pub fn srt_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("srt")
+}
+
+/// whisper.cpp 要求 16kHz 单声道 WAV
+fn wav_path(video_path: &Path) -> PathBuf {
+    video_path.with_extension("whisper.wav")
+}
+
+fn transcribe_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|cpus| (cpus.get() / 2).max(1))
+        .unwrap_or(1)
+}
+
+/// whisper.cpp 的参数，`-of` 为不带扩展名的输出路径
+fn whisper_args(
+    settings: &WhisperSettings,
+    wav: &Path,
+    output: &Path,
+    threads: usize,
+) -> Vec<String> {
+    let language = match settings.language.trim() {
+        "" => "auto",
+        language => language,
+    };
+
+    vec![
+        "-m".to_string(),
+        settings.model_path.trim().to_string(),
+        "-l".to_string(),
+        language.to_string(),
+        "-t".to_string(),
+        threads.to_string(),
+        "-osrt".to_string(),
+        "-of".to_string(),
+        output.with_extension("").to_string_lossy().to_string(),
+        "-f".to_string(),
+        wav.to_string_lossy().to_string(),
+    ]
+}
+
+/// 提取音轨并转写，返回字幕文件路径
+pub fn transcribe(video_path: &Path, settings: &WhisperSettings) -> Result<PathBuf> {
+    let wav = wav_path(video_path);
+    let srt = srt_path(video_path);
+
+    let output = Command::new(ffmpeg_path())
+        .args(["-v", "error", "-y", "-i"])
+        .arg(video_path)
+        .args(["-vn", "-ac", "1", "-ar", "16000", "-c:a", "pcm_s16le"])
+        .arg(&wav)
+        .output()
+        .context("无法启动FFmpeg进程")?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&wav);
+        anyhow::bail!(
+            "ffmpeg 提取音轨失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let result = Command::new(settings.binary.trim())
+        .args(whisper_args(settings, &wav, &srt, transcribe_threads()))
+        .output()
+        .context("无法启动 whisper.cpp，请检查可执行文件路径");
+    let _ = std::fs::remove_file(&wav);
+    let output = result?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "whisper.cpp 转写失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(srt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whisper_args() {
+        let settings = WhisperSettings {
+            model_path: " /models/ggml-base.bin ".to_string(),
+            language: String::new(),
+            ..Default::default()
+        };
+        let video = Path::new("/录制/a.flv");
+
+        assert_eq!(
+            whisper_args(&settings, &wav_path(video), &srt_path(video), 4),
+            [
+                "-m",
+                "/models/ggml-base.bin",
+                "-l",
+                "auto",
+                "-t",
+                "4",
+                "-osrt",
+                "-of",
+                "/录制/a",
+                "-f",
+                "/录制/a.whisper.wav",
+            ]
+        );
+    }
+}
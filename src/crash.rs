@@ -0,0 +1,176 @@
+use std::{
+    backtrace::Backtrace,
+    panic::PanicHookInfo,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chrono::Local;
+
+use crate::settings::config_dir;
+
+/// 正在录制的房间数，由下载器在启停时维护，供崩溃报告读取
+static ACTIVE_RECORDINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// 记录最后一份已提示过的崩溃报告
+const LAST_SEEN_FILE: &str = "last_seen";
+
+/// 崩溃报告目录
+pub fn crash_dir() -> PathBuf {
+    config_dir().join("crashes")
+}
+
+/// 录制开始/结束时更新正在录制的房间数
+pub fn set_recording(recording: bool) {
+    if recording {
+        ACTIVE_RECORDINGS.fetch_add(1, Ordering::Relaxed);
+    } else {
+        let _ = ACTIVE_RECORDINGS.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+            count.checked_sub(1)
+        });
+    }
+}
+
+/// 正在录制的房间数
+pub fn active_recordings() -> usize {
+    ACTIVE_RECORDINGS.load(Ordering::Relaxed)
+}
+
+/// 安装 panic hook，panic 时写入崩溃报告后再交给默认 hook 处理
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let report = crash_report(info);
+
+        match write_crash_report(&report) {
+            Ok(path) => tracing::error!("应用崩溃，报告已写入: {}", path.display()),
+            Err(e) => tracing::error!("应用崩溃，写入崩溃报告失败: {}", e),
+        }
+
+        default_hook(info);
+    }));
+}
+
+fn crash_report(info: &PanicHookInfo<'_>) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "未知错误".to_string());
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "未知位置".to_string());
+    let thread = std::thread::current()
+        .name()
+        .unwrap_or("unnamed")
+        .to_string();
+
+    format!(
+        "时间: {}\n版本: {}\n系统: {} {}\n线程: {}\n正在录制的房间数: {}\n错误: {}\n位置: {}\n\n{}\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread,
+        active_recordings(),
+        message,
+        location,
+        Backtrace::force_capture()
+    )
+}
+
+fn write_crash_report(report: &str) -> std::io::Result<PathBuf> {
+    let dir = crash_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, report)?;
+
+    Ok(path)
+}
+
+/// 按时间排序的全部崩溃报告
+pub fn crash_reports() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(crash_dir()) else {
+        return vec![];
+    };
+
+    let mut reports = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .is_some_and(|name| is_crash_report(&name))
+        })
+        .collect::<Vec<_>>();
+    reports.sort();
+
+    reports
+}
+
+/// 取出上次提示之后新产生的最新崩溃报告，并标记为已提示
+pub fn take_unseen_crash_report() -> Option<PathBuf> {
+    let dir = crash_dir();
+    let last_seen = std::fs::read_to_string(dir.join(LAST_SEEN_FILE)).ok();
+    let latest = crash_reports().pop()?;
+    let latest_name = latest.file_name()?.to_string_lossy().to_string();
+
+    if !is_unseen(&latest_name, last_seen.as_deref()) {
+        return None;
+    }
+
+    if let Err(e) = std::fs::write(dir.join(LAST_SEEN_FILE), &latest_name) {
+        tracing::warn!("记录崩溃报告提示状态失败: {}", e);
+    }
+
+    Some(latest)
+}
+
+fn is_crash_report(file_name: &str) -> bool {
+    file_name.starts_with("crash-") && file_name.ends_with(".txt")
+}
+
+/// 报告文件名带时间戳，按字典序比较即可判断先后
+fn is_unseen(report_name: &str, last_seen: Option<&str>) -> bool {
+    match last_seen.map(str::trim) {
+        Some(last_seen) if !last_seen.is_empty() => report_name > last_seen,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_unseen() {
+        assert!(is_unseen("crash-20250810-120000.txt", None));
+        assert!(is_unseen(
+            "crash-20250810-120000.txt",
+            Some("crash-20250801-080000.txt\n")
+        ));
+        assert!(!is_unseen(
+            "crash-20250810-120000.txt",
+            Some("crash-20250810-120000.txt")
+        ));
+        assert!(is_crash_report("crash-20250810-120000.txt"));
+        assert!(!is_crash_report(LAST_SEEN_FILE));
+    }
+
+    #[test]
+    fn test_recording_count_never_underflows() {
+        set_recording(false);
+        set_recording(true);
+        set_recording(false);
+        set_recording(false);
+
+        assert_eq!(ACTIVE_RECORDINGS.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,96 @@
+//! Linux D-Bus 接口：供桌面小部件、脚本与 KDE/GNOME 扩展集成
+//!
+//! 在会话总线上注册 `com.starknt.BLive`，方法与命令行转发共用同一套命令，
+//! 主窗口在状态变化时推送给后台线程发出 `StatusChanged` 信号，内容与 `blive status --json` 一致
+
+use std::sync::OnceLock;
+
+use zbus::{blocking::connection, fdo, interface, object_server::SignalEmitter};
+
+use crate::{
+    cli::{self, CliCommand},
+    status::AppStatus,
+};
+
+const BUS_NAME: &str = "com.starknt.BLive";
+
+const OBJECT_PATH: &str = "/com/starknt/BLive";
+
+/// 待发出的状态 JSON，接口注册成功后才有值
+static STATUS_CHANGES: OnceLock<flume::Sender<String>> = OnceLock::new();
+
+struct BLiveInterface;
+
+#[interface(name = "com.starknt.BLive1")]
+impl BLiveInterface {
+    /// 添加录制房间
+    fn add_room(&self, room_id: u64) -> fdo::Result<()> {
+        if room_id == 0 {
+            return Err(fdo::Error::InvalidArgs(format!("无效的房间号: {room_id}")));
+        }
+
+        tracing::info!("收到 D-Bus 命令: 添加房间 {}", room_id);
+        cli::dispatch(CliCommand::AddRoom(room_id));
+        Ok(())
+    }
+
+    /// 停止所有正在进行的录制
+    fn stop_all(&self) {
+        tracing::info!("收到 D-Bus 命令: 停止所有录制");
+        cli::dispatch(CliCommand::StopAll);
+    }
+
+    /// 显示并激活主窗口
+    fn activate(&self) {
+        cli::dispatch(CliCommand::Activate);
+    }
+
+    /// 状态 JSON
+    fn get_status(&self) -> fdo::Result<String> {
+        cli::collect_status().ok_or_else(|| fdo::Error::Failed("主窗口未响应".to_string()))
+    }
+
+    /// 房间开播、开始或结束录制、出错以及全局问题变化时发出
+    #[zbus(signal)]
+    async fn status_changed(emitter: &SignalEmitter<'_>, status: &str) -> zbus::Result<()>;
+}
+
+/// 连接会话总线并注册接口，之后在后台线程中发出状态变化信号
+pub fn serve() -> zbus::Result<()> {
+    let connection = connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, BLiveInterface)?
+        .build()?;
+    tracing::info!("D-Bus 接口已注册 - 名称: {}", BUS_NAME);
+
+    let (tx, rx) = flume::unbounded::<String>();
+    let _ = STATUS_CHANGES.set(tx);
+
+    std::thread::Builder::new()
+        .name("dbus".to_string())
+        .spawn(move || {
+            let emitter = match SignalEmitter::new(connection.inner(), OBJECT_PATH) {
+                Ok(emitter) => emitter,
+                Err(e) => {
+                    tracing::warn!("D-Bus 状态信号不可用: {}", e);
+                    return;
+                }
+            };
+
+            // 没有状态变化时阻塞等待，不再轮询主窗口
+            while let Ok(json) = rx.recv() {
+                if let Err(e) = zbus::block_on(BLiveInterface::status_changed(&emitter, &json)) {
+                    tracing::debug!("发送 D-Bus 状态信号失败: {}", e);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+/// 主窗口在状态变化时调用，未注册接口时直接丢弃
+pub fn publish_status(status: &AppStatus) {
+    if let Some(tx) = STATUS_CHANGES.get() {
+        let _ = tx.send(serde_json::to_string(status).unwrap_or_default());
+    }
+}
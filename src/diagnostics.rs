@@ -0,0 +1,224 @@
+pub mod health;
+
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use chrono::Local;
+use serde_json::Value;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    core::repair::ffmpeg_path,
+    crash::crash_reports,
+    error::{AppError, AppResult},
+    logger::log_dir,
+    settings::{APP_NAME, GlobalSettings},
+};
+
+/// 诊断包中附带的最近日志文件数量
+const MAX_LOG_FILES: usize = 3;
+
+/// 诊断包中附带的最近崩溃报告数量
+const MAX_CRASH_REPORTS: usize = 3;
+
+/// 配置中需要脱敏的字段名（小写匹配）
+const SENSITIVE_KEYS: &[&str] = &[
+    "cookie",
+    "sessdata",
+    "bili_jct",
+    "access_token",
+    "refresh_token",
+    "token",
+    "password",
+];
+
+const REDACTED: &str = "******";
+
+/// 诊断包默认文件名
+pub fn default_diagnostics_file_name() -> String {
+    format!(
+        "{APP_NAME}-diagnostics-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    )
+}
+
+/// 将最近日志、崩溃报告、脱敏后的配置、系统信息与 ffmpeg 版本打包为 zip
+pub fn export_diagnostics(settings: &GlobalSettings, target: &Path) -> AppResult<PathBuf> {
+    let file = File::create(target)
+        .map_err(|e| AppError::FileSystemError(format!("无法创建诊断包: {e}")))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let settings = serde_json::to_value(settings)
+        .map(sanitize_settings)
+        .and_then(|settings| serde_json::to_string_pretty(&settings))
+        .map_err(|e| AppError::ConfigError(format!("无法序列化配置: {e}")))?;
+    write_entry(&mut zip, "settings.json", settings.as_bytes(), options)?;
+    write_entry(&mut zip, "system.txt", system_info().as_bytes(), options)?;
+
+    let crash_reports = crash_reports();
+    let recent_crash_reports = crash_reports
+        .iter()
+        .skip(crash_reports.len().saturating_sub(MAX_CRASH_REPORTS));
+
+    for (dir, file) in recent_log_files()
+        .iter()
+        .map(|file| ("logs", file))
+        .chain(recent_crash_reports.map(|file| ("crashes", file)))
+    {
+        let Some(file_name) = file.file_name().map(|name| name.to_string_lossy()) else {
+            continue;
+        };
+
+        match std::fs::read(file) {
+            Ok(content) => {
+                write_entry(&mut zip, &format!("{dir}/{file_name}"), &content, options)?;
+            }
+            Err(e) => tracing::warn!("读取文件失败: {}, 错误: {}", file.display(), e),
+        }
+    }
+
+    zip.finish()
+        .map_err(|e| AppError::FileSystemError(format!("无法写入诊断包: {e}")))?;
+
+    tracing::info!("诊断包已导出: {}", target.display());
+
+    Ok(target.to_path_buf())
+}
+
+fn write_entry(
+    zip: &mut ZipWriter<File>,
+    name: &str,
+    content: &[u8],
+    options: SimpleFileOptions,
+) -> AppResult<()> {
+    zip.start_file(name, options)
+        .map_err(|e| AppError::FileSystemError(format!("无法写入诊断包: {e}")))?;
+    zip.write_all(content)
+        .map_err(|e| AppError::FileSystemError(format!("无法写入诊断包: {e}")))
+}
+
+/// 按修改时间取最近的日志文件
+fn recent_log_files() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(log_dir()) else {
+        return vec![];
+    };
+
+    let mut files = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect::<Vec<_>>();
+
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files
+        .into_iter()
+        .take(MAX_LOG_FILES)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// 收集系统与运行环境信息
+pub fn system_info() -> String {
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get().to_string())
+        .unwrap_or_else(|_| "未知".to_string());
+
+    [
+        format!("应用版本: {}", env!("CARGO_PKG_VERSION")),
+        format!(
+            "操作系统: {} ({})",
+            std::env::consts::OS,
+            std::env::consts::FAMILY
+        ),
+        format!("架构: {}", std::env::consts::ARCH),
+        format!("CPU 核心数: {cpus}"),
+        format!(
+            "ffmpeg 版本: {}",
+            ffmpeg_version().unwrap_or_else(|| "未检测到".to_string())
+        ),
+        format!("导出时间: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+    ]
+    .join("\n")
+}
+
+/// ffmpeg 版本信息（`ffmpeg -version` 的第一行）
+pub fn ffmpeg_version() -> Option<String> {
+    let output = Command::new(ffmpeg_path()).arg("-version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
+
+/// 移除配置中的敏感字段，并把用户目录替换为 `~`
+fn sanitize_settings(value: Value) -> Value {
+    let home = std::env::home_dir().map(|home| home.to_string_lossy().to_string());
+    sanitize_value(value, home.as_deref())
+}
+
+fn sanitize_value(value: Value, home: Option<&str>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let lower = key.to_lowercase();
+                    if SENSITIVE_KEYS
+                        .iter()
+                        .any(|sensitive| lower.contains(sensitive))
+                    {
+                        (key, Value::String(REDACTED.to_string()))
+                    } else {
+                        (key, sanitize_value(value, home))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| sanitize_value(value, home))
+                .collect(),
+        ),
+        Value::String(value) => match home {
+            Some(home) if !home.is_empty() && value.contains(home) => {
+                Value::String(value.replace(home, "~"))
+            }
+            _ => Value::String(value),
+        },
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sanitize_value() {
+        let value = json!({
+            "record_dir": "/home/alice/Videos/blive",
+            "auth": { "cookie": "SESSDATA=abc", "uid": 1 },
+            "rooms": [{ "room_id": 1, "record_dir": "/home/alice/rec" }],
+        });
+
+        let sanitized = sanitize_value(value, Some("/home/alice"));
+
+        assert_eq!(sanitized["record_dir"], "~/Videos/blive");
+        assert_eq!(sanitized["auth"]["cookie"], REDACTED);
+        assert_eq!(sanitized["auth"]["uid"], 1);
+        assert_eq!(sanitized["rooms"][0]["record_dir"], "~/rec");
+    }
+}
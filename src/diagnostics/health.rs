@@ -0,0 +1,170 @@
+use std::{path::Path, time::Duration};
+
+use crate::{
+    core::{
+        HttpClient, auth,
+        disk::disk_space,
+        downloader::utils::pretty_bytes,
+        hwaccel::{PreviewDecode, SOFTWARE_PREVIEW_QUALITY, available_hwaccels},
+    },
+    diagnostics::ffmpeg_version,
+};
+
+/// 剩余空间低于该值时给出警告
+const LOW_DISK_SPACE: u64 = 10 * 1024 * 1024 * 1024;
+
+/// 接口延迟超过该值时给出警告
+const SLOW_LATENCY: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Error,
+    Skipped,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub status: HealthStatus,
+    pub detail: String,
+}
+
+impl HealthCheck {
+    fn new(name: &'static str, status: HealthStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// 检测直播接口可达性与延迟
+pub async fn check_network(client: &HttpClient) -> HealthCheck {
+    const NAME: &str = "网络连通性";
+
+    match client.check_connectivity().await {
+        Ok(latency) => {
+            let status = if latency > SLOW_LATENCY {
+                HealthStatus::Warning
+            } else {
+                HealthStatus::Ok
+            };
+            HealthCheck::new(
+                NAME,
+                status,
+                format!("api.live.bilibili.com 可达，延迟 {}ms", latency.as_millis()),
+            )
+        }
+        Err(e) => HealthCheck::new(
+            NAME,
+            HealthStatus::Error,
+            format!("无法访问 api.live.bilibili.com: {e}"),
+        ),
+    }
+}
+
+/// 检测 ffmpeg 是否可用
+pub fn check_ffmpeg() -> HealthCheck {
+    const NAME: &str = "ffmpeg";
+
+    match ffmpeg_version() {
+        Some(version) => HealthCheck::new(NAME, HealthStatus::Ok, version),
+        None => HealthCheck::new(NAME, HealthStatus::Error, "未找到可用的 ffmpeg"),
+    }
+}
+
+/// 检测预览可用的硬件解码
+pub fn check_hwaccel() -> HealthCheck {
+    const NAME: &str = "硬件解码";
+
+    match PreviewDecode::select(available_hwaccels(), SOFTWARE_PREVIEW_QUALITY).hwaccel {
+        Some(hwaccel) => HealthCheck::new(NAME, HealthStatus::Ok, hwaccel.to_string()),
+        None => HealthCheck::new(
+            NAME,
+            HealthStatus::Warning,
+            format!("不可用，预览将软解并使用{SOFTWARE_PREVIEW_QUALITY}画质"),
+        ),
+    }
+}
+
+/// 检测录制目录是否可写
+pub fn check_record_dir(record_dir: &Path) -> HealthCheck {
+    const NAME: &str = "录制目录";
+
+    let probe_file = record_dir.join(".blive-write-test");
+    let result = std::fs::create_dir_all(record_dir)
+        .and_then(|_| std::fs::write(&probe_file, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe_file));
+
+    match result {
+        Ok(_) => HealthCheck::new(
+            NAME,
+            HealthStatus::Ok,
+            format!("{} 可写", record_dir.display()),
+        ),
+        Err(e) => HealthCheck::new(
+            NAME,
+            HealthStatus::Error,
+            format!("{} 不可写: {e}", record_dir.display()),
+        ),
+    }
+}
+
+/// 检测录制目录所在磁盘的剩余空间
+pub fn check_disk_space(record_dir: &Path) -> HealthCheck {
+    const NAME: &str = "磁盘空间";
+
+    match disk_space(record_dir) {
+        Some(space) => {
+            let status = if space.available < LOW_DISK_SPACE {
+                HealthStatus::Warning
+            } else {
+                HealthStatus::Ok
+            };
+            HealthCheck::new(
+                NAME,
+                status,
+                format!(
+                    "剩余 {} / 共 {}",
+                    pretty_bytes(space.available),
+                    pretty_bytes(space.total)
+                ),
+            )
+        }
+        None => HealthCheck::new(NAME, HealthStatus::Warning, "无法获取磁盘信息"),
+    }
+}
+
+/// 检测登录 Cookie 是否有效
+pub async fn check_cookie(client: &HttpClient) -> HealthCheck {
+    const NAME: &str = "Cookie";
+
+    let Some(credentials) = auth::current() else {
+        return HealthCheck::new(NAME, HealthStatus::Skipped, "未配置登录 Cookie");
+    };
+
+    match client.get_nav().await {
+        Ok(nav) if nav.is_login => {
+            let vip = if nav.is_vip() { "（大会员）" } else { "" };
+            if credentials.expires_soon(chrono::Local::now().timestamp()) {
+                HealthCheck::new(
+                    NAME,
+                    HealthStatus::Warning,
+                    format!("已登录 {}{vip}，Cookie 即将过期，请重新登录", nav.uname),
+                )
+            } else {
+                HealthCheck::new(NAME, HealthStatus::Ok, format!("已登录 {}{vip}", nav.uname))
+            }
+        }
+        Ok(_) => HealthCheck::new(NAME, HealthStatus::Error, "登录 Cookie 已失效，请重新登录"),
+        Err(e) => HealthCheck::new(
+            NAME,
+            HealthStatus::Warning,
+            format!("无法验证登录状态: {e}"),
+        ),
+    }
+}
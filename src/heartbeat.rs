@@ -0,0 +1,106 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::{crash::active_recordings, settings::config_dir};
+
+/// 心跳文件更新间隔
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 心跳文件，供外部监控检查修改时间或内容
+pub fn heartbeat_file() -> PathBuf {
+    config_dir().join("heartbeat.json")
+}
+
+#[derive(Debug, Serialize)]
+struct Heartbeat {
+    status: &'static str,
+    version: &'static str,
+    /// 正在录制的房间数
+    recording_rooms: usize,
+    /// 毫秒时间戳
+    timestamp: i64,
+}
+
+fn heartbeat_body() -> String {
+    serde_json::to_string(&Heartbeat {
+        status: "ok",
+        version: env!("CARGO_PKG_VERSION"),
+        recording_rooms: active_recordings(),
+        timestamp: chrono::Local::now().timestamp_millis(),
+    })
+    .unwrap_or_default()
+}
+
+/// 更新心跳文件
+pub fn write_heartbeat_file() {
+    if let Err(e) = std::fs::write(heartbeat_file(), heartbeat_body()) {
+        tracing::warn!("写入心跳文件失败: {}", e);
+    }
+}
+
+/// 在本机端口上提供 `/healthz` 端点
+pub fn serve_healthz(port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    tracing::info!("健康检查端点已启动: http://127.0.0.1:{}/healthz", port);
+
+    std::thread::Builder::new()
+        .name("healthz".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Err(e) = handle_connection(stream) {
+                    tracing::debug!("健康检查请求处理失败: {}", e);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let (status, body) = if is_healthz_request(&request_line) {
+        ("200 OK", heartbeat_body())
+    } else {
+        ("404 Not Found", String::new())
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// 只响应 `GET /healthz`
+fn is_healthz_request(request_line: &str) -> bool {
+    let mut parts = request_line.split_whitespace();
+
+    matches!(
+        (parts.next(), parts.next()),
+        (Some("GET"), Some("/healthz"))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_healthz_request() {
+        assert!(is_healthz_request("GET /healthz HTTP/1.1\r\n"));
+        assert!(is_healthz_request("GET /healthz HTTP/1.0\r\n"));
+        assert!(!is_healthz_request("GET / HTTP/1.1\r\n"));
+        assert!(!is_healthz_request("POST /healthz HTTP/1.1\r\n"));
+        assert!(!is_healthz_request(""));
+    }
+}
@@ -0,0 +1,78 @@
+//! 全局热键：在任意程序中一键停止全部录制
+
+use anyhow::{Context, Result};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState, hotkey::HotKey};
+
+/// 解析热键，如 `CmdOrCtrl+Alt+Shift+S`
+pub fn parse_hotkey(value: &str) -> Result<HotKey> {
+    value
+        .trim()
+        .parse::<HotKey>()
+        .map_err(|e| anyhow::anyhow!("无效的热键 {value}: {e}"))
+}
+
+/// 停止全部录制的热键，须在主线程创建
+pub struct StopAllHotkey {
+    manager: GlobalHotKeyManager,
+    shortcut: String,
+    hotkey: Option<HotKey>,
+}
+
+impl StopAllHotkey {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            manager: GlobalHotKeyManager::new().context("无法初始化全局热键")?,
+            shortcut: String::new(),
+            hotkey: None,
+        })
+    }
+
+    /// 按设置重新注册热键，与当前一致时不做处理，为空时取消注册
+    pub fn update(&mut self, shortcut: &str) -> Result<()> {
+        if self.shortcut == shortcut {
+            return Ok(());
+        }
+        self.shortcut = shortcut.to_string();
+
+        if let Some(hotkey) = self.hotkey.take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+
+        if shortcut.trim().is_empty() {
+            return Ok(());
+        }
+
+        let hotkey = parse_hotkey(shortcut)?;
+        self.manager
+            .register(hotkey)
+            .context("热键已被其他程序占用或当前桌面环境不支持")?;
+        self.hotkey = Some(hotkey);
+
+        Ok(())
+    }
+
+    /// 取出积压的热键事件，返回期间是否按下过热键
+    pub fn pressed(&self) -> bool {
+        let mut pressed = false;
+        while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+            pressed |= self.hotkey.is_some_and(|hotkey| hotkey.id() == event.id)
+                && event.state == HotKeyState::Pressed;
+        }
+
+        pressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(
+            parse_hotkey(" ctrl+alt+shift+S ").unwrap(),
+            parse_hotkey("Control+Option+Shift+KeyS").unwrap()
+        );
+        assert!(parse_hotkey("ctrl+alt+").is_err());
+    }
+}
@@ -3,12 +3,24 @@
 
 pub mod app;
 pub mod assets;
+pub mod cli;
+pub mod clipboard;
 pub mod components;
 pub mod core;
+pub mod crash;
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+pub mod dbus;
+pub mod diagnostics;
 pub mod error;
+pub mod heartbeat;
+pub mod hotkey;
 pub mod logger;
+pub mod notification;
+pub mod profiling;
+pub mod service;
 pub mod settings;
 pub mod state;
+pub mod status;
 pub mod themes;
 pub mod title_bar;
 pub mod tray;
@@ -1,10 +1,19 @@
 use crate::error::{AppError, AppResult};
-use chrono::Local;
+use crate::profiling::{self, ProfilingGuard};
+use crate::settings::{APP_NAME, LogLevelSettings, config_dir};
+use chrono::{Local, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::PathBuf;
 use std::sync::{LazyLock, RwLock};
 use tracing::Level;
-use tracing_subscriber::EnvFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::filter_fn;
 use tracing_subscriber::fmt::time::FormatTime;
-use tracing_subscriber::{FmtSubscriber, fmt::format::FmtSpan};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, Registry, fmt, fmt::format::FmtSpan, reload};
 
 struct SystemTime;
 
@@ -25,17 +34,47 @@ static GLOBAL_LOGGER: LazyLock<RwLock<LoggerManager>> = LazyLock::new(|| {
     RwLock::new(logger)
 });
 
+/// 需要脱敏的查询参数与 Cookie 字段，如 `token=xxx`、`SESSDATA=xxx`
+static SENSITIVE_PARAM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(token|sign|upsig|sessdata|bili_jct|access_key|access_token|refresh_token|csrf)=([^&;\s\x22']+)",
+    )
+    .expect("无效的脱敏正则")
+});
+
+/// 脱敏替换后的占位
+const REDACTED: &str = "***";
+
+/// 将文本（URL、Cookie、错误信息等）中的鉴权参数替换为占位符
+pub fn redact_sensitive(text: &str) -> Cow<'_, str> {
+    SENSITIVE_PARAM_REGEX.replace_all(text, format!("${{1}}={REDACTED}"))
+}
+
+/// 日志文件目录
+pub fn log_dir() -> PathBuf {
+    config_dir().join("logs")
+}
+
 pub struct LoggerManager {
-    log_level: Level,
+    levels: LogLevelSettings,
     initialized: bool,
+    file_guard: Option<WorkerGuard>,
+    profiling_guard: Option<ProfilingGuard>,
+    filter_handle: Option<reload::Handle<EnvFilter, Registry>>,
 }
 
 impl LoggerManager {
     /// 创建新的日志管理器
     pub fn new(log_level: Level) -> AppResult<Self> {
         Ok(Self {
-            log_level,
+            levels: LogLevelSettings {
+                default: log_level.into(),
+                ..Default::default()
+            },
             initialized: false,
+            file_guard: None,
+            profiling_guard: None,
+            filter_handle: None,
         })
     }
 
@@ -45,32 +84,86 @@ impl LoggerManager {
             return Ok(());
         }
 
-        let builder = FmtSubscriber::builder()
+        // 按天滚动的日志文件，过期文件由 cleanup_logs 按保留天数清理
+        let file_appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(APP_NAME)
+            .filename_suffix("log")
+            .build(log_dir())
+            .map_err(|e| AppError::FileSystemError(format!("无法创建日志文件: {e}")))?;
+        let (file_writer, file_guard) = tracing_appender::non_blocking(file_appender);
+
+        let console_layer = fmt::layer()
             .with_timer(SystemTime)
             .with_level(true)
             .with_target(false)
             .with_thread_ids(true)
             .with_thread_names(true)
             .with_span_events(FmtSpan::CLOSE)
-            .with_max_level(self.log_level)
-            .with_env_filter(
-                EnvFilter::from_default_env()
-                    .add_directive("blive=debug".parse().unwrap())
-                    .add_directive("reqwest=debug".parse().unwrap()),
-            );
+            .with_filter(filter_fn(|metadata| {
+                !profiling::is_profiling_target(metadata.target())
+            }));
+
+        let file_layer = fmt::layer()
+            .with_timer(SystemTime)
+            .with_level(true)
+            .with_target(false)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(false)
+            .with_writer(file_writer)
+            .with_filter(filter_fn(|metadata| {
+                !profiling::is_profiling_target(metadata.target())
+            }));
+
+        // 过滤层包在 reload 层里，运行时可以替换
+        let (filter_layer, filter_handle) = reload::Layer::new(build_filter(&self.levels));
+
+        let (profiling_layer, profiling_guard) = profiling::layer();
+
+        let subscriber = tracing_subscriber::registry()
+            .with(filter_layer)
+            .with(console_layer)
+            .with(file_layer)
+            .with(profiling_layer);
 
-        let subscriber = builder.finish();
         tracing::subscriber::set_global_default(subscriber)
             .map_err(|e| AppError::Unknown(format!("无法设置日志订阅者: {e}")))?;
 
+        self.file_guard = Some(file_guard);
+        self.profiling_guard = Some(profiling_guard);
+        self.filter_handle = Some(filter_handle);
         self.initialized = true;
         Ok(())
     }
 
+    /// 应用新的日志级别配置，未初始化时只记录配置
+    pub fn apply_levels(&mut self, levels: LogLevelSettings) -> AppResult<()> {
+        if let Some(handle) = &self.filter_handle {
+            handle
+                .reload(build_filter(&levels))
+                .map_err(|e| AppError::Unknown(format!("无法更新日志级别: {e}")))?;
+        }
+
+        if levels != self.levels {
+            tracing::info!("日志级别已更新: {}", filter_directives(&levels));
+        }
+
+        self.levels = levels;
+        Ok(())
+    }
+
+    /// 刷新并关闭日志文件写入
+    pub fn shutdown(&mut self) {
+        self.profiling_guard.take();
+        self.file_guard.take();
+    }
+
     /// 记录应用启动日志
     pub fn log_app_start(&self, version: &str) {
         tracing::info!("应用启动 - 版本: {}", version);
-        tracing::info!("日志级别: {:?}", self.log_level);
+        tracing::info!("日志级别: {}", filter_directives(&self.levels));
     }
 
     /// 记录应用关闭日志
@@ -95,12 +188,16 @@ impl LoggerManager {
 
     /// 记录录制错误
     pub fn log_recording_error(&self, room_id: u64, error: &str) {
-        tracing::error!("录制错误 - 房间: {}, 错误: {}", room_id, error);
+        tracing::error!(
+            "录制错误 - 房间: {}, 错误: {}",
+            room_id,
+            redact_sensitive(error)
+        );
     }
 
     /// 记录网络请求
     pub fn log_network_request(&self, url: &str, method: &str) {
-        tracing::debug!("网络请求 - {} {}", method, url);
+        tracing::debug!("网络请求 - {} {}", method, redact_sensitive(url));
     }
 
     /// 记录网络响应
@@ -110,13 +207,13 @@ impl LoggerManager {
 
     /// 记录配置变更
     pub fn log_config_change(&self, key: &str, value: &str) {
-        tracing::info!("配置变更 - {}: {}", key, value);
+        tracing::info!("配置变更 - {}: {}", key, redact_sensitive(value));
     }
 
     /// 记录用户操作
     pub fn log_user_action(&self, action: &str, details: Option<&str>) {
         if let Some(details) = details {
-            tracing::info!("用户操作 - {}: {}", action, details);
+            tracing::info!("用户操作 - {}: {}", action, redact_sensitive(details));
         } else {
             tracing::info!("用户操作 - {}", action);
         }
@@ -126,21 +223,87 @@ impl LoggerManager {
 impl Default for LoggerManager {
     fn default() -> Self {
         Self {
-            log_level: Level::INFO,
+            levels: LogLevelSettings::default(),
             initialized: false,
+            file_guard: None,
+            profiling_guard: None,
+            filter_handle: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Trace,
     Debug,
+    #[default]
     Info,
     Warn,
     Error,
 }
 
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<Level> for LogLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::TRACE => LogLevel::Trace,
+            Level::DEBUG => LogLevel::Debug,
+            Level::INFO => LogLevel::Info,
+            Level::WARN => LogLevel::Warn,
+            Level::ERROR => LogLevel::Error,
+        }
+    }
+}
+
+/// 可单独设置日志级别的模块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogModule {
+    /// B 站接口请求
+    Api,
+    /// 录制下载
+    Downloader,
+    /// 界面
+    Ui,
+}
+
+impl LogModule {
+    /// 模块对应的 tracing target
+    pub fn targets(&self) -> &'static [&'static str] {
+        match self {
+            LogModule::Api => &["blive::core::http_client", "reqwest"],
+            LogModule::Downloader => &["blive::core::downloader", "blive::core::ffprobe"],
+            LogModule::Ui => &["blive::app", "blive::components", "blive::title_bar"],
+        }
+    }
+}
+
 impl From<LogLevel> for Level {
     fn from(level: LogLevel) -> Self {
         match level {
@@ -161,13 +324,123 @@ pub fn init_logger() -> AppResult<()> {
     logger.init()
 }
 
-/// 设置日志级别
+/// 刷新日志文件，应在应用退出前调用
+pub fn shutdown_logger() {
+    if let Ok(mut logger) = GLOBAL_LOGGER.write() {
+        logger.shutdown();
+    }
+}
+
+/// 删除超过保留天数的日志文件
+pub fn cleanup_logs(retention_days: u32) {
+    let Ok(entries) = std::fs::read_dir(log_dir()) else {
+        return;
+    };
+
+    let today = Local::now().date_naive();
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        if is_expired_log(&file_name.to_string_lossy(), today, retention_days) {
+            match std::fs::remove_file(entry.path()) {
+                Ok(_) => tracing::info!("删除过期日志: {}", entry.path().display()),
+                Err(e) => {
+                    tracing::warn!("删除过期日志失败: {}, 错误: {}", entry.path().display(), e)
+                }
+            }
+        }
+    }
+}
+
+/// 判断日志文件（blive.YYYY-MM-DD.log）是否已过期
+fn is_expired_log(file_name: &str, today: NaiveDate, retention_days: u32) -> bool {
+    let Some(date) = file_name
+        .strip_prefix(&format!("{APP_NAME}."))
+        .and_then(|name| name.strip_suffix(".log"))
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    else {
+        return false;
+    };
+
+    (today - date).num_days() >= retention_days.max(1) as i64
+}
+
+/// 生成 EnvFilter 指令，模块级别覆盖全局级别，RUST_LOG 环境变量优先级最高
+fn filter_directives(levels: &LogLevelSettings) -> String {
+    let mut directives = vec![levels.default.to_string()];
+
+    for (module, level) in [
+        (LogModule::Api, levels.api),
+        (LogModule::Downloader, levels.downloader),
+        (LogModule::Ui, levels.ui),
+    ] {
+        if let Some(level) = level {
+            directives.extend(
+                module
+                    .targets()
+                    .iter()
+                    .map(|target| format!("{target}={level}")),
+            );
+        }
+    }
+
+    directives.join(",")
+}
+
+fn build_filter(levels: &LogLevelSettings) -> EnvFilter {
+    let mut directives = filter_directives(levels);
+
+    if let Some(directive) = profiling::filter_directive() {
+        directives.push(',');
+        directives.push_str(&directive);
+    }
+
+    if let Ok(env) = std::env::var(EnvFilter::DEFAULT_ENV)
+        && !env.trim().is_empty()
+    {
+        directives.push(',');
+        directives.push_str(&env);
+    }
+
+    EnvFilter::builder().parse_lossy(directives)
+}
+
+/// 设置全局日志级别，立即生效
 pub fn set_log_level(level: LogLevel) -> AppResult<()> {
     let mut logger = GLOBAL_LOGGER
         .write()
         .map_err(|e| AppError::Unknown(format!("无法获取日志管理器写锁: {e}")))?;
-    logger.log_level = level.into();
-    Ok(())
+    let levels = LogLevelSettings {
+        default: level,
+        ..logger.levels.clone()
+    };
+    logger.apply_levels(levels)
+}
+
+/// 设置单个模块的日志级别，`None` 表示跟随全局级别
+pub fn set_module_log_level(module: LogModule, level: Option<LogLevel>) -> AppResult<()> {
+    let mut logger = GLOBAL_LOGGER
+        .write()
+        .map_err(|e| AppError::Unknown(format!("无法获取日志管理器写锁: {e}")))?;
+    let mut levels = logger.levels.clone();
+    match module {
+        LogModule::Api => levels.api = level,
+        LogModule::Downloader => levels.downloader = level,
+        LogModule::Ui => levels.ui = level,
+    }
+    logger.apply_levels(levels)
+}
+
+/// 按配置应用全局及各模块日志级别
+pub fn apply_log_levels(levels: &LogLevelSettings) {
+    match GLOBAL_LOGGER.write() {
+        Ok(mut logger) => {
+            if let Err(e) = logger.apply_levels(levels.clone()) {
+                tracing::warn!("{}", e);
+            }
+        }
+        Err(e) => tracing::warn!("无法获取日志管理器写锁: {}", e),
+    }
 }
 
 // 全局日志记录函数，方便其他模块使用
@@ -234,3 +507,66 @@ pub fn log_user_action(action: &str, details: Option<&str>) {
         logger.log_user_action(action, details);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_expired_log() {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap();
+
+        assert!(!is_expired_log("blive.2025-08-10.log", today, 7));
+        assert!(!is_expired_log("blive.2025-08-04.log", today, 7));
+        assert!(is_expired_log("blive.2025-08-03.log", today, 7));
+        assert!(!is_expired_log("other.2025-01-01.log", today, 7));
+        assert!(!is_expired_log("blive.log", today, 7));
+    }
+
+    #[test]
+    fn test_redact_sensitive() {
+        assert_eq!(
+            redact_sensitive(
+                "https://cn-gddg.bilivideo.com/live/index.m3u8?expires=1&sign=abc123&token=def%3D&trid=x"
+            ),
+            "https://cn-gddg.bilivideo.com/live/index.m3u8?expires=1&sign=***&token=***&trid=x"
+        );
+        assert_eq!(
+            redact_sensitive("Cookie: SESSDATA=a%2Cb; bili_jct=c0ffee; buvid3=x"),
+            "Cookie: SESSDATA=***; bili_jct=***; buvid3=x"
+        );
+        assert_eq!(
+            redact_sensitive("https://api.live.bilibili.com/room/v1/Room/get_info?room_id=1"),
+            "https://api.live.bilibili.com/room/v1/Room/get_info?room_id=1"
+        );
+        assert_eq!(
+            redact_sensitive("design=1&access_token=xyz"),
+            "design=1&access_token=***"
+        );
+    }
+
+    #[test]
+    fn test_filter_directives() {
+        let levels = LogLevelSettings {
+            default: LogLevel::Info,
+            api: Some(LogLevel::Warn),
+            downloader: None,
+            ui: Some(LogLevel::Trace),
+        };
+
+        assert_eq!(
+            filter_directives(&levels),
+            "info,blive::core::http_client=warn,reqwest=warn,\
+             blive::app=trace,blive::components=trace,blive::title_bar=trace"
+        );
+        assert_eq!(
+            filter_directives(&LogLevelSettings {
+                default: LogLevel::Debug,
+                api: None,
+                downloader: None,
+                ui: None,
+            }),
+            "debug"
+        );
+    }
+}
@@ -2,10 +2,18 @@
 
 use std::time::Duration;
 
-use blive::logger::{init_logger, log_app_shutdown, log_app_start};
-use blive::settings::{APP_NAME, DISPLAY_NAME};
+use blive::app::{BLiveApp, show_window};
+use blive::cli::{self, CliArgs, CliCommand, USAGE};
+use blive::core::cover;
+use blive::crash::install_panic_hook;
+use blive::logger::{init_logger, log_app_shutdown, log_app_start, shutdown_logger};
+use blive::service::{self, ServiceAction};
+use blive::settings::{
+    APP_NAME, DISPLAY_NAME, GlobalSettings, RoomSettings, saver, set_settings_file,
+};
+use blive::status::AppStatus;
 use blive::tray::{SystemTray, TrayMessage};
-use blive::{app::BLiveApp, assets::Assets, state::AppState, themes::ThemeSwitcher};
+use blive::{assets::Assets, state::AppState, themes::ThemeSwitcher};
 use gpui::{
     App, Application, Bounds, KeyBinding, WindowBounds, WindowKind, WindowOptions, actions,
     prelude::*, px, size,
@@ -13,39 +21,91 @@ use gpui::{
 #[cfg(target_os = "macos")]
 use gpui::{Menu, MenuItem};
 use gpui_component::{Root, TitleBar, theme};
-use reqwest_client::ReqwestClient;
 
 actions!(menu, [Quit]);
 
 fn main() {
-    #[cfg(any(feature = "ffmpeg", debug_assertions))]
-    {
-        use ffmpeg_sidecar::command::ffmpeg_is_installed;
+    #[cfg(windows)]
+    attach_parent_console();
+
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
 
-        if !ffmpeg_is_installed() {
-            ffmpeg_sidecar::download::auto_download().expect("无法自动下载 ffmpeg");
+    if args.help {
+        println!("{USAGE}");
+        return;
+    }
+
+    if let Some(action) = args.service {
+        let result = match action {
+            ServiceAction::Install => service::install(args.config.as_deref()),
+            ServiceAction::Uninstall => service::uninstall(),
+        };
+        match result {
+            Ok(message) => println!("{message}"),
+            Err(e) => {
+                eprintln!("{e:#}");
+                std::process::exit(1);
+            }
         }
+        return;
+    }
+
+    // 设置文件决定了日志与实例端口文件的位置，须最先确定
+    if let Some(config) = &args.config {
+        set_settings_file(config);
     }
 
     init_logger().expect("无法初始化日志系统");
+    install_panic_hook();
+
+    if args.status {
+        let code = print_status(args.json);
+        shutdown_logger();
+        std::process::exit(code);
+    }
+
+    // 已有实例运行时交给它处理
+    if cli::forward_to_running(&args.commands()) {
+        tracing::info!("已将命令转发给正在运行的实例");
+        shutdown_logger();
+        return;
+    }
+
+    if args.stop_all && args.add_rooms.is_empty() {
+        eprintln!("没有正在运行的实例");
+        shutdown_logger();
+        return;
+    }
+
     log_app_start(env!("CARGO_PKG_VERSION"));
+    cover::clear_stale_keyframes();
 
-    let (tx, rx) = flume::unbounded();
-    let mut system_tray = SystemTray::new();
+    if let Err(e) = cli::serve_instance() {
+        tracing::warn!("单实例转发启动失败，重复启动将打开新窗口: {}", e);
+    }
 
-    let open_main_window_tx = tx.clone();
-    system_tray.add_menu_item("打开主窗口", move || {
-        open_main_window_tx.send(TrayMessage::OpenWindow).unwrap();
-    });
+    #[cfg(all(target_os = "linux", feature = "dbus"))]
+    if let Err(e) = blive::dbus::serve() {
+        tracing::warn!("D-Bus 接口启动失败: {}", e);
+    }
 
-    let quit_app_tx = tx.clone();
-    system_tray.add_menu_item("退出应用", move || {
-        quit_app_tx.send(TrayMessage::Quit).unwrap();
-    });
+    for room_id in &args.add_rooms {
+        cli::dispatch(CliCommand::AddRoom(*room_id));
+    }
+
+    let (tx, rx) = flume::unbounded();
+    let mut pinned = pinned_rooms(&GlobalSettings::load().rooms);
+    let mut system_tray = build_tray(&pinned, &tx);
 
     let app = Application::new().with_assets(Assets);
     app.on_reopen(|cx| {
-        open_main_window(cx);
+        open_main_window(false, cx);
     });
 
     // system_tray.display();
@@ -53,9 +113,6 @@ fn main() {
     app.run(move |cx| {
         gpui_component::init(cx);
 
-        let http_client = std::sync::Arc::new(ReqwestClient::user_agent("blive/0.1.0").unwrap());
-        cx.set_http_client(http_client);
-
         AppState::init(cx);
         theme::init(cx);
         ThemeSwitcher::init(cx);
@@ -68,8 +125,13 @@ fn main() {
 
         cx.on_app_quit(move |cx| {
             let downloaders = cx.read_global(|state: &AppState, _| {
-                state.settings.save();
-                state.room_states.iter().map(|room| room.downloader.clone()).collect::<Vec<_>>()
+                // 退出前写入尚在去抖中的设置
+                saver::flush(state.settings.clone());
+                state
+                    .room_states
+                    .iter()
+                    .map(|room| room.downloader.clone())
+                    .collect::<Vec<_>>()
             });
 
             async move {
@@ -82,6 +144,7 @@ fn main() {
 
                 // 记录应用关闭日志
                 log_app_shutdown();
+                shutdown_logger();
             }
         })
         .detach();
@@ -92,11 +155,23 @@ fn main() {
             items: vec![MenuItem::action("退出", Quit)],
         }]);
 
-        open_main_window(cx);
-        cx.activate(true);
+        open_main_window(args.minimized, cx);
+        if !args.minimized {
+            cx.activate(true);
+        }
 
         cx.spawn(async move |cx| {
             loop {
+                // 托盘菜单创建后无法修改，置顶房间或其名称变化时重建托盘
+                if let Ok(current) =
+                    cx.update(|cx| pinned_rooms(&AppState::global(cx).settings.rooms))
+                    && current != pinned
+                {
+                    system_tray.quit();
+                    system_tray = build_tray(&current, &tx);
+                    pinned = current;
+                }
+
                 if let Ok(event) = rx.try_recv() {
                     match event {
                         TrayMessage::Quit => {
@@ -105,30 +180,22 @@ fn main() {
                             });
                             break;
                         }
+                        TrayMessage::OpenRoom(room_id) => {
+                            let _ = cx.update(|cx| {
+                                if cx.windows().is_empty() {
+                                    open_main_window(false, cx);
+                                }
+                            });
+                            // 由主窗口显示并定位卡片，窗口刚打开时命令在通道中等待
+                            cli::dispatch(CliCommand::ShowRoom(room_id));
+                        }
                         TrayMessage::OpenWindow => {
                             let _ = cx.update(|cx| {
                                 if cx.windows().is_empty() {
-                                    open_main_window(cx);
+                                    open_main_window(false, cx);
                                 } else if let Some(window) = cx.windows().first() {
                                     window
-                                        .update(cx, |_, window, _| {
-                                            #[cfg(target_os = "windows")] {
-                                                unsafe  {
-                                                    use windows::Win32::Foundation::*;
-                                                    use raw_window_handle::HasWindowHandle;
-                                                    use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_RESTORE};
-
-                                                    if let Ok(handle) = window.window_handle()
-                                                        && let raw_window_handle::RawWindowHandle::Win32(handle) = handle.as_raw() {
-                                                            // If the window is minimized, restore it
-                                                            let _ = ShowWindow(HWND(handle.hwnd.get() as *mut std::ffi::c_void), SW_RESTORE);
-                                                        }
-
-                                                }
-                                            }
-
-                                            window.activate_window();
-                                        })
+                                        .update(cx, |_, window, _| show_window(window))
                                         .expect("Failed to activate window");
                                 }
                             });
@@ -143,7 +210,83 @@ fn main() {
     });
 }
 
-fn open_main_window(cx: &mut App) {
+/// 置顶房间的房间号与托盘中显示的名称
+fn pinned_rooms(rooms: &[RoomSettings]) -> Vec<(u64, String)> {
+    rooms
+        .iter()
+        .filter(|room| room.pinned)
+        .map(|room| {
+            let room_id = room.room_id;
+            (room_id, room.display_name(&room_id.to_string()).to_string())
+        })
+        .collect()
+}
+
+fn build_tray(pinned: &[(u64, String)], tx: &flume::Sender<TrayMessage>) -> SystemTray {
+    let mut system_tray = SystemTray::new();
+
+    let open_main_window_tx = tx.clone();
+    system_tray.add_menu_item("打开主窗口", move || {
+        open_main_window_tx.send(TrayMessage::OpenWindow).unwrap();
+    });
+
+    if !pinned.is_empty() {
+        system_tray.add_label("置顶房间");
+        for (room_id, name) in pinned {
+            let room_id = *room_id;
+            let open_room_tx = tx.clone();
+            system_tray.add_menu_item(name, move || {
+                open_room_tx.send(TrayMessage::OpenRoom(room_id)).unwrap();
+            });
+        }
+    }
+
+    let quit_app_tx = tx.clone();
+    system_tray.add_menu_item("退出应用", move || {
+        quit_app_tx.send(TrayMessage::Quit).unwrap();
+    });
+
+    system_tray
+}
+
+/// 发布版属于 GUI 子系统，没有自己的控制台；从终端启动时附加到父进程的控制台，
+/// 帮助、状态等命令行输出才能显示
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{ATTACH_PARENT_PROCESS, AttachConsole};
+
+    // 双击启动时没有父控制台，附加失败无需处理
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+}
+
+/// 查询运行中实例的状态并输出，返回进程返回码
+fn print_status(json: bool) -> i32 {
+    let Some(status) = cli::query_status() else {
+        eprintln!("没有正在运行的实例");
+        return 3;
+    };
+
+    let status = match serde_json::from_str::<AppStatus>(&status) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("无法解析实例返回的状态: {e}");
+            return 3;
+        }
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&status).unwrap_or_default()
+        );
+    } else {
+        println!("{}", status.summary());
+    }
+
+    if status.has_error() { 1 } else { 0 }
+}
+
+fn open_main_window(minimized: bool, cx: &mut App) {
     let mut window_size = size(px(1600.0), px(900.0));
     if let Some(display) = cx.primary_display() {
         let display_size = display.bounds().size;
@@ -192,7 +335,11 @@ fn open_main_window(cx: &mut App) {
         window
             .update(cx, |_, window, _| {
                 window.set_window_title(DISPLAY_NAME);
-                window.activate_window();
+                if minimized {
+                    window.minimize_window();
+                } else {
+                    window.activate_window();
+                }
             })
             .expect("Failed to update window");
     })
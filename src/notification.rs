@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use gpui::{App, Window};
+use gpui_component::{ContextModal, notification::Notification};
+
+use crate::{settings::DISPLAY_NAME, state::AppState};
+
+/// 同一房间同类通知的合并窗口
+pub const NOTIFICATION_THROTTLE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 通知类别，同房间同类别的通知会被合并
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    /// 录制出错
+    RecordingError,
+    /// 录制产物可能损坏
+    OutputCorrupted,
+    /// 录制目录切换到备用目录
+    RecordDirSwitched,
+}
+
+#[derive(Debug)]
+struct ThrottleEntry {
+    /// 上次实际弹出通知的时间
+    last_shown: Instant,
+    /// 上次弹出后被合并的次数
+    suppressed: u32,
+}
+
+/// 通知限流，窗口内重复的通知只弹出一次，其余计数后合并到下一条
+#[derive(Debug, Default)]
+pub struct NotificationThrottle {
+    entries: HashMap<(u64, NotificationKind), ThrottleEntry>,
+}
+
+impl NotificationThrottle {
+    /// 记录一次通知，应当弹出时返回自上次弹出以来发生的次数（含本次），否则返回 `None`
+    pub fn check(&mut self, room_id: u64, kind: NotificationKind, now: Instant) -> Option<u32> {
+        match self.entries.get_mut(&(room_id, kind)) {
+            Some(entry)
+                if now.saturating_duration_since(entry.last_shown)
+                    < NOTIFICATION_THROTTLE_WINDOW =>
+            {
+                entry.suppressed += 1;
+                None
+            }
+            Some(entry) => {
+                let count = entry.suppressed + 1;
+                entry.last_shown = now;
+                entry.suppressed = 0;
+                Some(count)
+            }
+            None => {
+                self.entries.insert(
+                    (room_id, kind),
+                    ThrottleEntry {
+                        last_shown: now,
+                        suppressed: 0,
+                    },
+                );
+                Some(1)
+            }
+        }
+    }
+
+    /// 移除房间的限流记录
+    pub fn remove_room(&mut self, room_id: u64) {
+        self.entries.retain(|(id, _), _| *id != room_id);
+    }
+}
+
+/// 合并次数的提示后缀
+fn count_suffix(count: u32) -> String {
+    if count > 1 {
+        format!("（近期共发生 {count} 次）")
+    } else {
+        String::new()
+    }
+}
+
+/// 按房间与类别限流后弹出通知，`build` 接收附加了发生次数的消息
+pub fn push_room_notification(
+    window: &mut Window,
+    cx: &mut App,
+    room_id: u64,
+    kind: NotificationKind,
+    message: impl Into<String>,
+    build: impl FnOnce(String) -> Notification,
+) {
+    // 静默时段内不打扰，出错信息仍会写入日志与时间线
+    if AppState::global(cx).settings.quiet_hours.is_active() {
+        return;
+    }
+
+    let Some(count) = AppState::global_mut(cx)
+        .notifications
+        .check(room_id, kind, Instant::now())
+    else {
+        return;
+    };
+
+    let message = format!("{}{}", message.into(), count_suffix(count));
+    window.push_notification(build(message), cx);
+}
+
+/// 弹出系统通知，窗口最小化或在其他程序中时也能看到
+pub fn push_system_notification(summary: &str, body: &str) {
+    if let Err(e) = notify_rust::Notification::new()
+        .appname(DISPLAY_NAME)
+        .summary(summary)
+        .body(body)
+        .show()
+    {
+        tracing::warn!("系统通知发送失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_throttle() {
+        let mut throttle = NotificationThrottle::default();
+        let start = Instant::now();
+        let kind = NotificationKind::RecordingError;
+
+        assert_eq!(throttle.check(1, kind, start), Some(1));
+        assert_eq!(
+            throttle.check(1, kind, start + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(
+            throttle.check(1, kind, start + Duration::from_secs(2)),
+            None
+        );
+
+        // 不同房间、不同类别互不影响
+        assert_eq!(throttle.check(2, kind, start), Some(1));
+        assert_eq!(
+            throttle.check(1, NotificationKind::OutputCorrupted, start),
+            Some(1)
+        );
+
+        assert_eq!(
+            throttle.check(1, kind, start + NOTIFICATION_THROTTLE_WINDOW),
+            Some(3)
+        );
+        assert_eq!(count_suffix(3), "（近期共发生 3 次）");
+        assert_eq!(count_suffix(1), "");
+    }
+}
@@ -0,0 +1,90 @@
+//! 性能剖析，启用 `profiling` feature 后在关键路径上记录 span
+//!
+//! 默认输出 chrome trace（日志目录下的 `trace-*.json`，可用 Perfetto 或 `chrome://tracing` 打开），
+//! 设置环境变量 `BLIVE_PROFILE=tracy` 时改为连接 Tracy。
+
+use tracing::Subscriber;
+use tracing_subscriber::{Layer, registry::LookupSpan};
+
+/// 剖析 span 使用的 target，普通日志输出会过滤掉
+pub const PROFILING_TARGET: &str = "blive::profiling";
+
+/// 选择剖析输出格式的环境变量
+pub const PROFILE_ENV: &str = "BLIVE_PROFILE";
+
+pub type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync>;
+
+/// 在当前作用域内记录一个剖析 span，未启用 `profiling` feature 时不产生任何代码
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:literal) => {
+        #[cfg(feature = "profiling")]
+        let _profile_span = tracing::trace_span!(target: "blive::profiling", $name).entered();
+    };
+}
+
+/// 创建剖析 span，用于 `.instrument()` 异步代码；未启用 `profiling` feature 时为空 span
+#[macro_export]
+macro_rules! profile_span {
+    ($name:literal) => {{
+        #[cfg(feature = "profiling")]
+        let span = tracing::trace_span!(target: "blive::profiling", $name);
+        #[cfg(not(feature = "profiling"))]
+        let span = tracing::Span::none();
+        span
+    }};
+}
+
+/// 剖析输出的守卫，丢弃时写出剩余数据
+#[derive(Default)]
+pub struct ProfilingGuard {
+    #[cfg(feature = "profiling")]
+    _chrome: Option<std::sync::Mutex<tracing_chrome::FlushGuard>>,
+}
+
+/// 是否为剖析 span，普通日志不输出这些 span
+pub fn is_profiling_target(target: &str) -> bool {
+    target == PROFILING_TARGET
+}
+
+/// 剖析 span 的过滤指令，追加到日志过滤器中
+pub fn filter_directive() -> Option<String> {
+    cfg!(feature = "profiling").then(|| format!("{PROFILING_TARGET}=trace"))
+}
+
+#[cfg(feature = "profiling")]
+pub fn layer<S>() -> (Option<BoxedLayer<S>>, ProfilingGuard)
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    if std::env::var(PROFILE_ENV).is_ok_and(|value| value.eq_ignore_ascii_case("tracy")) {
+        return (
+            Some(Box::new(tracing_tracy::TracyLayer::default())),
+            ProfilingGuard::default(),
+        );
+    }
+
+    let path = crate::logger::log_dir().join(format!(
+        "trace-{}.json",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+        .file(path)
+        .include_args(true)
+        .build();
+
+    (
+        Some(Box::new(layer)),
+        ProfilingGuard {
+            _chrome: Some(std::sync::Mutex::new(guard)),
+        },
+    )
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn layer<S>() -> (Option<BoxedLayer<S>>, ProfilingGuard)
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+{
+    (None, ProfilingGuard::default())
+}
@@ -0,0 +1,277 @@
+//! 开机自启：`blive service install/uninstall`
+//!
+//! 录制器带有窗口与托盘，系统级服务（Windows 服务、LaunchDaemon、systemd 系统单元）运行在没有桌面的会话中，
+//! 因此注册为当前用户登录后最小化启动：Linux 为 systemd 用户单元，macOS 为 LaunchAgent，Windows 为登录触发的计划任务
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+
+use crate::settings::APP_NAME;
+
+/// launchd 的任务标识，与应用包标识一致
+const LAUNCHD_LABEL: &str = "com.starknt.blive";
+
+/// Windows 计划任务名
+const TASK_NAME: &str = "BLive";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceAction {
+    Install,
+    Uninstall,
+}
+
+impl std::str::FromStr for ServiceAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "install" => Ok(ServiceAction::Install),
+            "uninstall" => Ok(ServiceAction::Uninstall),
+            _ => Err(format!("未知的 service 操作: {s}，可选 install、uninstall")),
+        }
+    }
+}
+
+/// 自启时的启动参数
+fn launch_args(config: Option<&Path>) -> Vec<String> {
+    let mut args = vec!["--minimized".to_string()];
+    if let Some(config) = config {
+        args.push("--config".to_string());
+        args.push(config.to_string_lossy().to_string());
+    }
+    args
+}
+
+/// systemd 单元中的参数需加引号并转义，`%` 为说明符前缀，`$` 为环境变量展开
+fn systemd_quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%")
+            .replace('$', "$$")
+    )
+}
+
+pub fn systemd_unit(exe: &Path, args: &[String]) -> String {
+    let exec = std::iter::once(exe.to_string_lossy().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "[Unit]
+Description=BLive 直播录制
+After=graphical-session.target network-online.target
+PartOf=graphical-session.target
+
+[Service]
+Type=simple
+ExecStart={exec}
+Restart=on-failure
+RestartSec=10
+
+[Install]
+WantedBy=graphical-session.target
+"
+    )
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn launchd_plist(exe: &Path, args: &[String]) -> String {
+    let arguments = std::iter::once(exe.to_string_lossy().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(&arg)))
+        .collect::<String>();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>ProcessType</key>
+    <string>Interactive</string>
+</dict>
+</plist>
+"#
+    )
+}
+
+/// 计划任务 `/TR` 的命令行
+pub fn task_command(exe: &Path, args: &[String]) -> String {
+    std::iter::once(exe.to_string_lossy().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| {
+            if arg.contains(' ') {
+                format!("\"{arg}\"")
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn home_dir() -> Result<PathBuf> {
+    directories::BaseDirs::new()
+        .map(|dirs| dirs.home_dir().to_path_buf())
+        .context("无法确定用户目录")
+}
+
+/// 执行系统命令，失败时带上输出
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .with_context(|| format!("无法执行 {program}"))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "{program} 执行失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+fn unit_file() -> Result<PathBuf> {
+    let dirs = directories::BaseDirs::new().context("无法确定用户目录")?;
+    Ok(dirs
+        .config_dir()
+        .join("systemd/user")
+        .join(format!("{APP_NAME}.service")))
+}
+
+fn plist_file() -> Result<PathBuf> {
+    Ok(home_dir()?
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist")))
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content).with_context(|| format!("无法写入 {}", path.display()))
+}
+
+/// 注册开机自启，返回描述信息
+pub fn install(config: Option<&Path>) -> Result<String> {
+    let exe = std::env::current_exe().context("无法确定程序路径")?;
+    let config = config.map(std::path::absolute).transpose()?;
+    let args = launch_args(config.as_deref());
+
+    if cfg!(target_os = "windows") {
+        run(
+            "schtasks",
+            &[
+                "/Create",
+                "/TN",
+                TASK_NAME,
+                "/SC",
+                "ONLOGON",
+                "/RL",
+                "LIMITED",
+                "/F",
+                "/TR",
+                &task_command(&exe, &args),
+            ],
+        )?;
+        Ok(format!("已创建登录时启动的计划任务 {TASK_NAME}"))
+    } else if cfg!(target_os = "macos") {
+        let path = plist_file()?;
+        write_file(&path, &launchd_plist(&exe, &args))?;
+        run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+        Ok(format!("已安装 {}", path.display()))
+    } else {
+        let path = unit_file()?;
+        write_file(&path, &systemd_unit(&exe, &args))?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        run(
+            "systemctl",
+            &["--user", "enable", &format!("{APP_NAME}.service")],
+        )?;
+        Ok(format!("已安装 {}", path.display()))
+    }
+}
+
+/// 取消开机自启
+pub fn uninstall() -> Result<String> {
+    if cfg!(target_os = "windows") {
+        run("schtasks", &["/Delete", "/TN", TASK_NAME, "/F"])?;
+        Ok(format!("已删除计划任务 {TASK_NAME}"))
+    } else if cfg!(target_os = "macos") {
+        let path = plist_file()?;
+        if !path.exists() {
+            anyhow::bail!("未安装开机自启");
+        }
+        // 任务未加载时 unload 会失败，不影响删除
+        let _ = run("launchctl", &["unload", "-w", &path.to_string_lossy()]);
+        std::fs::remove_file(&path)?;
+        Ok(format!("已删除 {}", path.display()))
+    } else {
+        let path = unit_file()?;
+        if !path.exists() {
+            anyhow::bail!("未安装开机自启");
+        }
+        let _ = run(
+            "systemctl",
+            &["--user", "disable", &format!("{APP_NAME}.service")],
+        );
+        std::fs::remove_file(&path)?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        Ok(format!("已删除 {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_files() {
+        let exe = Path::new("/opt/BLive/blive");
+        let args = launch_args(Some(Path::new("/home/a b/settings.json")));
+
+        let unit = systemd_unit(exe, &args);
+        assert!(unit.contains(
+            r#"ExecStart="/opt/BLive/blive" "--minimized" "--config" "/home/a b/settings.json""#
+        ));
+
+        let unit = systemd_unit(exe, &launch_args(Some(Path::new("/home/100%/$HOME.json"))));
+        assert!(unit.contains(r#""--config" "/home/100%%/$$HOME.json""#));
+
+        let plist = launchd_plist(exe, &args);
+        assert!(plist.contains("        <string>--minimized</string>\n"));
+        assert!(plist.contains("<string>com.starknt.blive</string>"));
+
+        assert_eq!(
+            task_command(
+                Path::new(r"C:\Program Files\BLive\blive.exe"),
+                &launch_args(None)
+            ),
+            r#""C:\Program Files\BLive\blive.exe" --minimized"#
+        );
+    }
+}
@@ -1,24 +1,59 @@
 use directories::ProjectDirs;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
-use crate::logger::log_user_action;
+use crate::logger::{LogLevel, log_user_action};
 use gpui::SharedString;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fmt,
+    net::IpAddr,
     ops::{Add, AddAssign},
-    path::Path,
-    sync::LazyLock,
+    path::{Path, PathBuf},
+    sync::{LazyLock, OnceLock},
 };
 
+pub mod import;
+pub mod saver;
+
 pub const APP_NAME: &str = "blive";
 pub const DISPLAY_NAME: &str = "BLive";
 pub const DEFAULT_RECORD_NAME: &str = "{up_name}_{room_title}_{datetime}";
 const DEFAULT_THEME: &str = "Catppuccin Mocha";
+const DEFAULT_LOG_RETENTION_DAYS: u32 = 7;
+const DEFAULT_RECONNECT_RESET_MINUTES: u32 = 10;
+const DEFAULT_MEMORY_WARN_MB: u32 = 1024;
+const DEFAULT_OFFLINE_GRACE_SECONDS: u32 = 60;
+const DEFAULT_LOUDNORM_INTEGRATED: f32 = -16.0;
+const DEFAULT_LOUDNORM_TRUE_PEAK: f32 = -1.5;
+const DEFAULT_LOUDNORM_LRA: f32 = 11.0;
+const DEFAULT_WHISPER_BINARY: &str = "whisper-cli";
+const DEFAULT_WHISPER_LANGUAGE: &str = "zh";
+pub const DEFAULT_MQTT_TOPIC: &str = "blive/{room_id}/{event}";
+const DEFAULT_MQTT_CLIENT_ID: &str = "blive";
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: u32 = 8;
+const DEFAULT_KEEP_ALIVE_SECONDS: u32 = 90;
+const DEFAULT_DNS_CACHE_SECONDS: u32 = 300;
+pub const DEFAULT_READ_BUFFER_KB: u32 = 128;
+const MIN_READ_BUFFER_KB: u32 = 16;
+/// 默认使用 mpv，直播流需要带上 UA 与 Referer 才能拉取
+pub const DEFAULT_PLAYER_COMMAND: &str = "mpv --user-agent={user_agent} --referrer={referer} {url}";
+/// 停止全部录制的默认全局快捷键
+pub const DEFAULT_STOP_ALL_HOTKEY: &str = "CmdOrCtrl+Alt+Shift+S";
+/// 静默时段的默认开始时间
+pub const DEFAULT_QUIET_HOURS_START: &str = "09:00";
+/// 静默时段的默认结束时间
+pub const DEFAULT_QUIET_HOURS_END: &str = "18:00";
+const MAX_READ_BUFFER_KB: u32 = 1024;
 const DEFAULT_VERSION: SettingsVersion = SettingsVersion::V1;
 
+/// 命令行 `--config` 指定的设置文件
+static SETTINGS_FILE_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
 static SETTINGS_FILE: LazyLock<String> = LazyLock::new(|| {
-    if cfg!(debug_assertions) {
+    if let Some(path) = SETTINGS_FILE_OVERRIDE.get() {
+        path.to_string_lossy().to_string()
+    } else if cfg!(debug_assertions) {
         "target/settings.json".to_string()
     } else if let Some(project_dirs) = ProjectDirs::from_path(APP_NAME.into()) {
         project_dirs
@@ -41,6 +76,55 @@ static SETTINGS_FILE: LazyLock<String> = LazyLock::new(|| {
     }
 });
 
+/// 使用指定的设置文件，传入目录时使用其中的 settings.json
+///
+/// 须在读取任何设置、初始化日志之前调用，之后调用不生效
+pub fn set_settings_file(path: &Path) {
+    let path = if path.is_dir() {
+        path.join("settings.json")
+    } else {
+        path.to_path_buf()
+    };
+    let path = std::path::absolute(&path).unwrap_or(path);
+
+    let _ = SETTINGS_FILE_OVERRIDE.set(path);
+}
+
+/// 配置目录，日志、缓存等运行数据都保存在该目录下
+pub fn config_dir() -> PathBuf {
+    Path::new(&*SETTINGS_FILE)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// 只有当前用户可读写的文件
+pub fn write_private(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    // mode 只对新建的文件生效，已存在的文件同样收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(content)
+}
+
 static DEFAULT_RECORD_DIR: LazyLock<String> = LazyLock::new(|| {
     let default = std::env::home_dir()
         .unwrap()
@@ -125,6 +209,52 @@ impl Default for VersionedSettings {
     }
 }
 
+/// 录制文件名预设，模板中的 `/` 表示子目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordNamePreset {
+    /// 全部放在录制目录下
+    Default,
+    /// 按主播与日期分目录
+    ByStreamer,
+    /// Plex/Jellyfin 可识别的剧集结构，以主播为剧集、年份为季
+    MediaLibrary,
+}
+
+impl RecordNamePreset {
+    pub const ALL: [RecordNamePreset; 3] = [
+        RecordNamePreset::Default,
+        RecordNamePreset::ByStreamer,
+        RecordNamePreset::MediaLibrary,
+    ];
+
+    pub fn template(self) -> &'static str {
+        match self {
+            RecordNamePreset::Default => DEFAULT_RECORD_NAME,
+            RecordNamePreset::ByStreamer => "{up_name}/{date}/{up_name}_{room_title}_{datetime}",
+            RecordNamePreset::MediaLibrary => {
+                "{up_name}/Season {year}/{up_name} - {date} - {room_title}"
+            }
+        }
+    }
+
+    /// 模板对应的预设，自定义模板返回 `None`
+    pub fn from_template(template: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.template() == template)
+    }
+}
+
+impl fmt::Display for RecordNamePreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordNamePreset::Default => write!(f, "默认"),
+            RecordNamePreset::ByStreamer => write!(f, "按主播/日期分目录"),
+            RecordNamePreset::MediaLibrary => write!(f, "Plex/Jellyfin 媒体库"),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, strum::EnumString)]
 pub enum Strategy {
     // 优化CPU占用
@@ -147,6 +277,60 @@ impl fmt::Display for Strategy {
     }
 }
 
+/// 录像总占用达到上限后的处理方式
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, strum::EnumString)]
+pub enum StorageLimitAction {
+    /// 暂停新录制，已在录制的房间不受影响
+    #[default]
+    #[serde(rename = "暂停录制")]
+    #[strum(serialize = "暂停录制")]
+    Pause,
+    /// 从最旧的录像开始删除
+    #[serde(rename = "清理最旧录像")]
+    #[strum(serialize = "清理最旧录像")]
+    DeleteOldest,
+}
+
+impl fmt::Display for StorageLimitAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageLimitAction::Pause => write!(f, "暂停录制"),
+            StorageLimitAction::DeleteOldest => write!(f, "清理最旧录像"),
+        }
+    }
+}
+
+/// 处于计费网络（手机热点等）时的自动录制方式
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, strum::EnumString)]
+pub enum MeteredAction {
+    /// 与普通网络相同
+    #[default]
+    #[serde(rename = "不处理")]
+    #[strum(serialize = "不处理")]
+    Ignore,
+    /// 不开始新的录制，已在录制的房间不受影响
+    #[serde(rename = "暂停自动录制")]
+    #[strum(serialize = "暂停自动录制")]
+    PauseAutoRecord,
+    /// 新开始的录制画质不高于 [`METERED_QUALITY`]
+    #[serde(rename = "限制画质")]
+    #[strum(serialize = "限制画质")]
+    LowQuality,
+}
+
+/// 计费网络下限制画质时使用的最高画质
+pub const METERED_QUALITY: Quality = Quality::HD;
+
+impl fmt::Display for MeteredAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeteredAction::Ignore => write!(f, "不处理"),
+            MeteredAction::PauseAutoRecord => write!(f, "暂停自动录制"),
+            MeteredAction::LowQuality => write!(f, "限制画质"),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, strum::EnumString)]
 pub enum LiveProtocol {
     #[serde(rename = "http_stream")]
@@ -191,6 +375,38 @@ impl fmt::Display for VideoContainer {
     }
 }
 
+/// 纯音频模式的输出格式
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    /// 复制音轨为 ADTS 裸流
+    Aac,
+    /// 复制音轨封装为 MP4 音频
+    #[default]
+    M4a,
+    /// 转码为无损 FLAC
+    Flac,
+}
+
+impl AudioFormat {
+    pub const ALL: [AudioFormat; 3] = [AudioFormat::Aac, AudioFormat::M4a, AudioFormat::Flac];
+
+    pub fn ext(&self) -> &str {
+        match self {
+            AudioFormat::Aac => "aac",
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Flac => "flac",
+        }
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.ext())
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, strum::EnumString)]
 pub enum Quality {
     // 杜比
@@ -239,6 +455,30 @@ impl fmt::Display for Quality {
 }
 
 impl Quality {
+    /// 不高于 `max` 的画质
+    pub fn capped(self, max: Quality) -> Quality {
+        if self.to_quality() > max.to_quality() {
+            max
+        } else {
+            self
+        }
+    }
+
+    /// 由接口返回的 qn 得到画质，未知的 qn 返回 `None`
+    pub fn from_qn(qn: u32) -> Option<Quality> {
+        [
+            Quality::Dolby,
+            Quality::UHD4K,
+            Quality::Original,
+            Quality::BlueRay,
+            Quality::UltraHD,
+            Quality::HD,
+            Quality::Smooth,
+        ]
+        .into_iter()
+        .find(|quality| quality.to_quality() == qn)
+    }
+
     pub fn to_quality(&self) -> u32 {
         match self {
             Quality::Dolby => 30000,
@@ -272,6 +512,474 @@ impl fmt::Display for StreamCodec {
     }
 }
 
+/// 弹幕设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DanmakuSettings {
+    /// 高亮关键词
+    #[serde(default)]
+    pub highlight_keywords: Vec<String>,
+    /// 屏蔽关键词
+    #[serde(default)]
+    pub block_keywords: Vec<String>,
+    /// 录制时在视频旁归档弹幕（JSONL）
+    #[serde(default)]
+    pub archive: bool,
+    /// 归档使用 zstd 压缩（`.jsonl.zst`）
+    #[serde(default = "default_compress_archive")]
+    pub compress_archive: bool,
+    /// 录制时在视频旁转储弹幕原始数据包（`.danmaku.bin`）
+    #[serde(default)]
+    pub raw_dump: bool,
+    /// 命中屏蔽词的弹幕仍写入归档
+    #[serde(default = "default_archive_blocked")]
+    pub archive_blocked: bool,
+    /// 房间录制弹幕时保存为 ASS 字幕，关闭时保存为 B 站 XML
+    #[serde(default)]
+    pub ass_sidecar: bool,
+}
+
+impl Default for DanmakuSettings {
+    fn default() -> Self {
+        Self {
+            highlight_keywords: vec![],
+            block_keywords: vec![],
+            archive: false,
+            compress_archive: true,
+            raw_dump: false,
+            archive_blocked: true,
+            ass_sidecar: false,
+        }
+    }
+}
+
+fn default_compress_archive() -> bool {
+    true
+}
+
+fn default_archive_blocked() -> bool {
+    true
+}
+
+/// 网络设置，连接池相关的设置重启后生效
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    /// 每个主机保留的空闲连接数，0 表示不复用连接
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: u32,
+    /// 空闲连接的保持时长（秒），同时用作 TCP keep-alive 间隔，0 表示不保持
+    #[serde(default = "default_keep_alive_seconds")]
+    pub keep_alive_seconds: u32,
+    /// 自定义 DNS 服务器（如 `223.5.5.5` 或 `223.5.5.5:53`），为空时使用系统 DNS
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_servers: Vec<String>,
+    /// DNS 解析结果缓存时长（秒），0 表示不缓存
+    #[serde(default = "default_dns_cache_seconds")]
+    pub dns_cache_seconds: u32,
+    /// 域名直连覆盖表（域名 → IP），请求直接连到该 IP 并保留原始 Host
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub host_overrides: BTreeMap<String, IpAddr>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            keep_alive_seconds: DEFAULT_KEEP_ALIVE_SECONDS,
+            dns_servers: vec![],
+            dns_cache_seconds: DEFAULT_DNS_CACHE_SECONDS,
+            host_overrides: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_pool_max_idle_per_host() -> u32 {
+    DEFAULT_POOL_MAX_IDLE_PER_HOST
+}
+
+fn default_keep_alive_seconds() -> u32 {
+    DEFAULT_KEEP_ALIVE_SECONDS
+}
+
+fn default_dns_cache_seconds() -> u32 {
+    DEFAULT_DNS_CACHE_SECONDS
+}
+
+/// 视频分辨率，文本形式为 `1920x1080`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl std::str::FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = s
+            .trim()
+            .split_once(['x', 'X', '*'])
+            .ok_or_else(|| format!("分辨率格式应为 宽x高: {s}"))?;
+
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|value| *value > 0)
+                .ok_or_else(|| format!("无效的分辨率: {s}"))
+        };
+
+        Ok(Self {
+            width: parse(width)?,
+            height: parse(height)?,
+        })
+    }
+}
+
+/// PriorityConfig 策略下 ffmpeg 的处理方式，默认只转封装不重编码
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscodeSettings {
+    /// 是否重编码，关闭时以 `-c copy` 流复制，不损失画质且几乎不占 CPU
+    #[serde(default)]
+    pub reencode: bool,
+    /// 重编码的目标分辨率，为空时保持原分辨率
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+}
+
+/// 录制完成后的响度标准化（ffmpeg loudnorm，EBU R128），只重编码音轨
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoudnormSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 目标响度（LUFS），-70 ~ -5
+    #[serde(default = "default_loudnorm_integrated")]
+    pub integrated: f32,
+    /// 真峰值上限（dBTP），-9 ~ 0
+    #[serde(default = "default_loudnorm_true_peak")]
+    pub true_peak: f32,
+    /// 响度范围（LU），1 ~ 50
+    #[serde(default = "default_loudnorm_lra")]
+    pub lra: f32,
+}
+
+impl Default for LoudnormSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            integrated: DEFAULT_LOUDNORM_INTEGRATED,
+            true_peak: DEFAULT_LOUDNORM_TRUE_PEAK,
+            lra: DEFAULT_LOUDNORM_LRA,
+        }
+    }
+}
+
+fn default_loudnorm_integrated() -> f32 {
+    DEFAULT_LOUDNORM_INTEGRATED
+}
+
+fn default_loudnorm_true_peak() -> f32 {
+    DEFAULT_LOUDNORM_TRUE_PEAK
+}
+
+fn default_loudnorm_lra() -> f32 {
+    DEFAULT_LOUDNORM_LRA
+}
+
+/// 静默时段：时段内新开播的房间不启动录制、不弹通知，已在录制的房间不受影响
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuietHoursSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 开始时间 `HH:MM`
+    #[serde(default = "default_quiet_hours_start")]
+    pub start: String,
+    /// 结束时间 `HH:MM`，早于开始时间表示跨过零点
+    #[serde(default = "default_quiet_hours_end")]
+    pub end: String,
+    /// 仅周一至周五生效，跨零点的时段按开始的那天计算
+    #[serde(default)]
+    pub weekdays_only: bool,
+}
+
+impl Default for QuietHoursSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: DEFAULT_QUIET_HOURS_START.to_string(),
+            end: DEFAULT_QUIET_HOURS_END.to_string(),
+            weekdays_only: true,
+        }
+    }
+}
+
+fn default_quiet_hours_start() -> String {
+    DEFAULT_QUIET_HOURS_START.to_string()
+}
+
+fn default_quiet_hours_end() -> String {
+    DEFAULT_QUIET_HOURS_END.to_string()
+}
+
+/// 解析 `HH:MM` 格式的时间
+pub fn parse_clock_time(value: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+impl QuietHoursSettings {
+    /// 当前是否处于静默时段
+    pub fn is_active(&self) -> bool {
+        self.contains(chrono::Local::now().naive_local())
+    }
+
+    /// 时间是否落在静默时段内，未开启或时间无效时为 `false`
+    pub fn contains(&self, time: chrono::NaiveDateTime) -> bool {
+        use chrono::{Datelike, Duration, Weekday};
+
+        if !self.enabled {
+            return false;
+        }
+        let (Some(start), Some(end)) = (parse_clock_time(&self.start), parse_clock_time(&self.end))
+        else {
+            return false;
+        };
+
+        let clock = time.time();
+        // 跨零点时，零点之后的部分属于前一天开始的时段
+        let started_on = match start <= end {
+            true if clock >= start && clock < end => time.date(),
+            false if clock >= start => time.date(),
+            false if clock < end => time.date() - Duration::days(1),
+            _ => return false,
+        };
+
+        !self.weekdays_only || !matches!(started_on.weekday(), Weekday::Sat | Weekday::Sun)
+    }
+}
+
+/// 推送到 Webhook 的事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// 开播
+    LiveStart,
+    /// 下播
+    LiveEnd,
+    /// 开始录制
+    RecordStart,
+    /// 录制文件写入完成
+    RecordComplete,
+    /// 录制出错且无法自动重连
+    RecordError,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 5] = [
+        WebhookEvent::LiveStart,
+        WebhookEvent::LiveEnd,
+        WebhookEvent::RecordStart,
+        WebhookEvent::RecordComplete,
+        WebhookEvent::RecordError,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            WebhookEvent::LiveStart => "live_start",
+            WebhookEvent::LiveEnd => "live_end",
+            WebhookEvent::RecordStart => "record_start",
+            WebhookEvent::RecordComplete => "record_complete",
+            WebhookEvent::RecordError => "record_error",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebhookEvent::LiveStart => write!(f, "开播"),
+            WebhookEvent::LiveEnd => write!(f, "下播"),
+            WebhookEvent::RecordStart => write!(f, "开始录制"),
+            WebhookEvent::RecordComplete => write!(f, "录制完成"),
+            WebhookEvent::RecordError => write!(f, "录制出错"),
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = String;
+
+    /// 接受 `live_start` 形式的键名或中文名称
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        WebhookEvent::ALL
+            .into_iter()
+            .find(|event| event.key() == s || event.to_string() == s)
+            .ok_or_else(|| format!("未知的事件类型: {s}"))
+    }
+}
+
+/// Webhook 推送目标
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    /// 推送地址，事件以 JSON POST 到每个地址
+    #[serde(default)]
+    pub urls: Vec<String>,
+    /// 推送的事件类型，为空时推送全部事件
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhookSettings {
+    /// 解析设置界面的输入，以逗号分隔，无法识别的事件类型会被忽略
+    pub fn parse(urls: &str, events: &str) -> Self {
+        let split = |value: &str| {
+            value
+                .split([',', '，', '\n'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let mut parsed_events = Vec::new();
+        for event in split(events) {
+            if let Ok(event) = event.parse::<WebhookEvent>()
+                && !parsed_events.contains(&event)
+            {
+                parsed_events.push(event);
+            }
+        }
+
+        Self {
+            urls: split(urls),
+            events: parsed_events,
+        }
+    }
+
+    pub fn events_text(&self) -> String {
+        self.events
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn accepts(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// MQTT 事件发布设置，broker 为空时不发布
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttSettings {
+    /// broker 地址，如 `mqtt://192.168.1.2:1883`
+    #[serde(default)]
+    pub broker: String,
+    /// 主题模板，支持 `{room_id}`、`{event}`、`{up_name}`
+    #[serde(default = "default_mqtt_topic")]
+    pub topic: String,
+    /// 客户端 ID 前缀，每次连接会追加随机后缀，避免多个实例互相踢下线
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// 保留消息，订阅者上线时能收到最近一次事件
+    #[serde(default)]
+    pub retain: bool,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            broker: String::new(),
+            topic: DEFAULT_MQTT_TOPIC.to_string(),
+            client_id: DEFAULT_MQTT_CLIENT_ID.to_string(),
+            username: String::new(),
+            password: String::new(),
+            retain: false,
+        }
+    }
+}
+
+fn default_mqtt_topic() -> String {
+    DEFAULT_MQTT_TOPIC.to_string()
+}
+
+fn default_mqtt_client_id() -> String {
+    DEFAULT_MQTT_CLIENT_ID.to_string()
+}
+
+/// whisper.cpp 语音转写设置，需启用 `whisper` feature，模型路径为空时不转写
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhisperSettings {
+    /// whisper.cpp 可执行文件
+    #[serde(default = "default_whisper_binary")]
+    pub binary: String,
+    /// ggml 模型文件路径
+    #[serde(default)]
+    pub model_path: String,
+    /// 语言代码，如 `zh`，`auto` 为自动检测
+    #[serde(default = "default_whisper_language")]
+    pub language: String,
+}
+
+impl Default for WhisperSettings {
+    fn default() -> Self {
+        Self {
+            binary: DEFAULT_WHISPER_BINARY.to_string(),
+            model_path: String::new(),
+            language: DEFAULT_WHISPER_LANGUAGE.to_string(),
+        }
+    }
+}
+
+fn default_whisper_binary() -> String {
+    DEFAULT_WHISPER_BINARY.to_string()
+}
+
+fn default_whisper_language() -> String {
+    DEFAULT_WHISPER_LANGUAGE.to_string()
+}
+
+/// 日志级别设置，模块未设置时跟随全局级别
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLevelSettings {
+    /// 全局日志级别
+    pub default: LogLevel,
+    /// 接口请求
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub api: Option<LogLevel>,
+    /// 录制下载
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub downloader: Option<LogLevel>,
+    /// 界面
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ui: Option<LogLevel>,
+}
+
+impl Default for LogLevelSettings {
+    fn default() -> Self {
+        Self {
+            default: if cfg!(debug_assertions) {
+                LogLevel::Debug
+            } else {
+                LogLevel::Info
+            },
+            api: None,
+            downloader: None,
+            ui: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalSettings {
     /// 策略
@@ -286,6 +994,91 @@ pub struct GlobalSettings {
     pub codec: StreamCodec,
     /// 录制目录
     pub record_dir: String,
+    /// 备用录制目录（按优先级），录制目录空间不足或不可写时依次回退
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub fallback_record_dirs: Vec<String>,
+    /// 弹幕设置
+    #[serde(default)]
+    pub danmaku: DanmakuSettings,
+    /// 监听剪贴板中的直播间链接
+    #[serde(default)]
+    pub clipboard_watch: bool,
+    /// 日志文件保留天数
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u32,
+    /// 日志级别
+    #[serde(default)]
+    pub log_levels: LogLevelSettings,
+    /// 连续稳定录制多少分钟后重置重连计数，0 表示不重置
+    #[serde(default = "default_reconnect_reset_minutes")]
+    pub reconnect_reset_minutes: u32,
+    /// 内存占用告警阈值（MB），0 表示不告警
+    #[serde(default = "default_memory_warn_mb")]
+    pub memory_warn_mb: u32,
+    /// 下播宽限期（秒），期间恢复直播则不停止录制，0 表示立即停止
+    #[serde(default = "default_offline_grace_seconds")]
+    pub offline_grace_seconds: u32,
+    /// 录制期间每隔多少分钟截图一次生成故事板，0 表示不截图
+    #[serde(default)]
+    pub storyboard_interval_minutes: u32,
+    /// 录制完成后检测持续超过多少分钟的黑屏/静音段，0 表示不检测
+    #[serde(default)]
+    pub blank_detect_minutes: u32,
+    /// 定期更新心跳文件，供外部监控确认程序存活
+    #[serde(default)]
+    pub heartbeat_file: bool,
+    /// 本机健康检查端口（`/healthz`），0 表示不启用
+    #[serde(default)]
+    pub healthz_port: u16,
+    /// 本地转发端口，录制时可用播放器打开 `/live/{房间号}` 边录边看，0 表示不启用
+    #[serde(default)]
+    pub relay_port: u16,
+    /// 录像总占用上限（GB），统计全局与各房间的录制目录，0 表示不限制
+    #[serde(default)]
+    pub storage_limit_gb: u32,
+    /// 录像总占用达到上限后的处理方式
+    #[serde(default)]
+    pub storage_limit_action: StorageLimitAction,
+    /// 严格画质：拿不到设置的画质、容器或编码时报错等待重试，而不是降级录制
+    #[serde(default)]
+    pub strict_quality: bool,
+    /// 处于计费网络时的自动录制方式
+    #[serde(default)]
+    pub metered_action: MeteredAction,
+    /// 网络设置
+    #[serde(default)]
+    pub network: NetworkSettings,
+    /// 转码设置
+    #[serde(default)]
+    pub transcode: TranscodeSettings,
+    /// LowCost 策略录制完成后重新封装一次，写入标题、主播等元数据
+    #[serde(default)]
+    pub embed_metadata: bool,
+    /// 录制完成后的响度标准化
+    #[serde(default)]
+    pub loudnorm: LoudnormSettings,
+    /// 静默时段
+    #[serde(default)]
+    pub quiet_hours: QuietHoursSettings,
+    /// 录制完成后的语音转写
+    #[serde(default)]
+    pub whisper: WhisperSettings,
+    /// 全局 Webhook，房间可追加自己的推送目标
+    #[serde(default)]
+    pub webhook: WebhookSettings,
+    /// MQTT 事件发布
+    #[serde(default)]
+    pub mqtt: MqttSettings,
+    /// 直播流读缓冲区大小（KB），高码率直播流可适当调大
+    #[serde(default = "default_read_buffer_kb")]
+    pub read_buffer_kb: u32,
+    /// 外部播放器命令，支持 `{url}`、`{user_agent}`、`{referer}` 占位符
+    #[serde(default = "default_player_command")]
+    pub player_command: String,
+    /// 一键停止全部录制的全局热键，为空表示不注册
+    #[serde(default = "default_stop_all_hotkey")]
+    pub stop_all_hotkey: String,
     /// 录制房间
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
@@ -293,6 +1086,59 @@ pub struct GlobalSettings {
 }
 
 impl GlobalSettings {
+    /// 全局与各房间的录制目录，去除空值与重复
+    pub fn record_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = vec![];
+        let room_dirs = self
+            .rooms
+            .iter()
+            .filter_map(|room| room.record_dir.as_deref());
+        let fallback_dirs = self.fallback_record_dirs.iter().map(String::as_str);
+        for dir in std::iter::once(self.record_dir.as_str())
+            .chain(room_dirs)
+            .chain(fallback_dirs)
+        {
+            let dir = PathBuf::from(dir.trim());
+            if !dir.as_os_str().is_empty() && !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+
+        dirs
+    }
+
+    /// 开始录制时依次尝试的目录：房间的录制目录在前，其后是备用目录
+    pub fn record_dir_candidates(&self, record_dir: &str) -> Vec<String> {
+        let mut dirs: Vec<String> = vec![];
+        for dir in
+            std::iter::once(record_dir).chain(self.fallback_record_dirs.iter().map(String::as_str))
+        {
+            let dir = dir.trim();
+            if !dir.is_empty() && !dirs.iter().any(|existing| existing == dir) {
+                dirs.push(dir.to_string());
+            }
+        }
+
+        dirs
+    }
+
+    /// 所有房间用到的标签，按名称排序
+    pub fn all_tags(&self) -> Vec<String> {
+        self.rooms
+            .iter()
+            .flat_map(|room| room.tags.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// 读缓冲区大小（字节），超出允许范围时取边界值
+    pub fn read_buffer_size(&self) -> usize {
+        self.read_buffer_kb
+            .clamp(MIN_READ_BUFFER_KB, MAX_READ_BUFFER_KB) as usize
+            * 1024
+    }
+
     pub fn load() -> Self {
         log_user_action("加载应用设置", None);
 
@@ -395,6 +1241,34 @@ impl GlobalSettings {
     }
 }
 
+fn default_log_retention_days() -> u32 {
+    DEFAULT_LOG_RETENTION_DAYS
+}
+
+fn default_reconnect_reset_minutes() -> u32 {
+    DEFAULT_RECONNECT_RESET_MINUTES
+}
+
+fn default_memory_warn_mb() -> u32 {
+    DEFAULT_MEMORY_WARN_MB
+}
+
+fn default_offline_grace_seconds() -> u32 {
+    DEFAULT_OFFLINE_GRACE_SECONDS
+}
+
+fn default_read_buffer_kb() -> u32 {
+    DEFAULT_READ_BUFFER_KB
+}
+
+fn default_player_command() -> String {
+    DEFAULT_PLAYER_COMMAND.to_string()
+}
+
+fn default_stop_all_hotkey() -> String {
+    DEFAULT_STOP_ALL_HOTKEY.to_string()
+}
+
 impl Default for GlobalSettings {
     fn default() -> Self {
         Self {
@@ -403,7 +1277,35 @@ impl Default for GlobalSettings {
             format: VideoContainer::default(),
             codec: StreamCodec::default(),
             record_dir: DEFAULT_RECORD_DIR.to_owned(),
+            fallback_record_dirs: vec![],
             theme_name: DEFAULT_THEME.into(),
+            danmaku: DanmakuSettings::default(),
+            clipboard_watch: false,
+            log_retention_days: DEFAULT_LOG_RETENTION_DAYS,
+            log_levels: LogLevelSettings::default(),
+            reconnect_reset_minutes: DEFAULT_RECONNECT_RESET_MINUTES,
+            memory_warn_mb: DEFAULT_MEMORY_WARN_MB,
+            offline_grace_seconds: DEFAULT_OFFLINE_GRACE_SECONDS,
+            storyboard_interval_minutes: 0,
+            blank_detect_minutes: 0,
+            heartbeat_file: false,
+            healthz_port: 0,
+            relay_port: 0,
+            storage_limit_gb: 0,
+            storage_limit_action: StorageLimitAction::default(),
+            strict_quality: false,
+            metered_action: MeteredAction::default(),
+            network: NetworkSettings::default(),
+            transcode: TranscodeSettings::default(),
+            embed_metadata: false,
+            loudnorm: LoudnormSettings::default(),
+            quiet_hours: QuietHoursSettings::default(),
+            whisper: WhisperSettings::default(),
+            webhook: WebhookSettings::default(),
+            mqtt: MqttSettings::default(),
+            read_buffer_kb: DEFAULT_READ_BUFFER_KB,
+            player_command: DEFAULT_PLAYER_COMMAND.to_string(),
+            stop_all_hotkey: DEFAULT_STOP_ALL_HOTKEY.to_string(),
             rooms: vec![],
         }
     }
@@ -413,6 +1315,18 @@ impl Default for GlobalSettings {
 pub struct RoomSettings {
     /// 房间号
     pub room_id: u64,
+    /// 别名，卡片、通知与文件名模板中的 `{alias}` 优先使用
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alias: Option<String>,
+    /// 备注，显示在卡片上并参与搜索
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub note: Option<String>,
+    /// 标签，用于在房间列表中筛选
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    /// 置顶，排在房间列表最前并在托盘菜单中单独列出
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub pinned: bool,
     /// 自动开启录制
     pub auto_record: bool,
     /// 录制目录
@@ -430,14 +1344,68 @@ pub struct RoomSettings {
     /// 录制编码
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub codec: Option<StreamCodec>,
-    /// 录制名称 {up_name}_{room_title}_{datetime}
+    /// 录制名称 {up_name}_{room_title}_{datetime}，`/` 表示子目录，可选用 [`RecordNamePreset`]
     pub record_name: String,
+    /// 直播标题变化时结束当前分段，按新标题开始新文件
+    #[serde(default)]
+    pub split_on_title_change: bool,
+    /// 录制时连接弹幕服务器，在视频旁保存弹幕、礼物与醒目留言
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub record_danmaku: bool,
+    /// 仅保留音频，录制结束后去除视频轨
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub audio_only: bool,
+    /// 纯音频模式的输出格式
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub audio_format: Option<AudioFormat>,
+    /// 房间的存储配额
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub quota: Option<RoomQuota>,
+    /// 房间自己的 Webhook，与全局目标合并推送
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub webhook: Option<WebhookSettings>,
+}
+
+/// 单房间存储配额，按录制历史统计该房间仍存在的录像
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoomQuota {
+    /// 录像总大小上限（GB），0 表示不限制
+    #[serde(default)]
+    pub max_gb: u32,
+    /// 保留场次上限，0 表示不限制
+    #[serde(default)]
+    pub max_sessions: u32,
+    /// 超出后清理该房间最旧的录像，或停止该房间的自动录制
+    #[serde(default)]
+    pub action: StorageLimitAction,
+}
+
+impl RoomQuota {
+    pub fn is_empty(&self) -> bool {
+        self.max_gb == 0 && self.max_sessions == 0
+    }
+}
+
+/// 解析逗号分隔的标签，去除空白与重复
+pub fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in value.split([',', '，']).map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|value| value == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    tags
 }
 
 impl RoomSettings {
     pub fn new(room_id: u64) -> Self {
         Self {
             room_id,
+            alias: None,
+            note: None,
+            tags: vec![],
+            pinned: false,
             auto_record: true,
             record_dir: None,
             strategy: None,
@@ -445,18 +1413,79 @@ impl RoomSettings {
             format: None,
             codec: None,
             record_name: DEFAULT_RECORD_NAME.to_string(),
+            split_on_title_change: false,
+            record_danmaku: false,
+            audio_only: false,
+            audio_format: None,
+            quota: None,
+            webhook: None,
+        }
+    }
+
+    /// 去除首尾空白后的别名，未设置时为 `None`
+    pub fn alias(&self) -> Option<&str> {
+        self.alias
+            .as_deref()
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+    }
+
+    /// 去除首尾空白后的备注，未填写时为 `None`
+    pub fn note(&self) -> Option<&str> {
+        self.note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty())
+    }
+
+    /// 房间号、别名、备注以及传入的主播名与标题中任一包含关键词即匹配，忽略大小写
+    pub fn matches_search(&self, query: &str, up_name: &str, title: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return true;
         }
+
+        let room_id = self.room_id.to_string();
+        [
+            Some(room_id.as_str()),
+            self.alias(),
+            self.note(),
+            Some(up_name),
+            Some(title),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|text| text.to_lowercase().contains(&query))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|value| value == tag)
+    }
+
+    /// 展示用的名称，优先使用别名
+    pub fn display_name<'a>(&'a self, up_name: &'a str) -> &'a str {
+        self.alias().unwrap_or(up_name)
     }
 
     pub fn merge_global(&mut self, global_settings: &GlobalSettings) -> Self {
         Self {
             room_id: self.room_id,
+            alias: self.alias.clone(),
+            note: self.note.clone(),
+            tags: self.tags.clone(),
+            pinned: self.pinned,
             auto_record: self.auto_record,
             strategy: Some(self.strategy.unwrap_or(global_settings.strategy)),
             quality: Some(self.quality.unwrap_or(global_settings.quality)),
             format: Some(self.format.unwrap_or(global_settings.format)),
             codec: Some(self.codec.unwrap_or(global_settings.codec)),
             record_name: self.record_name.clone(),
+            split_on_title_change: self.split_on_title_change,
+            record_danmaku: self.record_danmaku,
+            audio_only: self.audio_only,
+            audio_format: self.audio_format,
+            quota: self.quota.clone(),
+            webhook: self.webhook.clone(),
             record_dir: match self.record_dir.clone().unwrap_or_default().is_empty() {
                 true => Some(global_settings.record_dir.clone()),
                 false => self.record_dir.clone(),
@@ -691,6 +1720,23 @@ impl SettingsMigrator {
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private_fixes_existing_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("blive-private-{}", std::process::id()));
+        std::fs::write(&path, b"old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, b"secret").unwrap();
+
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert_eq!(std::fs::read(&path).unwrap(), b"secret");
+        let _ = std::fs::remove_file(path);
+    }
+
     #[test]
     fn test_migrate_v0_to_v1() {
         // 创建版本0的配置（无版本信息）
@@ -705,6 +1751,7 @@ mod tests {
                 room_id: 12345,
                 ..Default::default()
             }],
+            ..Default::default()
         };
 
         // 序列化为JSON
@@ -731,6 +1778,10 @@ mod tests {
             record_dir: "/test/path".to_string(),
             rooms: vec![RoomSettings {
                 room_id: 67890,
+                alias: None,
+                note: None,
+                tags: vec![],
+                pinned: false,
                 auto_record: true,
                 record_dir: None,
                 strategy: None,
@@ -738,7 +1789,14 @@ mod tests {
                 format: None,
                 codec: None,
                 record_name: "test_name".to_string(),
+                split_on_title_change: false,
+                record_danmaku: false,
+                audio_only: false,
+                audio_format: None,
+                quota: None,
+                webhook: None,
             }],
+            ..Default::default()
         };
 
         // 创建版本化配置
@@ -759,6 +1817,171 @@ mod tests {
         assert_eq!(migrated_settings.rooms[0].record_name, "test_name");
     }
 
+    #[test]
+    fn test_room_alias() {
+        let mut settings = RoomSettings::new(732);
+        assert_eq!(settings.display_name("主播"), "主播");
+
+        settings.alias = Some("  ".to_string());
+        assert_eq!(settings.display_name("主播"), "主播");
+
+        settings.alias = Some(" 小号 ".to_string());
+        assert_eq!(settings.display_name("主播"), "小号");
+    }
+
+    #[test]
+    fn test_tags() {
+        assert_eq!(
+            parse_tags("vup, 游戏，vup,, 电台 "),
+            ["vup", "游戏", "电台"]
+        );
+
+        let mut settings = GlobalSettings::default();
+        for (room_id, tags) in [(1, "vup,游戏"), (2, "电台,vup"), (3, "")] {
+            let mut room = RoomSettings::new(room_id);
+            room.tags = parse_tags(tags);
+            settings.rooms.push(room);
+        }
+
+        assert_eq!(settings.all_tags(), ["vup", "游戏", "电台"]);
+        assert!(settings.rooms[1].has_tag("电台"));
+        assert!(!settings.rooms[2].has_tag("vup"));
+    }
+
+    #[test]
+    fn test_room_search() {
+        let mut settings = RoomSettings::new(732);
+        settings.note = Some("只录周五的歌回".to_string());
+
+        assert!(settings.matches_search(" ", "主播", "标题"));
+        assert!(settings.matches_search("73", "主播", "标题"));
+        assert!(settings.matches_search("歌回", "主播", "标题"));
+        assert!(settings.matches_search("vtuber", "主播", "VTuber 杂谈"));
+        assert!(!settings.matches_search("游戏", "主播", "标题"));
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let at =
+            |value: &str| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+
+        // 2025-06-06 为周五
+        let mut quiet = QuietHoursSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(quiet.contains(at("2025-06-06 09:00")));
+        assert!(!quiet.contains(at("2025-06-06 18:00")));
+        assert!(!quiet.contains(at("2025-06-07 10:00")));
+
+        quiet.start = "23:00".to_string();
+        quiet.end = "07:00".to_string();
+        assert!(quiet.contains(at("2025-06-06 23:30")));
+        assert!(quiet.contains(at("2025-06-07 06:00")));
+        assert!(!quiet.contains(at("2025-06-08 06:00")));
+        assert!(!quiet.contains(at("2025-06-07 12:00")));
+
+        quiet.end = "7点".to_string();
+        assert!(!quiet.contains(at("2025-06-06 23:30")));
+    }
+
+    #[test]
+    fn test_record_dir_candidates() {
+        let settings = GlobalSettings {
+            record_dir: "/a".to_string(),
+            fallback_record_dirs: vec![" /b ".to_string(), "".to_string(), "/c".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(settings.record_dir_candidates("/c"), ["/c", "/b"]);
+        assert_eq!(
+            settings.record_dirs(),
+            [PathBuf::from("/a"), "/b".into(), "/c".into()]
+        );
+    }
+
+    #[test]
+    fn test_network_settings_default() {
+        // 旧配置没有网络设置时使用默认值
+        let mut json = serde_json::to_value(GlobalSettings::default()).unwrap();
+        json.as_object_mut().unwrap().remove("network");
+
+        let settings: GlobalSettings = serde_json::from_value(json).unwrap();
+        assert_eq!(settings.network, NetworkSettings::default());
+    }
+
+    #[test]
+    fn test_quality_capped() {
+        assert_eq!(Quality::Original.capped(METERED_QUALITY), Quality::HD);
+        assert_eq!(Quality::Smooth.capped(METERED_QUALITY), Quality::Smooth);
+        assert_eq!(Quality::from_qn(250), Some(Quality::UltraHD));
+        assert_eq!(Quality::from_qn(1), None);
+        assert_eq!(
+            "限制画质".parse::<MeteredAction>().unwrap(),
+            MeteredAction::LowQuality
+        );
+    }
+
+    #[test]
+    fn test_resolution_parse() {
+        assert_eq!(
+            "1920x1080".parse::<Resolution>(),
+            Ok(Resolution {
+                width: 1920,
+                height: 1080
+            })
+        );
+        assert_eq!(
+            " 1280 * 720 ".parse::<Resolution>().unwrap().to_string(),
+            "1280x720"
+        );
+        assert!("1920".parse::<Resolution>().is_err());
+        assert!("0x1080".parse::<Resolution>().is_err());
+
+        // 旧配置没有转码设置时默认不重编码
+        let mut json = serde_json::to_value(GlobalSettings::default()).unwrap();
+        json.as_object_mut().unwrap().remove("transcode");
+        let settings: GlobalSettings = serde_json::from_value(json).unwrap();
+        assert!(!settings.transcode.reencode);
+    }
+
+    #[test]
+    fn test_webhook_settings_parse() {
+        let settings = WebhookSettings::parse(
+            "https://a, https://b，",
+            "开播, record_complete, 开播, unknown",
+        );
+        assert_eq!(settings.urls, ["https://a", "https://b"]);
+        assert_eq!(
+            settings.events,
+            [WebhookEvent::LiveStart, WebhookEvent::RecordComplete]
+        );
+        assert_eq!(settings.events_text(), "开播,录制完成");
+        assert!(!settings.accepts(WebhookEvent::LiveEnd));
+        assert!(WebhookSettings::default().accepts(WebhookEvent::LiveEnd));
+    }
+
+    #[test]
+    fn test_read_buffer_size() {
+        let mut settings = GlobalSettings::default();
+        assert_eq!(
+            settings.read_buffer_size(),
+            DEFAULT_READ_BUFFER_KB as usize * 1024
+        );
+
+        settings.read_buffer_kb = 0;
+        assert_eq!(
+            settings.read_buffer_size(),
+            MIN_READ_BUFFER_KB as usize * 1024
+        );
+
+        settings.read_buffer_kb = u32::MAX;
+        assert_eq!(
+            settings.read_buffer_size(),
+            MAX_READ_BUFFER_KB as usize * 1024
+        );
+    }
+
     #[test]
     fn test_save_with_version() {
         let settings = GlobalSettings::default();
@@ -817,6 +2040,10 @@ mod tests {
         let mut invalid_settings = GlobalSettings::default();
         invalid_settings.rooms.push(RoomSettings {
             room_id: 12345,
+            alias: None,
+            note: None,
+            tags: vec![],
+            pinned: false,
             auto_record: true,
             record_dir: None,
             strategy: None,
@@ -824,6 +2051,12 @@ mod tests {
             format: None,
             codec: None,
             record_name: "".to_string(),
+            split_on_title_change: false,
+            record_danmaku: false,
+            audio_only: false,
+            audio_format: None,
+            quota: None,
+            webhook: None,
         });
         assert!(SettingsMigrator::validate_settings(&invalid_settings).is_err());
     }
@@ -0,0 +1,152 @@
+//! 从 B 站录播姬（BililiveRecorder）的 config.json 导入房间
+
+use serde::Deserialize;
+
+use crate::settings::{Quality, RoomSettings};
+
+/// 录播姬的可选配置项，`HasValue` 为 false 时表示继承全局设置
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Optional<T> {
+    #[serde(default)]
+    has_value: bool,
+    value: Option<T>,
+}
+
+impl<T> Default for Optional<T> {
+    fn default() -> Self {
+        Self {
+            has_value: false,
+            value: None,
+        }
+    }
+}
+
+impl<T> Optional<T> {
+    fn get(self) -> Option<T> {
+        self.has_value.then_some(self.value).flatten()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RecorderRoomConfig {
+    #[serde(default)]
+    room_id: Optional<u64>,
+    #[serde(default)]
+    auto_record: Optional<bool>,
+    /// 按优先级排列的 qn，如 `"10000,400"`
+    #[serde(default)]
+    recording_quality: Optional<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecorderConfig {
+    #[serde(default)]
+    global: RecorderRoomConfig,
+    #[serde(default)]
+    rooms: Vec<RecorderRoomConfig>,
+}
+
+/// 取画质列表中第一个能识别的 qn
+fn parse_quality(qualities: &str) -> Option<Quality> {
+    const QUALITIES: [Quality; 7] = [
+        Quality::Dolby,
+        Quality::UHD4K,
+        Quality::Original,
+        Quality::BlueRay,
+        Quality::UltraHD,
+        Quality::HD,
+        Quality::Smooth,
+    ];
+
+    qualities
+        .split([',', '，'])
+        .filter_map(|qn| qn.trim().parse::<u32>().ok())
+        .find_map(|qn| {
+            QUALITIES
+                .into_iter()
+                .find(|quality| quality.to_quality() == qn)
+        })
+}
+
+/// 解析录播姬配置，映射为房间设置
+///
+/// 房间未单独设置画质时沿用录播姬的全局画质；其余录制参数两边含义不同，使用本程序的全局设置
+pub fn import_bililive_recorder(content: &str) -> Result<Vec<RoomSettings>, serde_json::Error> {
+    let config: RecorderConfig = serde_json::from_str(content)?;
+    let global_quality = config
+        .global
+        .recording_quality
+        .get()
+        .and_then(|qualities| parse_quality(&qualities));
+
+    let mut rooms: Vec<RoomSettings> = vec![];
+    for room in config.rooms {
+        let Some(room_id) = room.room_id.get().filter(|room_id| *room_id > 0) else {
+            continue;
+        };
+        if rooms.iter().any(|settings| settings.room_id == room_id) {
+            continue;
+        }
+
+        let mut settings = RoomSettings::new(room_id);
+        settings.auto_record = room.auto_record.get().unwrap_or(true);
+        settings.quality = room
+            .recording_quality
+            .get()
+            .and_then(|qualities| parse_quality(&qualities))
+            .or(global_quality);
+        rooms.push(settings);
+    }
+
+    Ok(rooms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_bililive_recorder() {
+        let content = r#"{
+            "$schema": "https://raw.githubusercontent.com/BililiveRecorder/BililiveRecorder/dev/configV3.schema.json",
+            "version": 3,
+            "global": {
+                "RecordMode": { "HasValue": true, "Value": 0 },
+                "RecordingQuality": { "HasValue": true, "Value": "20000,10000" }
+            },
+            "rooms": [
+                {
+                    "RoomId": { "HasValue": true, "Value": 732 },
+                    "AutoRecord": { "HasValue": true, "Value": false }
+                },
+                {
+                    "RoomId": { "HasValue": true, "Value": 21452505 },
+                    "AutoRecord": { "HasValue": true, "Value": true },
+                    "RecordingQuality": { "HasValue": true, "Value": "1, 400" }
+                },
+                {
+                    "RoomId": { "HasValue": true, "Value": 732 }
+                },
+                {
+                    "RoomId": { "HasValue": false }
+                }
+            ]
+        }"#;
+
+        let rooms = import_bililive_recorder(content).unwrap();
+        assert_eq!(rooms.len(), 2);
+
+        assert_eq!(rooms[0].room_id, 732);
+        assert!(!rooms[0].auto_record);
+        assert_eq!(rooms[0].quality, Some(Quality::UHD4K));
+
+        assert_eq!(rooms[1].room_id, 21452505);
+        assert!(rooms[1].auto_record);
+        assert_eq!(rooms[1].quality, Some(Quality::BlueRay));
+
+        assert!(import_bililive_recorder("{}").unwrap().is_empty());
+        assert!(import_bililive_recorder("not json").is_err());
+    }
+}
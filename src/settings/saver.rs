@@ -0,0 +1,138 @@
+use std::{
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use crate::settings::GlobalSettings;
+
+/// 去抖间隔，期间的多次修改合并为一次写盘
+pub const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+enum SaveRequest {
+    /// 去抖后保存
+    Save(Arc<GlobalSettings>),
+    /// 立即保存并通知调用方，丢弃尚未写入的旧版本
+    Flush(Arc<GlobalSettings>, flume::Sender<()>),
+}
+
+static SAVER: LazyLock<flume::Sender<SaveRequest>> = LazyLock::new(|| {
+    let (tx, rx) = flume::unbounded();
+
+    std::thread::Builder::new()
+        .name("blive-settings".to_string())
+        .spawn(move || run_saver(rx, SAVE_DEBOUNCE, |settings| settings.save()))
+        .expect("无法启动设置保存线程");
+
+    tx
+});
+
+/// 在后台保存设置，[`SAVE_DEBOUNCE`] 内的多次调用只写最后一次
+pub fn schedule_save(settings: Arc<GlobalSettings>) {
+    let _ = SAVER.send(SaveRequest::Save(settings));
+}
+
+/// 立即保存并等待写盘完成，用于退出前
+pub fn flush(settings: Arc<GlobalSettings>) {
+    let (done_tx, done_rx) = flume::bounded(1);
+    if SAVER
+        .send(SaveRequest::Flush(settings.clone(), done_tx))
+        .is_err()
+    {
+        settings.save();
+        return;
+    }
+
+    let _ = done_rx.recv();
+}
+
+fn run_saver(
+    rx: flume::Receiver<SaveRequest>,
+    debounce: Duration,
+    mut write: impl FnMut(&GlobalSettings),
+) {
+    let mut pending: Option<(Arc<GlobalSettings>, Instant)> = None;
+
+    loop {
+        let request = match &pending {
+            Some((_, deadline)) => match rx.recv_deadline(*deadline) {
+                Ok(request) => request,
+                Err(flume::RecvTimeoutError::Timeout) => {
+                    if let Some((settings, _)) = pending.take() {
+                        write(&settings);
+                    }
+                    continue;
+                }
+                Err(flume::RecvTimeoutError::Disconnected) => break,
+            },
+            None => match rx.recv() {
+                Ok(request) => request,
+                Err(_) => break,
+            },
+        };
+
+        match request {
+            SaveRequest::Save(settings) => {
+                // 保留第一次修改的截止时间，持续修改时也不会一直推迟
+                let deadline = pending
+                    .as_ref()
+                    .map(|(_, deadline)| *deadline)
+                    .unwrap_or_else(|| Instant::now() + debounce);
+                pending = Some((settings, deadline));
+            }
+            SaveRequest::Flush(settings, done) => {
+                pending = None;
+                write(&settings);
+                let _ = done.send(());
+            }
+        }
+    }
+
+    if let Some((settings, _)) = pending {
+        write(&settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounced_save() {
+        let (tx, rx) = flume::unbounded();
+        let (written_tx, written_rx) = flume::unbounded();
+        let handle = std::thread::spawn(move || {
+            run_saver(rx, Duration::from_millis(50), |settings| {
+                let _ = written_tx.send(settings.log_retention_days);
+            })
+        });
+
+        // 去抖期间的多次修改只写最后一次
+        for days in 1..=5 {
+            tx.send(SaveRequest::Save(Arc::new(GlobalSettings {
+                log_retention_days: days,
+                ..Default::default()
+            })))
+            .unwrap();
+        }
+        assert_eq!(written_rx.recv_timeout(Duration::from_secs(1)), Ok(5));
+
+        // 立即保存会丢弃尚未写入的旧版本
+        tx.send(SaveRequest::Save(Arc::new(GlobalSettings::default())))
+            .unwrap();
+        let (done_tx, done_rx) = flume::bounded(1);
+        tx.send(SaveRequest::Flush(
+            Arc::new(GlobalSettings {
+                log_retention_days: 30,
+                ..Default::default()
+            }),
+            done_tx,
+        ))
+        .unwrap();
+        done_rx.recv().unwrap();
+        assert_eq!(written_rx.try_recv(), Ok(30));
+
+        drop(tx);
+        handle.join().unwrap();
+        assert!(written_rx.try_recv().is_err());
+    }
+}
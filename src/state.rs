@@ -1,12 +1,27 @@
 use crate::components::{DownloaderStatus, RoomCard, RoomCardStatus};
+use crate::core::auth;
+use crate::core::danmaku::{
+    DanmakuBuffer, DanmakuFilter, DanmakuMessage, archive::DanmakuArchive,
+    highlights::HighlightTracker, protocol::LiveMessage, sidecar::DanmakuSidecar,
+};
+use crate::core::dns;
 use crate::core::downloader::BLiveDownloader;
+use crate::core::downloader::chapters::ChapterMarks;
+use crate::core::downloader::storyboard::Storyboard;
+use crate::core::ffprobe::MediaInfo;
 use crate::core::http_client::room::LiveRoomInfoData;
 use crate::core::http_client::user::LiveUserInfo;
-use crate::logger::{log_config_change, log_user_action};
-use crate::settings::RoomSettings;
+use crate::core::http_client::{USER_AGENT, build_reqwest_client};
+use crate::core::monitor::{ProcessUsage, ResourceMonitor};
+use crate::core::storage::StorageUsage;
+use crate::logger::{apply_log_levels, cleanup_logs, log_config_change, log_user_action};
+use crate::notification::NotificationThrottle;
+use crate::settings::{METERED_QUALITY, MeteredAction, Quality, RoomSettings, saver};
 use crate::{core::HttpClient, settings::GlobalSettings};
 use gpui::{App, Global, WeakEntity};
+use parking_lot::Mutex;
 use rand::Rng;
+use reqwest_client::ReqwestClient;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -18,11 +33,91 @@ pub struct RoomCardState {
     pub(crate) user_info: Option<LiveUserInfo>,
     pub downloader: Option<Arc<BLiveDownloader>>,
     pub downloader_status: Option<DownloaderStatus>,
+    pub media_info: Option<MediaInfo>,
+    /// 当前录制实际使用的画质，可能因直播间不支持或计费网络低于设置
+    pub stream_quality: Option<Quality>,
+    /// 其他实例持有该房间的录制锁时为其 PID
+    pub locked_by: Option<u32>,
+    /// ffmpeg 子进程的资源占用，仅 PriorityConfig 策略下有值
+    pub process_usage: Option<ProcessUsage>,
     pub reconnecting: bool,
     pub reconnect_manager: ReconnectManager,
+    pub offline_debounce: OfflineDebounce,
+    pub poll: AdaptivePoll,
+    pub danmaku: DanmakuBuffer,
+    /// 当前录制的弹幕归档，未开启归档或未在录制时为 `None`
+    pub danmaku_archive: Option<Arc<Mutex<DanmakuArchive>>>,
+    /// 当前录制旁的弹幕文件（XML/ASS），房间未开启录制弹幕或未在录制时为 `None`
+    pub danmaku_sidecar: Option<Arc<Mutex<DanmakuSidecar>>>,
+    /// 当前录制按标题/分区变化生成的章节，未在录制时为 `None`
+    pub chapters: Option<ChapterMarks>,
+    /// 当前录制的弹幕密度统计，录制结束时生成高光时刻
+    pub highlights: Option<HighlightTracker>,
+    /// 当前录制的定时截图，未开启或未在录制时为 `None`
+    pub storyboard: Option<Storyboard>,
+    /// 本场直播结束后关闭自动录制，不持久化
+    pub stop_after_live: bool,
+    /// 本场直播因静默时段未录制，已写入历史记录
+    pub quiet_skipped: bool,
     pub entity: Option<WeakEntity<RoomCard>>,
 }
 
+/// 下播防抖：短暂掉线在宽限期内恢复时不停止下载器
+#[derive(Debug, Clone, Default)]
+pub struct OfflineDebounce {
+    offline_since: Option<std::time::Instant>,
+}
+
+impl OfflineDebounce {
+    /// 观察到下播状态，超过宽限期后返回 `true` 表示应停止录制
+    pub fn observe_offline(&mut self, grace: Duration) -> bool {
+        self.observe_offline_at(std::time::Instant::now(), grace)
+    }
+
+    fn observe_offline_at(&mut self, now: std::time::Instant, grace: Duration) -> bool {
+        let offline_since = *self.offline_since.get_or_insert(now);
+        now.duration_since(offline_since) >= grace
+    }
+
+    /// 观察到直播状态，清除下播计时
+    pub fn observe_live(&mut self) {
+        self.offline_since = None;
+    }
+}
+
+/// 房间状态的基础轮询间隔
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 长期未开播时的最长轮询间隔
+pub const MAX_POLL_INTERVAL: Duration = Duration::from_secs(120);
+
+/// 每连续这么多次未开播，轮询间隔翻倍一次
+const POLL_BACKOFF_STEP: u32 = 6;
+
+/// 自适应轮询：长期未开播的房间逐步拉长轮询间隔
+#[derive(Debug, Clone, Default)]
+pub struct AdaptivePoll {
+    offline_streak: u32,
+}
+
+impl AdaptivePoll {
+    /// 观察到一次未开播
+    pub fn observe_offline(&mut self) {
+        self.offline_streak = self.offline_streak.saturating_add(1);
+    }
+
+    /// 开播或有弹幕活动时立即恢复高频轮询
+    pub fn reset(&mut self) {
+        self.offline_streak = 0;
+    }
+
+    /// 下一次轮询前的等待时间
+    pub fn interval(&self) -> Duration {
+        let doublings = (self.offline_streak / POLL_BACKOFF_STEP).min(8);
+        (POLL_INTERVAL * 2u32.pow(doublings)).min(MAX_POLL_INTERVAL)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ReconnectManager {
     current_attempt: u32,
@@ -30,6 +125,11 @@ pub struct ReconnectManager {
     base_delay: Duration,
     max_delay: Duration,
     last_reconnect_time: Option<std::time::Instant>,
+    stable_since: Option<std::time::Instant>,
+    /// 每次安排重连递增，用于识别已被取代的延迟重连
+    generation: u64,
+    /// 已安排但尚未执行的延迟重连
+    scheduled: Option<u64>,
 }
 
 impl ReconnectManager {
@@ -40,6 +140,9 @@ impl ReconnectManager {
             base_delay,
             max_delay,
             last_reconnect_time: None,
+            stable_since: None,
+            generation: 0,
+            scheduled: None,
         }
     }
 
@@ -50,6 +153,39 @@ impl ReconnectManager {
     pub fn increment_attempt(&mut self) {
         self.current_attempt += 1;
         self.last_reconnect_time = Some(std::time::Instant::now());
+        self.stable_since = None;
+    }
+
+    pub fn current_attempt(&self) -> u32 {
+        self.current_attempt
+    }
+
+    /// 录制正常推进时调用，连续稳定超过 `reset_after` 后清零重连计数，返回是否发生了重置
+    pub fn record_stable(&mut self, reset_after: Option<Duration>) -> bool {
+        self.record_stable_at(std::time::Instant::now(), reset_after)
+    }
+
+    fn record_stable_at(&mut self, now: std::time::Instant, reset_after: Option<Duration>) -> bool {
+        if self.current_attempt == 0 {
+            return false;
+        }
+
+        let Some(reset_after) = reset_after else {
+            return false;
+        };
+
+        let stable_since = *self.stable_since.get_or_insert(now);
+        if now.duration_since(stable_since) >= reset_after {
+            self.reset_attempts();
+            return true;
+        }
+
+        false
+    }
+
+    /// 出现断流或错误时中断稳定计时
+    pub fn mark_unstable(&mut self) {
+        self.stable_since = None;
     }
 
     pub fn calculate_delay(&self) -> Duration {
@@ -62,9 +198,38 @@ impl ReconnectManager {
         delay.min(self.max_delay)
     }
 
+    /// 安排一次延迟重连并计入重连次数，返回该次重连的序号
+    pub fn schedule(&mut self) -> u64 {
+        self.increment_attempt();
+        self.generation += 1;
+        self.scheduled = Some(self.generation);
+        self.generation
+    }
+
+    /// 延迟结束时调用，该次重连未被取消时返回 `true`
+    pub fn take_scheduled(&mut self, generation: u64) -> bool {
+        if self.scheduled == Some(generation) {
+            self.scheduled = None;
+            return true;
+        }
+
+        false
+    }
+
+    /// 网络恢复时跳过退避：取消尚在等待的重连并清零重连计数，没有等待中的重连时返回 `false`
+    pub fn skip_backoff(&mut self) -> bool {
+        if self.scheduled.take().is_none() {
+            return false;
+        }
+
+        self.reset_attempts();
+        true
+    }
+
     pub fn reset_attempts(&mut self) {
         self.current_attempt = 0;
         self.last_reconnect_time = None;
+        self.stable_since = None;
     }
 }
 
@@ -78,28 +243,154 @@ impl RoomCardState {
             user_info: None,
             downloader: None,
             downloader_status: None,
+            media_info: None,
+            stream_quality: None,
+            locked_by: None,
+            process_usage: None,
             reconnecting: false,
             reconnect_manager: ReconnectManager::new(
                 10,
                 Duration::from_secs(1),
                 Duration::from_secs(30),
             ),
+            offline_debounce: OfflineDebounce::default(),
+            poll: AdaptivePoll::default(),
+            danmaku: DanmakuBuffer::default(),
+            danmaku_archive: None,
+            danmaku_sidecar: None,
+            chapters: None,
+            highlights: None,
+            storyboard: None,
+            stop_after_live: false,
+            quiet_skipped: false,
+        }
+    }
+
+    /// 更新直播间信息，录制中标题或分区变化时开始新章节，返回标题是否变化
+    pub fn update_room_info(&mut self, room_info: LiveRoomInfoData) -> bool {
+        let title_changed = self
+            .room_info
+            .as_ref()
+            .is_some_and(|previous| previous.title != room_info.title);
+
+        if let Some(chapters) = &mut self.chapters
+            && chapters.observe(
+                &room_info.title,
+                &room_info.area_name,
+                std::time::Instant::now(),
+            )
+        {
+            tracing::info!(
+                "直播内容变化，新增章节 - 房间: {}, 标题: {}, 分区: {}",
+                self.room_id,
+                room_info.title,
+                room_info.area_name
+            );
+        }
+
+        self.room_info = Some(room_info);
+        title_changed
+    }
+
+    /// 收到弹幕说明房间有活动，恢复高频轮询
+    ///
+    /// 屏蔽的弹幕仍进入缓冲区，由弹幕面板按当前屏蔽词过滤，修改屏蔽词后即时生效
+    pub fn record_danmaku(&mut self, message: DanmakuMessage, filter: &DanmakuFilter) {
+        if let Some(archive) = &self.danmaku_archive
+            && filter.should_archive(&message)
+        {
+            let mut archive = archive.lock();
+            if let Err(e) = archive.append(&message) {
+                tracing::warn!(
+                    "弹幕归档写入失败，停止归档: {} - {}",
+                    archive.path().display(),
+                    e
+                );
+                drop(archive);
+                self.danmaku_archive = None;
+            }
+        }
+
+        if let Some(highlights) = &mut self.highlights
+            && !filter.is_blocked(&message)
+        {
+            highlights.observe(&message);
+        }
+
+        self.danmaku.push(message);
+        self.poll.reset();
+    }
+
+    /// 弹幕服务器推送的消息，弹幕照常进入缓冲区与归档，三类消息都写入弹幕文件
+    pub fn record_live_message(&mut self, message: LiveMessage, filter: &DanmakuFilter) {
+        let blocked =
+            matches!(&message, LiveMessage::Danmaku(danmaku) if !filter.should_archive(danmaku));
+
+        if let Some(sidecar) = &self.danmaku_sidecar
+            && !blocked
+        {
+            let mut sidecar = sidecar.lock();
+            if let Err(e) = sidecar.append(&message) {
+                tracing::warn!(
+                    "弹幕文件写入失败，停止写入: {} - {}",
+                    sidecar.path().display(),
+                    e
+                );
+                drop(sidecar);
+                self.danmaku_sidecar = None;
+            }
+        }
+
+        if let LiveMessage::Danmaku(danmaku) = message {
+            self.record_danmaku(danmaku, filter);
         }
     }
 }
 
+/// 影响所有房间的全局问题，在主界面顶部横幅展示
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlobalIssue {
+    /// 登录 Cookie 失效
+    CookieExpired,
+    /// 录制目录所在磁盘空间不足
+    DiskFull { record_dir: String, available: u64 },
+    /// 未找到可用的 ffmpeg
+    FfmpegMissing,
+    /// 录像总占用达到上限，按设置暂停了新录制或无法清理到上限以内
+    StorageLimit { used: u64, limit: u64 },
+    /// 当前处于计费网络，按设置暂停或限制了自动录制
+    MeteredNetwork { action: MeteredAction },
+}
+
 pub struct AppState {
     pub client: HttpClient,
     pub room_states: Vec<RoomCardState>,
-    pub settings: GlobalSettings,
+    /// 全局设置，读取时共享同一份，修改统一通过 [`AppState::update_settings`]
+    pub settings: Arc<GlobalSettings>,
+    pub monitor: ResourceMonitor,
+    pub issues: Vec<GlobalIssue>,
+    /// 最近一次统计的录像占用
+    pub storage_usage: StorageUsage,
+    pub notifications: NotificationThrottle,
+    /// 按弹幕设置编译的过滤器，设置变化时重建
+    pub danmaku_filter: DanmakuFilter,
 }
 
 impl AppState {
     pub fn init(cx: &mut App) {
         log_user_action("初始化应用状态", None);
 
-        let client = HttpClient::new(cx.http_client());
         let global_settings = GlobalSettings::load();
+        dns::resolver().configure(&global_settings.network);
+        auth::load();
+
+        // 所有请求共用同一个底层客户端，复用连接池
+        let http_client = build_reqwest_client(&global_settings.network).unwrap_or_else(|e| {
+            tracing::warn!("按网络设置创建 HTTP 客户端失败，使用默认配置: {}", e);
+            ReqwestClient::user_agent(USER_AGENT).expect("无法创建 HTTP 客户端")
+        });
+        cx.set_http_client(Arc::new(http_client));
+        let client = HttpClient::new(cx.http_client());
 
         log_config_change("录制目录", &global_settings.record_dir);
         log_config_change("默认录制质量", &format!("{}", global_settings.quality));
@@ -107,6 +398,9 @@ impl AppState {
         log_config_change("默认编码格式", &format!("{}", global_settings.codec));
         log_config_change("主题", &global_settings.theme_name);
 
+        apply_log_levels(&global_settings.log_levels);
+        cleanup_logs(global_settings.log_retention_days);
+
         if !global_settings.rooms.is_empty() {
             log_user_action(
                 "加载已保存的房间",
@@ -121,8 +415,13 @@ impl AppState {
 
         let state = Self {
             client,
-            settings: global_settings,
+            danmaku_filter: DanmakuFilter::new(&global_settings.danmaku),
+            settings: Arc::new(global_settings),
             room_states: vec![],
+            monitor: ResourceMonitor::default(),
+            issues: vec![],
+            storage_usage: StorageUsage::default(),
+            notifications: NotificationThrottle::default(),
         };
         cx.set_global::<AppState>(state);
 
@@ -137,8 +436,64 @@ impl AppState {
         cx.global_mut::<Self>()
     }
 
+    /// 修改全局设置，设置仍被他处持有时先复制再修改（写时复制）
+    pub fn update_settings<R>(&mut self, update: impl FnOnce(&mut GlobalSettings) -> R) -> R {
+        let result = update(Arc::make_mut(&mut self.settings));
+        self.danmaku_filter = DanmakuFilter::new(&self.settings.danmaku);
+        result
+    }
+
+    /// 整体替换全局设置
+    pub fn replace_settings(&mut self, settings: GlobalSettings) {
+        self.danmaku_filter = DanmakuFilter::new(&settings.danmaku);
+        self.settings = Arc::new(settings);
+    }
+
+    /// 在后台保存当前设置，短时间内的多次保存会合并
+    pub fn save_settings(&self) {
+        saver::schedule_save(self.settings.clone());
+    }
+
     pub fn add_room(&mut self, settings: RoomSettings) {
-        self.settings.rooms.push(settings);
+        self.update_settings(|global| global.rooms.push(settings));
+    }
+
+    /// 合并以短号保存的房间
+    ///
+    /// 真实房间号已存在时直接移除短号配置，否则将短号改写为真实房间号并返回新的设置。
+    /// 短号房间的状态一并移除，其下载器交给调用方停止；正在录制时为真实房间开启自动录制，由它接着录制
+    pub fn merge_room_alias(
+        &mut self,
+        short_id: u64,
+        room_id: u64,
+    ) -> (Option<RoomSettings>, Option<Arc<BLiveDownloader>>) {
+        let downloader = self
+            .get_room_state_mut(short_id)
+            .and_then(|room_state| room_state.downloader.take());
+        self.remove_room_state(short_id);
+
+        let renamed = if self.has_room(room_id) {
+            self.update_settings(|global| {
+                global.rooms.retain(|settings| settings.room_id != short_id)
+            });
+            false
+        } else if let Some(settings) = self.get_room_settings_mut(short_id) {
+            settings.room_id = room_id;
+            true
+        } else {
+            false
+        };
+
+        if downloader.is_some()
+            && let Some(settings) = self.get_room_settings_mut(room_id)
+        {
+            settings.auto_record = true;
+        }
+
+        let settings = renamed
+            .then(|| self.get_room_settings(room_id).cloned())
+            .flatten();
+        (settings, downloader)
     }
 
     pub fn has_room(&self, room_id: u64) -> bool {
@@ -156,7 +511,7 @@ impl AppState {
     }
 
     pub fn get_room_settings_mut(&mut self, room_id: u64) -> Option<&mut RoomSettings> {
-        self.settings
+        Arc::make_mut(&mut self.settings)
             .rooms
             .iter_mut()
             .find(|settings| settings.room_id == room_id)
@@ -192,7 +547,263 @@ impl AppState {
 
     pub fn remove_room_state(&mut self, room_id: u64) {
         self.room_states.retain(|state| state.room_id != room_id);
+        self.notifications.remove_room(room_id);
+    }
+
+    /// 设置全局问题，同类问题只保留最新一条
+    pub fn set_issue(&mut self, issue: GlobalIssue) {
+        let kind = std::mem::discriminant(&issue);
+        self.clear_issue(|existing| std::mem::discriminant(existing) == kind);
+        self.issues.push(issue);
+    }
+
+    /// 正在写入的录制文件
+    pub fn recording_files(&self) -> Vec<std::path::PathBuf> {
+        self.room_states
+            .iter()
+            .filter_map(|state| match &state.downloader_status {
+                Some(DownloaderStatus::Started { file_path }) => Some(file_path.into()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 清除满足条件的全局问题
+    pub fn clear_issue(&mut self, matches: impl Fn(&GlobalIssue) -> bool) {
+        self.issues.retain(|issue| !matches(issue));
+    }
+
+    /// 计费网络下按设置需要执行的处理，不在计费网络时为 `None`
+    pub fn metered_action(&self) -> Option<MeteredAction> {
+        self.issues.iter().find_map(|issue| match issue {
+            GlobalIssue::MeteredNetwork { action } => Some(*action),
+            _ => None,
+        })
+    }
+
+    /// 开始录制时实际请求的画质，计费网络下可能被限制
+    pub fn recording_quality(&self, quality: Quality) -> Quality {
+        match self.metered_action() {
+            Some(MeteredAction::LowQuality) => quality.capped(METERED_QUALITY),
+            _ => quality,
+        }
+    }
+
+    /// 各子系统持有的对象数量，内存告警时输出以辅助定位泄漏
+    pub fn object_counts(&self) -> Vec<(&'static str, usize)> {
+        let downloaders = self
+            .room_states
+            .iter()
+            .filter_map(|state| state.downloader.as_ref())
+            .collect::<Vec<_>>();
+
+        vec![
+            ("房间", self.room_states.len()),
+            ("下载器", downloaders.len()),
+            (
+                "运行中的下载器",
+                downloaders
+                    .iter()
+                    .filter(|downloader| downloader.is_running())
+                    .count(),
+            ),
+            (
+                "弹幕缓存",
+                self.room_states
+                    .iter()
+                    .map(|state| state.danmaku.iter().count())
+                    .sum(),
+            ),
+            (
+                "时间线事件",
+                downloaders
+                    .iter()
+                    .map(|downloader| downloader.context.timeline().events().len())
+                    .sum(),
+            ),
+            ("内存采样", self.monitor.samples().count()),
+        ]
     }
 }
 
 impl Global for AppState {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn test_state(rooms: Vec<RoomSettings>) -> AppState {
+        let client = ReqwestClient::user_agent(USER_AGENT).unwrap();
+        let settings = GlobalSettings {
+            rooms,
+            ..Default::default()
+        };
+        AppState {
+            client: HttpClient::new(Arc::new(client)),
+            room_states: vec![],
+            danmaku_filter: DanmakuFilter::new(&settings.danmaku),
+            settings: Arc::new(settings),
+            monitor: ResourceMonitor::default(),
+            issues: vec![],
+            storage_usage: StorageUsage::default(),
+            notifications: NotificationThrottle::default(),
+        }
+    }
+
+    fn paused_room(room_id: u64) -> RoomSettings {
+        RoomSettings {
+            auto_record: false,
+            ..RoomSettings::new(room_id)
+        }
+    }
+
+    fn recording_state(state: &AppState, room_id: u64) -> RoomCardState {
+        let mut room_state = RoomCardState::new(room_id);
+        room_state.downloader = Some(Arc::new(BLiveDownloader::new(
+            LiveRoomInfoData::default(),
+            LiveUserInfo::default(),
+            Quality::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            state.client.clone(),
+            room_id,
+        )));
+        room_state
+    }
+
+    #[test]
+    fn test_merge_room_alias_hands_over_recording() {
+        let mut state = test_state(vec![paused_room(123)]);
+        let room_state = recording_state(&state, 123);
+        state.room_states.push(room_state);
+
+        let (settings, downloader) = state.merge_room_alias(123, 456789);
+
+        assert!(downloader.is_some());
+        assert!(!state.has_room_state(123));
+        assert!(!state.has_room(123));
+        let settings = settings.unwrap();
+        assert_eq!(settings.room_id, 456789);
+        assert!(settings.auto_record);
+        assert!(state.get_room_settings(456789).unwrap().auto_record);
+    }
+
+    #[test]
+    fn test_merge_room_alias_into_existing_room() {
+        let mut state = test_state(vec![paused_room(123), paused_room(456789)]);
+        let room_state = recording_state(&state, 123);
+        state.room_states.push(room_state);
+
+        let (settings, downloader) = state.merge_room_alias(123, 456789);
+
+        assert!(settings.is_none());
+        assert!(downloader.is_some());
+        assert!(!state.has_room(123));
+        assert!(state.get_room_settings(456789).unwrap().auto_record);
+    }
+
+    #[test]
+    fn test_merge_room_alias_without_recording() {
+        let mut state = test_state(vec![paused_room(123)]);
+        state.room_states.push(RoomCardState::new(123));
+
+        let (settings, downloader) = state.merge_room_alias(123, 456789);
+
+        assert!(downloader.is_none());
+        assert!(!state.has_room_state(123));
+        assert!(!settings.unwrap().auto_record);
+    }
+
+    #[test]
+    fn test_reconnect_attempts_reset_after_stable() {
+        let mut manager =
+            ReconnectManager::new(10, Duration::from_secs(1), Duration::from_secs(30));
+        let reset_after = Some(Duration::from_secs(600));
+        let start = Instant::now();
+
+        manager.increment_attempt();
+        manager.increment_attempt();
+
+        assert!(!manager.record_stable_at(start, reset_after));
+        assert!(!manager.record_stable_at(start + Duration::from_secs(300), reset_after));
+
+        // 中途断流会重新计时
+        manager.mark_unstable();
+        assert!(!manager.record_stable_at(start + Duration::from_secs(700), reset_after));
+        assert_eq!(manager.current_attempt(), 2);
+
+        assert!(manager.record_stable_at(start + Duration::from_secs(1300), reset_after));
+        assert_eq!(manager.current_attempt(), 0);
+    }
+
+    #[test]
+    fn test_reconnect_attempts_reset_disabled() {
+        let mut manager =
+            ReconnectManager::new(10, Duration::from_secs(1), Duration::from_secs(30));
+        let start = Instant::now();
+
+        manager.increment_attempt();
+        manager.record_stable_at(start, None);
+
+        assert!(!manager.record_stable_at(start + Duration::from_secs(3600), None));
+        assert_eq!(manager.current_attempt(), 1);
+    }
+
+    #[test]
+    fn test_reconnect_skip_backoff() {
+        let mut manager =
+            ReconnectManager::new(10, Duration::from_secs(1), Duration::from_secs(30));
+
+        let first = manager.schedule();
+        assert!(manager.take_scheduled(first));
+        assert!(!manager.take_scheduled(first));
+
+        // 网络恢复后原先的延迟重连不再执行
+        let second = manager.schedule();
+        assert_eq!(manager.current_attempt(), 2);
+        assert!(manager.skip_backoff());
+        assert!(!manager.take_scheduled(second));
+        assert_eq!(manager.current_attempt(), 0);
+        assert!(!manager.skip_backoff());
+    }
+
+    #[test]
+    fn test_offline_debounce() {
+        let mut debounce = OfflineDebounce::default();
+        let start = Instant::now();
+        let grace = Duration::from_secs(60);
+
+        assert!(!debounce.observe_offline_at(start, grace));
+        assert!(!debounce.observe_offline_at(start + Duration::from_secs(30), grace));
+
+        // 宽限期内恢复直播，重新计时
+        debounce.observe_live();
+        assert!(!debounce.observe_offline_at(start + Duration::from_secs(70), grace));
+        assert!(debounce.observe_offline_at(start + Duration::from_secs(130), grace));
+
+        // 宽限期为 0 时立即停止
+        let mut debounce = OfflineDebounce::default();
+        assert!(debounce.observe_offline_at(start, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_adaptive_poll() {
+        let mut poll = AdaptivePoll::default();
+        assert_eq!(poll.interval(), POLL_INTERVAL);
+
+        for _ in 0..POLL_BACKOFF_STEP {
+            poll.observe_offline();
+        }
+        assert_eq!(poll.interval(), POLL_INTERVAL * 2);
+
+        for _ in 0..POLL_BACKOFF_STEP * 100 {
+            poll.observe_offline();
+        }
+        assert_eq!(poll.interval(), MAX_POLL_INTERVAL);
+
+        poll.reset();
+        assert_eq!(poll.interval(), POLL_INTERVAL);
+    }
+}
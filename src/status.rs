@@ -0,0 +1,219 @@
+//! 供脚本轮询的运行状态快照，由 `blive status` 向运行中的实例查询
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::DownloaderStatus,
+    core::http_client::room::LiveStatus,
+    state::{AppState, GlobalIssue},
+};
+
+/// 房间的录制状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingState {
+    /// 等待开播
+    Waiting,
+    Recording,
+    /// 最近一次录制出错或产物校验未通过
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomStatus {
+    pub room_id: u64,
+    pub up_name: Option<String>,
+    pub title: Option<String>,
+    pub live: bool,
+    pub state: RecordingState,
+    /// 正在写入或最近一次录制的文件
+    pub file_path: Option<String>,
+    pub speed_kbps: Option<f32>,
+    pub bytes_downloaded: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppStatus {
+    pub version: String,
+    /// 毫秒时间戳
+    pub timestamp: i64,
+    pub rooms: Vec<RoomStatus>,
+    /// 全局问题，如 `cookie_expired`、`disk_full`、`ffmpeg_missing`
+    pub issues: Vec<String>,
+}
+
+impl AppStatus {
+    pub fn collect(state: &AppState) -> Self {
+        let rooms = state
+            .settings
+            .rooms
+            .iter()
+            .map(|settings| {
+                let room_state = state.get_room_state(settings.room_id);
+                let room_info = room_state.and_then(|room| room.room_info.as_ref());
+                let stats = room_state
+                    .and_then(|room| room.downloader.as_ref())
+                    .and_then(|downloader| downloader.get_download_stats());
+
+                let (file_path, error) =
+                    match room_state.and_then(|room| room.downloader_status.as_ref()) {
+                        Some(DownloaderStatus::Started { file_path })
+                        | Some(DownloaderStatus::Completed { file_path, .. }) => {
+                            (Some(file_path.clone()), None)
+                        }
+                        Some(DownloaderStatus::Corrupted { file_path, reason }) => {
+                            (Some(file_path.clone()), Some(reason.clone()))
+                        }
+                        Some(DownloaderStatus::Error { cause }) => (None, Some(cause.clone())),
+                        None => (None, None),
+                    };
+
+                let recording_state = if stats.is_some() {
+                    RecordingState::Recording
+                } else if error.is_some() {
+                    RecordingState::Error
+                } else {
+                    RecordingState::Waiting
+                };
+
+                RoomStatus {
+                    room_id: settings.room_id,
+                    up_name: room_state
+                        .and_then(|room| room.user_info.as_ref())
+                        .map(|user_info| user_info.uname.clone()),
+                    title: room_info.map(|room_info| room_info.title.clone()),
+                    live: room_info
+                        .is_some_and(|room_info| room_info.live_status == LiveStatus::Live),
+                    state: recording_state,
+                    file_path,
+                    speed_kbps: stats.as_ref().map(|stats| stats.download_speed_kbps),
+                    bytes_downloaded: stats.as_ref().map(|stats| stats.bytes_downloaded),
+                    error,
+                }
+            })
+            .collect();
+
+        let issues = state
+            .issues
+            .iter()
+            .map(|issue| match issue {
+                GlobalIssue::CookieExpired => "cookie_expired",
+                GlobalIssue::DiskFull { .. } => "disk_full",
+                GlobalIssue::FfmpegMissing => "ffmpeg_missing",
+                GlobalIssue::StorageLimit { .. } => "storage_limit",
+                GlobalIssue::MeteredNetwork { .. } => "metered_network",
+            })
+            .map(str::to_string)
+            .collect();
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: chrono::Local::now().timestamp_millis(),
+            rooms,
+            issues,
+        }
+    }
+
+    /// 有房间出错或存在全局问题
+    pub fn has_error(&self) -> bool {
+        // 计费网络只是提示，不算异常
+        self.issues.iter().any(|issue| issue != "metered_network")
+            || self
+                .rooms
+                .iter()
+                .any(|room| room.state == RecordingState::Error)
+    }
+
+    /// 忽略时间戳与速度等持续变化的字段，判断状态是否变化
+    pub fn same_state(&self, other: &Self) -> bool {
+        let strip = |status: &Self| {
+            let mut status = status.clone();
+            status.timestamp = 0;
+            for room in &mut status.rooms {
+                room.speed_kbps = None;
+                room.bytes_downloaded = None;
+            }
+            status
+        };
+
+        strip(self) == strip(other)
+    }
+
+    /// 终端中展示的摘要，每个房间一行
+    pub fn summary(&self) -> String {
+        let mut lines = self
+            .rooms
+            .iter()
+            .map(|room| {
+                let name = room.up_name.as_deref().unwrap_or("-");
+                match room.state {
+                    RecordingState::Recording => format!(
+                        "{} {name} 录制中 {:.0} KB/s {}",
+                        room.room_id,
+                        room.speed_kbps.unwrap_or_default(),
+                        room.file_path.as_deref().unwrap_or_default()
+                    ),
+                    RecordingState::Error => format!(
+                        "{} {name} 出错: {}",
+                        room.room_id,
+                        room.error.as_deref().unwrap_or_default()
+                    ),
+                    RecordingState::Waiting if room.live => {
+                        format!("{} {name} 直播中，未录制", room.room_id)
+                    }
+                    RecordingState::Waiting => format!("{} {name} 等待开播", room.room_id),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if !self.issues.is_empty() {
+            lines.push(format!("全局问题: {}", self.issues.join(", ")));
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_json() {
+        let mut status = AppStatus {
+            version: "0.1.0".to_string(),
+            timestamp: 0,
+            rooms: vec![RoomStatus {
+                room_id: 732,
+                up_name: Some("主播".to_string()),
+                title: Some("标题".to_string()),
+                live: true,
+                state: RecordingState::Recording,
+                file_path: Some("/录制/a.flv".to_string()),
+                speed_kbps: Some(512.0),
+                bytes_downloaded: Some(1024),
+                error: None,
+            }],
+            issues: vec![],
+        };
+        assert!(!status.has_error());
+        status.issues = vec!["metered_network".to_string()];
+        assert!(!status.has_error());
+        status.issues.clear();
+        assert_eq!(status.summary(), "732 主播 录制中 512 KB/s /录制/a.flv");
+
+        let json = serde_json::to_string(&status).unwrap();
+        assert!(json.contains(r#""state":"recording""#));
+        assert_eq!(serde_json::from_str::<AppStatus>(&json).unwrap(), status);
+
+        let mut next = status.clone();
+        next.timestamp = 5000;
+        next.rooms[0].speed_kbps = Some(480.0);
+        assert!(status.same_state(&next));
+
+        status.rooms[0].state = RecordingState::Error;
+        assert!(status.has_error());
+        assert!(!status.same_state(&next));
+    }
+}
@@ -102,7 +102,8 @@ impl Render for ThemeSwitcher {
                 }
 
                 // Save AppState
-                AppState::global_mut(cx).settings.theme_name = theme_name.clone();
+                AppState::global_mut(cx)
+                    .update_settings(|settings| settings.theme_name = theme_name.clone());
 
                 cx.notify();
             }))
@@ -6,12 +6,16 @@ use gpui_component::{
     scroll::ScrollbarShow,
 };
 
-use crate::{components::AppSettings, themes::ThemeSwitcher};
+use crate::{
+    components::{AppSettings, DiagnosticsPanel, LoginPanel},
+    themes::ThemeSwitcher,
+};
 
 pub struct AppTitleBar {
     title: String,
     theme_switcher: Entity<ThemeSwitcher>,
     settings: Entity<AppSettings>,
+    diagnostics: Entity<DiagnosticsPanel>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -25,11 +29,13 @@ impl AppTitleBar {
 
         let theme_switcher = cx.new(|cx| ThemeSwitcher::new(cx));
         let settings = cx.new(|cx| AppSettings::new(window, cx));
+        let diagnostics = DiagnosticsPanel::view(window, cx);
 
         Self {
             title,
             theme_switcher,
             settings,
+            diagnostics,
             _subscriptions: vec![],
         }
     }
@@ -42,6 +48,17 @@ impl AppTitleBar {
 
         Theme::change(mode, None, cx);
     }
+
+    fn open_diagnostics(&mut self, _: &ClickEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let diagnostics = self.diagnostics.clone();
+
+        window.open_modal(cx, move |modal, _window, _cx| {
+            modal
+                .rounded_lg()
+                .title(div().font_bold().text_2xl().child("诊断"))
+                .child(diagnostics.clone())
+        });
+    }
 }
 
 impl Render for AppTitleBar {
@@ -77,6 +94,22 @@ impl Render for AppTitleBar {
                     .gap_3()
                     .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
                     .child(self.settings.clone())
+                    .child(
+                        Button::new("account")
+                            .icon(IconName::CircleUser)
+                            .small()
+                            .ghost()
+                            .tooltip("账号")
+                            .on_click(|_, window, cx| LoginPanel::open(window, cx)),
+                    )
+                    .child(
+                        Button::new("diagnostics")
+                            .icon(IconName::Info)
+                            .small()
+                            .ghost()
+                            .tooltip("诊断")
+                            .on_click(cx.listener(Self::open_diagnostics)),
+                    )
                     .child(self.theme_switcher.clone())
                     .child(
                         Button::new("theme-mode")
@@ -1,10 +1,23 @@
+#[cfg(target_os = "linux")]
+use std::sync::Arc;
+
+#[cfg(not(target_os = "linux"))]
 use tray_item::{IconSource, TrayItem};
 
 pub enum TrayMessage {
     OpenWindow,
+    /// 在主窗口中显示置顶房间的卡片
+    OpenRoom(u64),
     Quit,
 }
 
+/// Linux 直接使用 ksni，持有服务句柄才能在重建前注销旧图标
+#[cfg(target_os = "linux")]
+pub struct SystemTray {
+    handle: ksni::Handle<KsniTray>,
+}
+
+#[cfg(not(target_os = "linux"))]
 pub struct SystemTray {
     tray: TrayItem,
 }
@@ -16,19 +29,130 @@ const ICON: &[u8] = include_bytes!("../resources/mac/icon.png");
 const ICON: &[u8] = include_bytes!("../resources/icons/png/32x32.png");
 
 #[cfg(not(windows))]
-fn load_icon_rgba(icon: &[u8]) -> IconSource {
+fn decode_icon_rgba(icon: &[u8]) -> (Vec<u8>, u32, u32) {
     let decoder = png::Decoder::new(icon);
     let (info, mut reader) = decoder.read_info().unwrap();
     let mut buf = vec![0; info.buffer_size()];
     reader.next_frame(&mut buf).unwrap();
 
+    (buf, info.width, info.height)
+}
+
+#[cfg(target_os = "macos")]
+fn load_icon_rgba(icon: &[u8]) -> IconSource {
+    let (data, _, _) = decode_icon_rgba(icon);
+
     IconSource::Data {
-        data: buf,
+        data,
         height: 32,
         width: 32,
     }
 }
 
+/// StatusNotifierItem 的图标为网络字节序的 ARGB
+#[cfg(target_os = "linux")]
+fn load_icon_argb(icon: &[u8]) -> ksni::Icon {
+    let (mut data, width, height) = decode_icon_rgba(icon);
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.rotate_right(1);
+    }
+
+    ksni::Icon {
+        width: width as i32,
+        height: height as i32,
+        data,
+    }
+}
+
+#[cfg(target_os = "linux")]
+enum TrayEntry {
+    Label(String),
+    Item(String, Arc<dyn Fn() + Send + Sync>),
+}
+
+#[cfg(target_os = "linux")]
+struct KsniTray {
+    icon: ksni::Icon,
+    entries: Vec<TrayEntry>,
+}
+
+#[cfg(target_os = "linux")]
+impl ksni::Tray for KsniTray {
+    fn id(&self) -> String {
+        crate::settings::APP_NAME.to_string()
+    }
+
+    fn title(&self) -> String {
+        "BLive 录制".to_string()
+    }
+
+    fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+        vec![self.icon.clone()]
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        use ksni::menu::StandardItem;
+
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                TrayEntry::Label(label) => StandardItem {
+                    label: label.clone(),
+                    enabled: false,
+                    ..Default::default()
+                }
+                .into(),
+                TrayEntry::Item(label, action) => {
+                    let action = action.clone();
+                    StandardItem {
+                        label: label.clone(),
+                        activate: Box::new(move |_| action()),
+                        ..Default::default()
+                    }
+                    .into()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SystemTray {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let service = ksni::TrayService::new(KsniTray {
+            icon: load_icon_argb(ICON),
+            entries: vec![TrayEntry::Label("BLive 录制".to_string())],
+        });
+        let handle = service.handle();
+        service.spawn();
+
+        Self { handle }
+    }
+
+    pub fn display(&mut self) {}
+
+    pub fn add_menu_item<F>(&mut self, label: &str, action: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let entry = TrayEntry::Item(label.to_string(), Arc::new(action));
+        self.handle.update(|tray| tray.entries.push(entry));
+    }
+
+    /// 不可点击的分组标题
+    pub fn add_label(&mut self, label: &str) {
+        let entry = TrayEntry::Label(label.to_string());
+        self.handle.update(|tray| tray.entries.push(entry));
+    }
+
+    /// 注销托盘服务，图标随之从系统托盘移除
+    pub fn quit(&mut self) {
+        self.handle.shutdown();
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
 impl SystemTray {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
@@ -43,8 +167,6 @@ impl SystemTray {
         tray.inner_mut().add_label("BLive 录制").unwrap();
         #[cfg(target_os = "windows")]
         tray.inner_mut().set_tooltip("BLive 录制").unwrap();
-        #[cfg(target_os = "linux")]
-        tray.inner_mut().add_label("BLive 录制").unwrap();
 
         Self { tray }
     }
@@ -61,6 +183,15 @@ impl SystemTray {
         self.tray.add_menu_item(label, action).unwrap();
     }
 
+    /// 不可点击的分组标题
+    pub fn add_label(&mut self, label: &str) {
+        self.tray.add_label(label).unwrap();
+    }
+
+    /// 移除托盘图标
+    ///
+    /// macOS 的状态栏图标只在 `display` 中创建，该调用会接管主线程的事件循环，
+    /// 与 GPUI 冲突而从未调用，因此没有需要移除的图标
     pub fn quit(&mut self) {
         #[cfg(windows)]
         self.tray.inner_mut().quit();